@@ -0,0 +1,14 @@
+// SPDX-License-Identifier: Apache-2.0
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rmsd_yaml::YamlValue;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = std::str::from_utf8(data)
+        && let Ok(value) = input.parse::<YamlValue>()
+    {
+        let _ = rmsd_yaml::to_string(&value);
+    }
+});
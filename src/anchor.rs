@@ -0,0 +1,251 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    ErrorKind, YamlError, YamlEvent, YamlParser, flow::FLOW_INDICATORS,
+};
+
+impl<'a> YamlParser<'a> {
+    /// Consume the `&anchor` property, the anchor will be attached to the
+    /// event of next node.
+    pub(crate) fn handle_anchor(&mut self) -> Result<(), YamlError> {
+        let start_pos = self.scanner.next_pos;
+        let name = self.read_anchor_name()?;
+        if let Some(anchor) = self.anchor.as_ref() {
+            return Err(YamlError::new(
+                ErrorKind::InvalidNodeProperty,
+                format!(
+                    "Node can only have one anchor, but got &{anchor} and \
+                     &{name}"
+                ),
+                start_pos,
+                self.scanner.done_pos,
+            ));
+        }
+        self.anchor = Some(name);
+        Ok(())
+    }
+
+    /// Consume the `*alias` node, alias node cannot have properties.
+    pub(crate) fn handle_alias(
+        &mut self,
+        tag: Option<String>,
+    ) -> Result<(), YamlError> {
+        let start_pos = self.scanner.next_pos;
+        let name = self.read_anchor_name()?;
+        if tag.is_some() || self.anchor.is_some() {
+            return Err(YamlError::new(
+                ErrorKind::InvalidNodeProperty,
+                format!("Alias node *{name} cannot have tag or anchor"),
+                start_pos,
+                self.scanner.done_pos,
+            ));
+        }
+        self.push_event(YamlEvent::Alias(name, start_pos));
+        Ok(())
+    }
+
+    // Consume the `&` or `*` indicator and the anchor name after it.
+    fn read_anchor_name(&mut self) -> Result<String, YamlError> {
+        let start_pos = self.scanner.next_pos;
+        self.scanner.next_char();
+        // YAML 1.2.2 SPEC, 6.9.2. Node Anchors:
+        //      ns-anchor-char ::= ns-char - c-flow-indicator
+        let name: String = self
+            .scanner
+            .remains()
+            .chars()
+            .take_while(|c| {
+                !matches!(c, ' ' | '\t' | '\n') && !FLOW_INDICATORS.contains(c)
+            })
+            .collect();
+        self.scanner.advance(name.chars().count());
+        if name.is_empty() {
+            Err(YamlError::new(
+                ErrorKind::InvalidNodeProperty,
+                "Anchor name should not be empty".to_string(),
+                start_pos,
+                self.scanner.done_pos,
+            ))
+        } else {
+            Ok(name)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{YamlParseOption, YamlPosition, YamlScalarStyle};
+
+    fn to_event_strs(input: &str) -> Vec<String> {
+        YamlParser::parse_to_events(input)
+            .unwrap()
+            .iter()
+            .map(|e| e.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_anchor_on_empty_node() {
+        assert_eq!(
+            to_event_strs("- &a\n- !!str\n-\n  &b : c\n  d: &e\n"),
+            vec![
+                "+STR",
+                "+DOC",
+                "+SEQ",
+                "=VAL &a :",
+                "=VAL <tag:yaml.org,2002:str> :",
+                "+MAP",
+                "=VAL &b :",
+                "=VAL :c",
+                "=VAL :d",
+                "=VAL &e :",
+                "-MAP",
+                "-SEQ",
+                "-DOC",
+                "-STR",
+            ]
+        );
+        let events = YamlParser::parse_to_events("- &a\n").unwrap();
+        assert_eq!(
+            events[3],
+            YamlEvent::Scalar(
+                Some("a".to_string()),
+                None,
+                YamlScalarStyle::Plain,
+                String::new(),
+                YamlPosition::new(1, 5),
+                YamlPosition::new(1, 5)
+            )
+        );
+    }
+
+    #[test]
+    fn test_anchor_on_collection() {
+        assert_eq!(
+            to_event_strs("a: !!map &b\n  c: &d [e]\nf: &g\n- h\n"),
+            vec![
+                "+STR",
+                "+DOC",
+                "+MAP",
+                "=VAL :a",
+                "+MAP &b <tag:yaml.org,2002:map>",
+                "=VAL :c",
+                "+SEQ &d",
+                "=VAL :e",
+                "-SEQ",
+                "-MAP",
+                "=VAL :f",
+                "+SEQ &g",
+                "=VAL :h",
+                "-SEQ",
+                "-MAP",
+                "-DOC",
+                "-STR",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_anchor() {
+        for input in ["&a &b c\n", "- &a - b\n", "&\n"] {
+            let e = YamlParser::parse_to_events(input).unwrap_err();
+            assert_eq!(e.kind(), ErrorKind::InvalidNodeProperty, "{input:?}");
+        }
+        let e = YamlParser::parse_to_events("--- &a b: c\n").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::TrailingContent);
+    }
+
+    #[test]
+    fn test_alias() -> Result<(), YamlError> {
+        let input = "a: &x [b]\n*x : c\nd: [*x, e]\n";
+        assert_eq!(
+            to_event_strs(input),
+            vec![
+                "+STR", "+DOC", "+MAP", "=VAL :a", "+SEQ &x", "=VAL :b",
+                "-SEQ", "=ALI *x", "=VAL :c", "=VAL :d", "+SEQ", "=ALI *x",
+                "=VAL :e", "-SEQ", "-MAP", "-DOC", "-STR",
+            ]
+        );
+        assert_eq!(
+            crate::from_str::<Vec<Vec<String>>>("- &x [a]\n- *x\n")?,
+            vec![vec!["a".to_string()], vec!["a".to_string()]]
+        );
+        let e = crate::to_value("a: *x\n").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::UnknownAlias);
+        let e = YamlParser::parse_to_events("- &a *b\n").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidNodeProperty);
+        Ok(())
+    }
+
+    #[test]
+    fn test_anchor_ignored_by_deserializer() -> Result<(), YamlError> {
+        assert_eq!(
+            crate::from_str::<Vec<String>>("- &a b\n- &c\n  d\n")?,
+            vec!["b".to_string(), "d".to_string()]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_anchor_name_with_colon() {
+        assert_eq!(
+            to_event_strs("&a: key: &a value\nfoo:\n  *a:\n"),
+            vec![
+                "+STR",
+                "+DOC",
+                "+MAP",
+                "=VAL &a: :key",
+                "=VAL &a :value",
+                "=VAL :foo",
+                "=ALI *a:",
+                "-MAP",
+                "-DOC",
+                "-STR",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_anchor() -> Result<(), YamlError> {
+        let input = "a: &x 1\nb: &x 2\nc: *x\n";
+        assert_eq!(crate::from_str::<HashMap<String, u8>>(input)?["c"], 2);
+        let (_, warnings) =
+            crate::to_value_with_warnings(input, Default::default())?;
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind(), ErrorKind::DuplicateAnchor);
+        assert_eq!(warnings[0].start_pos(), YamlPosition::new(2, 7));
+        let (_, warnings) = crate::to_value_with_warnings(
+            "a: &x 1\nb: *x\nc: &x 2\n",
+            Default::default(),
+        )?;
+        assert!(warnings.is_empty());
+
+        let option = YamlParseOption {
+            deny_duplicate_anchor: true,
+            ..Default::default()
+        };
+        let e = crate::to_value_with_opt(input, option.clone()).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::DuplicateAnchor);
+        assert_eq!(e.start_pos(), YamlPosition::new(2, 7));
+        assert!(
+            crate::to_value_with_opt("a: &x 1\nb: *x\nc: &x 2\n", option)
+                .is_ok()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_alias_message() {
+        let e = crate::to_value("a: &y 1\nb: &x 2\nc: *z\n").unwrap_err();
+        assert_eq!(e.start_pos(), YamlPosition::new(3, 4));
+        assert_eq!(
+            e.msg(),
+            "Alias *z refers to undefined anchor, known anchors: &x, &y"
+        );
+    }
+}
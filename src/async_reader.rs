@@ -0,0 +1,105 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::de::DeserializeOwned;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::{YamlError, from_slice};
+
+const READ_CHUNK_SIZE: usize = 8192;
+
+/// Deserialize YAML stream from [tokio::io::AsyncRead] without blocking
+/// the runtime thread. The stream is read in chunks until EOF, then parsed
+/// like [crate::from_slice()].
+pub async fn from_async_reader<R, T>(mut reader: R) -> Result<T, YamlError>
+where
+    R: AsyncRead + Unpin,
+    T: DeserializeOwned,
+{
+    let mut buffer: Vec<u8> = Vec::new();
+    loop {
+        buffer.reserve(READ_CHUNK_SIZE);
+        let read = reader.read_buf(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        trace!("Read {read} bytes of YAML stream");
+    }
+    from_slice(&buffer)
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        io::Cursor,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    use serde::Deserialize;
+    use tokio::io::ReadBuf;
+
+    use super::*;
+    use crate::ErrorKind;
+
+    // Reader returning at most 3 bytes each time, then fails if requested.
+    struct SlowReader {
+        data: Cursor<Vec<u8>>,
+        fail_at_end: bool,
+    }
+
+    impl AsyncRead for SlowReader {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<std::io::Result<()>> {
+            let pos = self.data.position() as usize;
+            let data = self.data.get_ref();
+            if pos >= data.len() && self.fail_at_end {
+                return Poll::Ready(Err(std::io::Error::other("broken")));
+            }
+            let end = data.len().min(pos + 3).min(pos + buf.remaining());
+            buf.put_slice(&data[pos..end]);
+            self.data.set_position(end as u64);
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Foo {
+        a: Vec<u32>,
+        b: String,
+    }
+
+    #[tokio::test]
+    async fn test_from_async_reader() -> Result<(), YamlError> {
+        let reader = SlowReader {
+            data: Cursor::new(
+                b"a: [1, 2]\nb: \xe4\xbd\xa0\xe5\xa5\xbd\n".to_vec(),
+            ),
+            fail_at_end: false,
+        };
+        let value: Foo = from_async_reader(reader).await?;
+        assert_eq!(
+            value,
+            Foo {
+                a: vec![1, 2],
+                b: "你好".into(),
+            }
+        );
+
+        let value: Vec<u8> = from_async_reader(&b"[1, 2]"[..]).await?;
+        assert_eq!(value, vec![1, 2]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_from_async_reader_io_error() {
+        let reader = SlowReader {
+            data: Cursor::new(b"a: b\n".to_vec()),
+            fail_at_end: true,
+        };
+        let result: Result<Foo, YamlError> = from_async_reader(reader).await;
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::Io);
+    }
+}
@@ -0,0 +1,372 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::cmp::Ordering;
+
+use crate::{YamlScalarStyle, YamlValue, YamlValueData, YamlValueMap};
+
+// Scalar interpreted by YAML 1.2.2 core schema, ordered as
+// null < bool < number < string.
+#[derive(Debug)]
+enum YamlScalarKind<'a> {
+    Null,
+    Bool(bool),
+    Integer(i128),
+    Float(f64),
+    String(&'a str),
+}
+
+impl<'a> YamlScalarKind<'a> {
+    // Quoted and block scalars are always string.
+    fn new(s: &'a str, style: YamlScalarStyle) -> Self {
+        if style != YamlScalarStyle::Plain {
+            return Self::String(s);
+        }
+        match s {
+            "" | "~" | "null" | "Null" | "NULL" => Self::Null,
+            "true" | "True" | "TRUE" => Self::Bool(true),
+            "false" | "False" | "FALSE" => Self::Bool(false),
+            _ => {
+                let value = YamlValue::from(s);
+                if value.is_integer() {
+                    if let Ok(i) = value.as_u64() {
+                        return Self::Integer(i.into());
+                    }
+                } else if value.is_signed_integer()
+                    && let Ok(i) = value.as_i64()
+                {
+                    return Self::Integer(i.into());
+                }
+                if s.chars().any(|c| c.is_ascii_digit())
+                    && s.chars().all(|c| "0123456789+-.eE".contains(c))
+                    && let Ok(f) = s.parse::<f64>()
+                {
+                    Self::Float(f)
+                } else {
+                    Self::String(s)
+                }
+            }
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            Self::Null => 0,
+            Self::Bool(_) => 1,
+            Self::Integer(_) | Self::Float(_) => 2,
+            Self::String(_) => 3,
+        }
+    }
+
+    fn canonical_cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Bool(a), Self::Bool(b)) => a.cmp(b),
+            (Self::Integer(a), Self::Integer(b)) => a.cmp(b),
+            (Self::Integer(a), Self::Float(b)) => (*a as f64).total_cmp(b),
+            (Self::Float(a), Self::Integer(b)) => a.total_cmp(&(*b as f64)),
+            (Self::Float(a), Self::Float(b)) => a.total_cmp(b),
+            (Self::String(a), Self::String(b)) => a.cmp(b),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+fn data_rank(data: &YamlValueData, style: YamlScalarStyle) -> u8 {
+    match data {
+        YamlValueData::Null => 0,
+        YamlValueData::String(s) => YamlScalarKind::new(s, style).rank(),
+        YamlValueData::Array(_) => 4,
+        YamlValueData::Map(_) => 5,
+        YamlValueData::Tag(_) => 6,
+    }
+}
+
+// Map is compared as entries sorted by key, because the order of map entries
+// is not considered by equality.
+fn sorted_entries(map: &YamlValueMap) -> Vec<(&YamlValue, &YamlValue)> {
+    let mut entries: Vec<(&YamlValue, &YamlValue)> = map.iter().collect();
+    entries.sort_by(|a, b| a.0.canonical_cmp(b.0));
+    entries
+}
+
+// Style of nested nodes is still considered, only the type of top level
+// scalar is resolved as plain, e.g. map key `'5'` is equal to `5`.
+pub(crate) fn canonical_cmp_data(
+    a: &YamlValueData,
+    b: &YamlValueData,
+) -> Ordering {
+    canonical_cmp_styled(a, YamlScalarStyle::Plain, b, YamlScalarStyle::Plain)
+}
+
+// The style of tagged node is the style of its content.
+fn canonical_cmp_styled(
+    a: &YamlValueData,
+    a_style: YamlScalarStyle,
+    b: &YamlValueData,
+    b_style: YamlScalarStyle,
+) -> Ordering {
+    match (a, b) {
+        (YamlValueData::String(a_str), YamlValueData::String(b_str)) => {
+            YamlScalarKind::new(a_str, a_style)
+                .canonical_cmp(&YamlScalarKind::new(b_str, b_style))
+                // Different literal of the same value, e.g. `0x10` and `16`
+                .then_with(|| a_str.cmp(b_str))
+        }
+        (YamlValueData::Array(a_items), YamlValueData::Array(b_items)) => {
+            for (a_item, b_item) in a_items.iter().zip(b_items.iter()) {
+                let ret = a_item.canonical_cmp(b_item);
+                if ret != Ordering::Equal {
+                    return ret;
+                }
+            }
+            a_items.len().cmp(&b_items.len())
+        }
+        (YamlValueData::Map(a_map), YamlValueData::Map(b_map)) => {
+            let a_entries = sorted_entries(a_map);
+            let b_entries = sorted_entries(b_map);
+            for ((a_key, a_value), (b_key, b_value)) in
+                a_entries.iter().zip(b_entries.iter())
+            {
+                let ret = a_key
+                    .canonical_cmp(b_key)
+                    .then_with(|| a_value.canonical_cmp(b_value));
+                if ret != Ordering::Equal {
+                    return ret;
+                }
+            }
+            a_entries.len().cmp(&b_entries.len())
+        }
+        (YamlValueData::Tag(a_tag), YamlValueData::Tag(b_tag)) => {
+            a_tag.name.cmp(&b_tag.name).then_with(|| {
+                canonical_cmp_styled(&a_tag.data, a_style, &b_tag.data, b_style)
+            })
+        }
+        // `YamlValueData::Null` is equal to null string in value, but not
+        // equal in data.
+        (YamlValueData::Null, YamlValueData::Null) => Ordering::Equal,
+        (YamlValueData::Null, YamlValueData::String(_)) => {
+            data_rank(a, a_style)
+                .cmp(&data_rank(b, b_style))
+                .then(Ordering::Less)
+        }
+        (YamlValueData::String(_), YamlValueData::Null) => {
+            data_rank(a, a_style)
+                .cmp(&data_rank(b, b_style))
+                .then(Ordering::Greater)
+        }
+        _ => data_rank(a, a_style).cmp(&data_rank(b, b_style)),
+    }
+}
+
+// Only invoked when `canonical_cmp_data()` is equal, hence both data should
+// be in the same layout.
+fn position_cmp_data(a: &YamlValueData, b: &YamlValueData) -> Ordering {
+    match (a, b) {
+        (YamlValueData::Array(a_items), YamlValueData::Array(b_items)) => {
+            for (a_item, b_item) in a_items.iter().zip(b_items.iter()) {
+                let ret = position_cmp(a_item, b_item);
+                if ret != Ordering::Equal {
+                    return ret;
+                }
+            }
+            Ordering::Equal
+        }
+        (YamlValueData::Map(a_map), YamlValueData::Map(b_map)) => {
+            for ((a_key, a_value), (b_key, b_value)) in
+                sorted_entries(a_map).into_iter().zip(sorted_entries(b_map))
+            {
+                let ret = position_cmp(a_key, b_key)
+                    .then_with(|| position_cmp(a_value, b_value));
+                if ret != Ordering::Equal {
+                    return ret;
+                }
+            }
+            Ordering::Equal
+        }
+        (YamlValueData::Tag(a_tag), YamlValueData::Tag(b_tag)) => {
+            position_cmp_data(&a_tag.data, &b_tag.data)
+        }
+        _ => Ordering::Equal,
+    }
+}
+
+// Positions and scalar styles, which are not compared by
+// `canonical_cmp_data()` but by PartialEq.
+fn position_cmp(a: &YamlValue, b: &YamlValue) -> Ordering {
+    a.start
+        .cmp(&b.start)
+        .then_with(|| a.end.cmp(&b.end))
+        .then_with(|| style_rank(a.style).cmp(&style_rank(b.style)))
+        .then_with(|| position_cmp_data(&a.data, &b.data))
+}
+
+fn style_rank(style: YamlScalarStyle) -> u8 {
+    match style {
+        YamlScalarStyle::Plain => 0,
+        YamlScalarStyle::SingleQuoted => 1,
+        YamlScalarStyle::DoubleQuoted => 2,
+        YamlScalarStyle::Literal => 3,
+        YamlScalarStyle::Folded => 4,
+    }
+}
+
+impl YamlValue {
+    /// Compare the value without caring the position.
+    /// Scalars are compared by their type in YAML core schema: null < bool
+    /// < number < string, then sequence < map < tagged node. Quoted and
+    /// block scalars are always string, e.g. `'10'` is not a number.
+    /// Numbers are compared by numeric value regardless of the notation.
+    /// Map entries are compared in the order of sorted keys.
+    pub fn canonical_cmp(&self, other: &Self) -> Ordering {
+        canonical_cmp_styled(&self.data, self.style, &other.data, other.style)
+    }
+}
+
+impl Ord for YamlValue {
+    /// Identical to [YamlValue::canonical_cmp()] with positions and scalar
+    /// styles as the tiebreaker to be consistent with [PartialEq].
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.canonical_cmp(other)
+            .then_with(|| position_cmp(self, other))
+    }
+}
+
+impl PartialOrd for YamlValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for YamlValueData {
+    fn cmp(&self, other: &Self) -> Ordering {
+        canonical_cmp_data(self, other)
+            .then_with(|| position_cmp_data(self, other))
+    }
+}
+
+impl PartialOrd for YamlValueData {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{YamlError, YamlPosition};
+
+    #[test]
+    fn test_cmp_scalar_kind() {
+        let mut values: Vec<YamlValue> = vec![
+            YamlValue::from("abc"),
+            YamlValue::from(vec![1u8]),
+            YamlValue::from(2.5f64),
+            YamlValue::from(0x10u32),
+            YamlValue::from("0x0f"),
+            YamlValue::from(-3i8),
+            YamlValue::from(true),
+            YamlValue::from(false),
+            YamlValue::from(None::<u8>),
+        ];
+        values.sort();
+
+        assert_eq!(
+            values,
+            vec![
+                YamlValue::from(None::<u8>),
+                YamlValue::from(false),
+                YamlValue::from(true),
+                YamlValue::from(-3i8),
+                YamlValue::from(2.5f64),
+                YamlValue::from("0x0f"),
+                YamlValue::from(0x10u32),
+                YamlValue::from("abc"),
+                YamlValue::from(vec![1u8]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cmp_map_ignore_entry_order() -> Result<(), YamlError> {
+        let a = crate::to_value("a: 1\nb:\n  - 2\n")?;
+        let b = crate::to_value("b:\n  - 2\na: 1\n")?;
+        let c = crate::to_value("a: 1\nb:\n  - 3\n")?;
+
+        assert_eq!(a.canonical_cmp(&b), Ordering::Equal);
+        assert_eq!(a.canonical_cmp(&c), Ordering::Less);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ord_consistent_with_eq() {
+        let a = YamlValue::from("abc");
+        let mut b = YamlValue::from("abc");
+        b.start = YamlPosition::new(2, 1);
+
+        assert_eq!(a.canonical_cmp(&b), Ordering::Equal);
+        assert_eq!(a.cmp(&b), Ordering::Less);
+        assert_eq!(BTreeSet::from([a.clone(), b, a]).len(), 2);
+    }
+
+    fn styled(s: &str, style: YamlScalarStyle) -> YamlValue {
+        YamlValue {
+            style,
+            ..YamlValue::from(s)
+        }
+    }
+
+    #[test]
+    fn test_cmp_quoted_scalar_as_string() {
+        let quoted_true = styled("true", YamlScalarStyle::SingleQuoted);
+        let quoted_num = styled("10", YamlScalarStyle::DoubleQuoted);
+
+        assert_eq!(
+            quoted_true.canonical_cmp(&YamlValue::from(true)),
+            Ordering::Greater
+        );
+        assert_eq!(
+            quoted_num.canonical_cmp(&YamlValue::from(10u8)),
+            Ordering::Greater
+        );
+        assert_eq!(
+            quoted_num.canonical_cmp(&YamlValue::from("a")),
+            Ordering::Less
+        );
+        assert_eq!(
+            styled("a", YamlScalarStyle::Literal)
+                .canonical_cmp(&YamlValue::from("a")),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn test_cmp_equal_implies_eq() -> Result<(), YamlError> {
+        let mut values = vec![
+            YamlValue::from("a"),
+            styled("a", YamlScalarStyle::SingleQuoted),
+            styled("a", YamlScalarStyle::DoubleQuoted),
+            YamlValue::from("10"),
+            styled("10", YamlScalarStyle::SingleQuoted),
+            YamlValue::from(""),
+            YamlValue::from(None::<u8>),
+            crate::to_value("- a\n- 'a'\n")?,
+            crate::to_value("- 'a'\n- a\n")?,
+            crate::to_value("[a, 'a']")?,
+            crate::to_value("a: 'b'\n")?,
+            crate::to_value("a: b\n")?,
+            crate::to_value("'a': b\n")?,
+            crate::to_value("!!str 10")?,
+            crate::to_value("!!str '10'")?,
+        ];
+        values.extend(values.clone());
+        for a in &values {
+            for b in &values {
+                assert_eq!(a.cmp(b).is_eq(), a == b, "{a:?} {b:?}");
+            }
+        }
+        Ok(())
+    }
+}
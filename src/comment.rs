@@ -0,0 +1,179 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+
+use crate::{
+    YamlError, YamlLexer, YamlPosition, YamlTokenKind,
+    deserializer::collect_positions, parse_documents, scanner::normalize_input,
+};
+
+/// Comment in YAML stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct YamlComment {
+    /// Text after `#` with trailing white spaces removed.
+    pub text: String,
+    /// Position of `#`.
+    pub position: YamlPosition,
+    /// Index of the document holding the node this comment attached to.
+    pub document: usize,
+    /// Key path of the node this comment attached to, None if no node
+    /// found. The key path is map keys and sequence indexes joined by `.`,
+    /// the document root is empty string.
+    pub attached_to: Option<String>,
+}
+
+/// Extract all comments of YAML stream. The node of comment is chosen
+/// heuristically:
+///     * Innermost node starting in the same line before the comment.
+///     * Outermost node starting at the next line with node and not less
+///       indented than the comment.
+///     * The last node before the comment.
+///
+/// Comments are the [YamlTokenKind::Comment] tokens of [YamlLexer], hence
+/// invalid YAML is not an error but the comments are not attached to any
+/// node.
+pub fn comments(input: &str) -> Result<Vec<YamlComment>, YamlError> {
+    let input = normalize_input(input)?;
+    let docs = parse_documents(&input).unwrap_or_default();
+
+    // (start, key path, document index) of all nodes
+    let mut nodes: Vec<(YamlPosition, String, usize)> = Vec::new();
+    for (index, doc) in docs.iter().enumerate() {
+        let mut positions = HashMap::new();
+        collect_positions(&doc.root, String::new(), &mut positions);
+        nodes.extend(
+            positions
+                .into_iter()
+                .map(|(path, (start, _))| (start, path, index)),
+        );
+    }
+    nodes.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.len().cmp(&b.1.len())));
+
+    let lines: Vec<&str> = input.lines().collect();
+    let mut ret = Vec::new();
+    for token in YamlLexer::new(&input)? {
+        if token.kind != YamlTokenKind::Comment {
+            continue;
+        }
+        let pos = token.start;
+        let line = lines.get(pos.line - 1).copied().unwrap_or_default();
+        let text = line
+            .chars()
+            .skip(pos.column)
+            .collect::<String>()
+            .trim_end()
+            .to_string();
+        let is_trailing = line
+            .chars()
+            .take(pos.column - 1)
+            .any(|c| !c.is_whitespace());
+        let (document, attached_to) =
+            match attach_node(&nodes, pos, is_trailing) {
+                Some((_, path, document)) => {
+                    (*document, Some(path.to_string()))
+                }
+                None => (0, None),
+            };
+        ret.push(YamlComment {
+            text,
+            position: pos,
+            document,
+            attached_to,
+        });
+    }
+    Ok(ret)
+}
+
+fn attach_node(
+    nodes: &[(YamlPosition, String, usize)],
+    pos: YamlPosition,
+    is_trailing: bool,
+) -> Option<&(YamlPosition, String, usize)> {
+    if is_trailing
+        && let Some(node) = nodes
+            .iter()
+            .rfind(|(start, _, _)| start.line == pos.line && *start < pos)
+    {
+        return Some(node);
+    }
+    if let Some((next_start, _, _)) =
+        nodes.iter().find(|(start, _, _)| start.line > pos.line)
+    {
+        let next_line_nodes = nodes
+            .iter()
+            .filter(|(start, _, _)| start.line == next_start.line);
+        return next_line_nodes
+            .clone()
+            .find(|(start, _, _)| start.column >= pos.column)
+            .or_else(|| next_line_nodes.clone().next());
+    }
+    nodes.iter().rfind(|(start, _, _)| *start < pos)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_comments() -> Result<(), YamlError> {
+        let comments = comments(
+            r#"# Network config
+interfaces:
+  # The first NIC
+  - name: eth0 # main
+    desc: "not # comment"
+    script: |
+      echo 1 # not comment
+  - name: eth1
+dns: # servers
+  - 8.8.8.8
+---
+# Second
+a: b#c
+"#,
+        )?;
+        let result: Vec<(&str, YamlPosition, usize, Option<&str>)> = comments
+            .iter()
+            .map(|c| {
+                (
+                    c.text.as_str(),
+                    c.position,
+                    c.document,
+                    c.attached_to.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            result,
+            vec![
+                (" Network config", YamlPosition::new(1, 1), 0, Some("")),
+                (
+                    " The first NIC",
+                    YamlPosition::new(3, 3),
+                    0,
+                    Some("interfaces.0")
+                ),
+                (
+                    " main",
+                    YamlPosition::new(4, 16),
+                    0,
+                    Some("interfaces.0.name")
+                ),
+                (" servers", YamlPosition::new(9, 6), 0, Some("dns")),
+                (" Second", YamlPosition::new(12, 1), 1, Some("")),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_comments_of_invalid_yaml() -> Result<(), YamlError> {
+        let comments = comments("a: [b, 'c # d' # e\n")?;
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].text, " e");
+        assert_eq!(comments[0].position, YamlPosition::new(1, 16));
+        assert_eq!(comments[0].attached_to, None);
+        Ok(())
+    }
+}
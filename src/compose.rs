@@ -1,19 +1,147 @@
 // SPDX-License-Identifier: Apache-2.0
 
+use std::collections::HashMap;
+
 use crate::{
-    ErrorKind, YamlError, YamlEvent, YamlEventIter, YamlPosition, YamlTag,
-    YamlValue, YamlValueData, YamlValueMap,
+    ErrorKind, YamlError, YamlEvent, YamlEventIter, YamlParseOption,
+    YamlPosition, YamlScalarStyle, YamlTag, YamlValue, YamlValueData,
+    YamlValueMap,
 };
 
 impl YamlValue {
     pub(crate) fn compose(events: Vec<YamlEvent>) -> Result<Self, YamlError> {
+        Self::compose_with_opt(
+            events,
+            &YamlParseOption::default(),
+            &mut Vec::new(),
+        )
+    }
+
+    /// Compose value of single document from events, e.g. events of
+    /// [crate::YamlDocumentEventParser] filtered or transformed by application.
+    /// The stream and document events are optional.
+    pub fn from_events<I>(events: I) -> Result<Self, YamlError>
+    where
+        I: IntoIterator<Item = YamlEvent>,
+    {
+        let events: Vec<YamlEvent> = events.into_iter().collect();
+        validate_event_order(&events)?;
+        Self::compose(events)
+    }
+
+    pub(crate) fn compose_with_opt(
+        events: Vec<YamlEvent>,
+        option: &YamlParseOption,
+        warnings: &mut Vec<YamlError>,
+    ) -> Result<Self, YamlError> {
         let mut events_iter = YamlEventIter::new(events);
-        compose_value(&mut events_iter)
+        let mut anchors = YamlAnchors {
+            deny_duplicate: option.deny_duplicate_anchor,
+            ..Default::default()
+        };
+        let value = compose_value(&mut events_iter, &mut anchors)?;
+        expect_no_trailing_events(&mut events_iter)?;
+        warnings.append(&mut anchors.warnings);
+        Ok(value)
+    }
+}
+
+// Anchored nodes for alias to copy from.
+#[derive(Debug, Default)]
+struct YamlAnchors {
+    // Node and whether it is referred by any alias
+    nodes: HashMap<String, (YamlValue, bool)>,
+    deny_duplicate: bool,
+    warnings: Vec<YamlError>,
+}
+
+// Events not generated by parser might be unbalanced.
+fn validate_event_order(events: &[YamlEvent]) -> Result<(), YamlError> {
+    // Whether is map, node count and start position of collections we are
+    // currently in.
+    let mut collections: Vec<(bool, usize, YamlPosition)> = Vec::new();
+    let error = |msg: String, pos: YamlPosition| {
+        Err(YamlError::new(ErrorKind::InvalidEventOrder, msg, pos, pos))
+    };
+    for event in events {
+        if let Some((_, count, _)) = collections.last_mut()
+            && !matches!(
+                event,
+                YamlEvent::SequenceEnd(_) | YamlEvent::MapEnd(_)
+            )
+        {
+            *count += 1;
+        }
+        match event {
+            YamlEvent::SequenceStart(_, _, pos) => {
+                collections.push((false, 0, *pos))
+            }
+            YamlEvent::MapStart(_, _, pos) => collections.push((true, 0, *pos)),
+            YamlEvent::SequenceEnd(pos) => match collections.pop() {
+                Some((false, _, _)) => (),
+                _ => {
+                    return error(
+                        format!("Unexpected sequence end {event}"),
+                        *pos,
+                    );
+                }
+            },
+            YamlEvent::MapEnd(pos) => match collections.pop() {
+                Some((true, count, _)) if count % 2 == 0 => (),
+                Some((true, _, start)) => {
+                    return error(
+                        format!("Map started at {start} has key without value"),
+                        *pos,
+                    );
+                }
+                _ => {
+                    return error(format!("Unexpected map end {event}"), *pos);
+                }
+            },
+            _ => (),
+        }
+    }
+    if let Some((_, _, start)) = collections.last() {
+        return error(
+            format!("Collection started at {start} is not ended"),
+            *start,
+        );
     }
+    Ok(())
+}
+
+// All events after the root node should be document or stream end.
+fn expect_no_trailing_events(
+    events_iter: &mut YamlEventIter,
+) -> Result<(), YamlError> {
+    while let Some(event) = events_iter.next() {
+        match event {
+            YamlEvent::DocumentEnd(_, _) | YamlEvent::StreamEnd => (),
+            YamlEvent::DocumentStart(_, pos) => {
+                return Err(YamlError::new(
+                    ErrorKind::NoSupportMultipleDocuments,
+                    "No support of multiple YAML documents".to_string(),
+                    pos,
+                    pos,
+                ));
+            }
+            event => {
+                let pos = event.position();
+                return Err(YamlError::new(
+                    ErrorKind::TrailingContent,
+                    format!("Unexpected content {event} after document root"),
+                    pos,
+                    pos,
+                ));
+            }
+        }
+    }
+    Ok(())
 }
 
 fn compose_value(
     events_iter: &mut YamlEventIter,
+    anchors: &mut YamlAnchors,
 ) -> Result<YamlValue, YamlError> {
     let mut doc_started_pos: Option<YamlPosition> = None;
     while let Some(event) = events_iter.next() {
@@ -34,20 +162,12 @@ fn compose_value(
             YamlEvent::DocumentEnd(_, _) | YamlEvent::StreamEnd => {
                 break;
             }
-            YamlEvent::SequenceStart(tag, pos) => {
-                let array = compose_sequence(events_iter, pos)?;
+            YamlEvent::SequenceStart(anchor, tag, pos) => {
+                let mut array = compose_sequence(events_iter, anchors, pos)?;
                 if let Some(tag) = tag {
-                    return Ok(YamlValue {
-                        data: YamlValueData::Tag(Box::new(YamlTag {
-                            name: tag,
-                            data: array.data,
-                        })),
-                        start: array.start,
-                        end: array.end,
-                    });
-                } else {
-                    return Ok(array);
+                    array = compose_tagged_collection(tag, array)?;
                 }
+                return register_anchor(anchors, anchor, array);
             }
             YamlEvent::SequenceEnd(pos) => {
                 return Err(YamlError::new(
@@ -62,20 +182,12 @@ fn compose_value(
                     pos,
                 ));
             }
-            YamlEvent::MapStart(tag, pos) => {
-                let map = compose_map(events_iter, pos)?;
+            YamlEvent::MapStart(anchor, tag, pos) => {
+                let mut map = compose_map(events_iter, anchors, pos)?;
                 if let Some(tag) = tag {
-                    return Ok(YamlValue {
-                        data: YamlValueData::Tag(Box::new(YamlTag {
-                            name: tag,
-                            data: map.data,
-                        })),
-                        start: map.start,
-                        end: map.end,
-                    });
-                } else {
-                    return Ok(map);
+                    map = compose_tagged_collection(tag, map)?;
                 }
+                return register_anchor(anchors, anchor, map);
             }
             YamlEvent::MapEnd(pos) => {
                 return Err(YamlError::new(
@@ -90,23 +202,53 @@ fn compose_value(
                     pos,
                 ));
             }
-            YamlEvent::Scalar(tag, val, start, end) => {
-                if let Some(tag) = tag {
-                    return Ok(YamlValue {
-                        data: YamlValueData::Tag(Box::new(YamlTag {
-                            name: tag,
-                            data: YamlValueData::String(val),
-                        })),
-                        start,
-                        end,
-                    });
-                } else {
-                    return Ok(YamlValue {
+            YamlEvent::Scalar(anchor, tag, style, val, start, end) => {
+                let data = if let Some(tag) = tag {
+                    YamlValueData::Tag(Box::new(YamlTag {
+                        name: tag,
                         data: YamlValueData::String(val),
+                    }))
+                } else {
+                    YamlValueData::String(val)
+                };
+                return register_anchor(
+                    anchors,
+                    anchor,
+                    YamlValue {
+                        data,
                         start,
                         end,
-                    });
-                }
+                        style,
+                    },
+                );
+            }
+            YamlEvent::Alias(name, pos) => {
+                return if let Some((value, used)) = anchors.nodes.get_mut(&name)
+                {
+                    *used = true;
+                    Ok(value.clone())
+                } else {
+                    let mut known: Vec<String> =
+                        anchors.nodes.keys().map(|n| format!("&{n}")).collect();
+                    known.sort();
+                    Err(YamlError::new(
+                        ErrorKind::UnknownAlias,
+                        if known.is_empty() {
+                            format!(
+                                "Alias *{name} refers to undefined anchor, no \
+                                 anchor defined before it"
+                            )
+                        } else {
+                            format!(
+                                "Alias *{name} refers to undefined anchor, \
+                                 known anchors: {}",
+                                known.join(", ")
+                            )
+                        },
+                        pos,
+                        pos,
+                    ))
+                };
             }
         }
     }
@@ -114,11 +256,130 @@ fn compose_value(
     Ok(Default::default())
 }
 
+// Later anchor with the same name overrides the previous one. Redefining
+// anchor never referred is likely a mistake.
+fn register_anchor(
+    anchors: &mut YamlAnchors,
+    anchor: Option<String>,
+    value: YamlValue,
+) -> Result<YamlValue, YamlError> {
+    if let Some(anchor) = anchor {
+        if let Some((previous, false)) = anchors.nodes.get(&anchor) {
+            let msg = format!(
+                "Anchor &{anchor} is redefined before used, previously \
+                 defined at {}",
+                previous.start
+            );
+            let e = YamlError::new(
+                ErrorKind::DuplicateAnchor,
+                msg,
+                value.start,
+                value.end,
+            );
+            if anchors.deny_duplicate {
+                return Err(e);
+            }
+            warn!("{} {}", e.start_pos(), e.msg());
+            anchors.warnings.push(e);
+        }
+        anchors.nodes.insert(anchor, (value.clone(), false));
+    }
+    Ok(value)
+}
+
+// YAML 1.1 types defined in https://yaml.org/type/
+const TAG_SET: &str = "<tag:yaml.org,2002:set>";
+const TAG_OMAP: &str = "<tag:yaml.org,2002:omap>";
+const TAG_PAIRS: &str = "<tag:yaml.org,2002:pairs>";
+
+// The content of `!!set` should be map with null values, the content of
+// `!!omap` and `!!pairs` should be sequence of single pair maps.
+fn compose_tagged_collection(
+    tag: String,
+    value: YamlValue,
+) -> Result<YamlValue, YamlError> {
+    match (tag.as_str(), &value.data) {
+        (TAG_SET, YamlValueData::Map(map)) => {
+            if let Some((key, _)) = map.iter().find(|(_, v)| !v.is_null()) {
+                return Err(YamlError::new(
+                    ErrorKind::UnexpectedYamlNodeType,
+                    format!(
+                        "Expecting null value for !!set entry {}, but got \
+                         non-null value",
+                        key.key_path_name()
+                    ),
+                    key.start,
+                    key.end,
+                ));
+            }
+            Ok(tag_value(tag, value))
+        }
+        (TAG_OMAP | TAG_PAIRS, YamlValueData::Array(items)) => {
+            let mut keys: Vec<&YamlValue> = Vec::new();
+            for item in items {
+                let key = match &item.data {
+                    YamlValueData::Map(map) if map.len() == 1 => {
+                        map.keys().next()
+                    }
+                    _ => None,
+                };
+                let Some(key) = key else {
+                    return Err(YamlError::new(
+                        ErrorKind::UnexpectedYamlNodeType,
+                        format!(
+                            "Expecting single pair map as entry of {tag}, but \
+                             got {}",
+                            item.data
+                        ),
+                        item.start,
+                        item.end,
+                    ));
+                };
+                if tag == TAG_OMAP {
+                    if keys.iter().any(|k| k.canonical_cmp(key).is_eq()) {
+                        return Err(YamlError::new(
+                            ErrorKind::UnexpectedYamlNodeType,
+                            format!(
+                                "Duplicate key {} in !!omap",
+                                key.key_path_name()
+                            ),
+                            key.start,
+                            key.end,
+                        ));
+                    }
+                    keys.push(key);
+                }
+            }
+            Ok(tag_value(tag, value))
+        }
+        (TAG_SET | TAG_OMAP | TAG_PAIRS, data) => Err(YamlError::new(
+            ErrorKind::UnexpectedYamlNodeType,
+            format!("Unexpected node type for {tag}: {data}"),
+            value.start,
+            value.end,
+        )),
+        _ => Ok(tag_value(tag, value)),
+    }
+}
+
+fn tag_value(tag: String, value: YamlValue) -> YamlValue {
+    YamlValue {
+        data: YamlValueData::Tag(Box::new(YamlTag {
+            name: tag,
+            data: value.data,
+        })),
+        start: value.start,
+        end: value.end,
+        style: value.style,
+    }
+}
+
 fn compose_sequence(
     events_iter: &mut YamlEventIter,
+    anchors: &mut YamlAnchors,
     start_pos: YamlPosition,
 ) -> Result<YamlValue, YamlError> {
-    let mut ret: Vec<YamlValue> = Vec::new();
+    let mut ret: Vec<YamlValue> = Vec::with_capacity(events_iter.child_count());
     let mut end_pos = YamlPosition::default();
     while let Some(event) = events_iter.peek() {
         match event {
@@ -128,7 +389,7 @@ fn compose_sequence(
                 break;
             }
             _ => {
-                ret.push(compose_value(events_iter)?);
+                ret.push(compose_value(events_iter, anchors)?);
             }
         }
     }
@@ -137,14 +398,16 @@ fn compose_sequence(
         data: YamlValueData::Array(ret),
         start: start_pos,
         end: end_pos,
+        style: YamlScalarStyle::Plain,
     })
 }
 
 fn compose_map(
     events_iter: &mut YamlEventIter,
+    anchors: &mut YamlAnchors,
     start_pos: YamlPosition,
 ) -> Result<YamlValue, YamlError> {
-    let mut ret: YamlValueMap = YamlValueMap::new();
+    let mut ret = YamlValueMap::with_capacity(events_iter.child_count() / 2);
     let mut end_pos = YamlPosition::default();
     let mut key: Option<YamlValue> = None;
     while let Some(event) = events_iter.peek() {
@@ -156,10 +419,10 @@ fn compose_map(
             }
             _ => {
                 if let Some(key) = key.take() {
-                    let value = compose_value(events_iter)?;
+                    let value = compose_value(events_iter, anchors)?;
                     ret.insert(key, value);
                 } else {
-                    key = Some(compose_value(events_iter)?);
+                    key = Some(compose_value(events_iter, anchors)?);
                 }
             }
         }
@@ -169,6 +432,7 @@ fn compose_map(
         data: YamlValueData::Map(Box::new(ret)),
         start: start_pos,
         end: end_pos,
+        style: YamlScalarStyle::Plain,
     })
 }
 
@@ -177,6 +441,7 @@ mod test {
     use pretty_assertions::assert_eq;
 
     use super::*;
+    use crate::YamlParser;
 
     #[test]
     fn test_compose_single_scalar() {
@@ -185,6 +450,8 @@ mod test {
             YamlEvent::DocumentStart(false, YamlPosition::new(1, 1)),
             YamlEvent::Scalar(
                 None,
+                None,
+                YamlScalarStyle::Plain,
                 "abc".to_string(),
                 YamlPosition::new(1, 1),
                 YamlPosition::new(1, 3),
@@ -198,7 +465,8 @@ mod test {
             YamlValue {
                 data: YamlValueData::String("abc".to_string()),
                 start: YamlPosition::new(1, 1),
-                end: YamlPosition::new(1, 3)
+                end: YamlPosition::new(1, 3),
+                style: YamlScalarStyle::Plain,
             }
         );
     }
@@ -208,15 +476,19 @@ mod test {
         let events = vec![
             YamlEvent::StreamStart,
             YamlEvent::DocumentStart(false, YamlPosition::new(1, 1)),
-            YamlEvent::SequenceStart(None, YamlPosition::new(1, 1)),
+            YamlEvent::SequenceStart(None, None, YamlPosition::new(1, 1)),
             YamlEvent::Scalar(
                 None,
+                None,
+                YamlScalarStyle::Plain,
                 "abc".to_string(),
                 YamlPosition::new(1, 3),
                 YamlPosition::new(1, 5),
             ),
             YamlEvent::Scalar(
                 None,
+                None,
+                YamlScalarStyle::Plain,
                 "def".to_string(),
                 YamlPosition::new(2, 3),
                 YamlPosition::new(2, 5),
@@ -234,15 +506,18 @@ mod test {
                         data: YamlValueData::String("abc".into()),
                         start: YamlPosition::new(1, 3),
                         end: YamlPosition::new(1, 5),
+                        style: YamlScalarStyle::Plain,
                     },
                     YamlValue {
                         data: YamlValueData::String("def".into()),
                         start: YamlPosition::new(2, 3),
                         end: YamlPosition::new(2, 5),
+                        style: YamlScalarStyle::Plain,
                     }
                 ]),
                 start: YamlPosition::new(1, 1),
                 end: YamlPosition::new(2, 5),
+                style: YamlScalarStyle::Plain,
             }
         );
     }
@@ -252,15 +527,19 @@ mod test {
         let events = vec![
             YamlEvent::StreamStart,
             YamlEvent::DocumentStart(false, YamlPosition::new(1, 1)),
-            YamlEvent::MapStart(None, YamlPosition::new(1, 1)),
+            YamlEvent::MapStart(None, None, YamlPosition::new(1, 1)),
             YamlEvent::Scalar(
                 None,
+                None,
+                YamlScalarStyle::Plain,
                 "abc".to_string(),
                 YamlPosition::new(1, 3),
                 YamlPosition::new(1, 5),
             ),
             YamlEvent::Scalar(
                 None,
+                None,
+                YamlScalarStyle::Plain,
                 "def".to_string(),
                 YamlPosition::new(2, 3),
                 YamlPosition::new(2, 5),
@@ -276,11 +555,13 @@ mod test {
                 data: YamlValueData::String("abc".into()),
                 start: YamlPosition::new(1, 3),
                 end: YamlPosition::new(1, 5),
+                style: YamlScalarStyle::Plain,
             },
             YamlValue {
                 data: YamlValueData::String("def".into()),
                 start: YamlPosition::new(2, 3),
                 end: YamlPosition::new(2, 5),
+                style: YamlScalarStyle::Plain,
             },
         );
 
@@ -290,6 +571,7 @@ mod test {
                 data: YamlValueData::Map(Box::new(map)),
                 start: YamlPosition::new(1, 1),
                 end: YamlPosition::new(2, 5),
+                style: YamlScalarStyle::Plain,
             }
         );
     }
@@ -299,30 +581,38 @@ mod test {
         let events = vec![
             YamlEvent::StreamStart,
             YamlEvent::DocumentStart(false, YamlPosition::new(1, 1)),
-            YamlEvent::SequenceStart(None, YamlPosition::new(1, 1)),
-            YamlEvent::MapStart(None, YamlPosition::new(1, 1)),
+            YamlEvent::SequenceStart(None, None, YamlPosition::new(1, 1)),
+            YamlEvent::MapStart(None, None, YamlPosition::new(1, 1)),
             YamlEvent::Scalar(
                 None,
+                None,
+                YamlScalarStyle::Plain,
                 "abc".to_string(),
                 YamlPosition::new(1, 3),
                 YamlPosition::new(1, 5),
             ),
             YamlEvent::Scalar(
                 None,
+                None,
+                YamlScalarStyle::Plain,
                 "def".to_string(),
                 YamlPosition::new(1, 8),
                 YamlPosition::new(1, 10),
             ),
             YamlEvent::MapEnd(YamlPosition::new(1, 10)),
-            YamlEvent::MapStart(None, YamlPosition::new(2, 1)),
+            YamlEvent::MapStart(None, None, YamlPosition::new(2, 1)),
             YamlEvent::Scalar(
                 None,
+                None,
+                YamlScalarStyle::Plain,
                 "hig".to_string(),
                 YamlPosition::new(2, 3),
                 YamlPosition::new(2, 5),
             ),
             YamlEvent::Scalar(
                 None,
+                None,
+                YamlScalarStyle::Plain,
                 "klm".to_string(),
                 YamlPosition::new(2, 8),
                 YamlPosition::new(2, 10),
@@ -339,11 +629,13 @@ mod test {
                 data: YamlValueData::String("abc".into()),
                 start: YamlPosition::new(1, 3),
                 end: YamlPosition::new(1, 5),
+                style: YamlScalarStyle::Plain,
             },
             YamlValue {
                 data: YamlValueData::String("def".into()),
                 start: YamlPosition::new(1, 8),
                 end: YamlPosition::new(1, 10),
+                style: YamlScalarStyle::Plain,
             },
         );
         let mut map2 = YamlValueMap::new();
@@ -352,11 +644,13 @@ mod test {
                 data: YamlValueData::String("hig".into()),
                 start: YamlPosition::new(2, 3),
                 end: YamlPosition::new(2, 5),
+                style: YamlScalarStyle::Plain,
             },
             YamlValue {
                 data: YamlValueData::String("klm".into()),
                 start: YamlPosition::new(2, 8),
                 end: YamlPosition::new(2, 10),
+                style: YamlScalarStyle::Plain,
             },
         );
 
@@ -368,15 +662,18 @@ mod test {
                         data: YamlValueData::Map(Box::new(map1)),
                         start: YamlPosition::new(1, 1),
                         end: YamlPosition::new(1, 10),
+                        style: YamlScalarStyle::Plain,
                     },
                     YamlValue {
                         data: YamlValueData::Map(Box::new(map2)),
                         start: YamlPosition::new(2, 1),
                         end: YamlPosition::new(2, 10),
+                        style: YamlScalarStyle::Plain,
                     },
                 ]),
                 start: YamlPosition::new(1, 1),
                 end: YamlPosition::new(2, 10),
+                style: YamlScalarStyle::Plain,
             }
         );
     }
@@ -386,28 +683,36 @@ mod test {
         let events = vec![
             YamlEvent::StreamStart,
             YamlEvent::DocumentStart(false, YamlPosition::new(1, 1)),
-            YamlEvent::MapStart(None, YamlPosition::new(1, 1)),
+            YamlEvent::MapStart(None, None, YamlPosition::new(1, 1)),
             YamlEvent::Scalar(
                 None,
+                None,
+                YamlScalarStyle::Plain,
                 "abc".to_string(),
                 YamlPosition::new(1, 1),
                 YamlPosition::new(1, 3),
             ),
-            YamlEvent::SequenceStart(None, YamlPosition::new(2, 1)),
+            YamlEvent::SequenceStart(None, None, YamlPosition::new(2, 1)),
             YamlEvent::Scalar(
                 None,
+                None,
+                YamlScalarStyle::Plain,
                 "def".to_string(),
                 YamlPosition::new(2, 3),
                 YamlPosition::new(2, 5),
             ),
             YamlEvent::Scalar(
                 None,
+                None,
+                YamlScalarStyle::Plain,
                 "hig".to_string(),
                 YamlPosition::new(3, 3),
                 YamlPosition::new(3, 5),
             ),
             YamlEvent::Scalar(
                 None,
+                None,
+                YamlScalarStyle::Plain,
                 "klm".to_string(),
                 YamlPosition::new(4, 3),
                 YamlPosition::new(4, 5),
@@ -424,6 +729,7 @@ mod test {
                 data: YamlValueData::String("abc".into()),
                 start: YamlPosition::new(1, 1),
                 end: YamlPosition::new(1, 3),
+                style: YamlScalarStyle::Plain,
             },
             YamlValue {
                 data: YamlValueData::Array(vec![
@@ -431,20 +737,24 @@ mod test {
                         data: YamlValueData::String("def".into()),
                         start: YamlPosition::new(2, 3),
                         end: YamlPosition::new(2, 5),
+                        style: YamlScalarStyle::Plain,
                     },
                     YamlValue {
                         data: YamlValueData::String("hig".into()),
                         start: YamlPosition::new(3, 3),
                         end: YamlPosition::new(3, 5),
+                        style: YamlScalarStyle::Plain,
                     },
                     YamlValue {
                         data: YamlValueData::String("klm".into()),
                         start: YamlPosition::new(4, 3),
                         end: YamlPosition::new(4, 5),
+                        style: YamlScalarStyle::Plain,
                     },
                 ]),
                 start: YamlPosition::new(2, 1),
                 end: YamlPosition::new(4, 5),
+                style: YamlScalarStyle::Plain,
             },
         );
         assert_eq!(
@@ -453,7 +763,170 @@ mod test {
                 data: YamlValueData::Map(Box::new(map)),
                 start: YamlPosition::new(1, 1),
                 end: YamlPosition::new(4, 5),
+                style: YamlScalarStyle::Plain,
             }
         );
     }
+
+    #[test]
+    fn test_compose_trailing_content() {
+        let events = vec![
+            YamlEvent::StreamStart,
+            YamlEvent::DocumentStart(false, YamlPosition::new(1, 1)),
+            YamlEvent::Scalar(
+                None,
+                None,
+                YamlScalarStyle::Plain,
+                "abc".to_string(),
+                YamlPosition::new(1, 1),
+                YamlPosition::new(1, 3),
+            ),
+            YamlEvent::Scalar(
+                None,
+                None,
+                YamlScalarStyle::Plain,
+                "def".to_string(),
+                YamlPosition::new(2, 1),
+                YamlPosition::new(2, 3),
+            ),
+            YamlEvent::DocumentEnd(false, YamlPosition::new(2, 3)),
+            YamlEvent::StreamEnd,
+        ];
+
+        let e = YamlValue::compose(events).unwrap_err();
+
+        assert_eq!(e.kind(), ErrorKind::TrailingContent);
+        assert_eq!(e.start_pos(), YamlPosition::new(2, 1));
+    }
+
+    #[test]
+    fn test_compose_set_and_omap() {
+        let value = YamlValue::compose(
+            YamlParser::parse_to_events("--- !!set\n? a\n? b\n").unwrap(),
+        )
+        .unwrap();
+        let expected = crate::to_value("{a: , b: }").unwrap();
+        assert_eq!(value.tag(), Some("<tag:yaml.org,2002:set>"));
+        assert_eq!(
+            YamlValue::from(value.content().clone()).canonical_cmp(&expected),
+            std::cmp::Ordering::Equal
+        );
+
+        for tag in ["omap", "pairs"] {
+            let value = YamlValue::compose(
+                YamlParser::parse_to_events(&format!(
+                    "--- !!{tag}\n- b: 1\n- a: 2\n"
+                ))
+                .unwrap(),
+            )
+            .unwrap();
+            let expected = crate::to_value("[{b: 1}, {a: 2}]").unwrap();
+            assert_eq!(
+                YamlValue::from(value.content().clone())
+                    .canonical_cmp(&expected),
+                std::cmp::Ordering::Equal
+            );
+        }
+
+        let value = YamlValue::compose(
+            YamlParser::parse_to_events("--- !!pairs\n- a: 1\n- a: 2\n")
+                .unwrap(),
+        )
+        .unwrap();
+        assert!(matches!(value.content(), YamlValueData::Array(_)));
+    }
+
+    #[test]
+    fn test_compose_invalid_set_and_omap() {
+        for input in [
+            "--- !!set\na: 1\n",
+            "--- !!set\n- a\n",
+            "--- !!omap\n- a: 1\n- a: 2\n",
+            "--- !!omap\n- a: 1\n  b: 2\n",
+            "--- !!pairs\n- a\n",
+        ] {
+            let e =
+                YamlValue::compose(YamlParser::parse_to_events(input).unwrap())
+                    .unwrap_err();
+            assert_eq!(e.kind(), ErrorKind::UnexpectedYamlNodeType, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_from_events() -> Result<(), YamlError> {
+        // Drop the `password` entry
+        let mut skip = false;
+        let events = crate::YamlDocumentEventParser::new(
+            "user: foo\npassword: bar\nport: 22\n",
+        )?
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .filter(|event| {
+            let is_password = matches!(
+                event,
+                YamlEvent::Scalar(_, _, _, v, _, _) if v == "password"
+            );
+            let keep = !is_password && !skip;
+            skip = is_password;
+            keep
+        });
+        assert_eq!(
+            YamlValue::from_events(events)?
+                .canonical_cmp(&crate::to_value("user: foo\nport: 22\n")?),
+            std::cmp::Ordering::Equal
+        );
+
+        let scalar = |v: &str| {
+            YamlEvent::Scalar(
+                None,
+                None,
+                YamlScalarStyle::Plain,
+                v.to_string(),
+                YamlPosition::default(),
+                YamlPosition::default(),
+            )
+        };
+        let pos = YamlPosition::default();
+        for events in [
+            vec![YamlEvent::SequenceStart(None, None, pos), scalar("a")],
+            vec![scalar("a"), YamlEvent::MapEnd(pos)],
+            vec![
+                YamlEvent::MapStart(None, None, pos),
+                scalar("a"),
+                YamlEvent::MapEnd(pos),
+            ],
+            vec![
+                YamlEvent::MapStart(None, None, pos),
+                YamlEvent::SequenceEnd(pos),
+            ],
+        ] {
+            assert_eq!(
+                YamlValue::from_events(events.clone()).unwrap_err().kind(),
+                ErrorKind::InvalidEventOrder,
+                "{events:?}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_compose_capacity() -> Result<(), YamlError> {
+        let value = YamlValue::compose(YamlParser::parse_to_events(
+            "- &x [a, [b, c], d]\n- {e: *x, f: [g]}\n- []\n",
+        )?)?;
+        let YamlValueData::Array(items) = &value.data else {
+            panic!("Expecting sequence, got {value}");
+        };
+        assert_eq!(items.capacity(), 3);
+        let YamlValueData::Array(inner) = &items[0].data else {
+            panic!("Expecting sequence, got {}", items[0]);
+        };
+        assert_eq!(inner.capacity(), 3);
+        let YamlValueData::Map(map) = &items[1].data else {
+            panic!("Expecting map, got {}", items[1]);
+        };
+        assert_eq!(map.len(), 2);
+        assert!(map.capacity() >= 2);
+        Ok(())
+    }
 }
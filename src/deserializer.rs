@@ -5,29 +5,360 @@
 //      (https://github.com/serde-rs/serde-rs.github.io)
 // which is licensed under CC-BY-SA-4.0 license
 
-use std::str::FromStr;
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
 
 use serde::{
     Deserialize,
-    de::{Deserializer, Visitor},
+    de::{DeserializeOwned, Deserializer, Visitor},
 };
 
 use crate::{
-    ErrorKind, YamlError, YamlValue, YamlValueData, YamlValueEnumAccess,
-    YamlValueMapAccess, YamlValueSeqAccess,
+    ErrorKind, YamlError, YamlNumberKind, YamlParseOption, YamlParser,
+    YamlPosition, YamlResolvedScalar, YamlScalarSchema, YamlScalarStyle,
+    YamlValue, YamlValueData, YamlValueEnumAccess, YamlValueMap,
+    YamlValueMapAccess, YamlValueSeqAccess, decode_yaml_bytes,
+    number::NUMBER_TOKEN,
+    parse_option::prepare_input,
+    raw::{RAW_VALUE_TOKEN, raw_value_map},
+    resolve_scalar,
+    scanner::normalize_input,
+    sub_key_path,
+    version::{YamlVersion, document_version},
 };
 
 #[derive(Debug, Default)]
 pub struct YamlDeserializer {
     pub(crate) parsed: YamlValue,
+    pub(crate) ctx: Arc<YamlDeContext>,
+    // Entry of `!!omap` or `!!pairs`, single pair map could be 2-tuple
+    pub(crate) pair_entry: bool,
+    // Keep the parsed value for [YamlDeserializer::positions()] after
+    // deserializing, other nodes are moved out when deserialized.
+    keep_parsed: bool,
+}
+
+// Shared by deserializers of all nodes in the same document.
+#[derive(Debug, Default)]
+pub(crate) struct YamlDeContext {
+    // Normalized input, used by [crate::YamlRawValue] to capture source text
+    pub(crate) source: String,
+    // Errors recovered in collect-all-errors mode
+    pub(crate) errors: Option<Mutex<Vec<YamlError>>>,
+    // Map keys and sequence indexes of the node being deserialized
+    pub(crate) key_path: Mutex<Vec<String>>,
+}
+
+impl YamlDeContext {
+    // Run `f` deserializing child node named `segment`, errors without key
+    // path get the key path of that child.
+    pub(crate) fn in_child<T>(
+        &self,
+        segment: String,
+        f: impl FnOnce() -> Result<T, YamlError>,
+    ) -> Result<T, YamlError> {
+        if let Ok(mut key_path) = self.key_path.lock() {
+            key_path.push(segment);
+        }
+        let result = f().map_err(|e| e.or_key_path(|| self.key_path()));
+        if let Ok(mut key_path) = self.key_path.lock() {
+            key_path.pop();
+        }
+        result
+    }
+
+    fn key_path(&self) -> String {
+        self.key_path
+            .lock()
+            .map(|key_path| key_path.join("."))
+            .unwrap_or_default()
+    }
+}
+
+impl FromStr for YamlDeserializer {
+    type Err = YamlError;
+
+    fn from_str(input: &str) -> Result<Self, YamlError> {
+        let mut deserializer =
+            Self::new(input, false, &YamlParseOption::default())?;
+        deserializer.keep_parsed = true;
+        Ok(deserializer)
+    }
+}
+
+impl YamlDeserializer {
+    fn new(
+        input: &str,
+        collect_errors: bool,
+        option: &YamlParseOption,
+    ) -> Result<Self, YamlError> {
+        // Warnings are only logged
+        let mut warnings = Vec::new();
+        let source =
+            prepare_input(&normalize_input(input)?, option, &mut warnings)?
+                .to_string();
+        Ok(Self {
+            parsed: parse_value(&source, option, &mut warnings)?,
+            ctx: Arc::new(YamlDeContext {
+                source,
+                errors: collect_errors.then(|| Mutex::new(Vec::new())),
+                key_path: Mutex::new(Vec::new()),
+            }),
+            pair_entry: false,
+            keep_parsed: false,
+        })
+    }
+
+    // Deserializer of child node sharing the same context.
+    pub(crate) fn child(parsed: YamlValue, ctx: Arc<YamlDeContext>) -> Self {
+        Self {
+            parsed,
+            ctx,
+            pair_entry: false,
+            keep_parsed: false,
+        }
+    }
+
+    // Each node is deserialized once, hence the value is moved out instead
+    // of cloning the subtree at every level.
+    fn take(&mut self) -> YamlValue {
+        if self.keep_parsed {
+            self.parsed.clone()
+        } else {
+            std::mem::take(&mut self.parsed)
+        }
+    }
+
+    // Move out the content of tagged node with the position and style of
+    // the node, also return the tag name.
+    fn take_untagged(&mut self) -> (Option<String>, YamlValue) {
+        let YamlValue {
+            data,
+            start,
+            end,
+            style,
+        } = self.take();
+        let (tag, data) = match data {
+            YamlValueData::Tag(tag) => (Some(tag.name), tag.data),
+            data => (None, data),
+        };
+        (
+            tag,
+            YamlValue {
+                data,
+                start,
+                end,
+                style,
+            },
+        )
+    }
+
+    /// Start and end position of every node in parsed document indexed by
+    /// key path, useful for reporting error of deserialized data.
+    /// The key path is map keys and sequence indexes joined by `.`, for
+    /// example `interfaces.0.name`. The document root is empty string.
+    pub fn positions(&self) -> HashMap<String, (YamlPosition, YamlPosition)> {
+        let mut ret = HashMap::new();
+        collect_positions(&self.parsed, String::new(), &mut ret);
+        ret
+    }
+
+    /// Replace every node of the local tag, e.g. `secret` for `!secret`,
+    /// by the value returned from `handler` which gets the node with tag
+    /// removed. Useful for decoding application specific tags, should be
+    /// invoked before deserializing.
+    pub fn resolve_tag<F>(
+        &mut self,
+        tag: &str,
+        mut handler: F,
+    ) -> Result<(), YamlError>
+    where
+        F: FnMut(YamlValue) -> Result<YamlValue, YamlError>,
+    {
+        resolve_tag(&mut self.parsed, tag, &mut handler)
+    }
+
+    fn not_supported(&self, feature: &str) -> YamlError {
+        YamlError::new(
+            ErrorKind::NotSupported,
+            format!("Deserializing {feature} is not supported yet"),
+            self.parsed.start,
+            self.parsed.end,
+        )
+    }
+
+    // In collect-all-errors mode, record the error and continue with the
+    // fallback value, so that sibling nodes could still be deserialized.
+    fn recover<T>(
+        &self,
+        result: Result<T, YamlError>,
+        fallback: T,
+    ) -> Result<T, YamlError> {
+        match (result, self.ctx.errors.as_ref()) {
+            (Err(e), Some(errors)) => {
+                let key_path = self.ctx.key_path();
+                let e = if key_path.is_empty() {
+                    e
+                } else {
+                    e.or_key_path(|| key_path)
+                };
+                if let Ok(mut errors) = errors.lock() {
+                    errors.push(e);
+                }
+                Ok(fallback)
+            }
+            (result, _) => result,
+        }
+    }
+}
+
+pub(crate) fn resolve_tag<F>(
+    value: &mut YamlValue,
+    tag: &str,
+    handler: &mut F,
+) -> Result<(), YamlError>
+where
+    F: FnMut(YamlValue) -> Result<YamlValue, YamlError>,
+{
+    let data = match &mut value.data {
+        YamlValueData::Tag(t) if t.name == tag => {
+            let (start, end) = (value.start, value.end);
+            let untagged = YamlValue {
+                data: std::mem::take(&mut t.data),
+                start,
+                end,
+                style: value.style,
+            };
+            // Errors of the resolved value should point to the tagged node
+            *value = handler(untagged)?;
+            value.start = start;
+            value.end = end;
+            return Ok(());
+        }
+        YamlValueData::Tag(t) => &mut t.data,
+        data => data,
+    };
+    match data {
+        YamlValueData::Array(items) => {
+            for item in items {
+                resolve_tag(item, tag, handler)?;
+            }
+        }
+        YamlValueData::Map(map) => {
+            for (_, v) in map.iter_mut() {
+                resolve_tag(v, tag, handler)?;
+            }
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+pub(crate) fn collect_positions(
+    value: &YamlValue,
+    path: String,
+    positions: &mut HashMap<String, (YamlPosition, YamlPosition)>,
+) {
+    let data = if let YamlValueData::Tag(tag) = &value.data {
+        &tag.data
+    } else {
+        &value.data
+    };
+    match data {
+        YamlValueData::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                collect_positions(
+                    item,
+                    sub_key_path(&path, &index.to_string()),
+                    positions,
+                );
+            }
+        }
+        YamlValueData::Map(map) => {
+            for (key, item) in map.iter() {
+                collect_positions(
+                    item,
+                    sub_key_path(&path, &key.key_path_name()),
+                    positions,
+                );
+            }
+        }
+        _ => (),
+    }
+    positions.insert(path, (value.start, value.end));
 }
 
 pub fn from_str<'a, T>(s: &'a str) -> Result<T, YamlError>
 where
     T: Deserialize<'a>,
 {
-    let parsed = YamlValue::from_str(s)?;
-    let mut deserializer = YamlDeserializer { parsed };
+    let mut deserializer =
+        YamlDeserializer::new(s, false, &YamlParseOption::default())?;
+
+    T::deserialize(&mut deserializer)
+}
+
+pub fn from_str_with_opt<'a, T>(
+    s: &'a str,
+    option: YamlParseOption,
+) -> Result<T, YamlError>
+where
+    T: Deserialize<'a>,
+{
+    let mut deserializer = YamlDeserializer::new(s, false, &option)?;
+
+    T::deserialize(&mut deserializer)
+}
+
+/// Deserialize YAML string and return all errors found instead of the first
+/// one. Type mismatch of node is recorded and replaced by default value of
+/// desired type to continue with sibling nodes. Other errors, e.g.
+/// [ErrorKind::MissingField] reported at the map lacking the field, still
+/// stop the deserialization and the error is placed at the end. Other
+/// errors are sorted by position.
+pub fn from_str_collect_errors<'a, T>(s: &'a str) -> Result<T, Vec<YamlError>>
+where
+    T: Deserialize<'a>,
+{
+    let mut deserializer =
+        YamlDeserializer::new(s, true, &YamlParseOption::default())
+            .map_err(|e| vec![e])?;
+    let result = T::deserialize(&mut deserializer);
+    let mut errors = deserializer
+        .ctx
+        .errors
+        .as_ref()
+        .and_then(|errors| {
+            errors.lock().ok().map(|mut e| std::mem::take(&mut *e))
+        })
+        .unwrap_or_default();
+    // Deserialize implementation may visit nodes out of document order
+    errors.sort_by_key(|e| e.start_pos());
+    match result {
+        Ok(v) if errors.is_empty() => Ok(v),
+        Ok(_) => Err(errors),
+        Err(e) => {
+            errors.push(e);
+            Err(errors)
+        }
+    }
+}
+
+/// Deserialize YAML stream in UTF-8, UTF-16 or UTF-32 encoding.
+/// The encoding is detected by the byte order mark or the null bytes pattern
+/// of the first characters.
+pub fn from_slice<T>(input: &[u8]) -> Result<T, YamlError>
+where
+    T: DeserializeOwned,
+{
+    let mut deserializer = YamlDeserializer::new(
+        &decode_yaml_bytes(input)?,
+        false,
+        &YamlParseOption::default(),
+    )?;
 
     T::deserialize(&mut deserializer)
 }
@@ -36,6 +367,60 @@ pub fn to_value(input: &str) -> Result<YamlValue, YamlError> {
     YamlValue::from_str(input)
 }
 
+pub fn to_value_with_opt(
+    input: &str,
+    option: YamlParseOption,
+) -> Result<YamlValue, YamlError> {
+    Ok(to_value_with_warnings(input, option)?.0)
+}
+
+/// Same as [to_value_with_opt()] but also return the warnings, e.g. the
+/// [ErrorKind::TabIndentation] for [crate::YamlTabIndent::Warn] or
+/// [ErrorKind::DuplicateAnchor] when
+/// [YamlParseOption::deny_duplicate_anchor] is false, sorted by position. The
+/// warnings are otherwise only logged with the `logging` feature.
+pub fn to_value_with_warnings(
+    input: &str,
+    option: YamlParseOption,
+) -> Result<(YamlValue, Vec<YamlError>), YamlError> {
+    let mut warnings = Vec::new();
+    let value = parse_value(
+        &prepare_input(&normalize_input(input)?, &option, &mut warnings)?,
+        &option,
+        &mut warnings,
+    )?;
+    warnings.sort_by_key(|w| w.start_pos());
+    Ok((value, warnings))
+}
+
+// Compose the first document of prepared input, plain scalars are resolved
+// by the YAML version of the document.
+fn parse_value(
+    input: &str,
+    option: &YamlParseOption,
+    warnings: &mut Vec<YamlError>,
+) -> Result<YamlValue, YamlError> {
+    let mut value = YamlValue::compose_with_opt(
+        YamlParser::parse_to_events(input)?,
+        option,
+        warnings,
+    )?;
+    if document_version(input, option) == YamlVersion::V1_1 {
+        value.resolve_yaml_1_1();
+    }
+    Ok(value)
+}
+
+/// Start and end position of every node in YAML document indexed by key
+/// path, same as [YamlDeserializer::positions()] without deserializing.
+pub fn index(
+    input: &str,
+) -> Result<HashMap<String, (YamlPosition, YamlPosition)>, YamlError> {
+    let mut ret = HashMap::new();
+    collect_positions(&to_value(input)?, String::new(), &mut ret);
+    Ok(ret)
+}
+
 impl<'de> Deserializer<'de> for &mut YamlDeserializer {
     type Error = YamlError;
 
@@ -44,29 +429,60 @@ impl<'de> Deserializer<'de> for &mut YamlDeserializer {
         V: Visitor<'de>,
     {
         match &self.parsed.data {
-            YamlValueData::String(_) => {
-                if self.parsed.is_bool() {
-                    self.deserialize_bool(visitor)
-                } else if self.parsed.is_integer() {
+            YamlValueData::String(s) => match resolve_scalar(
+                s,
+                self.parsed.style,
+                YamlScalarSchema::Core,
+            ) {
+                YamlResolvedScalar::Null => visitor.visit_unit(),
+                YamlResolvedScalar::Bool(_) => self.deserialize_bool(visitor),
+                YamlResolvedScalar::Int(num)
+                    if num.kind() == YamlNumberKind::Unsigned =>
+                {
                     self.deserialize_u64(visitor)
-                } else if self.parsed.is_signed_integer() {
-                    self.deserialize_i64(visitor)
-                } else {
-                    self.deserialize_str(visitor)
                 }
-            }
+                YamlResolvedScalar::Int(_) => self.deserialize_i64(visitor),
+                YamlResolvedScalar::Float(_) => self.deserialize_f64(visitor),
+                // Self-describing consumers like `#[serde(untagged)]` keep
+                // the owned string
+                _ => self.deserialize_string(visitor),
+            },
+            YamlValueData::Null => visitor.visit_unit(),
             YamlValueData::Array(_) => self.deserialize_seq(visitor),
             YamlValueData::Map(_) => self.deserialize_map(visitor),
+            YamlValueData::Tag(tag) if tag.core_type() == Some("str") => {
+                self.deserialize_string(visitor)
+            }
+            YamlValueData::Tag(tag) if tag.core_type().is_some() => {
+                let (_, value) = self.take_untagged();
+                YamlDeserializer::child(value, self.ctx.clone())
+                    .deserialize_any(visitor)
+            }
+            // Self-describing consumers like `#[serde(flatten)]` buffer the
+            // content before knowing the type, enum variant in local tag is
+            // provided as single entry map `{variant: data}` which they
+            // accept as enum also.
             YamlValueData::Tag(_) => {
-                let access = YamlValueEnumAccess::new(self.parsed.clone());
-                visitor.visit_enum(access)
+                let (tag, value) = self.take_untagged();
+                let (start, end) = (value.start, value.end);
+                let mut map = YamlValueMap::new();
+                map.insert(
+                    YamlValue::from(YamlValueData::String(
+                        tag.unwrap_or_default(),
+                    )),
+                    value,
+                );
+                YamlDeserializer::child(
+                    YamlValue {
+                        data: YamlValueData::Map(Box::new(map)),
+                        start,
+                        end,
+                        style: YamlScalarStyle::Plain,
+                    },
+                    self.ctx.clone(),
+                )
+                .deserialize_map(visitor)
             }
-            v => Err(YamlError::new(
-                ErrorKind::Bug,
-                format!("deserialize_any() got unexpected data {v:?}"),
-                self.parsed.start,
-                self.parsed.end,
-            )),
         }
     }
 
@@ -74,105 +490,134 @@ impl<'de> Deserializer<'de> for &mut YamlDeserializer {
     where
         V: Visitor<'de>,
     {
-        visitor.visit_bool(self.parsed.as_bool()?)
+        visitor.visit_bool(self.recover(self.parsed.as_bool(), false)?)
     }
 
     fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_i8(self.parsed.as_i8()?)
+        visitor.visit_i8(self.recover(self.parsed.as_i8(), 0)?)
     }
 
     fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_i16(self.parsed.as_i16()?)
+        visitor.visit_i16(self.recover(self.parsed.as_i16(), 0)?)
     }
 
     fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_i32(self.parsed.as_i32()?)
+        visitor.visit_i32(self.recover(self.parsed.as_i32(), 0)?)
     }
 
     fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_i64(self.parsed.as_i64()?)
+        visitor.visit_i64(self.recover(self.parsed.as_i64(), 0)?)
+    }
+
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_i128(self.recover(self.parsed.as_i128(), 0)?)
     }
 
     fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u8(self.parsed.as_u8()?)
+        visitor.visit_u8(self.recover(self.parsed.as_u8(), 0)?)
     }
 
     fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u16(self.parsed.as_u16()?)
+        visitor.visit_u16(self.recover(self.parsed.as_u16(), 0)?)
     }
 
     fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u32(self.parsed.as_u32()?)
+        visitor.visit_u32(self.recover(self.parsed.as_u32(), 0)?)
     }
 
     fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u64(self.parsed.as_u64()?)
+        visitor.visit_u64(self.recover(self.parsed.as_u64(), 0)?)
+    }
+
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_u128(self.recover(self.parsed.as_u128(), 0)?)
     }
 
-    fn deserialize_f32<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        todo!()
+        visitor.visit_f32(self.recover(self.parsed.as_f32(), 0.0)?)
     }
 
-    fn deserialize_f64<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        todo!()
+        visitor.visit_f64(self.recover(self.parsed.as_f64(), 0.0)?)
     }
 
     fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_char(self.parsed.as_char()?)
+        visitor.visit_char(self.recover(self.parsed.as_char(), '\0')?)
     }
 
     fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_str(self.parsed.as_str()?)
+        visitor.visit_str(self.recover(self.parsed.as_str(), "")?)
     }
 
     fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_string(self.parsed.as_str()?.to_string())
+        // Move the string out instead of copying it, as each node is only
+        // deserialized once, e.g. into `String`, `Box<str>` or `Arc<str>`.
+        if !self.keep_parsed
+            && let YamlValueData::String(_) = self.parsed.content()
+            && let (
+                _,
+                YamlValue {
+                    data: YamlValueData::String(s),
+                    ..
+                },
+            ) = self.take_untagged()
+        {
+            return visitor.visit_string(s);
+        }
+        visitor
+            .visit_string(self.recover(self.parsed.as_str(), "")?.to_string())
     }
 
     fn deserialize_bytes<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        todo!()
+        Err(self.not_supported("bytes"))
     }
 
     fn deserialize_byte_buf<V>(
@@ -182,92 +627,146 @@ impl<'de> Deserializer<'de> for &mut YamlDeserializer {
     where
         V: Visitor<'de>,
     {
-        todo!()
+        Err(self.not_supported("byte_buf"))
     }
 
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        match self.parsed.data {
-            YamlValueData::Null => visitor.visit_none(),
-            _ => visitor.visit_some(self),
+        if self.parsed.is_null() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
         }
     }
 
-    fn deserialize_unit<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        todo!()
+        if !self.parsed.is_null() {
+            self.recover(
+                Err(YamlError::new(
+                    ErrorKind::UnexpectedYamlNodeType,
+                    format!("Expecting null, got {}", self.parsed.data),
+                    self.parsed.start,
+                    self.parsed.end,
+                )),
+                (),
+            )?;
+        }
+        visitor.visit_unit()
     }
 
     fn deserialize_unit_struct<V>(
         self,
         _name: &'static str,
-        _visitor: V,
+        visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        todo!()
+        self.deserialize_unit(visitor)
     }
 
     fn deserialize_newtype_struct<V>(
         self,
-        _name: &'static str,
+        name: &'static str,
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_newtype_struct(self)
+        if name == RAW_VALUE_TOKEN {
+            visitor.visit_map(YamlValueMapAccess::new(
+                raw_value_map(&self.ctx.source, &self.parsed),
+                self.ctx.clone(),
+            ))
+        } else if name == NUMBER_TOKEN {
+            // Keep the literal text of number, quoted scalar is string.
+            match &self.parsed.data {
+                YamlValueData::String(s)
+                    if self.parsed.style == YamlScalarStyle::Plain =>
+                {
+                    visitor.visit_str(s).map_err(|e: YamlError| {
+                        YamlError::new(
+                            e.kind(),
+                            e.msg().to_string(),
+                            self.parsed.start,
+                            self.parsed.end,
+                        )
+                    })
+                }
+                _ => Err(YamlError::new(
+                    ErrorKind::InvalidNumber,
+                    format!("Expecting a number, but got {}", self.parsed.data),
+                    self.parsed.start,
+                    self.parsed.end,
+                )),
+            }
+        } else {
+            visitor.visit_newtype_struct(self)
+        }
     }
 
     fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        if let YamlValueData::Array(v) = &self.parsed.data {
-            // TODO: We cannot move data output of `&mut self`, so we use
-            // to_vec() to clone here. Maybe should use `Option<YamlValue>` for
-            // Self::parsed, where we can use `Option::take()` to move data out.
-            let access = YamlValueSeqAccess::new(v.to_vec());
-            visitor.visit_seq(access)
-        } else if let YamlValueData::Tag(tag) = &self.parsed.data {
-            if let YamlValueData::Array(v) = &tag.data {
-                let access = YamlValueSeqAccess::new(v.to_vec());
-                visitor.visit_seq(access)
-            } else {
-                Err(YamlError::new(
-                    ErrorKind::UnexpectedYamlNodeType,
-                    format!(
-                        "Expecting a sequence in tag, got {}",
-                        self.parsed.data
-                    ),
-                    self.parsed.start,
-                    self.parsed.end,
-                ))
+        let core_type = self.parsed.core_tag_type();
+        let is_set = core_type == Some("set");
+        let pairs = matches!(core_type, Some("omap" | "pairs"));
+        let (_, parsed) = self.take_untagged();
+        let items = match parsed.data {
+            YamlValueData::Array(v) => Ok(v),
+            // Keys of `!!set`, which is map with null values
+            YamlValueData::Map(m) if is_set => {
+                Ok(m.into_iter().map(|(k, _)| k).collect())
             }
-        } else {
-            Err(YamlError::new(
+            data => Err(YamlError::new(
                 ErrorKind::UnexpectedYamlNodeType,
-                format!("Expecting a sequence, got {}", self.parsed.data),
-                self.parsed.start,
-                self.parsed.end,
-            ))
-        }
+                format!("Expecting a sequence, got {data}"),
+                parsed.start,
+                parsed.end,
+            )),
+        };
+        let items = self.recover(items, Vec::new())?;
+        let mut access = YamlValueSeqAccess::new(items, self.ctx.clone());
+        access.pairs = pairs;
+        visitor
+            .visit_seq(access)
+            .map_err(|e| e.or_position(parsed.start, parsed.end))
     }
 
     fn deserialize_tuple<V>(
         self,
-        _len: usize,
+        len: usize,
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        self.deserialize_seq(visitor)
+        // Single pair map of `!!omap` or `!!pairs` entry as 2-tuple
+        if self.pair_entry
+            && len == 2
+            && matches!(self.parsed.content(), YamlValueData::Map(m) if m.len() == 1)
+            && let (
+                _,
+                YamlValue {
+                    data: YamlValueData::Map(m),
+                    ..
+                },
+            ) = self.take_untagged()
+        {
+            let access = YamlValueSeqAccess::new(
+                m.into_iter().flat_map(|(k, v)| [k, v]).collect(),
+                self.ctx.clone(),
+            );
+            visitor.visit_seq(access)
+        } else {
+            self.deserialize_seq(visitor)
+        }
     }
 
     fn deserialize_tuple_struct<V>(
@@ -286,23 +785,26 @@ impl<'de> Deserializer<'de> for &mut YamlDeserializer {
     where
         V: Visitor<'de>,
     {
-        if let YamlValueData::Map(v) = &self.parsed.data {
-            // TODO: We cannot move data output of `&mut self`, so we use clone
-            // here. Maybe should use `Option<YamlValue>` for Self::parsed,
-            // where we can use `Option::take()` to move data out.
-            let access = YamlValueMapAccess::new(*v.clone());
-            visitor.visit_map(access)
-        } else if let YamlValueData::Null = &self.parsed.data {
-            let access = YamlValueMapAccess::new(Default::default());
-            visitor.visit_map(access)
-        } else {
-            Err(YamlError::new(
+        let (start, end) = (self.parsed.start, self.parsed.end);
+        let map = match self.parsed.content() {
+            YamlValueData::Map(_) => match self.take_untagged().1.data {
+                YamlValueData::Map(v) => Ok(*v),
+                _ => Ok(Default::default()),
+            },
+            YamlValueData::Null => Ok(Default::default()),
+            _ => Err(YamlError::new(
                 ErrorKind::UnexpectedYamlNodeType,
                 format!("Expecting a map, got {}", self.parsed.data),
                 self.parsed.start,
                 self.parsed.end,
-            ))
-        }
+            )),
+        };
+        let map = self.recover(map, Default::default())?;
+        // Content buffered by `#[serde(flatten)]` is deserialized after all
+        // entries consumed, hence its errors are reported at this map.
+        visitor
+            .visit_map(YamlValueMapAccess::new(map, self.ctx.clone()))
+            .map_err(|e| e.or_position(start, end))
     }
 
     fn deserialize_struct<V>(
@@ -326,10 +828,7 @@ impl<'de> Deserializer<'de> for &mut YamlDeserializer {
     where
         V: Visitor<'de>,
     {
-        // TODO: We cannot move data output of `&mut self`, so we use clone
-        // here. Maybe should use `Option<YamlValue>` for Self::parsed,
-        // where we can use `Option::take()` to move data out.
-        let access = YamlValueEnumAccess::new(self.parsed.clone());
+        let access = YamlValueEnumAccess::new(self.take(), self.ctx.clone());
 
         visitor.visit_enum(access)
     }
@@ -344,6 +843,8 @@ impl<'de> Deserializer<'de> for &mut YamlDeserializer {
         self.deserialize_str(visitor)
     }
 
+    // The subtree is already parsed and moved into this deserializer by
+    // its parent, skip it without walking it.
     fn deserialize_ignored_any<V>(
         self,
         visitor: V,
@@ -351,7 +852,7 @@ impl<'de> Deserializer<'de> for &mut YamlDeserializer {
     where
         V: Visitor<'de>,
     {
-        self.deserialize_any(visitor)
+        visitor.visit_unit()
     }
 
     fn is_human_readable(&self) -> bool {
@@ -361,14 +862,40 @@ impl<'de> Deserializer<'de> for &mut YamlDeserializer {
 
 #[cfg(test)]
 mod test {
+    use std::{
+        borrow::Cow,
+        collections::{BTreeMap, HashMap, HashSet},
+        rc::Rc,
+        str::FromStr,
+        sync::Arc,
+    };
+
     use pretty_assertions::assert_eq;
     use serde::{Deserialize, Serialize};
 
-    use crate::YamlError;
+    use crate::{ErrorKind, YamlDeserializer, YamlError, YamlPosition};
 
     #[test]
     fn test_de_char() -> Result<(), YamlError> {
         assert_eq!(crate::from_str::<char>("q")?, 'q');
+        assert_eq!(crate::from_str::<char>("'q'")?, 'q');
+        assert_eq!(crate::from_str::<char>("''''")?, '\'');
+        assert_eq!(crate::from_str::<char>(r#""♥""#)?, '\u{2665}');
+        assert_eq!(crate::from_str::<char>(r#""\t""#)?, '\t');
+        assert_eq!(crate::from_str::<char>("\u{2665}")?, '\u{2665}');
+        assert_eq!(crate::from_str::<char>("|-\n  q\n")?, 'q');
+        assert_eq!(
+            crate::from_str::<Vec<char>>(r#"[ 'a' , "\x62", c ]"#)?,
+            vec!['a', 'b', 'c']
+        );
+
+        let e = crate::from_str::<Vec<char>>("[a, ' b']").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::UnexpectedYamlNodeType);
+        assert_eq!(e.start_pos(), YamlPosition::new(1, 5));
+        assert_eq!(e.end_pos(), YamlPosition::new(1, 8));
+
+        let e = crate::from_str::<char>("''").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::UnexpectedYamlNodeType);
         Ok(())
     }
 
@@ -383,23 +910,116 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_de_smart_pointer_str() -> Result<(), YamlError> {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct SharedStr {
+            boxed: Box<str>,
+            rc: Rc<str>,
+            arc: Arc<str>,
+            cow: Cow<'static, str>,
+            names: Vec<Arc<str>>,
+            ids: HashMap<Arc<str>, Option<Box<str>>>,
+        }
+
+        let data: SharedStr = crate::from_str(
+            "boxed: 'a'\nrc: 1500\narc: |\n  b\ncow: !!str true\nnames: [c, \
+             \"d\"]\nids:\n  e: f\n  g:\n",
+        )?;
+        assert_eq!(
+            data,
+            SharedStr {
+                boxed: "a".into(),
+                rc: "1500".into(),
+                arc: "b\n".into(),
+                cow: Cow::Borrowed("true"),
+                names: vec!["c".into(), "d".into()],
+                ids: HashMap::from([
+                    ("e".into(), Some("f".into())),
+                    ("g".into(), None)
+                ]),
+            }
+        );
+
+        let e = crate::from_str::<Arc<str>>("[a]").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::UnexpectedYamlNodeType);
+        Ok(())
+    }
+
+    #[test]
+    fn test_de_string_moved_to_self_describing() -> Result<(), YamlError> {
+        // Records whether the string is handed over as owned
+        #[derive(Debug, PartialEq)]
+        struct Owned(bool);
+
+        impl<'de> Deserialize<'de> for Owned {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                struct OwnedVisitor;
+                impl serde::de::Visitor<'_> for OwnedVisitor {
+                    type Value = Owned;
+                    fn expecting(
+                        &self,
+                        formatter: &mut std::fmt::Formatter,
+                    ) -> std::fmt::Result {
+                        formatter.write_str("string")
+                    }
+                    fn visit_str<E>(self, _v: &str) -> Result<Owned, E> {
+                        Ok(Owned(false))
+                    }
+                    fn visit_string<E>(self, _v: String) -> Result<Owned, E> {
+                        Ok(Owned(true))
+                    }
+                }
+                deserializer.deserialize_any(OwnedVisitor)
+            }
+        }
+
+        let data: HashMap<String, Vec<Owned>> =
+            crate::from_str("a: [b, 'c', !!str 1]")?;
+        assert_eq!(data["a"], vec![Owned(true), Owned(true), Owned(true)]);
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        #[serde(untagged)]
+        enum Untagged {
+            Num(u32),
+            Str(Arc<str>),
+        }
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Flatten {
+            #[serde(flatten)]
+            rest: HashMap<Arc<str>, Untagged>,
+        }
+        let data: Flatten = crate::from_str("a: 1\nb: c\n")?;
+        assert_eq!(data.rest["a"], Untagged::Num(1));
+        assert_eq!(data.rest["b"], Untagged::Str("c".into()));
+        Ok(())
+    }
+
     #[test]
     fn test_de_bool() -> Result<(), YamlError> {
         assert!(!crate::from_str("false")?);
 
         assert!(crate::from_str("true")?);
+
+        assert!(crate::from_str("True")?);
+        assert!(!crate::from_str("FALSE")?);
+        assert!(crate::from_str::<bool>("tRUE").is_err());
+        assert!(crate::from_str::<bool>("yes").is_err());
         Ok(())
     }
 
     #[test]
     fn test_de_unsign_number() -> Result<(), YamlError> {
-        assert_eq!(123114u32, crate::from_str("\n---\n123114")?);
+        assert_eq!(123114u32, crate::from_str::<u32>("\n---\n123114")?);
 
-        assert_eq!(1234u16, crate::from_str("+1234")?);
+        assert_eq!(1234u16, crate::from_str::<u16>("+1234")?);
 
-        assert_eq!(0x123123u64, crate::from_str("0x123123")?);
-        assert_eq!(0o123u16, crate::from_str("0o123")?);
-        assert_eq!(0b1001u8, crate::from_str("0b1001")?);
+        assert_eq!(0x123123u64, crate::from_str::<u64>("0x123123")?);
+        assert_eq!(0o123u16, crate::from_str::<u16>("0o123")?);
+        assert_eq!(0b1001u8, crate::from_str::<u8>("0b1001")?);
 
         Ok(())
     }
@@ -653,6 +1273,83 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_de_enum_single_entry_map() -> Result<(), YamlError> {
+        #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+        enum EnumTest {
+            Unit,
+            Newtype(u32),
+            Tuple(u32, String),
+            Struct { uint_a: u32 },
+        }
+
+        assert_eq!(
+            vec![
+                EnumTest::Unit,
+                EnumTest::Unit,
+                EnumTest::Newtype(1),
+                EnumTest::Tuple(2, "b".into()),
+                EnumTest::Struct { uint_a: 3 },
+                EnumTest::Newtype(4),
+            ],
+            crate::from_str::<Vec<EnumTest>>(
+                r#"
+            - Unit
+            - Unit:
+            - Newtype: 1
+            - Tuple:
+                - 2
+                - b
+            - Struct:
+                uint_a: 3
+            - {Newtype: 4}
+            "#
+            )?
+        );
+
+        let e =
+            crate::from_str::<EnumTest>("Newtype: 1\nUnit: null").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::UnexpectedYamlNodeType);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_de_unit_and_not_supported() -> Result<(), YamlError> {
+        #[derive(Debug, Deserialize, PartialEq, Eq)]
+        struct UnitTest;
+
+        #[derive(Debug, Deserialize, PartialEq, Eq)]
+        struct FooTest {
+            a: (),
+            b: UnitTest,
+        }
+
+        assert_eq!(
+            crate::from_str::<FooTest>("a: null\nb: ~")?,
+            FooTest { a: (), b: UnitTest }
+        );
+
+        let e = crate::from_str::<()>("abc").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::UnexpectedYamlNodeType);
+
+        struct BytesTest;
+        impl<'de> Deserialize<'de> for BytesTest {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                deserializer.deserialize_bytes(serde::de::IgnoredAny)?;
+                Ok(Self)
+            }
+        }
+        let e = crate::from_str::<BytesTest>("\n  abc").err().unwrap();
+        assert_eq!(e.kind(), ErrorKind::NotSupported);
+        assert_eq!(e.start_pos(), YamlPosition::new(2, 3));
+
+        Ok(())
+    }
+
     #[test]
     fn test_de_struct_with_enum_member() -> Result<(), YamlError> {
         #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
@@ -689,6 +1386,303 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_de_flatten() -> Result<(), YamlError> {
+        #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+        enum EnumTest {
+            Unit,
+            Newtype(u32),
+        }
+
+        #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+        struct InnerTest {
+            uint_b: u32,
+            float_c: f64,
+            opt_d: Option<u32>,
+            str_e: String,
+            enum_f: EnumTest,
+            enum_g: EnumTest,
+            list_h: Vec<String>,
+        }
+
+        #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+        struct FooTest {
+            bool_a: bool,
+            #[serde(flatten)]
+            inner: InnerTest,
+            #[serde(flatten)]
+            others: HashMap<String, u32>,
+        }
+
+        let expected = FooTest {
+            bool_a: true,
+            inner: InnerTest {
+                uint_b: 1,
+                float_c: 1.5,
+                opt_d: None,
+                str_e: "abc".into(),
+                enum_f: EnumTest::Unit,
+                enum_g: EnumTest::Newtype(2),
+                list_h: vec!["x".into(), "y".into()],
+            },
+            others: HashMap::from([("uint_i".to_string(), 3)]),
+        };
+
+        let yaml_str = r#"
+            bool_a: true
+            uint_b: 1
+            float_c: 1.5
+            opt_d: ~
+            str_e: abc
+            enum_f: Unit
+            enum_g: !Newtype 2
+            list_h: [x, y]
+            uint_i: 3
+            "#;
+        let foo = crate::from_str::<FooTest>(yaml_str)?;
+        assert_eq!(foo, expected);
+
+        // Quoted scalar is always string
+        let foo = crate::from_str::<FooTest>(
+            &yaml_str.replace("str_e: abc", "str_e: '123'"),
+        )?;
+        assert_eq!(foo.inner.str_e, "123");
+
+        let yaml_str = crate::to_string(&expected)?;
+        assert_eq!(crate::from_str::<FooTest>(&yaml_str)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_de_flatten_invalid_type() -> Result<(), YamlError> {
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Inner {
+            x: u32,
+            #[serde(flatten)]
+            rest: BTreeMap<String, String>,
+        }
+
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Outer {
+            a: Inner,
+        }
+
+        let e = crate::from_str::<Inner>("x: 1\nw: 3\n").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::UnexpectedYamlNodeType);
+        assert_eq!(e.msg(), "invalid type: integer `3`, expected a string");
+
+        // Buffered content has no position, reported at the map holding it
+        let input = "a:\n  x: 1\n  w: 3\n";
+        let e = crate::from_str::<Outer>(input).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::UnexpectedYamlNodeType);
+        assert_eq!((e.start_pos(), e.end_pos()), crate::index(input)?["a"]);
+        assert_eq!(e.key_path(), Some("a"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_de_ignored_any() -> Result<(), YamlError> {
+        #[derive(Debug, Deserialize, PartialEq, Eq)]
+        struct FooTest {
+            uint_a: u32,
+        }
+
+        assert_eq!(
+            crate::from_str::<FooTest>(
+                "unknown:\n  b: [1, {c: !d e}]\nuint_a: 1\nother: &x f"
+            )?,
+            FooTest { uint_a: 1 }
+        );
+
+        // Only unit is visited for ignored node
+        struct UnitOnly;
+        impl<'de> Deserialize<'de> for UnitOnly {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                struct UnitVisitor;
+                impl serde::de::Visitor<'_> for UnitVisitor {
+                    type Value = UnitOnly;
+
+                    fn expecting(
+                        &self,
+                        formatter: &mut std::fmt::Formatter,
+                    ) -> std::fmt::Result {
+                        formatter.write_str("unit")
+                    }
+
+                    fn visit_unit<E>(self) -> Result<UnitOnly, E> {
+                        Ok(UnitOnly)
+                    }
+                }
+                deserializer.deserialize_ignored_any(UnitVisitor)
+            }
+        }
+        assert!(crate::from_str::<UnitOnly>("a: [1, 2]").is_ok());
+        Ok(())
+    }
+
+    // Timing sensitive, run by `cargo test --release -- --ignored`
+    #[test]
+    #[ignore]
+    fn test_de_ignored_any_depth_independent() -> Result<(), YamlError> {
+        #[derive(Deserialize)]
+        struct Leaf {
+            #[allow(dead_code)]
+            x: u32,
+        }
+        #[derive(Deserialize)]
+        struct Nested<T> {
+            #[allow(dead_code)]
+            a: T,
+        }
+        type Depth4 = Nested<Nested<Nested<Nested<Leaf>>>>;
+
+        fn measure<T: for<'de> Deserialize<'de>>(
+            input: &str,
+        ) -> Result<std::time::Duration, YamlError> {
+            let mut deserializer = YamlDeserializer::from_str(input)?;
+            // Skip the clone of root value kept for `positions()`
+            deserializer.keep_parsed = false;
+            let now = std::time::Instant::now();
+            T::deserialize(&mut deserializer)?;
+            Ok(now.elapsed())
+        }
+
+        let big = format!(
+            "{{ignored: [{}], x: 1}}",
+            vec!["{b: [c, d]}"; 50000].join(", ")
+        );
+        let flat = measure::<Leaf>(&big)?;
+        let nested =
+            measure::<Depth4>(&format!("a: {{a: {{a: {{a: {big}}}}}}}"))?;
+        println!("flat {flat:?}, nested at depth 4 {nested:?}");
+        assert!(nested < flat * 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_de_collect_errors() {
+        #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+        struct FooTest {
+            uint_a: u32,
+            bool_b: bool,
+            list_c: Vec<u8>,
+            bar: BarTest,
+        }
+
+        #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+        struct BarTest {
+            uint_d: u16,
+        }
+
+        let errors = crate::from_str_collect_errors::<FooTest>(
+            "uint_a: abc\nbool_b: 1\nlist_c: [1, 256, 2]\nbar:\n  uint_d: -1\n",
+        )
+        .unwrap_err();
+        assert_eq!(
+            errors
+                .iter()
+                .map(|e| (e.kind(), e.start_pos(), e.key_path()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    ErrorKind::InvalidNumber,
+                    YamlPosition::new(1, 9),
+                    Some("uint_a")
+                ),
+                (
+                    ErrorKind::InvalidBool,
+                    YamlPosition::new(2, 9),
+                    Some("bool_b")
+                ),
+                (
+                    ErrorKind::NumberOverflow,
+                    YamlPosition::new(3, 13),
+                    Some("list_c.1")
+                ),
+                (
+                    ErrorKind::InvalidNumber,
+                    YamlPosition::new(5, 11),
+                    Some("bar.uint_d")
+                ),
+            ]
+        );
+
+        let errors = crate::from_str_collect_errors::<FooTest>(
+            "uint_a: abc\nbool_b: true\nlist_c: []\n",
+        )
+        .unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[1].msg(), "missing field `bar`");
+        assert_eq!(errors[1].kind(), ErrorKind::MissingField);
+        assert_eq!(errors[1].start_pos(), YamlPosition::new(1, 1));
+
+        // Reported at the map missing the field
+        let errors = crate::from_str_collect_errors::<FooTest>(
+            "uint_a: 1\nbool_b: true\nlist_c: []\nbar:\n  uint_e: 1\n",
+        )
+        .unwrap_err();
+        assert_eq!(
+            errors
+                .iter()
+                .map(|e| (e.kind(), e.start_pos(), e.key_path()))
+                .collect::<Vec<_>>(),
+            vec![(
+                ErrorKind::MissingField,
+                YamlPosition::new(5, 1),
+                Some("bar")
+            )]
+        );
+
+        assert_eq!(
+            crate::from_str_collect_errors::<BarTest>("uint_d: 1"),
+            Ok(BarTest { uint_d: 1 })
+        );
+    }
+
+    #[test]
+    fn test_de_core_tag() -> Result<(), YamlError> {
+        #[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+        #[serde(untagged)]
+        enum Scalar {
+            Uint(u64),
+            Str(String),
+        }
+
+        #[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+        struct FooTest {
+            str_a: Scalar,
+            uint_b: Scalar,
+            uint_c: u32,
+            str_d: Option<String>,
+            set_e: Vec<String>,
+        }
+
+        let value = crate::to_value("!!str 5")?;
+        assert_eq!(value.tag(), Some("<tag:yaml.org,2002:str>"));
+        assert_eq!(crate::to_value("!foo 5")?.tag(), Some("foo"));
+        assert_eq!(crate::to_value("5")?.tag(), None);
+
+        assert_eq!(
+            crate::from_str::<FooTest>(
+                "str_a: !!str 5\nuint_b: 5\nuint_c: !!int 6\nstr_d: !!str \
+                 ~\nset_e: !!set {a, b}\n"
+            )?,
+            FooTest {
+                str_a: Scalar::Str("5".into()),
+                uint_b: Scalar::Uint(5),
+                uint_c: 6,
+                str_d: Some("~".into()),
+                set_e: vec!["a".into(), "b".into()],
+            }
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_signed_interger() -> Result<(), YamlError> {
         #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
@@ -708,6 +1702,107 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_de_integer_overflow() -> Result<(), YamlError> {
+        assert_eq!(crate::from_str::<i64>("-9223372036854775808")?, i64::MIN);
+        assert_eq!(
+            crate::from_str::<u128>("0xffffffffffffffffffffffffffffffff")?,
+            u128::MAX
+        );
+        assert_eq!(crate::from_str::<i128>("-0x10")?, -16);
+        let e = crate::from_str::<u64>("18446744073709551616").unwrap_err();
+        assert_eq!(e.kind(), crate::ErrorKind::NumberOverflow);
+        let e =
+            crate::from_str::<u128>("340282366920938463463374607431768211456")
+                .unwrap_err();
+        assert_eq!(e.kind(), crate::ErrorKind::NumberOverflow);
+        assert_eq!(
+            crate::from_str::<i128>(
+                "-170141183460469231731687303715884105728"
+            )?,
+            i128::MIN
+        );
+        let e = crate::from_str::<i128>("0x80000000000000000000000000000000")
+            .unwrap_err();
+        assert_eq!(e.kind(), crate::ErrorKind::NumberOverflow);
+        Ok(())
+    }
+
+    #[test]
+    fn test_de_integer_forms() -> Result<(), YamlError> {
+        assert_eq!(
+            crate::from_str::<Vec<u32>>(
+                "[0xFF_FF, 0XfF, 0o1_7, 0O17, 0b1_0, 0B10, 1_000, +7, -0]"
+            )?,
+            vec![0xffff, 0xff, 0o17, 0o17, 2, 2, 1000, 7, 0]
+        );
+        assert_eq!(
+            crate::from_str::<Vec<i32>>("[-0xFF_FF, +0o17, -0b10, -1_000]")?,
+            vec![-0xffff, 0o17, -2, -1000]
+        );
+        for input in ["--5", "-+5", "0x", "0x-5", "_1", "1_", "0b12", "0o8"] {
+            let e = crate::from_str::<i32>(input).unwrap_err();
+            assert_eq!(e.kind(), crate::ErrorKind::InvalidNumber, "{input}");
+        }
+        let e = crate::from_str::<u32>("-1").unwrap_err();
+        assert_eq!(e.kind(), crate::ErrorKind::InvalidNumber);
+        Ok(())
+    }
+
+    #[test]
+    fn test_de_float() -> Result<(), YamlError> {
+        assert_eq!(
+            crate::from_str::<Vec<f64>>("[1, -2.5, .5, 1e3, +1.E-2, -.INF]")?,
+            vec![1.0, -2.5, 0.5, 1000.0, 0.01, f64::NEG_INFINITY]
+        );
+        assert_eq!(
+            crate::from_str::<Vec<f64>>("[+.5, 5., -0.0, 1E+3, 2e-1, +12]")?,
+            vec![0.5, 5.0, 0.0, 1000.0, 0.2, 12.0]
+        );
+        assert!(crate::from_str::<f64>("-0.0")?.is_sign_negative());
+        assert!(crate::from_str::<f32>(".NaN")?.is_nan());
+        for input in ["inf", "NaN", "1.2.3", ".", "1e", "0x10"] {
+            let e = crate::from_str::<f64>(input).unwrap_err();
+            assert_eq!(e.kind(), crate::ErrorKind::InvalidNumber, "{input}");
+        }
+        let e = crate::from_str::<f32>("1e39").unwrap_err();
+        assert_eq!(e.kind(), crate::ErrorKind::NumberOverflow);
+        Ok(())
+    }
+
+    #[test]
+    fn test_de_positions() -> Result<(), YamlError> {
+        #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+        struct FooTest {
+            name: String,
+            addresses: Vec<BarTest>,
+        }
+
+        #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+        struct BarTest {
+            ip: String,
+        }
+
+        let input =
+            "name: eth1\naddresses:\n  - ip: 192.0.2.1\n  - ip: 192.0.2.2\n";
+        let mut deserializer = YamlDeserializer::from_str(input)?;
+        FooTest::deserialize(&mut deserializer)?;
+        let positions = deserializer.positions();
+        assert_eq!(crate::index(input)?, positions);
+
+        assert_eq!(
+            positions["name"],
+            (YamlPosition::new(1, 7), YamlPosition::new(1, 10))
+        );
+        assert_eq!(
+            positions["addresses.1.ip"],
+            (YamlPosition::new(4, 9), YamlPosition::new(4, 17))
+        );
+        assert_eq!(positions["addresses"].0, YamlPosition::new(3, 1));
+        assert_eq!(positions[""].0, YamlPosition::new(1, 1));
+        Ok(())
+    }
+
     #[test]
     fn test_empty_input() -> Result<(), YamlError> {
         #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
@@ -720,7 +1815,105 @@ mod test {
         Ok(())
     }
 
-    /*
+    #[test]
+    fn test_de_empty_value_as_none() -> Result<(), YamlError> {
+        #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+        struct FooTest {
+            uint_a: Option<u32>,
+            str_b: Option<String>,
+            uint_c: Option<u32>,
+        }
+
+        assert_eq!(
+            FooTest {
+                uint_a: None,
+                str_b: None,
+                uint_c: Some(1),
+            },
+            crate::from_str::<FooTest>(
+                "uint_a:
+? str_b
+uint_c: 1
+"
+            )?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_de_set_and_omap() -> Result<(), YamlError> {
+        let set = crate::from_str::<HashSet<String>>(
+            r#"
+            # Sets are represented as a
+            # Mapping where each key is
+            # associated with a null value
+            --- !!set
+            ? Mark McGwire
+            ? Sammy Sosa
+            ? Ken Griff
+            "#,
+        )?;
+        assert_eq!(
+            set,
+            HashSet::from([
+                "Mark McGwire".to_string(),
+                "Sammy Sosa".to_string(),
+                "Ken Griff".to_string(),
+            ])
+        );
+
+        let omap = crate::from_str::<Vec<(String, u32)>>(
+            r#"
+            # Ordered maps are represented as
+            # A sequence of mappings, with
+            # each mapping having one key
+            --- !!omap
+            - Mark McGwire: 65
+            - Sammy Sosa: 63
+            - Ken Griffy: 58
+            "#,
+        )?;
+        assert_eq!(
+            omap,
+            vec![
+                ("Mark McGwire".to_string(), 65),
+                ("Sammy Sosa".to_string(), 63),
+                ("Ken Griffy".to_string(), 58),
+            ]
+        );
+        let pairs = crate::from_str::<Vec<(String, u32)>>(
+            "--- !!pairs\n- a: 1\n- a: 2\n",
+        )?;
+        assert_eq!(pairs, vec![("a".to_string(), 1), ("a".to_string(), 2)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_de_untagged_set_and_pair() {
+        for e in [
+            crate::from_str::<Vec<String>>("a:\nb:\n").unwrap_err(),
+            crate::from_str::<HashSet<String>>("? a\n? b\n").unwrap_err(),
+            crate::from_str::<(String, u32)>("a: 1").unwrap_err(),
+            crate::from_str::<Vec<(String, u32)>>("- a: 1\n").unwrap_err(),
+        ] {
+            assert_eq!(e.kind(), ErrorKind::UnexpectedYamlNodeType);
+        }
+    }
+
+    #[test]
+    fn test_de_from_slice() -> Result<(), YamlError> {
+        let input: Vec<u8> = "\u{FEFF}- abc\r\n- d\u{e9}f\r\n"
+            .encode_utf16()
+            .flat_map(|u| u.to_le_bytes())
+            .collect();
+        assert_eq!(
+            crate::from_slice::<Vec<String>>(&input)?,
+            vec!["abc".to_string(), "d\u{e9}f".to_string()]
+        );
+        assert_eq!(crate::from_slice::<u32>(b"12")?, 12);
+        Ok(())
+    }
+
     #[test]
     fn test_line_folding() -> Result<(), YamlError> {
         #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
@@ -742,7 +1935,24 @@ mod test {
             },
             crate::from_str::<FooTest>(yaml_str)?
         );
+
+        #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+        struct BarTest {
+            strip: String,
+            clip: String,
+            keep: String,
+        }
+
+        assert_eq!(
+            BarTest {
+                strip: "a\nb".into(),
+                clip: "c\n".into(),
+                keep: "d\n\n".into(),
+            },
+            crate::from_str::<BarTest>(
+                "strip: |-\n  a\n  b\nclip: >\n  c\n\nkeep: |+\n  d\n\n"
+            )?
+        );
         Ok(())
     }
-    */
 }
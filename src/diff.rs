@@ -0,0 +1,169 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::cmp::Ordering;
+
+use crate::{YamlValue, YamlValueData, sub_key_path};
+
+/// Difference of a node between two YAML documents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct YamlDiffEntry {
+    /// Key path of the node, map keys and sequence indexes joined by `.`,
+    /// for example `interfaces.0.name`. The document root is empty string.
+    pub path: String,
+    /// The node in old document with its positions. None if the node is
+    /// newly added.
+    pub old: Option<YamlValue>,
+    /// The node in new document with its positions. None if the node is
+    /// removed.
+    pub new: Option<YamlValue>,
+}
+
+/// Find the differences between two YAML documents.
+/// Maps are compared by keys regardless of their order, sequences are
+/// compared by indexes. Scalars are compared by their literal strings.
+/// Position changes are not considered as differences.
+pub fn diff(old: &YamlValue, new: &YamlValue) -> Vec<YamlDiffEntry> {
+    let mut ret = Vec::new();
+    diff_value(old, new, String::new(), &mut ret);
+    ret
+}
+
+fn diff_value(
+    old: &YamlValue,
+    new: &YamlValue,
+    path: String,
+    entries: &mut Vec<YamlDiffEntry>,
+) {
+    match (&old.data, &new.data) {
+        (YamlValueData::Map(old_map), YamlValueData::Map(new_map)) => {
+            for (key, old_value) in old_map.iter() {
+                let sub_path = sub_key_path(&path, &key.key_path_name());
+                if let Some(new_value) = new_map.get(key.clone()) {
+                    diff_value(old_value, new_value, sub_path, entries);
+                } else {
+                    entries.push(YamlDiffEntry {
+                        path: sub_path,
+                        old: Some(old_value.clone()),
+                        new: None,
+                    });
+                }
+            }
+            for (key, new_value) in new_map.iter() {
+                if !old_map.contains_key(key.clone()) {
+                    entries.push(YamlDiffEntry {
+                        path: sub_key_path(&path, &key.key_path_name()),
+                        old: None,
+                        new: Some(new_value.clone()),
+                    });
+                }
+            }
+        }
+        (YamlValueData::Array(old_items), YamlValueData::Array(new_items)) => {
+            for index in 0..old_items.len().max(new_items.len()) {
+                let sub_path = sub_key_path(&path, &index.to_string());
+                match (old_items.get(index), new_items.get(index)) {
+                    (Some(old_item), Some(new_item)) => {
+                        diff_value(old_item, new_item, sub_path, entries);
+                    }
+                    (old_item, new_item) => {
+                        entries.push(YamlDiffEntry {
+                            path: sub_path,
+                            old: old_item.cloned(),
+                            new: new_item.cloned(),
+                        });
+                    }
+                }
+            }
+        }
+        _ => {
+            if old.canonical_cmp(new) != Ordering::Equal {
+                entries.push(YamlDiffEntry {
+                    path,
+                    old: Some(old.clone()),
+                    new: Some(new.clone()),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{YamlError, YamlPosition};
+
+    #[test]
+    fn test_diff() -> Result<(), YamlError> {
+        let old = crate::to_value(
+            "name: eth1\nmtu: 1500\naddresses:\n  - 192.0.2.1\n  - \
+             192.0.2.2\nstate: up\n",
+        )?;
+        let new = crate::to_value(
+            "state: up\nname: eth1\nmtu: 9000\naddresses:\n  - 192.0.2.1\n",
+        )?;
+
+        let entries = diff(&old, &new);
+
+        assert_eq!(
+            entries
+                .iter()
+                .map(|e| e.path.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["mtu", "addresses.1"]
+        );
+        let old_mtu = entries[0].old.as_ref().unwrap();
+        let new_mtu = entries[0].new.as_ref().unwrap();
+        assert_eq!(old_mtu.as_u32()?, 1500);
+        assert_eq!(old_mtu.start, YamlPosition::new(2, 6));
+        assert_eq!(new_mtu.as_u32()?, 9000);
+        assert_eq!(new_mtu.start, YamlPosition::new(3, 6));
+        assert_eq!(entries[1].new, None);
+        assert_eq!(
+            entries[1].old.as_ref().map(|v| v.as_str()),
+            Some(Ok("192.0.2.2"))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_diff_type_change_and_added_key() -> Result<(), YamlError> {
+        let old = crate::to_value("a:\n  b: 1\n")?;
+        let new = crate::to_value("a: 1\nc: 2\n")?;
+
+        let entries = diff(&old, &new);
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].path, "a");
+        assert!(entries[0].old.is_some() && entries[0].new.is_some());
+        assert_eq!(entries[1].path, "c");
+        assert_eq!(entries[1].old, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_diff_identical() -> Result<(), YamlError> {
+        let old = crate::to_value("a: 1\nb: 2\n")?;
+        let new = crate::to_value("b: 2\n\na: 1\n")?;
+
+        assert!(diff(&old, &new).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_diff_collection_key() -> Result<(), YamlError> {
+        let old = crate::to_value("? [a]\n: 1\n? {b: c}\n: 2\n")?;
+        let new = crate::to_value("x: 0\n? {b: c}\n: 3\n? [a]\n: 1\n")?;
+
+        let entries = diff(&old, &new);
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].old.as_ref().map(|v| v.as_u32()), Some(Ok(2)));
+        assert_eq!(entries[0].new.as_ref().map(|v| v.as_u32()), Some(Ok(3)));
+        assert_eq!(entries[1].path, "x");
+        assert_eq!(entries[1].old, None);
+        Ok(())
+    }
+}
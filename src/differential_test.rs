@@ -0,0 +1,59 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Differential testing against serde_yaml (based on libyaml) to find spec
+// gaps beyond the expectations of yaml-test-suite. The full run only lists
+// divergences without failing, run it by:
+//      cargo test differential -- --ignored --nocapture
+
+use serde_yaml::Value;
+
+use crate::fuzz_test::corpus;
+
+// Description of divergence, None if both parsers agree. Multiple documents
+// are not compared as serde_yaml::from_str() only supports single document.
+fn diverge(input: &str) -> Option<String> {
+    let expected = serde_yaml::from_str::<Value>(input);
+    let result = crate::from_str::<Value>(input);
+    match (expected, result) {
+        (Ok(expected), Ok(result)) => (expected != result)
+            .then(|| format!("expecting {expected:?}, got {result:?}")),
+        (Ok(expected), Err(e)) => {
+            Some(format!("expecting {expected:?}, got error {e}"))
+        }
+        (Err(e), Ok(result)) => {
+            if e.to_string().contains("more than one document") {
+                None
+            } else {
+                Some(format!("expecting error {e}, got {result:?}"))
+            }
+        }
+        (Err(_), Err(_)) => None,
+    }
+}
+
+#[test]
+fn test_differential_common_values() {
+    for input in [
+        "a: 1\nb: -2.5\nc: [true, null, ~]\n",
+        "- 'x'\n- \"y\\n\"\n- |\n  z\n- >-\n  w\n  v\n",
+        "? [a, b]\n: c\n",
+        "&a x: *a\n",
+        "a: 0x1F\nb: 0o17\nc: .inf\nd: -.Inf\ne: 1e3\n",
+        "",
+    ] {
+        assert_eq!(diverge(input), None, "{input:?}");
+    }
+}
+
+#[test]
+#[ignore]
+fn test_differential_yaml_test_suite() {
+    let mut count = 0usize;
+    for input in corpus() {
+        if let Some(msg) = diverge(&input) {
+            count += 1;
+            println!("DIVERGENCE {input:?}\n    {msg}");
+        }
+    }
+    println!("Found {count} divergences");
+}
@@ -0,0 +1,231 @@
+// SPDX-License-Identifier: Apache-2.0
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+#[cfg(feature = "rayon")]
+use crate::scanner::normalize_input;
+use crate::{
+    YamlError, YamlEvent, YamlParser, YamlPosition, YamlValue,
+    find_comment_start,
+    parser::{is_document_end, is_document_start},
+};
+
+/// Directive before document start marker, e.g. `%YAML 1.2` or
+/// `%TAG !e! tag:example.com,2000:`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct YamlDirective {
+    /// Name without leading `%`, e.g. `YAML` or `TAG`.
+    pub name: String,
+    pub parameters: Vec<String>,
+    pub position: YamlPosition,
+}
+
+impl YamlDirective {
+    pub(crate) fn parse(line: &str, position: YamlPosition) -> Self {
+        let line = line.strip_prefix('%').unwrap_or(line);
+        let line = find_comment_start(line).map(|i| &line[..i]).unwrap_or(line);
+        let mut words = line.split([' ', '\t']).filter(|w| !w.is_empty());
+        Self {
+            name: words.next().unwrap_or_default().to_string(),
+            parameters: words.map(ToString::to_string).collect(),
+            position,
+        }
+    }
+}
+
+/// Single document of YAML stream with its structure details.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct YamlDocument {
+    pub root: YamlValue,
+    /// Directives before the document start marker.
+    pub directives: Vec<YamlDirective>,
+    /// Whether document started with `---`.
+    pub has_start_marker: bool,
+    /// Whether document ended with `...`.
+    pub has_end_marker: bool,
+    /// Position of `---` or the first node of document.
+    pub start: YamlPosition,
+    /// Position of `...` or the last character of document.
+    pub end: YamlPosition,
+}
+
+/// Parse all documents of the YAML stream.
+pub fn parse_documents(input: &str) -> Result<Vec<YamlDocument>, YamlError> {
+    let (events, mut directives) =
+        YamlParser::parse_to_events_and_directives(input)?;
+    let mut ret = Vec::new();
+    let mut doc_events = Vec::new();
+    let mut has_start_marker = false;
+    let mut start = YamlPosition::default();
+    for event in events {
+        match event {
+            YamlEvent::DocumentStart(marker, pos) => {
+                has_start_marker = marker;
+                start = pos;
+                doc_events = vec![event];
+            }
+            YamlEvent::DocumentEnd(has_end_marker, end) => {
+                doc_events.push(event);
+                let doc_directives = directives
+                    .iter()
+                    .take_while(|d| d.position < start)
+                    .count();
+                ret.push(YamlDocument {
+                    root: YamlValue::compose(std::mem::take(&mut doc_events))?,
+                    directives: directives.drain(..doc_directives).collect(),
+                    has_start_marker,
+                    has_end_marker,
+                    start,
+                    end,
+                });
+            }
+            YamlEvent::StreamStart | YamlEvent::StreamEnd => (),
+            event => doc_events.push(event),
+        }
+    }
+    Ok(ret)
+}
+
+/// Parse all documents of the YAML stream in parallel. The stream is split
+/// before each `---` marker line and directives of the following document,
+/// the result is the same as [parse_documents()] for valid stream. For
+/// invalid stream, the first error in stream order is returned, which might
+/// differ from [parse_documents()] when the error spans document markers,
+/// e.g. unclosed flow collection.
+#[cfg(feature = "rayon")]
+pub fn parse_documents_parallel(
+    input: &str,
+) -> Result<Vec<YamlDocument>, YamlError> {
+    let input = normalize_input(input)?;
+    let results: Vec<Result<Vec<YamlDocument>, YamlError>> =
+        split_documents(&input)
+            .into_par_iter()
+            .map(|(line_offset, chunk)| {
+                // Prefixed line breaks keep positions relative to the stream
+                let chunk = format!("{}{chunk}", "\n".repeat(line_offset));
+                parse_documents(&chunk)
+            })
+            .collect();
+    let mut ret = Vec::new();
+    for result in results {
+        ret.extend(result?);
+    }
+    Ok(ret)
+}
+
+// Chunks of stream with their line offset, each chunk other than first one
+// starts with directives or `---` marker line. Directives are only allowed
+// at stream start or after `...`.
+pub(crate) fn split_documents(input: &str) -> Vec<(usize, &str)> {
+    let mut ret = Vec::new();
+    let mut chunk_start = (0usize, 0usize);
+    // Byte offset and line index of first directive of next document
+    let mut directive_start: Option<(usize, usize)> = None;
+    let mut allow_directive = true;
+    let mut offset = 0usize;
+    for (line_index, line) in input.split_inclusive('\n').enumerate() {
+        let content = line.trim_end_matches('\n');
+        if is_document_start(content) {
+            let split = directive_start.take().unwrap_or((offset, line_index));
+            if split.0 > chunk_start.0 {
+                ret.push((chunk_start.1, &input[chunk_start.0..split.0]));
+                chunk_start = split;
+            }
+            allow_directive = false;
+        } else if is_document_end(content) {
+            allow_directive = true;
+        } else if content.starts_with('%') && allow_directive {
+            directive_start.get_or_insert((offset, line_index));
+        } else if !content.trim_start_matches([' ', '\t']).is_empty()
+            && !content.trim_start_matches([' ', '\t']).starts_with('#')
+        {
+            directive_start = None;
+            allow_directive = false;
+        }
+        offset += line.len();
+    }
+    if offset > chunk_start.0 {
+        ret.push((chunk_start.1, &input[chunk_start.0..]));
+    }
+    ret
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_documents() -> Result<(), YamlError> {
+        let docs = parse_documents(
+            "%YAML 1.2 # version\n%TAG !e! tag:example.com,2000:\n---\na: \
+             1\n...\nb\n---\n",
+        )?;
+
+        assert_eq!(docs.len(), 3);
+        assert_eq!(
+            docs[0].directives,
+            vec![
+                YamlDirective {
+                    name: "YAML".into(),
+                    parameters: vec!["1.2".into()],
+                    position: YamlPosition::new(1, 1),
+                },
+                YamlDirective {
+                    name: "TAG".into(),
+                    parameters: vec![
+                        "!e!".into(),
+                        "tag:example.com,2000:".into()
+                    ],
+                    position: YamlPosition::new(2, 1),
+                },
+            ]
+        );
+        assert!(docs[0].has_start_marker);
+        assert!(docs[0].has_end_marker);
+        assert_eq!(docs[0].start, YamlPosition::new(3, 1));
+        assert_eq!(docs[0].end, YamlPosition::new(5, 1));
+        assert_eq!(
+            docs[0].root.canonical_cmp(&crate::to_value("a: 1")?),
+            std::cmp::Ordering::Equal
+        );
+
+        assert!(docs[1].directives.is_empty());
+        assert!(!docs[1].has_start_marker);
+        assert!(!docs[1].has_end_marker);
+        assert_eq!(docs[1].root.as_str()?, "b");
+        assert_eq!(docs[1].start, YamlPosition::new(6, 1));
+
+        assert!(docs[2].has_start_marker);
+        assert!(docs[2].root.is_null());
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parse_documents_parallel() -> Result<(), YamlError> {
+        for input in [
+            "",
+            "# only comment\n",
+            "a: 1\n",
+            "%YAML 1.2\n---\na\n...\n# c\n%TAG !e! tag:e.com,2000:\n---\n!e!b \
+             c\n",
+            "- a\n---\n- |\n  b\n  ---x\n--- c\n...\nd\n...\n---\n",
+            "\u{feff}a: b\r\n---\r\nc: d\r\n",
+        ] {
+            assert_eq!(
+                parse_documents_parallel(input)?,
+                parse_documents(input)?,
+                "{input:?}"
+            );
+        }
+        let input = "a\n---\nb: c: d\n---\n[e\n";
+        assert_eq!(
+            parse_documents_parallel(input).unwrap_err(),
+            parse_documents(input).unwrap_err()
+        );
+        Ok(())
+    }
+}
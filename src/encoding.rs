@@ -0,0 +1,198 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{ErrorKind, YamlError, YamlPosition};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum YamlEncoding {
+    Utf8,
+    Utf16Be,
+    Utf16Le,
+    Utf32Be,
+    Utf32Le,
+}
+
+impl std::fmt::Display for YamlEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Utf8 => "UTF-8",
+                Self::Utf16Be => "UTF-16BE",
+                Self::Utf16Le => "UTF-16LE",
+                Self::Utf32Be => "UTF-32BE",
+                Self::Utf32Le => "UTF-32LE",
+            }
+        )
+    }
+}
+
+impl YamlEncoding {
+    /// YAML 1.2.2 SPEC, 5.2. Character Encodings:
+    ///     | Byte0 | Byte1 | Byte2 | Byte3 | Encoding                |
+    ///     | x00   | x00   | xFE   | xFF   | UTF-32BE                |
+    ///     | x00   | x00   | x00   | any   | UTF-32BE                |
+    ///     | xFF   | xFE   | x00   | x00   | UTF-32LE                |
+    ///     | any   | x00   | x00   | x00   | UTF-32LE                |
+    ///     | xFE   | xFF   |       |       | UTF-16BE                |
+    ///     | x00   | any   |       |       | UTF-16BE                |
+    ///     | xFF   | xFE   |       |       | UTF-16LE                |
+    ///     | any   | x00   |       |       | UTF-16LE                |
+    ///     | xEF   | xBB   | xBF   |       | UTF-8                   |
+    ///     |       |       |       |       | Default UTF-8           |
+    fn detect(input: &[u8]) -> Self {
+        match input {
+            [0, 0, 0xFE, 0xFF, ..] | [0, 0, 0, _, ..] => Self::Utf32Be,
+            [0xFF, 0xFE, 0, 0, ..] | [_, 0, 0, 0, ..] => Self::Utf32Le,
+            [0xFE, 0xFF, ..] | [0, _, ..] => Self::Utf16Be,
+            [0xFF, 0xFE, ..] | [_, 0, ..] => Self::Utf16Le,
+            _ => Self::Utf8,
+        }
+    }
+}
+
+/// Detect the encoding of YAML stream and decode it into string.
+/// The byte order mark is kept in the output.
+pub(crate) fn decode_yaml_bytes(input: &[u8]) -> Result<String, YamlError> {
+    let encoding = YamlEncoding::detect(input);
+    match encoding {
+        YamlEncoding::Utf8 => match std::str::from_utf8(input) {
+            Ok(s) => Ok(s.to_string()),
+            Err(e) => Err(decode_error(
+                encoding,
+                std::str::from_utf8(&input[..e.valid_up_to()])
+                    .unwrap_or_default(),
+            )),
+        },
+        YamlEncoding::Utf16Be | YamlEncoding::Utf16Le => {
+            let mut units = Vec::with_capacity(input.len() / 2);
+            for chunk in input.chunks(2) {
+                let bytes: [u8; 2] = chunk.try_into().map_err(|_| {
+                    decode_error(encoding, &String::from_utf16_lossy(&units))
+                })?;
+                units.push(if encoding == YamlEncoding::Utf16Be {
+                    u16::from_be_bytes(bytes)
+                } else {
+                    u16::from_le_bytes(bytes)
+                });
+            }
+            let mut ret = String::with_capacity(units.len());
+            for c in char::decode_utf16(units) {
+                match c {
+                    Ok(c) => ret.push(c),
+                    Err(_) => return Err(decode_error(encoding, &ret)),
+                }
+            }
+            Ok(ret)
+        }
+        YamlEncoding::Utf32Be | YamlEncoding::Utf32Le => {
+            let mut ret = String::with_capacity(input.len() / 4);
+            for chunk in input.chunks(4) {
+                let c = <[u8; 4]>::try_from(chunk)
+                    .ok()
+                    .map(|bytes| {
+                        if encoding == YamlEncoding::Utf32Be {
+                            u32::from_be_bytes(bytes)
+                        } else {
+                            u32::from_le_bytes(bytes)
+                        }
+                    })
+                    .and_then(char::from_u32);
+                match c {
+                    Some(c) => ret.push(c),
+                    None => return Err(decode_error(encoding, &ret)),
+                }
+            }
+            Ok(ret)
+        }
+    }
+}
+
+/// The error position is the character after `decoded`.
+fn decode_error(encoding: YamlEncoding, decoded: &str) -> YamlError {
+    let line = decoded.matches('\n').count() + 1;
+    let column = decoded
+        .rsplit('\n')
+        .next()
+        .map(|l| l.chars().count())
+        .unwrap_or_default()
+        + 1;
+    let pos = YamlPosition::new(line, column);
+    YamlError::new(
+        ErrorKind::InvalidEncoding,
+        format!("Invalid {encoding} character"),
+        pos,
+        pos,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const INPUT: &str = "a: \u{e9}\nb: \u{1F600}\n";
+
+    fn encode_utf16(input: &str, big_endian: bool) -> Vec<u8> {
+        input
+            .encode_utf16()
+            .flat_map(|u| {
+                if big_endian {
+                    u.to_be_bytes()
+                } else {
+                    u.to_le_bytes()
+                }
+            })
+            .collect()
+    }
+
+    fn encode_utf32(input: &str, big_endian: bool) -> Vec<u8> {
+        input
+            .chars()
+            .flat_map(|c| {
+                if big_endian {
+                    (c as u32).to_be_bytes()
+                } else {
+                    (c as u32).to_le_bytes()
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_decode_with_and_without_bom() -> Result<(), YamlError> {
+        let with_bom = format!("\u{FEFF}{INPUT}");
+        for input in [INPUT, with_bom.as_str()] {
+            for (bytes, encoding) in [
+                (input.as_bytes().to_vec(), YamlEncoding::Utf8),
+                (encode_utf16(input, true), YamlEncoding::Utf16Be),
+                (encode_utf16(input, false), YamlEncoding::Utf16Le),
+                (encode_utf32(input, true), YamlEncoding::Utf32Be),
+                (encode_utf32(input, false), YamlEncoding::Utf32Le),
+            ] {
+                assert_eq!(YamlEncoding::detect(&bytes), encoding);
+                assert_eq!(decode_yaml_bytes(&bytes)?, input);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        let e = decode_yaml_bytes(b"a: b\nc: \xFF\n").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidEncoding);
+        assert_eq!(e.start_pos(), YamlPosition::new(2, 4));
+
+        let mut bytes = encode_utf16("a: b\n", false);
+        // Unpaired surrogate
+        bytes.extend_from_slice(&[0x00, 0xD8, b'c', 0]);
+        let e = decode_yaml_bytes(&bytes).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidEncoding);
+        assert_eq!(e.start_pos(), YamlPosition::new(2, 1));
+
+        let e = decode_yaml_bytes(&[b'a', 0, 0]).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidEncoding);
+        assert_eq!(e.start_pos(), YamlPosition::new(1, 2));
+    }
+}
@@ -1,6 +1,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::YamlPosition;
+use serde::{Serialize, Serializer, ser::SerializeStruct};
+
+use crate::{YamlPosition, YamlSerializeOption};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
 pub enum ErrorKind {
@@ -54,6 +56,55 @@ pub enum ErrorKind {
     LessIndentedWithoutParent,
     /// No support of multiple documents
     NoSupportMultipleDocuments,
+    /// Unexpected content after the end of a node or document
+    TrailingContent,
+    /// Flow collection entries should be separated by single `,`
+    InvalidFlowEntry,
+    /// Tab(\t) cannot be used as indentation
+    TabIndentation,
+    /// Content is less indented than required by its parent block collection
+    WrongIndentation,
+    /// Non-printable character found in YAML stream
+    InvalidCharacter,
+    /// YAML stream is not valid UTF-8, UTF-16 or UTF-32
+    InvalidEncoding,
+    /// Directive found without document end marker `...` of previous
+    /// document
+    MissingDocumentEnd,
+    /// Directives are not followed by document start marker `---`
+    MissingDocumentStart,
+    /// Invalid anchor or tag of node, e.g. node with two anchors
+    InvalidNodeProperty,
+    /// JSON compatible output only support string as map key
+    NonStringMapKey,
+    /// Alias refers to anchor not defined before it
+    UnknownAlias,
+    /// Node does not match the schema
+    SchemaViolation,
+    /// Invalid schema, e.g. malformed pattern
+    InvalidSchema,
+    /// Number cannot be represented by target type without precision loss
+    PrecisionLoss,
+    /// Feature not implemented yet
+    NotSupported,
+    /// Comment is not separated from preceding content by white space
+    InvalidCommentPlacement,
+    /// Failed to read or write YAML stream
+    Io,
+    /// Anchor is redefined before any alias referring to it
+    DuplicateAnchor,
+    /// Events are not balanced, e.g. sequence start without end
+    InvalidEventOrder,
+    /// Invalid `!include` node, e.g. include cycle
+    InvalidInclude,
+    /// Undefined variable or unclosed `${` in interpolation
+    InvalidInterpolation,
+    /// Invalid or duplicate `%YAML` directive
+    InvalidDirective,
+    /// Serialized output is not parsed back to the same data
+    RoundtripMismatch,
+    /// Map does not contain a field required by the struct
+    MissingField,
 }
 
 impl std::fmt::Display for ErrorKind {
@@ -89,26 +140,101 @@ impl std::fmt::Display for ErrorKind {
                     "less_indented_without_parent",
                 Self::NoSupportMultipleDocuments =>
                     "no_support_mulitple_documents",
+                Self::TrailingContent => "trailing_content",
+                Self::InvalidFlowEntry => "invalid_flow_entry",
+                Self::TabIndentation => "tab_indentation",
+                Self::WrongIndentation => "wrong_indentation",
+                Self::InvalidCharacter => "invalid_character",
+                Self::InvalidEncoding => "invalid_encoding",
+                Self::MissingDocumentEnd => "missing_document_end",
+                Self::MissingDocumentStart => "missing_document_start",
+                Self::InvalidNodeProperty => "invalid_node_property",
+                Self::NonStringMapKey => "non_string_map_key",
+                Self::UnknownAlias => "unknown_alias",
+                Self::SchemaViolation => "schema_violation",
+                Self::InvalidSchema => "invalid_schema",
+                Self::PrecisionLoss => "precision_loss",
+                Self::NotSupported => "not_supported",
+                Self::InvalidCommentPlacement => "invalid_comment_placement",
+                Self::Io => "io",
+                Self::DuplicateAnchor => "duplicate_anchor",
+                Self::InvalidEventOrder => "invalid_event_order",
+                Self::InvalidInclude => "invalid_include",
+                Self::InvalidInterpolation => "invalid_interpolation",
+                Self::InvalidDirective => "invalid_directive",
+                Self::RoundtripMismatch => "roundtrip_mismatch",
+                Self::MissingField => "missing_field",
             }
         )
     }
 }
 
+// All kinds, used to parse the string generated by Display.
+const ALL_ERROR_KINDS: [ErrorKind; 45] = [
+    ErrorKind::Bug,
+    ErrorKind::InvalidStartOfToken,
+    ErrorKind::InvalidPosition,
+    ErrorKind::StartWithReservedIndicator,
+    ErrorKind::InvalidEscapeScalar,
+    ErrorKind::UnfinishedQuote,
+    ErrorKind::InvalidErrorType,
+    ErrorKind::UnexpectedYamlNodeType,
+    ErrorKind::InvalidBool,
+    ErrorKind::InvalidNumber,
+    ErrorKind::NumberOverflow,
+    ErrorKind::UnfinishedMapIndicator,
+    ErrorKind::UnfinishedSequenceIndicator,
+    ErrorKind::IndentTooSmall,
+    ErrorKind::ExpectingCommentOrLineBreak,
+    ErrorKind::InvalidPlainScalarStart,
+    ErrorKind::AmbiguityPlainScalar,
+    ErrorKind::InvalidImplicitKey,
+    ErrorKind::InvalidSequnceStartIndicator,
+    ErrorKind::LessIndentedWithoutParent,
+    ErrorKind::NoSupportMultipleDocuments,
+    ErrorKind::TrailingContent,
+    ErrorKind::InvalidFlowEntry,
+    ErrorKind::TabIndentation,
+    ErrorKind::WrongIndentation,
+    ErrorKind::InvalidCharacter,
+    ErrorKind::InvalidEncoding,
+    ErrorKind::MissingDocumentEnd,
+    ErrorKind::MissingDocumentStart,
+    ErrorKind::InvalidNodeProperty,
+    ErrorKind::NonStringMapKey,
+    ErrorKind::UnknownAlias,
+    ErrorKind::SchemaViolation,
+    ErrorKind::InvalidSchema,
+    ErrorKind::PrecisionLoss,
+    ErrorKind::NotSupported,
+    ErrorKind::InvalidCommentPlacement,
+    ErrorKind::Io,
+    ErrorKind::DuplicateAnchor,
+    ErrorKind::InvalidEventOrder,
+    ErrorKind::InvalidInclude,
+    ErrorKind::InvalidInterpolation,
+    ErrorKind::InvalidDirective,
+    ErrorKind::RoundtripMismatch,
+    ErrorKind::MissingField,
+];
+
+/// Parse the string generated by Display of [ErrorKind], so the kind
+/// survives errors crossing serde via `custom()`.
 impl TryFrom<&str> for ErrorKind {
     type Error = YamlError;
 
     fn try_from(value: &str) -> Result<Self, YamlError> {
-        Ok(match value {
-            s if s == Self::Bug.to_string() => Self::Bug,
-            _ => {
-                return Err(YamlError::new(
+        ALL_ERROR_KINDS
+            .into_iter()
+            .find(|kind| kind.to_string() == value)
+            .ok_or_else(|| {
+                YamlError::new(
                     ErrorKind::InvalidErrorType,
                     format!("Invalid error type: {value}"),
                     YamlPosition::default(),
                     YamlPosition::default(),
-                ));
-            }
-        })
+                )
+            })
     }
 }
 
@@ -118,6 +244,7 @@ pub struct YamlError {
     msg: String,
     start_pos: YamlPosition,
     end_pos: YamlPosition,
+    key_path: Option<String>,
 }
 
 impl YamlError {
@@ -132,6 +259,7 @@ impl YamlError {
             msg,
             start_pos,
             end_pos,
+            key_path: None,
         }
     }
 
@@ -150,6 +278,73 @@ impl YamlError {
     pub fn end_pos(&self) -> YamlPosition {
         self.end_pos
     }
+
+    /// Key path of the node failed to deserialize, None for parsing errors
+    /// or errors of the document root. The key path is map keys and sequence
+    /// indexes joined by `.`, for example `interfaces.0.mtu`.
+    pub fn key_path(&self) -> Option<&str> {
+        self.key_path.as_deref()
+    }
+
+    // The innermost key path wins as errors are propagated to the parent
+    // node.
+    pub(crate) fn or_key_path(
+        mut self,
+        key_path: impl FnOnce() -> String,
+    ) -> Self {
+        if self.key_path.is_none() {
+            self.key_path = Some(key_path());
+        }
+        self
+    }
+
+    // Errors raised by serde without position, e.g. invalid type of content
+    // buffered by `#[serde(flatten)]`, are reported at the innermost node
+    // holding them.
+    pub(crate) fn or_position(
+        mut self,
+        start_pos: YamlPosition,
+        end_pos: YamlPosition,
+    ) -> Self {
+        if self.start_pos == YamlPosition::EOF
+            && self.end_pos == YamlPosition::EOF
+        {
+            self.start_pos = start_pos;
+            self.end_pos = end_pos;
+        }
+        self
+    }
+
+    /// JSON object holding `kind`, `msg`, `start`, `end` and `key_path` of
+    /// this error for tools consuming errors programmatically.
+    pub fn to_json(&self) -> String {
+        crate::to_string_with_opt(
+            self,
+            YamlSerializeOption {
+                json_compatible: true,
+                ..Default::default()
+            },
+        )
+        .map(|s| s.trim_end().to_string())
+        .unwrap_or_default()
+    }
+}
+
+/// Serialized as map of `kind` in [ErrorKind] display string, `msg`,
+/// `start` and `end` positions and `key_path`.
+impl Serialize for YamlError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("YamlError", 5)?;
+        state.serialize_field("kind", &self.kind.to_string())?;
+        state.serialize_field("msg", &self.msg)?;
+        state.serialize_field("start", &self.start_pos)?;
+        state.serialize_field("end", &self.end_pos)?;
+        state.serialize_field("key_path", &self.key_path)?;
+        state.end()
+    }
 }
 
 impl std::fmt::Display for YamlError {
@@ -167,30 +362,54 @@ impl std::fmt::Display for YamlError {
 
 impl From<&str> for YamlError {
     fn from(msg: &str) -> Self {
-        if let Some((pos_kind_str, msg_str)) = msg.split_once("error: ")
-            && let Some((pos_str, kind_str)) =
-                pos_kind_str.split_once(" kind: ")
-            && let Some((start_pos_str, end_pos_str)) = pos_str.split_once(":")
-        {
-            Self {
-                start_pos: YamlPosition::try_from(start_pos_str)
-                    .unwrap_or_default(),
-                end_pos: YamlPosition::try_from(end_pos_str)
-                    .unwrap_or_default(),
-                msg: msg_str.to_string(),
-                kind: ErrorKind::try_from(kind_str).unwrap_or_default(),
-            }
-        } else {
-            Self {
-                msg: msg.to_string(),
-                ..Default::default()
-            }
-        }
+        Self::from_display(msg).unwrap_or_else(|| Self {
+            msg: msg.to_string(),
+            ..Default::default()
+        })
+    }
+}
+
+impl YamlError {
+    // Parse the string generated by Display.
+    fn from_display(msg: &str) -> Option<Self> {
+        let (pos_kind_str, msg_str) = msg.split_once("error: ")?;
+        let (pos_str, kind_str) = pos_kind_str.split_once(" kind: ")?;
+        let (start_pos_str, end_pos_str) = pos_str.split_once(":")?;
+        Some(Self {
+            start_pos: YamlPosition::try_from(start_pos_str)
+                .unwrap_or_default(),
+            end_pos: YamlPosition::try_from(end_pos_str).unwrap_or_default(),
+            msg: msg_str.to_string(),
+            kind: ErrorKind::try_from(kind_str.trim_end()).unwrap_or_default(),
+            key_path: None,
+        })
     }
 }
 
 impl std::error::Error for YamlError {}
 
+impl From<std::io::Error> for YamlError {
+    fn from(e: std::io::Error) -> Self {
+        Self::new(
+            ErrorKind::Io,
+            e.to_string(),
+            YamlPosition::EOF,
+            YamlPosition::EOF,
+        )
+    }
+}
+
+impl From<std::fmt::Error> for YamlError {
+    fn from(e: std::fmt::Error) -> Self {
+        Self::new(
+            ErrorKind::Io,
+            e.to_string(),
+            YamlPosition::EOF,
+            YamlPosition::EOF,
+        )
+    }
+}
+
 impl serde::ser::Error for YamlError {
     fn custom<T>(msg: T) -> Self
     where
@@ -200,14 +419,106 @@ impl serde::ser::Error for YamlError {
     }
 }
 
+// Position is filled by the deserializer of node holding the error, see
+// YamlError::or_position().
 impl serde::de::Error for YamlError {
     fn custom<T>(msg: T) -> Self
     where
         T: std::fmt::Display,
     {
-        YamlError::from(msg.to_string().as_str())
+        let msg = msg.to_string();
+        YamlError::from_display(&msg).unwrap_or_else(|| {
+            YamlError::new(
+                ErrorKind::Bug,
+                msg,
+                YamlPosition::EOF,
+                YamlPosition::EOF,
+            )
+        })
     }
 
-    // TOOD: Implement more functions of this trait with position stored in
-    // error.
+    fn invalid_type(
+        unexp: serde::de::Unexpected,
+        exp: &dyn serde::de::Expected,
+    ) -> Self {
+        YamlError::new(
+            ErrorKind::UnexpectedYamlNodeType,
+            format!("invalid type: {unexp}, expected {exp}"),
+            YamlPosition::EOF,
+            YamlPosition::EOF,
+        )
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        YamlError::new(
+            ErrorKind::MissingField,
+            format!("missing field `{field}`"),
+            YamlPosition::EOF,
+            YamlPosition::EOF,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde::de::Error;
+
+    use super::*;
+
+    #[test]
+    fn test_error_kind_from_str() -> Result<(), YamlError> {
+        for kind in ALL_ERROR_KINDS {
+            assert_eq!(ErrorKind::try_from(kind.to_string().as_str())?, kind);
+        }
+        assert_eq!(
+            ErrorKind::try_from("foo").unwrap_err().kind(),
+            ErrorKind::InvalidErrorType
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_error_through_serde_custom() {
+        let error = YamlError::new(
+            ErrorKind::UnknownAlias,
+            "Unknown alias a".to_string(),
+            YamlPosition::new(2, 3),
+            YamlPosition::new(2, 4),
+        );
+        assert_eq!(YamlError::custom(&error), error);
+    }
+
+    #[test]
+    fn test_error_to_json() {
+        #[derive(Debug, serde::Deserialize)]
+        #[allow(dead_code)]
+        struct Interface {
+            name: String,
+            mtu: u32,
+        }
+
+        #[derive(Debug, serde::Deserialize)]
+        #[allow(dead_code)]
+        struct NetConf {
+            interfaces: Vec<Interface>,
+        }
+
+        let error = crate::from_str::<NetConf>(
+            "interfaces:\n- name: eth0\n  mtu: 1500\n- name: eth1\n  mtu: \
+             abc\n",
+        )
+        .unwrap_err();
+        assert_eq!(error.key_path(), Some("interfaces.1.mtu"));
+        assert_eq!(
+            error.to_json(),
+            r#"{"kind": "invalid_number", "msg": "Expecting unsigned integer like 87, 0xfa, 0o20 or 0b10, but got abc", "start": {"line": 5, "column": 8}, "end": {"line": 5, "column": 10}, "key_path": "interfaces.1.mtu"}"#
+        );
+
+        let error = crate::from_str::<NetConf>("interfaces:\n- name: eth0\n")
+            .unwrap_err();
+        assert_eq!(error.key_path(), Some("interfaces.0"));
+
+        let error = crate::from_str::<NetConf>("a: [").unwrap_err();
+        assert_eq!(error.key_path(), None);
+    }
 }
@@ -5,17 +5,55 @@ use crate::YamlPosition;
 #[derive(Debug, Clone, PartialEq)]
 pub(crate) struct YamlEventIter {
     data: Vec<Option<YamlEvent>>,
+    // Number of child nodes of the collection started at each index, used
+    // as capacity hint when composing.
+    child_counts: Vec<usize>,
     next_index: usize,
 }
 
 impl YamlEventIter {
     pub(crate) fn new(events: Vec<YamlEvent>) -> Self {
+        let mut child_counts = vec![0; events.len()];
+        let mut starts: Vec<usize> = Vec::new();
+        for (index, event) in events.iter().enumerate() {
+            match event {
+                YamlEvent::SequenceEnd(_) | YamlEvent::MapEnd(_) => {
+                    starts.pop();
+                }
+                YamlEvent::SequenceStart(..)
+                | YamlEvent::MapStart(..)
+                | YamlEvent::Scalar(..)
+                | YamlEvent::Alias(..) => {
+                    if let Some(start) = starts.last() {
+                        child_counts[*start] += 1;
+                    }
+                    if matches!(
+                        event,
+                        YamlEvent::SequenceStart(..) | YamlEvent::MapStart(..)
+                    ) {
+                        starts.push(index);
+                    }
+                }
+                _ => (),
+            }
+        }
         Self {
             data: events.into_iter().map(Some).collect(),
+            child_counts,
             next_index: 0,
         }
     }
 
+    // Number of child nodes of the collection whose start event is the last
+    // one taken by `next()`, 0 for other events.
+    pub(crate) fn child_count(&self) -> usize {
+        self.next_index
+            .checked_sub(1)
+            .and_then(|index| self.child_counts.get(index))
+            .copied()
+            .unwrap_or_default()
+    }
+
     pub(crate) fn next(&mut self) -> Option<YamlEvent> {
         if self.next_index >= self.data.len() {
             None
@@ -35,21 +73,130 @@ impl YamlEventIter {
     }
 }
 
+/// Parsing event of YAML stream, see [crate::YamlDocumentEventParser].
 #[derive(Debug, Clone, PartialEq)]
-pub(crate) enum YamlEvent {
+#[non_exhaustive]
+pub enum YamlEvent {
     StreamStart,
     StreamEnd,
     /// Whether document start with `---`
     DocumentStart(bool, YamlPosition),
-    /// Whether document start with `...`
+    /// Whether document end with `...`
     DocumentEnd(bool, YamlPosition),
-    /// Tag and position
-    SequenceStart(Option<String>, YamlPosition),
+    /// Anchor, tag and position
+    SequenceStart(Option<String>, Option<String>, YamlPosition),
     SequenceEnd(YamlPosition),
-    /// Tag and position
-    MapStart(Option<String>, YamlPosition),
+    /// Anchor, tag and position
+    MapStart(Option<String>, Option<String>, YamlPosition),
     MapEnd(YamlPosition),
-    Scalar(Option<String>, String, YamlPosition, YamlPosition),
+    /// Anchor, tag, style, value, start and end position
+    Scalar(
+        Option<String>,
+        Option<String>,
+        YamlScalarStyle,
+        String,
+        YamlPosition,
+        YamlPosition,
+    ),
+    /// Alias name and position
+    Alias(String, YamlPosition),
+}
+
+/// Presentation style of scalar in YAML document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum YamlScalarStyle {
+    #[default]
+    Plain,
+    /// Quoted by `'`
+    SingleQuoted,
+    /// Quoted by `"`
+    DoubleQuoted,
+    /// Block scalar started by `|`
+    Literal,
+    /// Block scalar started by `>`
+    Folded,
+}
+
+// Serializer treat newtype struct with these names as string in specified
+// style, other serializers just see the string.
+const STYLE_PLAIN: &str = "$rmsd_yaml::plain";
+const STYLE_SINGLE_QUOTED: &str = "$rmsd_yaml::single_quoted";
+const STYLE_DOUBLE_QUOTED: &str = "$rmsd_yaml::double_quoted";
+const STYLE_LITERAL: &str = "$rmsd_yaml::literal";
+const STYLE_FOLDED: &str = "$rmsd_yaml::folded";
+
+impl YamlScalarStyle {
+    /// Style indicator used by yaml-test-suite event format.
+    fn indicator(&self) -> char {
+        match self {
+            Self::Plain => ':',
+            Self::SingleQuoted => '\'',
+            Self::DoubleQuoted => '"',
+            Self::Literal => '|',
+            Self::Folded => '>',
+        }
+    }
+
+    pub(crate) fn newtype_name(&self) -> Option<&'static str> {
+        match self {
+            Self::Plain => Some(STYLE_PLAIN),
+            Self::SingleQuoted => Some(STYLE_SINGLE_QUOTED),
+            Self::DoubleQuoted => Some(STYLE_DOUBLE_QUOTED),
+            Self::Literal => Some(STYLE_LITERAL),
+            Self::Folded => Some(STYLE_FOLDED),
+        }
+    }
+
+    pub(crate) fn from_newtype_name(name: &str) -> Option<Self> {
+        match name {
+            STYLE_PLAIN => Some(Self::Plain),
+            STYLE_SINGLE_QUOTED => Some(Self::SingleQuoted),
+            STYLE_DOUBLE_QUOTED => Some(Self::DoubleQuoted),
+            STYLE_LITERAL => Some(Self::Literal),
+            STYLE_FOLDED => Some(Self::Folded),
+            _ => None,
+        }
+    }
+}
+
+impl YamlEvent {
+    /// Start position of the event, [YamlPosition::EOF] for stream events.
+    pub fn position(&self) -> YamlPosition {
+        match self {
+            Self::StreamStart | Self::StreamEnd => YamlPosition::EOF,
+            Self::DocumentStart(_, pos)
+            | Self::DocumentEnd(_, pos)
+            | Self::SequenceStart(_, _, pos)
+            | Self::SequenceEnd(pos)
+            | Self::MapStart(_, _, pos)
+            | Self::MapEnd(pos)
+            | Self::Scalar(_, _, _, _, pos, _)
+            | Self::Alias(_, pos) => *pos,
+        }
+    }
+
+    /// Position of the last character of the event, e.g. end of scalar or
+    /// the last `-` of `---`.
+    pub fn end_position(&self) -> YamlPosition {
+        match self {
+            Self::DocumentStart(true, pos) | Self::DocumentEnd(true, pos) => {
+                YamlPosition::new(pos.line, pos.column + 2)
+            }
+            Self::Scalar(_, _, _, _, _, end) => *end,
+            _ => self.position(),
+        }
+    }
+
+    /// Anchor of node event, None for other events.
+    pub(crate) fn anchor_mut(&mut self) -> Option<&mut Option<String>> {
+        match self {
+            Self::SequenceStart(anchor, _, _)
+            | Self::MapStart(anchor, _, _)
+            | Self::Scalar(anchor, _, _, _, _, _) => Some(anchor),
+            _ => None,
+        }
+    }
 }
 
 impl std::fmt::Display for YamlEvent {
@@ -61,37 +208,43 @@ impl std::fmt::Display for YamlEvent {
             Self::DocumentStart(false, _) => write!(f, "+DOC"),
             Self::DocumentEnd(true, _) => write!(f, "-DOC ..."),
             Self::DocumentEnd(false, _) => write!(f, "-DOC"),
-            Self::SequenceStart(tag, _) => {
-                if let Some(tag) = tag {
-                    write!(f, "+SEQ {tag}")
-                } else {
-                    write!(f, "+SEQ")
-                }
+            Self::SequenceStart(anchor, tag, _) => {
+                write!(f, "+SEQ{}", show_node_props(anchor, tag))
             }
             Self::SequenceEnd(_) => write!(f, "-SEQ"),
-            Self::MapStart(tag, _) => {
-                if let Some(tag) = tag {
-                    write!(f, "+MAP {tag}")
-                } else {
-                    write!(f, "+MAP")
-                }
+            Self::MapStart(anchor, tag, _) => {
+                write!(f, "+MAP{}", show_node_props(anchor, tag))
             }
             Self::MapEnd(_) => write!(f, "-MAP"),
-            Self::Scalar(tag, v, _, _) => {
-                if let Some(tag) = tag {
-                    write!(f, "=VAL {tag} {}", show_scalar_str(v))
-                } else {
-                    write!(f, "=VAL {}", show_scalar_str(v))
-                }
+            Self::Scalar(anchor, tag, style, v, _, _) => {
+                write!(
+                    f,
+                    "=VAL{} {}{}",
+                    show_node_props(anchor, tag),
+                    style.indicator(),
+                    show_scalar_str(v)
+                )
             }
+            Self::Alias(name, _) => write!(f, "=ALI *{name}"),
         }
     }
 }
 
-fn show_scalar_str(v: &str) -> String {
-    if v.contains("\n") {
-        format!("|{}", v.replace("\n", "\\n"))
-    } else {
-        format!(":{}", v)
+fn show_node_props(anchor: &Option<String>, tag: &Option<String>) -> String {
+    let mut ret = String::new();
+    if let Some(anchor) = anchor {
+        ret.push_str(&format!(" &{anchor}"));
+    }
+    if let Some(tag) = tag {
+        ret.push_str(&format!(" {tag}"));
     }
+    ret
+}
+
+fn show_scalar_str(v: &str) -> String {
+    v.replace('\\', "\\\\")
+        .replace('\u{08}', "\\b")
+        .replace('\t', "\\t")
+        .replace('\r', "\\r")
+        .replace('\n', "\\n")
 }
@@ -0,0 +1,236 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    ErrorKind, YamlError, YamlEvent, YamlParser, YamlScalarStyle,
+    scalar::line_folding, scanner::invalid_comment_error,
+};
+
+pub(crate) const FLOW_INDICATORS: [char; 5] = [',', '[', ']', '{', '}'];
+
+fn is_white_space_or_line_break(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\r' | '\n')
+}
+
+impl<'a> YamlParser<'a> {
+    /// Skip white spaces, line breaks and comments between flow collection
+    /// entries and indicators.
+    pub(crate) fn skip_flow_separation(&mut self) -> Result<(), YamlError> {
+        // Comment should be separated from other tokens by white spaces.
+        let mut separated = false;
+        while let Some(c) = self.scanner.peek_char() {
+            if is_white_space_or_line_break(c) {
+                self.scanner.next_char();
+                if c == '\n' || c == '\r' {
+                    self.expect_flow_line_indent()?;
+                }
+                separated = true;
+            } else if c == '#' && separated {
+                self.scanner.advance_till_linebreak();
+                self.expect_flow_line_indent()?;
+            } else if c == '#' {
+                return Err(invalid_comment_error(self.scanner.next_pos));
+            } else {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether next character is `:` acting as mapping value indicator.
+    /// Inside flow collection, `:` is value indicator when followed by white
+    /// space, line break or flow indicator.
+    pub(crate) fn is_flow_value_indicator(&self) -> bool {
+        let mut chars = self.scanner.remains().chars();
+        chars.next() == Some(':')
+            && chars.next().is_none_or(|c| {
+                is_white_space_or_line_break(c) || FLOW_INDICATORS.contains(&c)
+            })
+    }
+
+    /// Handle a node inside flow collection.
+    pub(crate) fn handle_flow_node(
+        &mut self,
+        mut tag: Option<String>,
+    ) -> Result<(), YamlError> {
+        trace!("handle_flow_node {:?}", self.scanner.remains());
+        self.skip_flow_separation()?;
+        if matches!(self.scanner.peek_char(), Some('!' | '&')) {
+            tag = self.handle_node_properties(tag)?;
+            self.skip_flow_separation()?;
+        }
+        match self.scanner.peek_char() {
+            Some('[') => self.handle_flow_seq(tag),
+            Some('{') => self.handle_flow_map(tag),
+            Some('"') => self.handle_double_quoted_flow_scalar(tag),
+            Some('\'') => self.handle_single_quoted_flow_scalar(tag),
+            Some('*') => self.handle_alias(tag),
+            Some(_) => self.handle_flow_plain_scalar(tag),
+            None => Err(self.unfinished_flow_error()),
+        }
+    }
+
+    /// Handle the value after `:` of flow map entry. Empty value is allowed
+    /// when followed by `,` or `closing` indicator.
+    pub(crate) fn handle_flow_map_value(
+        &mut self,
+        closing: char,
+    ) -> Result<(), YamlError> {
+        self.skip_flow_separation()?;
+        match self.scanner.peek_char() {
+            Some(c) if c == ',' || c == closing => {
+                self.push_empty_flow_scalar();
+                Ok(())
+            }
+            _ => self.handle_flow_node(None),
+        }
+    }
+
+    pub(crate) fn push_empty_flow_scalar(&mut self) {
+        self.push_event(YamlEvent::Scalar(
+            None,
+            None,
+            YamlScalarStyle::Plain,
+            String::new(),
+            self.scanner.done_pos,
+            self.scanner.done_pos,
+        ));
+    }
+
+    pub(crate) fn unfinished_flow_error(&self) -> YamlError {
+        let (kind, indicator) = if self.cur_state().is_flow_seq() {
+            (ErrorKind::UnfinishedSequenceIndicator, '[')
+        } else {
+            (ErrorKind::UnfinishedMapIndicator, '{')
+        };
+        let start = self
+            .flow_starts
+            .last()
+            .copied()
+            .unwrap_or(self.scanner.done_pos);
+        YamlError::new(
+            kind,
+            format!(
+                "Flow collection started by {indicator:?} at {start} is not \
+                 closed before end of input"
+            ),
+            start,
+            self.scanner.done_pos,
+        )
+    }
+
+    /// Expecting `,` or `closing` indicator after flow collection entry.
+    /// Return true if `closing` indicator found, the `,` is consumed.
+    pub(crate) fn expect_flow_entry_end(
+        &mut self,
+        closing: char,
+    ) -> Result<bool, YamlError> {
+        self.skip_flow_separation()?;
+        match self.scanner.peek_char() {
+            Some(',') => {
+                self.scanner.next_char();
+                Ok(false)
+            }
+            Some(c) if c == closing => Ok(true),
+            Some(c) => {
+                let pos = self.scanner.next_pos;
+                Err(YamlError::new(
+                    ErrorKind::InvalidFlowEntry,
+                    format!(
+                        "Expecting ',' or {closing:?} after flow collection \
+                         entry, but got {c:?}"
+                    ),
+                    pos,
+                    pos,
+                ))
+            }
+            None => Err(self.unfinished_flow_error()),
+        }
+    }
+
+    /// Plain scalar inside flow collection, ends by flow indicators,
+    /// value indicator or comment.
+    fn handle_flow_plain_scalar(
+        &mut self,
+        tag: Option<String>,
+    ) -> Result<(), YamlError> {
+        trace!("handle_flow_plain_scalar {:?}", self.scanner.remains());
+        let start_pos = self.scanner.next_pos;
+        let mut chars = self.scanner.remains().chars();
+        let first_char = chars.next();
+        let second_char = chars.next();
+        // YAML 1.2.2 SPEC, 7.3.3. Plain Style:
+        //      Plain scalars must never contain the “: ” and “ #” character
+        //      combinations. Plain scalars must not begin with most
+        //      indicators. However, the “:”, “?” and “-” indicators may be
+        //      used as the first character if followed by a non-space “safe”
+        //      character.
+        let is_invalid_start = match first_char {
+            Some('-' | '?' | ':') => second_char.is_none_or(|c| {
+                is_white_space_or_line_break(c) || FLOW_INDICATORS.contains(&c)
+            }),
+            Some(c) => {
+                "#&*!|>'\"%@`".contains(c) || FLOW_INDICATORS.contains(&c)
+            }
+            None => true,
+        };
+        if is_invalid_start {
+            return Err(YamlError::new(
+                ErrorKind::InvalidPlainScalarStart,
+                format!(
+                    "Invalid start of plain scalar in flow collection: {:?}",
+                    self.scanner.peek_till_linebreak()
+                ),
+                start_pos,
+                start_pos,
+            ));
+        }
+
+        let mut lines: Vec<String> = Vec::new();
+        let mut cur_line = String::new();
+        let mut end_pos = start_pos;
+        while let Some(c) = self.scanner.peek_char() {
+            if FLOW_INDICATORS.contains(&c)
+                || self.is_flow_value_indicator()
+                || (c == '#'
+                    && (cur_line.is_empty() || cur_line.ends_with([' ', '\t'])))
+            {
+                break;
+            }
+            self.scanner.next_char();
+            if c == '\n' || c == '\r' {
+                if c == '\r' {
+                    self.scanner.advance_if_starts_with("\n");
+                }
+                lines.push(std::mem::take(&mut cur_line));
+                self.expect_flow_line_indent()?;
+                while let Some(' ' | '\t') = self.scanner.peek_char() {
+                    self.scanner.next_char();
+                }
+            } else {
+                cur_line.push(c);
+                if c != ' ' && c != '\t' {
+                    end_pos = self.scanner.done_pos;
+                }
+            }
+        }
+        lines.push(cur_line);
+        // Empty lines before the terminating indicator are separation.
+        while lines.len() > 1
+            && lines
+                .last()
+                .is_some_and(|l| l.trim_matches([' ', '\t']).is_empty())
+        {
+            lines.pop();
+        }
+
+        self.push_event(YamlEvent::Scalar(
+            None,
+            tag,
+            YamlScalarStyle::Plain,
+            line_folding(lines.iter().map(|l| l.as_str()).collect()),
+            start_pos,
+            end_pos,
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Regression tests derived from the fuzzing corpus, see `fuzz/` folder.
+// Any input should be parsed to value or error without panic.
+
+use std::path::Path;
+
+use crate::{YamlParser, YamlValue};
+
+const TEST_DATA_FOLDER_PATH: &str = "yaml-test-suit-data";
+const INPUT_YAML_FILE_NAME: &str = "in.yaml";
+
+fn parse_all(input: &str) {
+    let _ = YamlParser::parse_to_events(input);
+    if let Ok(value) = input.parse::<YamlValue>() {
+        let _ = crate::to_string(&value);
+    }
+}
+
+// Content of all `in.yaml` files of yaml-test-suite.
+pub(crate) fn corpus() -> Vec<String> {
+    let mut ret = Vec::new();
+    let mut dirs =
+        vec![Path::new(env!("CARGO_MANIFEST_DIR")).join(TEST_DATA_FOLDER_PATH)];
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() && !path.is_symlink() {
+                dirs.push(path);
+            } else if path.ends_with(INPUT_YAML_FILE_NAME)
+                && let Ok(content) = std::fs::read_to_string(&path)
+            {
+                ret.push(content);
+            }
+        }
+    }
+    ret
+}
+
+#[test]
+fn test_no_panic_on_truncated_input() {
+    for input in corpus() {
+        for (i, _) in input.char_indices() {
+            parse_all(&input[..i]);
+        }
+        parse_all(&input);
+    }
+}
+
+#[test]
+fn test_no_panic_on_fuzz_regressions() {
+    for input in [
+        "",
+        "-",
+        "--",
+        "?-",
+        "\"\\x",
+        "\"\\u12",
+        "'",
+        "[",
+        "{a: ",
+        "&",
+        "*",
+        "!",
+        "%",
+        "|",
+        ">-1",
+        "? : x",
+        "- ? : x\n",
+        "\u{feff}",
+        "a:\r\n\t- b",
+    ] {
+        parse_all(input);
+    }
+}
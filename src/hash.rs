@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use sha2::{Digest, Sha256};
+
+use crate::{YamlValue, YamlValueData};
+
+const NULL: u8 = 0;
+const STRING: u8 = 1;
+const ARRAY: u8 = 2;
+const MAP: u8 = 3;
+const TAG: u8 = 4;
+
+impl YamlValue {
+    /// SHA-256 digest of the value content, stable across Rust releases,
+    /// platforms and crate versions, useful for caching and change
+    /// detection. Positions, scalar styles and order of map entries are
+    /// ignored, hence values equal by [YamlValue::canonical_cmp()] share
+    /// the same digest. Scalars are hashed by their literal, so `0x10` and
+    /// `16` are different.
+    ///
+    /// The digested bytes are type byte (null 0, scalar 1, sequence 2,
+    /// map 3, tagged node 4) followed by the content: length in 64 bits
+    /// little endian followed by UTF-8 of scalar, item count followed by
+    /// items of sequence, entry count followed by keys and values of map
+    /// sorted by key, length and UTF-8 of tag name followed by tagged
+    /// content.
+    pub fn stable_hash(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hash_data(&self.data, &mut hasher);
+        hasher.finalize().into()
+    }
+}
+
+fn hash_data(data: &YamlValueData, hasher: &mut Sha256) {
+    match data {
+        YamlValueData::Null => hasher.update([NULL]),
+        YamlValueData::String(s) => {
+            hasher.update([STRING]);
+            hash_str(s, hasher);
+        }
+        YamlValueData::Array(items) => {
+            hasher.update([ARRAY]);
+            hash_len(items.len(), hasher);
+            for item in items {
+                hash_data(&item.data, hasher);
+            }
+        }
+        YamlValueData::Map(map) => {
+            hasher.update([MAP]);
+            hash_len(map.len(), hasher);
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.canonical_cmp(b.0));
+            for (k, v) in entries {
+                hash_data(&k.data, hasher);
+                hash_data(&v.data, hasher);
+            }
+        }
+        YamlValueData::Tag(tag) => {
+            hasher.update([TAG]);
+            hash_str(&tag.name, hasher);
+            hash_data(&tag.data, hasher);
+        }
+    }
+}
+
+fn hash_len(len: usize, hasher: &mut Sha256) {
+    hasher.update((len as u64).to_le_bytes());
+}
+
+fn hash_str(s: &str, hasher: &mut Sha256) {
+    hash_len(s.len(), hasher);
+    hasher.update(s.as_bytes());
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::YamlError;
+
+    fn hex(digest: [u8; 32]) -> String {
+        digest.iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    #[test]
+    fn test_stable_hash() -> Result<(), YamlError> {
+        let value = YamlValue::from_str("a: 1\nb: [x, !t y]\n")?;
+        let reordered =
+            YamlValue::from_str("# comment\nb:\n  - 'x'\n  - !t y\na: 1\n")?;
+        assert_eq!(value.stable_hash(), reordered.stable_hash());
+
+        for other in ["a: 1\nb: [x, y]\n", "a: 0x1\nb: [x, !t y]\n", "a: 1\n"] {
+            assert_ne!(
+                value.stable_hash(),
+                YamlValue::from_str(other)?.stable_hash(),
+                "{other}"
+            );
+        }
+        assert_ne!(
+            YamlValue::from(YamlValueData::Null).stable_hash(),
+            YamlValue::from("").stable_hash()
+        );
+
+        // Pin the encoding: type byte 1, length 2 in u64 little endian, "ab"
+        assert_eq!(
+            hex(YamlValue::from("ab").stable_hash()),
+            "b10f40039ec5bea06099c4af3dc9e7550b357c9148de1421683eb6ae14b6d7bc"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,169 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::str::FromStr;
+
+use crate::{
+    ErrorKind, YamlDeserializer, YamlError, YamlValue,
+    deserializer::resolve_tag,
+};
+
+const INCLUDE_TAG: &str = "include";
+
+/// Loader of YAML source referred by `!include path` node, closure of
+/// `FnMut(&str) -> Result<String, YamlError>` is also a resolver.
+pub trait YamlIncludeResolver {
+    /// Load YAML source of the path.
+    fn load(&mut self, path: &str) -> Result<String, YamlError>;
+}
+
+impl<F> YamlIncludeResolver for F
+where
+    F: FnMut(&str) -> Result<String, YamlError>,
+{
+    fn load(&mut self, path: &str) -> Result<String, YamlError> {
+        self(path)
+    }
+}
+
+impl YamlDeserializer {
+    /// Replace every `!include path` node by the document loaded through
+    /// `resolver`, should be invoked before deserializing. The includes of
+    /// included document are resolved too up to `max_depth` levels. Include
+    /// cycle and exceeding `max_depth` fail with [ErrorKind::InvalidInclude].
+    /// The position of included root is the `!include` node while nodes
+    /// inside hold positions of the included document.
+    pub fn resolve_includes<R>(
+        &mut self,
+        resolver: &mut R,
+        max_depth: usize,
+    ) -> Result<(), YamlError>
+    where
+        R: YamlIncludeResolver,
+    {
+        resolve_includes(&mut self.parsed, resolver, &mut Vec::new(), max_depth)
+    }
+}
+
+// The `stack` holds paths of documents being included.
+fn resolve_includes<R>(
+    value: &mut YamlValue,
+    resolver: &mut R,
+    stack: &mut Vec<String>,
+    max_depth: usize,
+) -> Result<(), YamlError>
+where
+    R: YamlIncludeResolver,
+{
+    resolve_tag(value, INCLUDE_TAG, &mut |node: YamlValue| {
+        let path = node.as_str()?.to_string();
+        let error = |msg: String| {
+            YamlError::new(ErrorKind::InvalidInclude, msg, node.start, node.end)
+        };
+        if stack.contains(&path) {
+            return Err(error(format!(
+                "Include cycle detected: {} -> {path}",
+                stack.join(" -> ")
+            )));
+        }
+        if stack.len() >= max_depth {
+            return Err(error(format!(
+                "Including {path} exceeds the max depth {max_depth}"
+            )));
+        }
+        let source = resolver.load(&path)?;
+        let mut included = YamlValue::from_str(&source).map_err(|e| {
+            YamlError::new(
+                e.kind(),
+                format!("Failed to parse included {path}: {e}"),
+                node.start,
+                node.end,
+            )
+        })?;
+        stack.push(path);
+        resolve_includes(&mut included, resolver, stack, max_depth)?;
+        stack.pop();
+        Ok(included)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::YamlPosition;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Config {
+        name: String,
+        interfaces: Vec<Interface>,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Interface {
+        name: String,
+        mtu: u32,
+    }
+
+    fn resolver(
+        files: &HashMap<&str, &str>,
+    ) -> impl FnMut(&str) -> Result<String, YamlError> {
+        move |path| {
+            files.get(path).map(ToString::to_string).ok_or_else(|| {
+                YamlError::new(
+                    ErrorKind::Io,
+                    format!("{path} not found"),
+                    YamlPosition::EOF,
+                    YamlPosition::EOF,
+                )
+            })
+        }
+    }
+
+    #[test]
+    fn test_resolve_includes() -> Result<(), YamlError> {
+        let files = HashMap::from([
+            ("eth0.yml", "name: eth0\nmtu: !include mtu.yml\n"),
+            ("mtu.yml", "1500\n"),
+            ("loop.yml", "- !include loop.yml\n"),
+        ]);
+        let mut deserializer = YamlDeserializer::from_str(
+            "name: foo\ninterfaces:\n  - !include eth0.yml\n",
+        )?;
+        deserializer.resolve_includes(&mut resolver(&files), 8)?;
+        assert_eq!(
+            Config::deserialize(&mut deserializer)?,
+            Config {
+                name: "foo".into(),
+                interfaces: vec![Interface {
+                    name: "eth0".into(),
+                    mtu: 1500,
+                }],
+            }
+        );
+
+        let mut deserializer =
+            YamlDeserializer::from_str("a: !include eth0.yml\n")?;
+        let e = deserializer
+            .resolve_includes(&mut resolver(&files), 1)
+            .unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidInclude);
+
+        let mut deserializer = YamlDeserializer::from_str("!include loop.yml")?;
+        let e = deserializer
+            .resolve_includes(&mut resolver(&files), 8)
+            .unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidInclude);
+        assert_eq!(e.msg(), "Include cycle detected: loop.yml -> loop.yml");
+
+        let mut deserializer =
+            YamlDeserializer::from_str("a: !include none.yml\n")?;
+        let e = deserializer
+            .resolve_includes(&mut resolver(&files), 8)
+            .unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::Io);
+        Ok(())
+    }
+}
@@ -0,0 +1,335 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::ops::Range;
+
+use crate::{
+    ErrorKind, YamlDocument, YamlError, YamlPosition, YamlValue, YamlValueData,
+    document::split_documents, parse_documents, scanner::normalize_input,
+};
+
+/// Replace text between `start` and `end` by `text`. The `start` is
+/// inclusive while `end` is exclusive, hence the same `start` and `end` is
+/// inserting. Column after the last character of line is allowed for
+/// appending to the line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct YamlTextEdit {
+    pub start: YamlPosition,
+    pub end: YamlPosition,
+    pub text: String,
+}
+
+impl YamlTextEdit {
+    pub fn new(start: YamlPosition, end: YamlPosition, text: &str) -> Self {
+        Self {
+            start,
+            end,
+            text: text.to_string(),
+        }
+    }
+}
+
+/// Parsed YAML stream which reparses only the documents touched by
+/// [YamlTextEdit], useful for editors and language servers. The stream is
+/// split before `---` marker line and directives of following document,
+/// untouched documents are reused with positions moved.
+#[derive(Debug, Clone)]
+pub struct YamlIncrementalParser {
+    source: String,
+    chunks: Vec<YamlChunk>,
+}
+
+#[derive(Debug, Clone)]
+struct YamlChunk {
+    // Byte range in source
+    range: Range<usize>,
+    line_offset: usize,
+    documents: Result<Vec<YamlDocument>, YamlError>,
+}
+
+impl YamlIncrementalParser {
+    pub fn new(input: &str) -> Result<Self, YamlError> {
+        let source = normalize_input(input)?.to_string();
+        let (chunks, _) = parse_chunks(&source, &[], "");
+        let ret = Self { source, chunks };
+        ret.documents()?;
+        Ok(ret)
+    }
+
+    /// Normalized YAML stream with all edits applied.
+    pub fn source(&self) -> &str {
+        self.source.as_str()
+    }
+
+    /// Documents of current stream or the first error.
+    pub fn documents(&self) -> Result<Vec<&YamlDocument>, YamlError> {
+        let mut ret = Vec::new();
+        for chunk in &self.chunks {
+            match &chunk.documents {
+                Ok(docs) => ret.extend(docs),
+                Err(e) => return Err(e.clone()),
+            }
+        }
+        Ok(ret)
+    }
+
+    /// Apply the edit and reparse affected documents, return index range
+    /// of reparsed documents in [YamlIncrementalParser::documents()].
+    /// On invalid edit position, error is returned without change. On
+    /// parsing error, the edit is still applied, so that following edits
+    /// could fix it.
+    pub fn edit(
+        &mut self,
+        edit: &YamlTextEdit,
+    ) -> Result<Range<usize>, YamlError> {
+        let start = byte_offset(&self.source, edit.start)?;
+        let end = byte_offset(&self.source, edit.end)?;
+        if start > end {
+            return Err(YamlError::new(
+                ErrorKind::InvalidPosition,
+                format!("Edit start {} is after end {}", edit.start, edit.end),
+                edit.start,
+                edit.end,
+            ));
+        }
+        let text = normalize_input(&edit.text)?;
+        let mut source = String::with_capacity(
+            self.source.len() - (end - start) + text.len(),
+        );
+        source.push_str(&self.source[..start]);
+        source.push_str(&text);
+        source.push_str(&self.source[end..]);
+
+        let old_source = std::mem::replace(&mut self.source, source);
+        let (chunks, reparsed) =
+            parse_chunks(&self.source, &self.chunks, &old_source);
+        self.chunks = chunks;
+
+        let doc_count = |chunks: &[YamlChunk]| -> usize {
+            chunks
+                .iter()
+                .map(|c| c.documents.as_ref().map(Vec::len).unwrap_or(0))
+                .sum()
+        };
+        let reparsed_chunks = &self.chunks[reparsed.clone()];
+        if let Some(Err(e)) = reparsed_chunks
+            .iter()
+            .map(|c| &c.documents)
+            .find(|d| d.is_err())
+        {
+            return Err(e.clone());
+        }
+        let start = doc_count(&self.chunks[..reparsed.start]);
+        Ok(start..start + doc_count(reparsed_chunks))
+    }
+}
+
+// Split the source into chunks, chunks with the same text as the leading or
+// trailing old chunks are reused. Return the chunks and index range of
+// reparsed chunks.
+fn parse_chunks(
+    source: &str,
+    old_chunks: &[YamlChunk],
+    old_source: &str,
+) -> (Vec<YamlChunk>, Range<usize>) {
+    let base = source.as_ptr() as usize;
+    let new_chunks: Vec<(usize, Range<usize>)> = split_documents(source)
+        .into_iter()
+        .map(|(line_offset, chunk)| {
+            let start = chunk.as_ptr() as usize - base;
+            (line_offset, start..start + chunk.len())
+        })
+        .collect();
+    let same_text = |old: &YamlChunk, new: &Range<usize>| {
+        old.documents.is_ok()
+            && old_source[old.range.clone()] == source[new.clone()]
+    };
+    let prefix = old_chunks
+        .iter()
+        .zip(new_chunks.iter())
+        .take_while(|(old, (_, new))| same_text(old, new))
+        .count();
+    let suffix = old_chunks[prefix..]
+        .iter()
+        .rev()
+        .zip(new_chunks[prefix..].iter().rev())
+        .take_while(|(old, (_, new))| same_text(old, new))
+        .count();
+
+    let mut ret = Vec::with_capacity(new_chunks.len());
+    for (index, (line_offset, range)) in new_chunks.iter().enumerate() {
+        let old = if index < prefix {
+            Some(&old_chunks[index])
+        } else if index >= new_chunks.len() - suffix {
+            Some(&old_chunks[old_chunks.len() - (new_chunks.len() - index)])
+        } else {
+            None
+        };
+        let documents = match old {
+            Some(old) => {
+                let mut documents = old.documents.clone();
+                if let Ok(docs) = documents.as_mut() {
+                    for doc in docs {
+                        shift_document(doc, old.line_offset, *line_offset);
+                    }
+                }
+                documents
+            }
+            None => {
+                // Prefixed line breaks keep positions relative to the stream
+                let chunk = format!(
+                    "{}{}",
+                    "\n".repeat(*line_offset),
+                    &source[range.clone()]
+                );
+                parse_documents(&chunk)
+            }
+        };
+        ret.push(YamlChunk {
+            range: range.clone(),
+            line_offset: *line_offset,
+            documents,
+        });
+    }
+    (ret, prefix..new_chunks.len() - suffix)
+}
+
+fn byte_offset(source: &str, pos: YamlPosition) -> Result<usize, YamlError> {
+    let mut line_start = 0;
+    for (index, line) in source.split('\n').enumerate() {
+        if index + 1 == pos.line {
+            let column = pos.column.max(1) - 1;
+            if column == line.chars().count() {
+                return Ok(line_start + line.len());
+            }
+            if let Some((i, _)) = line.char_indices().nth(column) {
+                return Ok(line_start + i);
+            }
+            break;
+        }
+        line_start += line.len() + 1;
+    }
+    Err(YamlError::new(
+        ErrorKind::InvalidPosition,
+        format!("Position {pos} is out of the YAML stream"),
+        pos,
+        pos,
+    ))
+}
+
+fn shift_document(
+    doc: &mut YamlDocument,
+    old_offset: usize,
+    new_offset: usize,
+) {
+    if old_offset == new_offset {
+        return;
+    }
+    let shift = |pos: &mut YamlPosition| {
+        if *pos != YamlPosition::EOF {
+            pos.line = pos.line + new_offset - old_offset;
+        }
+    };
+    shift(&mut doc.start);
+    shift(&mut doc.end);
+    for directive in doc.directives.iter_mut() {
+        shift(&mut directive.position);
+    }
+    shift_value(&mut doc.root, &shift);
+}
+
+fn shift_value(value: &mut YamlValue, shift: &impl Fn(&mut YamlPosition)) {
+    shift(&mut value.start);
+    shift(&mut value.end);
+    shift_data(&mut value.data, shift);
+}
+
+fn shift_data(data: &mut YamlValueData, shift: &impl Fn(&mut YamlPosition)) {
+    match data {
+        YamlValueData::Array(items) => {
+            for item in items {
+                shift_value(item, shift);
+            }
+        }
+        YamlValueData::Map(map) => {
+            **map = std::mem::take(&mut **map)
+                .into_iter()
+                .map(|(mut k, mut v)| {
+                    shift_value(&mut k, shift);
+                    shift_value(&mut v, shift);
+                    (k, v)
+                })
+                .collect();
+        }
+        YamlValueData::Tag(tag) => shift_data(&mut tag.data, shift),
+        YamlValueData::Null | YamlValueData::String(_) => (),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn edit(
+        parser: &mut YamlIncrementalParser,
+        start: (usize, usize),
+        end: (usize, usize),
+        text: &str,
+    ) -> Result<Range<usize>, YamlError> {
+        let ret = parser.edit(&YamlTextEdit::new(
+            YamlPosition::new(start.0, start.1),
+            YamlPosition::new(end.0, end.1),
+            text,
+        ));
+        if ret.is_ok() {
+            assert_eq!(
+                parser.documents()?,
+                parse_documents(parser.source())?.iter().collect::<Vec<_>>(),
+                "{:?}",
+                parser.source()
+            );
+        }
+        ret
+    }
+
+    #[test]
+    fn test_incremental_parser() -> Result<(), YamlError> {
+        let mut parser =
+            YamlIncrementalParser::new("a: 1\n---\nb: [2]\n---\nc: !t 3\n")?;
+        assert_eq!(parser.documents()?.len(), 3);
+
+        // Changing value of second document
+        assert_eq!(edit(&mut parser, (3, 5), (3, 6), "20")?, 1..2);
+        assert_eq!(parser.source(), "a: 1\n---\nb: [20]\n---\nc: !t 3\n");
+
+        // Inserting lines moves the following documents
+        assert_eq!(edit(&mut parser, (1, 5), (1, 5), "\nd:\n  - 4")?, 0..1);
+        assert_eq!(parser.documents()?[2].start, YamlPosition::new(6, 1));
+
+        // Splitting document
+        assert_eq!(edit(&mut parser, (2, 1), (2, 1), "---\n")?, 0..2);
+        assert_eq!(parser.documents()?.len(), 4);
+
+        // Merging documents
+        assert_eq!(edit(&mut parser, (2, 1), (3, 1), "")?, 0..1);
+        assert_eq!(parser.documents()?.len(), 3);
+
+        // Invalid edit is fixed by following edit
+        assert_eq!(
+            edit(&mut parser, (5, 4), (5, 4), "[").unwrap_err().kind(),
+            ErrorKind::UnfinishedSequenceIndicator
+        );
+        assert!(parser.documents().is_err());
+        assert_eq!(edit(&mut parser, (5, 4), (5, 5), "")?, 1..2);
+
+        assert_eq!(
+            edit(&mut parser, (20, 1), (20, 1), "x").unwrap_err().kind(),
+            ErrorKind::InvalidPosition
+        );
+        assert_eq!(
+            edit(&mut parser, (1, 2), (1, 1), "x").unwrap_err().kind(),
+            ErrorKind::InvalidPosition
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{ErrorKind, YamlDeserializer, YamlError, YamlValue, YamlValueData};
+
+impl YamlValue {
+    /// Expand `${VAR}` and `${VAR:-default}` in string of all scalars
+    /// except map keys using the `lookup` function, e.g.
+    /// `|name| std::env::var(name).ok()`. The default is used when variable
+    /// is undefined or empty. The `$${` is escaped `${`. Undefined variable
+    /// without default fails with [ErrorKind::InvalidInterpolation] holding
+    /// position of the scalar.
+    pub fn interpolate<F>(&mut self, mut lookup: F) -> Result<(), YamlError>
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        interpolate_value(self, &mut lookup)
+    }
+}
+
+impl YamlDeserializer {
+    /// Same as [YamlValue::interpolate()] on the parsed document, should
+    /// be invoked before deserializing.
+    pub fn interpolate<F>(&mut self, lookup: F) -> Result<(), YamlError>
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        self.parsed.interpolate(lookup)
+    }
+}
+
+fn interpolate_value<F>(
+    value: &mut YamlValue,
+    lookup: &mut F,
+) -> Result<(), YamlError>
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    let data = if let YamlValueData::Tag(tag) = &mut value.data {
+        &mut tag.data
+    } else {
+        &mut value.data
+    };
+    match data {
+        YamlValueData::String(s) if s.contains('$') => {
+            *s = interpolate_str(s, lookup).map_err(|msg| {
+                YamlError::new(
+                    ErrorKind::InvalidInterpolation,
+                    msg,
+                    value.start,
+                    value.end,
+                )
+            })?;
+        }
+        YamlValueData::Array(items) => {
+            for item in items {
+                interpolate_value(item, lookup)?;
+            }
+        }
+        YamlValueData::Map(map) => {
+            for (_, v) in map.iter_mut() {
+                interpolate_value(v, lookup)?;
+            }
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+fn interpolate_str<F>(input: &str, lookup: &mut F) -> Result<String, String>
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    let mut ret = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(index) = rest.find('$') {
+        ret.push_str(&rest[..index]);
+        rest = &rest[index..];
+        if let Some(after) = rest.strip_prefix("$${") {
+            ret.push_str("${");
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("${") {
+            let Some(end) = after.find('}') else {
+                return Err(format!("Unclosed `${{` in {input:?}"));
+            };
+            let (name, default) = match after[..end].split_once(":-") {
+                Some((name, default)) => (name, Some(default)),
+                None => (&after[..end], None),
+            };
+            match (lookup(name), default) {
+                (Some(v), Some(default)) if v.is_empty() => {
+                    ret.push_str(default)
+                }
+                (Some(v), _) => ret.push_str(&v),
+                (None, Some(default)) => ret.push_str(default),
+                (None, None) => {
+                    return Err(format!("Undefined variable {name}"));
+                }
+            }
+            rest = &after[end + 1..];
+        } else {
+            ret.push('$');
+            rest = &rest[1..];
+        }
+    }
+    ret.push_str(rest);
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::YamlPosition;
+
+    #[test]
+    fn test_interpolate() -> Result<(), YamlError> {
+        let lookup = |name: &str| match name {
+            "HOST" => Some("example.com".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        };
+        let mut value = YamlValue::from_str(
+            "url: https://${HOST}:${PORT:-443}/\nlist:\n  - ${EMPTY:-a}$5\n  \
+             - !!str $${HOST}\n'${HOST}': b\n",
+        )?;
+        value.interpolate(lookup)?;
+        assert_eq!(
+            value.canonical_cmp(&YamlValue::from_str(
+                "url: https://example.com:443/\nlist:\n  - a$5\n  - !!str \
+                 ${HOST}\n'${HOST}': b\n"
+            )?),
+            std::cmp::Ordering::Equal
+        );
+
+        let mut value = YamlValue::from_str("a: 1\nb: ${USER}\n")?;
+        let e = value.interpolate(lookup).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidInterpolation);
+        assert_eq!(e.start_pos(), YamlPosition::new(2, 4));
+
+        let mut value = YamlValue::from_str("a: ${HOST\n")?;
+        let e = value.interpolate(lookup).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidInterpolation);
+        Ok(())
+    }
+}
@@ -0,0 +1,193 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Conversion between `serde_json::Value` and `YamlValue`.
+
+use serde_json::{Map, Number, Value};
+
+use crate::{
+    ErrorKind, YamlError, YamlScalarStyle, YamlValue, YamlValueData,
+    YamlValueMap,
+};
+
+impl From<Value> for YamlValue {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Null => YamlValueData::Null.into(),
+            Value::Bool(v) => v.to_string().into(),
+            Value::Number(v) => v.to_string().into(),
+            Value::String(v) => {
+                let mut value = Self::from(v);
+                // Quote the string which will be resolved as non-string
+                if value.is_null()
+                    || value.is_bool()
+                    || value.is_integer()
+                    || value.as_f64().is_ok()
+                {
+                    value.style = YamlScalarStyle::DoubleQuoted;
+                }
+                value
+            }
+            Value::Array(v) => {
+                YamlValueData::Array(v.into_iter().map(Self::from).collect())
+                    .into()
+            }
+            Value::Object(v) => v
+                .into_iter()
+                .map(|(k, v)| (Self::from(k), Self::from(v)))
+                .collect::<YamlValueMap>()
+                .into(),
+        }
+    }
+}
+
+/// Plain scalars are resolved by YAML 1.2.2 core schema, quoted and block
+/// scalars are always string. The `!!` tags are resolved, value with local
+/// tag, map with non-string key, NaN and infinite float are not supported by
+/// JSON.
+impl TryFrom<YamlValue> for Value {
+    type Error = YamlError;
+
+    fn try_from(value: YamlValue) -> Result<Self, YamlError> {
+        match value.data {
+            YamlValueData::Null => Ok(Value::Null),
+            YamlValueData::String(s)
+                if value.style != YamlScalarStyle::Plain =>
+            {
+                Ok(Value::String(s))
+            }
+            YamlValueData::String(_) if value.is_null() => Ok(Value::Null),
+            YamlValueData::String(_) if value.is_bool() => {
+                Ok(Value::Bool(value.as_bool()?))
+            }
+            YamlValueData::String(_) if value.is_integer() => {
+                if let Ok(v) = value.as_i64() {
+                    Ok(Value::Number(v.into()))
+                } else {
+                    Ok(Value::Number(value.as_u64()?.into()))
+                }
+            }
+            YamlValueData::String(ref s) => {
+                if let Ok(v) = value.as_f64() {
+                    Number::from_f64(v).map(Value::Number).ok_or_else(|| {
+                        YamlError::new(
+                            ErrorKind::InvalidNumber,
+                            format!(
+                                "Non-finite float {s} is not supported by JSON"
+                            ),
+                            value.start,
+                            value.end,
+                        )
+                    })
+                } else {
+                    Ok(Value::String(s.to_string()))
+                }
+            }
+            YamlValueData::Array(v) => Ok(Value::Array(
+                v.into_iter()
+                    .map(Value::try_from)
+                    .collect::<Result<Vec<_>, _>>()?,
+            )),
+            YamlValueData::Map(v) => {
+                let mut ret = Map::new();
+                for (k, v) in *v {
+                    if let YamlValueData::String(k) = k.data {
+                        ret.insert(k, Value::try_from(v)?);
+                    } else {
+                        return Err(YamlError::new(
+                            ErrorKind::NonStringMapKey,
+                            format!(
+                                "JSON only support string as map key, but got \
+                                 {}",
+                                k.data
+                            ),
+                            k.start,
+                            k.end,
+                        ));
+                    }
+                }
+                Ok(Value::Object(ret))
+            }
+            YamlValueData::Tag(tag) if tag.core_type().is_some() => {
+                let is_str = tag.core_type() == Some("str");
+                match (is_str, tag.data) {
+                    (true, YamlValueData::String(s)) => Ok(Value::String(s)),
+                    (_, data) => Value::try_from(YamlValue {
+                        data,
+                        start: value.start,
+                        end: value.end,
+                        style: value.style,
+                    }),
+                }
+            }
+            YamlValueData::Tag(tag) => Err(YamlError::new(
+                ErrorKind::UnexpectedYamlNodeType,
+                format!("JSON does not support YAML tag {}", tag.name),
+                value.start,
+                value.end,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_json_value_to_yaml_value() -> Result<(), YamlError> {
+        let json: Value = serde_json::from_str(
+            r#"{"a": [1, -2.5, true, null, "b"], "c": {"d": "123"}}"#,
+        )
+        .unwrap();
+        let yaml_value = YamlValue::from(json.clone());
+
+        assert_eq!(
+            yaml_value,
+            YamlValueMap::from_iter([
+                (
+                    "a",
+                    YamlValue::from(vec![
+                        YamlValue::from("1"),
+                        "-2.5".into(),
+                        "true".into(),
+                        YamlValueData::Null.into(),
+                        "b".into(),
+                    ])
+                ),
+                (
+                    "c",
+                    YamlValueMap::from_iter([(
+                        "d",
+                        YamlValue {
+                            style: YamlScalarStyle::DoubleQuoted,
+                            ..YamlValue::from("123")
+                        }
+                    )])
+                    .into()
+                ),
+            ])
+            .into()
+        );
+        assert_eq!(Value::try_from(yaml_value)?, json);
+        assert_eq!(
+            Value::try_from("a: '1'\nb: 1\n".parse::<YamlValue>()?)?,
+            serde_json::from_str::<Value>(r#"{"a": "1", "b": 1}"#).unwrap()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_yaml_value_to_json_value_error() -> Result<(), YamlError> {
+        let e =
+            Value::try_from("? [a]\n: b\n".parse::<YamlValue>()?).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::NonStringMapKey);
+        let e =
+            Value::try_from("a: !foo b\n".parse::<YamlValue>()?).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::UnexpectedYamlNodeType);
+        let e = Value::try_from("a: .nan\n".parse::<YamlValue>()?).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidNumber);
+        Ok(())
+    }
+}
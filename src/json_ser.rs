@@ -0,0 +1,432 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// JSON is subset of YAML flow style, this serializer emits JSON which could
+// be consumed by both YAML and JSON parsers.
+
+use std::fmt::Write;
+
+use serde::{Serialize, ser};
+
+use crate::{ErrorKind, YamlError, YamlPosition, scalar_ser::to_json_string};
+
+#[derive(Debug, Default)]
+pub(crate) struct YamlJsonSerializer {
+    pub(crate) output: String,
+}
+
+pub(crate) struct YamlJsonCompound<'a> {
+    ser: &'a mut YamlJsonSerializer,
+    is_first: bool,
+    // Closing indicators, e.g. `]}` for tuple variant
+    closing: &'static str,
+}
+
+impl YamlJsonSerializer {
+    fn start_compound(
+        &mut self,
+        variant: Option<&str>,
+        opening: char,
+    ) -> YamlJsonCompound<'_> {
+        let closing = if let Some(variant) = variant {
+            write!(self.output, "{{{}: ", to_json_string(variant)).ok();
+            if opening == '[' { "]}" } else { "}}" }
+        } else if opening == '[' {
+            "]"
+        } else {
+            "}"
+        };
+        self.output.push(opening);
+        YamlJsonCompound {
+            ser: self,
+            is_first: true,
+            closing,
+        }
+    }
+}
+
+impl YamlJsonCompound<'_> {
+    fn serialize_entry_separator(&mut self) {
+        if self.is_first {
+            self.is_first = false;
+        } else {
+            self.ser.output.push_str(", ");
+        }
+    }
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), YamlError>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.serialize_entry_separator();
+        value.serialize(&mut *self.ser)
+    }
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), YamlError>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.serialize_entry_separator();
+        let mut key_ser = YamlJsonSerializer::default();
+        key.serialize(&mut key_ser)?;
+        if !key_ser.output.starts_with('"') {
+            return Err(YamlError::new(
+                ErrorKind::NonStringMapKey,
+                format!(
+                    "JSON only support string as map key, but got {}",
+                    key_ser.output
+                ),
+                YamlPosition::EOF,
+                YamlPosition::EOF,
+            ));
+        }
+        self.ser.output.push_str(&key_ser.output);
+        self.ser.output.push_str(": ");
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), YamlError> {
+        self.ser.output.push_str(self.closing);
+        Ok(())
+    }
+}
+
+impl<'a> ser::Serializer for &'a mut YamlJsonSerializer {
+    type Ok = ();
+    type Error = YamlError;
+
+    type SerializeSeq = YamlJsonCompound<'a>;
+    type SerializeTuple = YamlJsonCompound<'a>;
+    type SerializeTupleStruct = YamlJsonCompound<'a>;
+    type SerializeTupleVariant = YamlJsonCompound<'a>;
+    type SerializeMap = YamlJsonCompound<'a>;
+    type SerializeStruct = YamlJsonCompound<'a>;
+    type SerializeStructVariant = YamlJsonCompound<'a>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), YamlError> {
+        self.output.push_str(if v { "true" } else { "false" });
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), YamlError> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), YamlError> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), YamlError> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), YamlError> {
+        write!(self.output, "{v}").ok();
+        Ok(())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), YamlError> {
+        write!(self.output, "{v}").ok();
+        Ok(())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), YamlError> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), YamlError> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), YamlError> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), YamlError> {
+        write!(self.output, "{v}").ok();
+        Ok(())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), YamlError> {
+        write!(self.output, "{v}").ok();
+        Ok(())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), YamlError> {
+        self.serialize_f64(f64::from(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), YamlError> {
+        if !v.is_finite() {
+            return Err(YamlError::new(
+                ErrorKind::InvalidNumber,
+                format!("Non-finite float {v} is not supported by JSON"),
+                YamlPosition::EOF,
+                YamlPosition::EOF,
+            ));
+        }
+        write!(self.output, "{v}").ok();
+        Ok(())
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), YamlError> {
+        self.serialize_str(&v.to_string())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), YamlError> {
+        self.output.push_str(&to_json_string(v));
+        Ok(())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), YamlError> {
+        let mut seq = self.serialize_seq(Some(v.len()))?;
+        for byte in v {
+            seq.serialize_element(byte)?;
+        }
+        seq.end()
+    }
+
+    fn serialize_none(self) -> Result<(), YamlError> {
+        self.output.push_str("null");
+        Ok(())
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<(), YamlError>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), YamlError> {
+        self.serialize_none()
+    }
+
+    fn serialize_unit_struct(
+        self,
+        _name: &'static str,
+    ) -> Result<(), YamlError> {
+        self.serialize_none()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), YamlError> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), YamlError>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), YamlError>
+    where
+        T: ?Sized + Serialize,
+    {
+        write!(self.output, "{{{}: ", to_json_string(variant)).ok();
+        value.serialize(&mut *self)?;
+        self.output.push('}');
+        Ok(())
+    }
+
+    fn serialize_seq(
+        self,
+        _len: Option<usize>,
+    ) -> Result<Self::SerializeSeq, YamlError> {
+        Ok(self.start_compound(None, '['))
+    }
+
+    fn serialize_tuple(
+        self,
+        _len: usize,
+    ) -> Result<Self::SerializeTuple, YamlError> {
+        Ok(self.start_compound(None, '['))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, YamlError> {
+        Ok(self.start_compound(None, '['))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, YamlError> {
+        Ok(self.start_compound(Some(variant), '['))
+    }
+
+    fn serialize_map(
+        self,
+        _len: Option<usize>,
+    ) -> Result<Self::SerializeMap, YamlError> {
+        Ok(self.start_compound(None, '{'))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, YamlError> {
+        Ok(self.start_compound(None, '{'))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, YamlError> {
+        Ok(self.start_compound(Some(variant), '{'))
+    }
+}
+
+impl ser::SerializeSeq for YamlJsonCompound<'_> {
+    type Ok = ();
+    type Error = YamlError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), YamlError>
+    where
+        T: ?Sized + Serialize,
+    {
+        YamlJsonCompound::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<(), YamlError> {
+        YamlJsonCompound::end(self)
+    }
+}
+
+impl ser::SerializeTuple for YamlJsonCompound<'_> {
+    type Ok = ();
+    type Error = YamlError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), YamlError>
+    where
+        T: ?Sized + Serialize,
+    {
+        YamlJsonCompound::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<(), YamlError> {
+        YamlJsonCompound::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for YamlJsonCompound<'_> {
+    type Ok = ();
+    type Error = YamlError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), YamlError>
+    where
+        T: ?Sized + Serialize,
+    {
+        YamlJsonCompound::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<(), YamlError> {
+        YamlJsonCompound::end(self)
+    }
+}
+
+impl ser::SerializeTupleVariant for YamlJsonCompound<'_> {
+    type Ok = ();
+    type Error = YamlError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), YamlError>
+    where
+        T: ?Sized + Serialize,
+    {
+        YamlJsonCompound::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<(), YamlError> {
+        YamlJsonCompound::end(self)
+    }
+}
+
+impl ser::SerializeMap for YamlJsonCompound<'_> {
+    type Ok = ();
+    type Error = YamlError;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), YamlError>
+    where
+        T: ?Sized + Serialize,
+    {
+        YamlJsonCompound::serialize_key(self, key)
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), YamlError>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), YamlError> {
+        YamlJsonCompound::end(self)
+    }
+}
+
+impl ser::SerializeStruct for YamlJsonCompound<'_> {
+    type Ok = ();
+    type Error = YamlError;
+
+    fn serialize_field<T>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), YamlError>
+    where
+        T: ?Sized + Serialize,
+    {
+        YamlJsonCompound::serialize_key(self, key)?;
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), YamlError> {
+        YamlJsonCompound::end(self)
+    }
+}
+
+impl ser::SerializeStructVariant for YamlJsonCompound<'_> {
+    type Ok = ();
+    type Error = YamlError;
+
+    fn serialize_field<T>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), YamlError>
+    where
+        T: ?Sized + Serialize,
+    {
+        YamlJsonCompound::serialize_key(self, key)?;
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), YamlError> {
+        YamlJsonCompound::end(self)
+    }
+}
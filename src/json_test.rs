@@ -0,0 +1,84 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// YAML 1.2 is a superset of JSON, every valid JSON document should be parsed
+// as the same data of its block style YAML equivalent.
+
+use std::{cmp::Ordering, collections::HashMap};
+
+use pretty_assertions::assert_eq;
+use serde::Deserialize;
+
+use crate::YamlError;
+
+const JSON_CORPUS: &[(&str, &str)] = &[
+    ("{}", "{}"),
+    ("[]", "[]"),
+    ("\"abc\"", "abc"),
+    ("-1.5e3", "-1.5e3"),
+    ("null", "null"),
+    ("{\"a\":1}", "a: 1"),
+    ("{\"a\":{\"b\":[]}}", "a:\n  b: []"),
+    (
+        "{\"a\" : \"b\",\"c\":[1,2 ,3]}",
+        "a: b\nc:\n  - 1\n  - 2\n  - 3",
+    ),
+    ("[true,false,null]", "- true\n- false\n- null"),
+    ("[[],{},[[1]]]", "- []\n- {}\n-\n  -\n    - 1"),
+    ("{\"a:b\":\"c,d\",\"e\":\"[f]\"}", "a:b: c,d\ne: \"[f]\""),
+    ("{\"\":\"empty key\"}", "\"\": empty key"),
+    ("{\"a\":\"\\u00e9\\n\\\"\"}", "a: \"é\\n\\\"\""),
+    (
+        "{\n  \"name\": \"eth1\",\n  \"mtu\": 1500,\n  \"ipv4\": {\n    \
+         \"address\": [\n      {\"ip\": \"192.0.2.1\", \"prefix\": 24}\n    \
+         ]\n  }\n}\n",
+        "name: eth1\nmtu: 1500\nipv4:\n  address:\n    - ip: 192.0.2.1\n      \
+         prefix: 24",
+    ),
+];
+
+#[test]
+fn test_json_corpus() -> Result<(), YamlError> {
+    crate::testlib::init_logger();
+    for (json, yaml) in JSON_CORPUS {
+        trace!("JSON: {json:?}");
+        let json_value = crate::to_value(json)?;
+        let yaml_value = crate::to_value(yaml)?;
+        assert_eq!(
+            json_value.canonical_cmp(&yaml_value),
+            Ordering::Equal,
+            "JSON {json:?} parsed as {json_value:?}"
+        );
+    }
+    Ok(())
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Interface {
+    name: String,
+    mtu: u32,
+    enabled: bool,
+    dns: Vec<String>,
+    options: HashMap<String, u8>,
+}
+
+#[test]
+fn test_json_to_struct() -> Result<(), YamlError> {
+    let iface: Interface = crate::from_str(
+        r#"{"name":"eth1","mtu":9000,"enabled":true,
+            "dns":["192.0.2.1","2001:db8::1"],"options":{"a":1,"b":2}}"#,
+    )?;
+    assert_eq!(
+        iface,
+        Interface {
+            name: "eth1".to_string(),
+            mtu: 9000,
+            enabled: true,
+            dns: vec!["192.0.2.1".to_string(), "2001:db8::1".to_string()],
+            options: HashMap::from([
+                ("a".to_string(), 1),
+                ("b".to_string(), 2)
+            ]),
+        }
+    );
+    Ok(())
+}
@@ -0,0 +1,118 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::str::FromStr;
+
+use crate::{
+    YamlError, YamlPosition, YamlValue, YamlValueData, find_value_indicator,
+    scanner::normalize_input,
+};
+
+/// Parse YAML document and keep going on errors, useful for editors holding
+/// incomplete document. On error, the invalid value is replaced by null
+/// placeholder, or the invalid lines are dropped when not in map value,
+/// then the document is parsed again. Positions of the returned value and
+/// errors are relative to the input. The returned errors are sorted by
+/// position and empty for valid document.
+pub fn parse_lenient(input: &str) -> (YamlValue, Vec<YamlError>) {
+    let mut lines: Vec<String> = match normalize_input(input) {
+        Ok(input) => input.split('\n').map(ToString::to_string).collect(),
+        Err(e) => {
+            return (YamlValue::from(YamlValueData::Null), vec![e]);
+        }
+    };
+    let mut errors: Vec<YamlError> = Vec::new();
+    // Each retry is blanking or truncating a line, hence the loop ends
+    loop {
+        let e = match YamlValue::from_str(&lines.join("\n")) {
+            Ok(value) => {
+                errors.sort_by_key(|e| e.start_pos());
+                return (value, errors);
+            }
+            Err(e) => e,
+        };
+        let changed = drop_invalid(&mut lines, &e);
+        if errors.last() != Some(&e) {
+            errors.push(e);
+        }
+        if !changed {
+            errors.sort_by_key(|e| e.start_pos());
+            return (YamlValue::from(YamlValueData::Null), errors);
+        }
+    }
+}
+
+// Return false if nothing left to drop.
+fn drop_invalid(lines: &mut [String], error: &YamlError) -> bool {
+    let start = error.start_pos();
+    // Error at the end of input, e.g. unfinished flow sequence
+    if start == YamlPosition::EOF || start.line > lines.len() {
+        return drop_last_line(lines);
+    }
+    let line = &mut lines[start.line - 1];
+    if let Some(i) = find_value_indicator(line)
+        && line[..i].chars().count() + 1 < start.column
+    {
+        line.truncate(i + 1);
+        return true;
+    }
+    let end_line = if error.end_pos() == YamlPosition::EOF {
+        start.line
+    } else {
+        error.end_pos().line.clamp(start.line, lines.len())
+    };
+    let mut changed = false;
+    for line in &mut lines[start.line - 1..end_line] {
+        if !line.is_empty() {
+            line.clear();
+            changed = true;
+        }
+    }
+    changed || drop_last_line(lines)
+}
+
+fn drop_last_line(lines: &mut [String]) -> bool {
+    match lines.iter_mut().rfind(|l| !l.trim().is_empty()) {
+        Some(line) => {
+            line.clear();
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_lenient(input: &str, expected: &str, error_lines: &[usize]) {
+        let (value, errors) = parse_lenient(input);
+        let expected = YamlValue::from_str(expected).unwrap();
+        assert!(
+            value.canonical_cmp(&expected).is_eq(),
+            "{input:?} parsed as {value:?}"
+        );
+        assert_eq!(
+            errors
+                .iter()
+                .map(|e| e.start_pos().line)
+                .collect::<Vec<_>>(),
+            error_lines,
+            "{errors:?}"
+        );
+    }
+
+    #[test]
+    fn test_parse_lenient() {
+        assert_lenient("a: 1\nb: [2, 3]\n", "a: 1\nb: [2, 3]\n", &[]);
+        assert_lenient(
+            "name: eth0\nmtu: \"1500\nstate: up\n",
+            "name: eth0\nmtu:\n",
+            &[2, 3],
+        );
+        assert_lenient("a: 1\nb: @x\nc: 3\n", "a: 1\nb:\nc: 3\n", &[2]);
+        assert_lenient("a: *x\n  b: 2\nc: 3\n", "a:\n  b: 2\nc: 3\n", &[1]);
+        assert_lenient("a:\n  - 1\n  -2\n  - 3\n", "a: [1, 3]", &[3]);
+        assert_lenient("a: [1,\n", "a:", &[1]);
+        assert_lenient("[1, 2\n", "", &[1]);
+    }
+}
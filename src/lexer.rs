@@ -0,0 +1,431 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    YamlError, YamlEvent, YamlParser, YamlPosition, YamlScalarStyle,
+    comment_indicators, scanner::normalize_input,
+};
+
+/// Category of [YamlToken].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum YamlTokenKind {
+    /// Scalar used as map key, in any style.
+    Key,
+    /// Plain scalar other than map key.
+    Value,
+    /// Quoted or block scalar other than map key.
+    String,
+    /// Structure indicators, e.g. `-`, `:`, `[`, `|-` and `---`.
+    Indicator,
+    /// From `#` to the end of line.
+    Comment,
+    /// Node tag, e.g. `!!str` or `!foo`.
+    Tag,
+    /// Node anchor, e.g. `&base`.
+    Anchor,
+    /// Alias node, e.g. `*base`.
+    Alias,
+    /// Whole directive line except comment, e.g. `%YAML 1.2`.
+    Directive,
+}
+
+/// Span of source text with its category, the end position is inclusive.
+/// Positions are based on the input with line break normalized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct YamlToken {
+    pub kind: YamlTokenKind,
+    pub start: YamlPosition,
+    pub end: YamlPosition,
+}
+
+/// Tokens of YAML stream in the order of source, suitable for syntax
+/// highlighting. The input is parsed first, hence tokens follow the
+/// interpretation of this parser. For invalid YAML, e.g. document still
+/// being edited, the scalars are guessed line by line instead.
+#[derive(Debug, Clone)]
+pub struct YamlLexer {
+    tokens: std::vec::IntoIter<YamlToken>,
+}
+
+impl YamlLexer {
+    /// Only fails when the input cannot be normalized, invalid YAML does
+    /// not fail.
+    pub fn new(input: &str) -> Result<Self, YamlError> {
+        let input = normalize_input(input)?;
+        let parsed = YamlParser::parse_to_events(&input).ok();
+        let mut tokens =
+            parsed.as_deref().map(scalar_tokens).unwrap_or_default();
+        let scalars = ScalarSpans::new(&tokens);
+
+        for (line_index, line) in input.lines().enumerate() {
+            let line_no = line_index + 1;
+            let chars: Vec<char> = line.chars().collect();
+            let guessed;
+            let scalars = if parsed.is_none() && !line.starts_with('%') {
+                let line_tokens = guess_scalars(&chars, line_no);
+                guessed = ScalarSpans::new(&line_tokens);
+                tokens.extend(line_tokens);
+                &guessed
+            } else {
+                &scalars
+            };
+            let comment = find_comment(line, line_no, scalars);
+            let code_len = comment.unwrap_or(chars.len());
+            if let Some(col_index) = comment {
+                tokens.push(YamlToken {
+                    kind: YamlTokenKind::Comment,
+                    start: YamlPosition::new(line_no, col_index + 1),
+                    end: YamlPosition::new(line_no, chars.len()),
+                });
+            }
+            if line.starts_with('%') {
+                let len = chars[..code_len]
+                    .iter()
+                    .rposition(|c| !c.is_whitespace())
+                    .map(|i| i + 1)
+                    .unwrap_or(1);
+                tokens.push(YamlToken {
+                    kind: YamlTokenKind::Directive,
+                    start: YamlPosition::new(line_no, 1),
+                    end: YamlPosition::new(line_no, len),
+                });
+                continue;
+            }
+            lex_line(&chars[..code_len], line_no, scalars, &mut tokens);
+        }
+        tokens.sort_by_key(|t| t.start);
+        Ok(Self {
+            tokens: tokens.into_iter(),
+        })
+    }
+}
+
+impl Iterator for YamlLexer {
+    type Item = YamlToken;
+
+    fn next(&mut self) -> Option<YamlToken> {
+        self.tokens.next()
+    }
+}
+
+// Scalar tokens with map key detected by the order of events.
+fn scalar_tokens(events: &[YamlEvent]) -> Vec<YamlToken> {
+    let mut ret = Vec::new();
+    // Whether map is expecting key for each opened collection, None for
+    // sequence.
+    let mut collections: Vec<Option<bool>> = Vec::new();
+    for event in events {
+        let is_key = collections.last().copied().flatten().unwrap_or_default();
+        let is_node_end = match event {
+            YamlEvent::Scalar(_, _, style, value, start, end) => {
+                if !(value.is_empty() && *style == YamlScalarStyle::Plain) {
+                    ret.push(YamlToken {
+                        kind: if is_key {
+                            YamlTokenKind::Key
+                        } else if *style == YamlScalarStyle::Plain {
+                            YamlTokenKind::Value
+                        } else {
+                            YamlTokenKind::String
+                        },
+                        start: *start,
+                        end: *end,
+                    });
+                }
+                true
+            }
+            YamlEvent::Alias(_, _) => true,
+            YamlEvent::MapStart(_, _, _) => {
+                collections.push(Some(true));
+                false
+            }
+            YamlEvent::SequenceStart(_, _, _) => {
+                collections.push(None);
+                false
+            }
+            YamlEvent::MapEnd(_) | YamlEvent::SequenceEnd(_) => {
+                collections.pop();
+                true
+            }
+            _ => false,
+        };
+        if is_node_end && let Some(Some(is_key)) = collections.last_mut() {
+            *is_key = !*is_key;
+        }
+    }
+    ret
+}
+
+// Spans of scalars sorted by start position, scalars never overlap, hence
+// lookup is binary search instead of checking every scalar.
+#[derive(Debug, Default)]
+struct ScalarSpans(Vec<(YamlPosition, YamlPosition)>);
+
+impl ScalarSpans {
+    fn new(tokens: &[YamlToken]) -> Self {
+        let mut spans: Vec<(YamlPosition, YamlPosition)> =
+            tokens.iter().map(|t| (t.start, t.end)).collect();
+        spans.sort_unstable();
+        Self(spans)
+    }
+
+    fn contains(&self, pos: YamlPosition) -> bool {
+        let index = self.0.partition_point(|(start, _)| *start <= pos);
+        index > 0 && pos <= self.0[index - 1].1
+    }
+}
+
+/// Character index of `#` starting comment in specified line, the `#`
+/// should not be inside of any scalar.
+fn find_comment(
+    line: &str,
+    line_no: usize,
+    scalars: &ScalarSpans,
+) -> Option<usize> {
+    let mut col_index = 0;
+    let mut counted = 0;
+    for offset in comment_indicators(line) {
+        col_index += line[counted..offset].chars().count();
+        counted = offset;
+        if !scalars.contains(YamlPosition::new(line_no, col_index + 1)) {
+            return Some(col_index);
+        }
+    }
+    None
+}
+
+// Scalar tokens of a line guessed without parsing, used when input is not
+// valid YAML. Quoted scalar ends at the end of line at most.
+fn guess_scalars(chars: &[char], line_no: usize) -> Vec<YamlToken> {
+    let is_blank = |i: usize| chars.get(i).is_none_or(|c| c.is_whitespace());
+    let is_flow = |c: char| matches!(c, ',' | '[' | ']' | '{' | '}');
+    let mut ret = Vec::new();
+    let mut flow_level = 0usize;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        let kind = match c {
+            _ if c.is_whitespace() => None,
+            // Comment
+            '#' if i == 0 || matches!(chars[i - 1], ' ' | '\t') => break,
+            '[' | '{' => {
+                flow_level += 1;
+                None
+            }
+            ']' | '}' => {
+                flow_level = flow_level.saturating_sub(1);
+                None
+            }
+            ',' => None,
+            '-' | '?' | ':' if is_blank(i + 1) => None,
+            '-' | '.' if i == 0 && chars.starts_with(&[c, c, c]) => {
+                i += 2;
+                None
+            }
+            '&' | '*' | '!' | '|' | '>' => {
+                while !is_blank(i + 1) && !is_flow(chars[i + 1]) {
+                    i += 1;
+                }
+                None
+            }
+            '\'' | '"' => {
+                i += 1;
+                while i < chars.len() && chars[i] != c {
+                    if chars[i] == '\\' && c == '"'
+                        || chars[i] == '\'' && chars.get(i + 1) == Some(&'\'')
+                    {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                i = i.min(chars.len() - 1);
+                Some(YamlTokenKind::String)
+            }
+            _ => {
+                while i + 1 < chars.len()
+                    && !(chars[i + 1] == ':'
+                        && (flow_level > 0 || is_blank(i + 2)))
+                    && !(chars[i + 1] == '#' && chars[i].is_whitespace())
+                    && !(flow_level > 0 && is_flow(chars[i + 1]))
+                {
+                    i += 1;
+                }
+                while chars[i].is_whitespace() {
+                    i -= 1;
+                }
+                Some(YamlTokenKind::Value)
+            }
+        };
+        if let Some(kind) = kind {
+            let is_key = chars[i + 1..]
+                .iter()
+                .find(|c| !c.is_whitespace())
+                .is_some_and(|c| *c == ':');
+            ret.push(YamlToken {
+                kind: if is_key { YamlTokenKind::Key } else { kind },
+                start: YamlPosition::new(line_no, start + 1),
+                end: YamlPosition::new(line_no, i + 1),
+            });
+        }
+        i += 1;
+    }
+    ret
+}
+
+// Tokens outside of scalars and comment.
+fn lex_line(
+    chars: &[char],
+    line_no: usize,
+    scalars: &ScalarSpans,
+    tokens: &mut Vec<YamlToken>,
+) {
+    let in_scalar =
+        |i: usize| scalars.contains(YamlPosition::new(line_no, i + 1));
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i].is_whitespace() || in_scalar(i) {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        let kind = match chars[i] {
+            '&' => YamlTokenKind::Anchor,
+            '*' => YamlTokenKind::Alias,
+            '!' => YamlTokenKind::Tag,
+            _ => YamlTokenKind::Indicator,
+        };
+        match chars[i] {
+            '&' | '*' | '!' => {
+                while i + 1 < chars.len()
+                    && !chars[i + 1].is_whitespace()
+                    && !matches!(chars[i + 1], ',' | '[' | ']' | '{' | '}')
+                {
+                    i += 1;
+                }
+            }
+            '|' | '>' => {
+                while i + 1 < chars.len()
+                    && matches!(chars[i + 1], '+' | '-' | '0'..='9')
+                {
+                    i += 1;
+                }
+            }
+            '-' | '.'
+                if start == 0
+                    && chars.len() >= 3
+                    && chars[1] == chars[0]
+                    && chars[2] == chars[0]
+                    && chars.get(3).is_none_or(|c| c.is_whitespace()) =>
+            {
+                i += 2;
+            }
+            _ => (),
+        }
+        tokens.push(YamlToken {
+            kind,
+            start: YamlPosition::new(line_no, start + 1),
+            end: YamlPosition::new(line_no, i + 1),
+        });
+        i += 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_lexer() -> Result<(), YamlError> {
+        let input =
+            "%YAML 1.2\n---\na: &x 'b' # c\n\"d\": [!e f, *x]\ng: |-\n  h\n";
+        let tokens: Vec<(YamlTokenKind, String)> = YamlLexer::new(input)?
+            .map(|t| {
+                let line = input.lines().nth(t.start.line - 1).unwrap();
+                let text = if t.start.line == t.end.line {
+                    line.chars()
+                        .skip(t.start.column - 1)
+                        .take(t.end.column + 1 - t.start.column)
+                        .collect()
+                } else {
+                    format!("{}..", &line[t.start.column - 1..])
+                };
+                (t.kind, text)
+            })
+            .collect();
+        assert_eq!(
+            tokens,
+            vec![
+                (YamlTokenKind::Directive, "%YAML 1.2".into()),
+                (YamlTokenKind::Indicator, "---".into()),
+                (YamlTokenKind::Key, "a".into()),
+                (YamlTokenKind::Indicator, ":".into()),
+                (YamlTokenKind::Anchor, "&x".into()),
+                (YamlTokenKind::String, "'b'".into()),
+                (YamlTokenKind::Comment, "# c".into()),
+                (YamlTokenKind::Key, "\"d\"".into()),
+                (YamlTokenKind::Indicator, ":".into()),
+                (YamlTokenKind::Indicator, "[".into()),
+                (YamlTokenKind::Tag, "!e".into()),
+                (YamlTokenKind::Value, "f".into()),
+                (YamlTokenKind::Indicator, ",".into()),
+                (YamlTokenKind::Alias, "*x".into()),
+                (YamlTokenKind::Indicator, "]".into()),
+                (YamlTokenKind::Key, "g".into()),
+                (YamlTokenKind::Indicator, ":".into()),
+                (YamlTokenKind::Indicator, "|-".into()),
+                (YamlTokenKind::String, "h".into()),
+            ]
+        );
+        Ok(())
+    }
+
+    fn token_texts(
+        input: &str,
+    ) -> Result<Vec<(YamlTokenKind, String)>, YamlError> {
+        let lines: Vec<&str> = input.lines().collect();
+        Ok(YamlLexer::new(input)?
+            .map(|t| {
+                let line = lines[t.start.line - 1];
+                let text = line
+                    .chars()
+                    .skip(t.start.column - 1)
+                    .take(t.end.column + 1 - t.start.column)
+                    .collect();
+                (t.kind, text)
+            })
+            .collect())
+    }
+
+    #[test]
+    fn test_lexer_invalid_yaml() -> Result<(), YamlError> {
+        assert_eq!(
+            token_texts("%YAML 1.2 # a\nb: [c, 'd # e' # f\n\"g\": !h \"i\n")?,
+            vec![
+                (YamlTokenKind::Directive, "%YAML 1.2".into()),
+                (YamlTokenKind::Comment, "# a".into()),
+                (YamlTokenKind::Key, "b".into()),
+                (YamlTokenKind::Indicator, ":".into()),
+                (YamlTokenKind::Indicator, "[".into()),
+                (YamlTokenKind::Value, "c".into()),
+                (YamlTokenKind::Indicator, ",".into()),
+                (YamlTokenKind::String, "'d # e'".into()),
+                (YamlTokenKind::Comment, "# f".into()),
+                (YamlTokenKind::Key, "\"g\"".into()),
+                (YamlTokenKind::Indicator, ":".into()),
+                (YamlTokenKind::Tag, "!h".into()),
+                (YamlTokenKind::String, "\"i".into()),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_lexer_many_scalars() -> Result<(), YamlError> {
+        let input = "- a: b # c\n".repeat(2000);
+        let tokens = token_texts(&input)?;
+        assert_eq!(tokens.len(), 2000 * 5);
+        assert_eq!(tokens[4], (YamlTokenKind::Comment, "# c".into()));
+        Ok(())
+    }
+}
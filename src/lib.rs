@@ -1,46 +1,126 @@
 // SPDX-License-Identifier: Apache-2.0
 
+// Library should never write to stdout or stderr, use `trace!()` instead.
+#![cfg_attr(not(test), deny(clippy::print_stdout, clippy::print_stderr))]
+
+#[macro_use]
+mod logging;
+
+mod anchor;
+#[cfg(feature = "tokio")]
+mod async_reader;
+mod cmp;
+mod comment;
 mod compose;
 mod deserializer;
+mod diff;
+mod document;
+mod encoding;
 mod error;
 mod event;
+mod flow;
+#[cfg(feature = "hash")]
+mod hash;
+mod include;
+mod incremental;
+mod interpolate;
+#[cfg(feature = "json")]
+mod json;
+mod json_ser;
+mod lenient;
+mod lexer;
 mod map;
+mod merge;
+mod number;
+mod parse_option;
 mod parser;
+mod path;
 mod position;
+mod raw;
+mod resolve;
 mod scalar;
 mod scalar_ser;
 mod scanner;
+#[cfg(feature = "schema")]
+mod schema;
 mod sequence;
 mod serializer;
+mod singleton_map;
 mod state;
+mod summary;
 mod tag;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 mod value;
 mod variant;
+mod version;
+mod walk;
+pub mod with;
 
+#[cfg(test)]
+mod differential_test;
+#[cfg(test)]
+mod fuzz_test;
+#[cfg(test)]
+mod json_test;
 #[cfg(test)]
 pub(crate) mod testlib;
 #[cfg(test)]
 mod yaml_test_suite;
 
-
+#[cfg(feature = "tokio")]
+pub use self::async_reader::from_async_reader;
+#[cfg(feature = "rayon")]
+pub use self::document::parse_documents_parallel;
+#[cfg(feature = "schema")]
+pub use self::schema::{YamlMapSchema, YamlSchema};
 pub use self::{
-    deserializer::{YamlDeserializer, from_str, to_value},
+    comment::{YamlComment, comments},
+    deserializer::{
+        YamlDeserializer, from_slice, from_str, from_str_collect_errors,
+        from_str_with_opt, index, to_value, to_value_with_opt,
+        to_value_with_warnings,
+    },
+    diff::{YamlDiffEntry, diff},
+    document::{YamlDirective, YamlDocument, parse_documents},
     error::{ErrorKind, YamlError},
-    map::YamlValueMap,
+    event::{YamlEvent, YamlScalarStyle},
+    include::YamlIncludeResolver,
+    incremental::{YamlIncrementalParser, YamlTextEdit},
+    lenient::parse_lenient,
+    lexer::{YamlLexer, YamlToken, YamlTokenKind},
+    map::{YamlValueMap, YamlValueMapEntry},
+    merge::{YamlMergeSequence, YamlMergeStrategy},
+    number::{YamlNumber, YamlNumberKind},
+    parse_option::{YamlParseOption, YamlTabIndent},
+    parser::{YamlDocumentEventParser, parse_trace},
     position::YamlPosition,
+    raw::YamlRawValue,
+    resolve::{YamlResolvedScalar, YamlScalarSchema, resolve_scalar},
     serializer::{
-        YamlSerializeOption, YamlSerializer, to_string, to_string_with_opt,
+        YamlBlockChomping, YamlBoolStyle, YamlNullStyle, YamlQuoteStyle,
+        YamlSequenceIndent, YamlSerializeOption, YamlSerializer,
+        YamlVariantStyle, to_string, to_string_multi, to_string_with_opt,
     },
     value::{YamlValue, YamlValueData},
+    version::YamlVersion,
 };
 pub(crate) use self::{
-    event::{YamlEvent, YamlEventIter},
+    deserializer::YamlDeContext,
+    encoding::decode_yaml_bytes,
+    event::YamlEventIter,
     map::YamlValueMapAccess,
-    parser::YamlParser,
-    scalar_ser::to_scalar_string,
+    parser::{
+        YamlParser, comment_indicators, find_comment_start,
+        find_missing_value_space, find_value_indicator, is_block_seq_indicator,
+        is_document_end, is_document_marker, is_explicit_key_indicator,
+        is_map_value_indicator,
+    },
+    scalar_ser::{is_plain_non_string, to_styled_scalar_string},
     scanner::YamlScanner,
     sequence::YamlValueSeqAccess,
     state::YamlState,
     tag::YamlTag,
+    value::sub_key_path,
     variant::YamlValueEnumAccess,
 };
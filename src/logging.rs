@@ -0,0 +1,34 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// The `log` crate is only used when `logging` feature is enabled. Otherwise
+// the arguments are still type checked but nothing is evaluated at runtime.
+
+macro_rules! trace {
+    ($($arg:tt)+) => {
+        #[cfg(feature = "logging")]
+        {
+            log::trace!($($arg)+);
+        }
+        #[cfg(not(feature = "logging"))]
+        {
+            if false {
+                let _ = format!($($arg)+);
+            }
+        }
+    };
+}
+
+macro_rules! warn {
+    ($($arg:tt)+) => {
+        #[cfg(feature = "logging")]
+        {
+            log::warn!($($arg)+);
+        }
+        #[cfg(not(feature = "logging"))]
+        {
+            if false {
+                let _ = format!($($arg)+);
+            }
+        }
+    };
+}
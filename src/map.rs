@@ -1,18 +1,50 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use std::hash::{DefaultHasher, Hasher};
+use std::{
+    cmp::Ordering,
+    hash::{DefaultHasher, Hasher},
+    sync::Arc,
+};
 
-use indexmap::IndexMap;
+use indexmap::{Equivalent, IndexMap, map::Entry};
 use serde::de::{DeserializeSeed, MapAccess};
 
 use crate::{
-    ErrorKind, YamlDeserializer, YamlError, YamlEvent, YamlParser,
-    YamlPosition, YamlState, YamlValue,
+    ErrorKind, YamlDeContext, YamlDeserializer, YamlError, YamlEvent,
+    YamlParser, YamlPosition, YamlScalarStyle, YamlState, YamlValue,
+    YamlValueData, cmp::canonical_cmp_data, find_value_indicator,
+    is_document_marker, is_explicit_key_indicator, is_map_value_indicator,
 };
 
+/// Ordered map of YAML mapping node, entries are kept in the order of
+/// document through parsing, deserializing and serializing.
+/// Key is matched by data only, position and scalar style of key are
+/// ignored.
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct YamlValueMap(IndexMap<YamlValue, YamlValue>);
 
+pub type YamlValueMapEntry<'a> = Entry<'a, YamlValue, YamlValue>;
+
+// Used to lookup map key without caring its position and scalar style,
+// including those of nested nodes in collection key.
+struct YamlValueDataKey<'a>(&'a YamlValueData);
+
+impl std::hash::Hash for YamlValueDataKey<'_> {
+    fn hash<H>(&self, state: &mut H)
+    where
+        H: Hasher,
+    {
+        // Should be identical to the hash of YamlValue
+        self.0.hash(state);
+    }
+}
+
+impl Equivalent<YamlValue> for YamlValueDataKey<'_> {
+    fn equivalent(&self, key: &YamlValue) -> bool {
+        canonical_cmp_data(self.0, &key.data) == Ordering::Equal
+    }
+}
+
 impl std::hash::Hash for YamlValueMap {
     fn hash<H>(&self, state: &mut H)
     where
@@ -30,38 +62,170 @@ impl std::hash::Hash for YamlValueMap {
 }
 
 impl YamlValueMap {
-    pub(crate) fn new() -> Self {
+    pub fn new() -> Self {
         Self(IndexMap::new())
     }
 
-    pub(crate) fn insert(&mut self, key: YamlValue, val: YamlValue) {
-        self.0.insert(key, val);
+    /// Empty map with space for at least `capacity` entries.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self(IndexMap::with_capacity(capacity))
+    }
+
+    /// Number of entries the map can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    /// Insert key-value pair and return the old value if key already exists.
+    /// The order and position of existing key is preserved.
+    pub fn insert<K, V>(&mut self, key: K, val: V) -> Option<YamlValue>
+    where
+        K: Into<YamlValue>,
+        V: Into<YamlValue>,
+    {
+        let key = key.into();
+        if let Some(old) = self.0.get_mut(&YamlValueDataKey(&key.data)) {
+            Some(std::mem::replace(old, val.into()))
+        } else {
+            self.0.insert(key, val.into());
+            None
+        }
+    }
+
+    pub fn get<K>(&self, key: K) -> Option<&YamlValue>
+    where
+        K: Into<YamlValue>,
+    {
+        self.0.get(&YamlValueDataKey(&key.into().data))
+    }
+
+    pub fn get_mut<K>(&mut self, key: K) -> Option<&mut YamlValue>
+    where
+        K: Into<YamlValue>,
+    {
+        self.0.get_mut(&YamlValueDataKey(&key.into().data))
+    }
+
+    pub fn contains_key<K>(&self, key: K) -> bool
+    where
+        K: Into<YamlValue>,
+    {
+        self.0.contains_key(&YamlValueDataKey(&key.into().data))
+    }
+
+    /// Remove the key-value pair and preserve the order of other entries.
+    pub fn remove<K>(&mut self, key: K) -> Option<YamlValue>
+    where
+        K: Into<YamlValue>,
+    {
+        self.0.shift_remove(&YamlValueDataKey(&key.into().data))
+    }
+
+    /// Get the entry of specified key for in-place manipulation.
+    pub fn entry<K>(&mut self, key: K) -> YamlValueMapEntry<'_>
+    where
+        K: Into<YamlValue>,
+    {
+        let key = key.into();
+        // The `IndexMap::entry()` compares position of key also, so use
+        // existing key for occupied entry.
+        let key = match self.0.get_key_value(&YamlValueDataKey(&key.data)) {
+            Some((existing_key, _)) => existing_key.clone(),
+            None => key,
+        };
+        self.0.entry(key)
+    }
+
+    /// Keep only the entries `f` returns true for, the order of kept
+    /// entries is preserved.
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&YamlValue, &mut YamlValue) -> bool,
+    {
+        self.0.retain(f)
     }
 
     pub(crate) fn pop(&mut self) -> Option<(YamlValue, YamlValue)> {
         self.0.pop()
     }
 
-    pub(crate) fn len(&self) -> usize {
+    pub fn len(&self) -> usize {
         self.0.len()
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> indexmap::map::Iter<'_, YamlValue, YamlValue> {
+        self.0.iter()
+    }
+
+    pub fn iter_mut(
+        &mut self,
+    ) -> indexmap::map::IterMut<'_, YamlValue, YamlValue> {
+        self.0.iter_mut()
+    }
+
+    pub fn keys(&self) -> indexmap::map::Keys<'_, YamlValue, YamlValue> {
+        self.0.keys()
+    }
+
+    pub fn values(&self) -> indexmap::map::Values<'_, YamlValue, YamlValue> {
+        self.0.values()
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+impl IntoIterator for YamlValueMap {
+    type Item = (YamlValue, YamlValue);
+    type IntoIter = indexmap::map::IntoIter<YamlValue, YamlValue>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a YamlValueMap {
+    type Item = (&'a YamlValue, &'a YamlValue);
+    type IntoIter = indexmap::map::Iter<'a, YamlValue, YamlValue>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for YamlValueMap
+where
+    K: Into<YamlValue>,
+    V: Into<YamlValue>,
+{
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let mut ret = Self::new();
+        for (k, v) in iter {
+            ret.insert(k, v);
+        }
+        ret
+    }
+}
+
+#[derive(Debug, Clone)]
 pub(crate) struct YamlValueMapAccess {
-    data: YamlValueMap,
-    // Used to cache key drained from data
-    cached_key: Option<YamlValue>,
-    // Used to cache value drained from data
-    cached_value: Option<YamlValue>,
+    // Entries are drained from the front to keep the order of document
+    entries: indexmap::map::IntoIter<YamlValue, YamlValue>,
+    // Key path name and value of the key drained by `next_key_seed()`
+    value: Option<(String, YamlValue)>,
+    ctx: Arc<YamlDeContext>,
 }
 
 impl YamlValueMapAccess {
-    pub(crate) fn new(data: YamlValueMap) -> Self {
+    pub(crate) fn new(data: YamlValueMap, ctx: Arc<YamlDeContext>) -> Self {
         Self {
-            data,
-            cached_key: None,
-            cached_value: None,
+            entries: data.into_iter(),
+            value: None,
+            ctx,
         }
     }
 }
@@ -76,42 +240,42 @@ impl<'de> MapAccess<'de> for YamlValueMapAccess {
     where
         K: DeserializeSeed<'de>,
     {
-        let key = if let Some(k) = self.cached_key.take() {
-            k
-        } else if let Some((k, v)) = self.data.pop() {
-            self.cached_value = Some(v);
-            k
-        } else {
+        let Some((key, value)) = self.entries.next() else {
             return Ok(None);
         };
+        self.value = Some((key.key_path_name(), value));
 
-        seed.deserialize(&mut YamlDeserializer { parsed: key })
+        let (start, end) = (key.start, key.end);
+        seed.deserialize(&mut YamlDeserializer::child(key, self.ctx.clone()))
             .map(Some)
+            .map_err(|e| e.or_position(start, end))
     }
 
     fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
     where
         V: DeserializeSeed<'de>,
     {
-        let value = if let Some(v) = self.cached_value.take() {
-            v
-        } else if let Some((k, v)) = self.data.pop() {
-            self.cached_key = Some(k);
-            v
-        } else {
+        let Some((key_name, value)) = self.value.take() else {
             return Err(YamlError::new(
-                ErrorKind::UnexpectedYamlNodeType,
-                "Expecting a map value, but none".to_string(),
+                ErrorKind::Bug,
+                "next_value_seed() invoked before next_key_seed()".to_string(),
                 YamlPosition::EOF,
                 YamlPosition::EOF,
             ));
         };
 
-        seed.deserialize(&mut YamlDeserializer { parsed: value })
+        let (start, end) = (value.start, value.end);
+        self.ctx.in_child(key_name, || {
+            seed.deserialize(&mut YamlDeserializer::child(
+                value,
+                self.ctx.clone(),
+            ))
+            .map_err(|e| e.or_position(start, end))
+        })
     }
 
     fn size_hint(&self) -> Option<usize> {
-        Some(self.data.len())
+        Some(self.entries.len())
     }
 }
 
@@ -123,18 +287,21 @@ impl<'a> YamlParser<'a> {
         rest_indent_count: usize,
         tag: Option<String>,
     ) -> Result<(), YamlError> {
-        log::trace!(
+        trace!(
             "handle_block_map {first_indent_count} {rest_indent_count} {:?}",
             self.scanner.remains()
         );
-        self.push_event(YamlEvent::MapStart(tag, self.scanner.next_pos));
+        self.push_event(YamlEvent::MapStart(None, tag, self.scanner.next_pos));
         self.push_state(YamlState::InBlockMapKey);
+        self.push_block_indent(rest_indent_count);
         let mut value_first_indent_count = first_indent_count;
         let mut value_rest_indent_count = first_indent_count;
         let mut is_first_line = true;
         while let Some(line) = self.scanner.peek_line() {
             let pre_pos = self.scanner.done_pos;
-            if line.is_empty() {
+            // Empty line or comment line
+            let content = line.trim_start_matches([' ', '\t']);
+            if content.is_empty() || content.starts_with('#') {
                 self.scanner.next_line();
                 continue;
             }
@@ -146,7 +313,9 @@ impl<'a> YamlParser<'a> {
                 rest_indent_count
             };
 
-            if cur_indent < desired_indent_count {
+            if cur_indent < desired_indent_count
+                || (self.scanner.at_line_start() && is_document_marker(line))
+            {
                 break;
             }
 
@@ -164,72 +333,104 @@ impl<'a> YamlParser<'a> {
                 // YAML 1.2.2 SPEC, 7.3.3. Plain Style:
                 //      Plain scalars are further restricted to a single line
                 //      when contained inside an implicit key.
-                let _spliter_offset = line.find(": ");
-                self.handle_plain_scalar(
-                    desired_indent_count,
-                    desired_indent_count,
-                    None,
-                )?;
+                let trimmed = line.trim_start_matches(' ');
+                if trimmed.starts_with('\t') {
+                    self.scanner.advance(cur_indent);
+                    return Err(YamlError::new(
+                        ErrorKind::TabIndentation,
+                        format!(
+                            "Tab(\\t) cannot be used as indentation of \
+                             mapping key: {line:?}"
+                        ),
+                        self.scanner.next_pos,
+                        self.scanner.next_pos,
+                    ));
+                }
+                if is_explicit_key_indicator(trimmed) {
+                    self.handle_block_map_explicit_entry(
+                        cur_indent,
+                        rest_indent_count,
+                    )?;
+                    continue;
+                }
+                let mut key_tag = None;
+                let mut key_indent_count = desired_indent_count;
+                if trimmed.starts_with(['!', '&']) {
+                    self.scanner.advance(cur_indent);
+                    key_tag = self.handle_node_properties(None)?;
+                    key_indent_count = 0;
+                    let rest = self.scanner.peek_till_linebreak();
+                    if rest.is_empty() || rest.starts_with('#') {
+                        return Err(YamlError::new(
+                            ErrorKind::InvalidImplicitKey,
+                            format!(
+                                "Expecting implicit key after node \
+                                 properties, but got: {line:?}"
+                            ),
+                            self.scanner.next_pos,
+                            self.scanner.next_pos,
+                        ));
+                    }
+                }
+                if self
+                    .scanner
+                    .peek_line()
+                    .is_some_and(|l| l.trim_start_matches(' ').starts_with('*'))
+                {
+                    self.scanner.advance_till_non_space();
+                    self.handle_alias(key_tag)?;
+                    self.scanner.advance_till_non_space();
+                } else if self.scanner.peek_line().is_some_and(|l| {
+                    l.trim_start_matches(' ').starts_with(['"', '\''])
+                }) {
+                    self.handle_scalar(0, 0, key_tag)?;
+                    self.scanner.advance_till_non_space();
+                } else {
+                    self.handle_plain_scalar(
+                        key_indent_count,
+                        key_indent_count,
+                        key_tag,
+                    )?;
+                }
                 let Some(line) = self.scanner.peek_line() else {
                     continue;
                 };
                 self.pop_state();
                 self.push_state(YamlState::InBlockMapValue);
-                let trimmed_line = line.trim_end_matches(' ');
+                let trimmed_line = line.trim_end_matches([' ', '\t']);
                 // TODO: Handle comment after `:`
-                if trimmed_line.ends_with(":") {
+                if trimmed_line.trim_start_matches([' ', '\t']) == ":" {
                     self.scanner.next_line();
-                    if let Some(next_line) = self.scanner.peek_line() {
-                        let next_line_indent_count =
-                            next_line.chars().take_while(|c| *c == ' ').count();
-                        if next_line_indent_count < desired_indent_count {
-                            return Err(YamlError::new(
-                                ErrorKind::Bug,
-                                format!(
-                                    "Got less indented than parent: {}",
-                                    self.scanner.remains()
-                                ),
-                                self.scanner.done_pos,
-                                self.scanner.done_pos,
-                            ));
-                        } else {
-                            value_first_indent_count = next_line_indent_count;
-                            value_rest_indent_count = next_line_indent_count;
-                        }
+                    self.handle_next_line_node(None)?;
+                } else {
+                    if find_value_indicator(line).is_some() {
+                        self.scanner.advance_offset(2);
+                        value_first_indent_count = 0;
+                        value_rest_indent_count = self.scanner.done_pos.column;
+                    } else if trimmed_line.is_empty() {
+                        self.scanner.next_line();
                     } else {
-                        // No next line after ':\n', so empty value
-                        self.push_event(YamlEvent::Scalar(
-                            None,
-                            String::new(),
+                        return Err(YamlError::new(
+                            ErrorKind::Bug,
+                            format!(
+                                "Expecting ending with : or contains ': ', \
+                                 but got {}",
+                                line
+                            ),
                             self.scanner.done_pos,
                             self.scanner.done_pos,
                         ));
-                        break;
                     }
-                } else if line.contains(": ") {
-                    self.scanner.advance_offset(2);
-                    value_first_indent_count = 0;
-                    value_rest_indent_count = self.scanner.done_pos.column;
-                } else if trimmed_line.is_empty() {
-                    self.scanner.next_line();
-                } else {
-                    return Err(YamlError::new(
-                        ErrorKind::Bug,
-                        format!(
-                            "Expecting ending with : or contains ': ', but \
-                             got {}",
-                            line
-                        ),
-                        self.scanner.done_pos,
-                        self.scanner.done_pos,
-                    ));
+                    self.handle_node(
+                        value_first_indent_count,
+                        value_rest_indent_count,
+                        None,
+                    )?;
                 }
-                self.handle_node(
-                    value_first_indent_count,
-                    value_rest_indent_count,
-                    None,
-                )?;
+                // Back to key state of this map, otherwise the state of
+                // parent map value will be treated as ours.
                 self.pop_state();
+                self.push_state(YamlState::InBlockMapKey);
             }
             if pre_pos == self.scanner.done_pos {
                 return Err(YamlError::new(
@@ -244,18 +445,143 @@ impl<'a> YamlParser<'a> {
             }
         }
 
-        self.push_event(YamlEvent::MapEnd(self.scanner.done_pos));
+        self.push_event(YamlEvent::MapEnd(self.last_event_end()));
         self.pop_state();
+        self.pop_block_indent();
         Ok(())
     }
 
+    /// Handle explicit entry started by `?` indicator, the optional value
+    /// should be started by `:` indicator with the same indentation.
+    fn handle_block_map_explicit_entry(
+        &mut self,
+        cur_indent: usize,
+        map_indent: usize,
+    ) -> Result<(), YamlError> {
+        self.push_state(YamlState::InBlockMapExplicitEntry);
+        self.scanner.advance(cur_indent + 1);
+        self.handle_block_map_explicit_node(map_indent)?;
+
+        while let Some(line) = self.scanner.peek_line() {
+            let trimmed = line.trim_start_matches([' ', '\t']);
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                self.scanner.next_line();
+            } else {
+                break;
+            }
+        }
+        let value_indent = self.scanner.peek_line().and_then(|line| {
+            let indent = line.chars().take_while(|c| *c == ' ').count();
+            (indent == map_indent && is_map_value_indicator(&line[indent..]))
+                .then_some(indent)
+        });
+        if let Some(indent) = value_indent {
+            self.scanner.advance(indent + 1);
+            self.handle_block_map_explicit_node(map_indent)?;
+        } else {
+            self.push_event(YamlEvent::Scalar(
+                None,
+                None,
+                YamlScalarStyle::Plain,
+                String::new(),
+                self.scanner.done_pos,
+                self.scanner.done_pos,
+            ));
+        }
+        self.pop_state();
+        Ok(())
+    }
+
+    /// Handle the node after `?` or `:` indicator of explicit entry.
+    /// Compact notation is allowed, e.g. `? a: b`.
+    fn handle_block_map_explicit_node(
+        &mut self,
+        map_indent: usize,
+    ) -> Result<(), YamlError> {
+        let rest = self.scanner.peek_till_linebreak();
+        let rest = rest.trim_start_matches([' ', '\t']);
+        if rest.is_empty() || rest.starts_with('#') {
+            self.scanner.advance_till_linebreak();
+            self.handle_next_line_node(None)
+        } else {
+            // Skip the separator after indicator, leave the tab to
+            // `handle_node()` as it cannot be used as indentation of block
+            // collection.
+            if self.scanner.peek_char() == Some(' ') {
+                self.scanner.next_char();
+            }
+            self.handle_node(0, map_indent + 2, None)
+        }
+    }
+
     /// Consume the scanner till a flow map is finished and insert the parsed
     /// event.
     pub(crate) fn handle_flow_map(
         &mut self,
-        _tag: Option<String>,
+        tag: Option<String>,
     ) -> Result<(), YamlError> {
-        todo!()
+        trace!("handle_flow_map {:?}", self.scanner.remains());
+        self.scanner.advance_till_non_space();
+        self.scanner.next_char();
+        self.push_event(YamlEvent::MapStart(None, tag, self.scanner.done_pos));
+        self.flow_starts.push(self.scanner.done_pos);
+        self.push_state(YamlState::InFlowMapKey);
+        loop {
+            self.skip_flow_separation()?;
+            match self.scanner.peek_char() {
+                Some('}') => break,
+                Some(',') => {
+                    return Err(YamlError::new(
+                        ErrorKind::InvalidFlowEntry,
+                        "Got ',' without flow map entry before it".to_string(),
+                        self.scanner.next_pos,
+                        self.scanner.next_pos,
+                    ));
+                }
+                None => return Err(self.unfinished_flow_error()),
+                Some(_) => {
+                    // Explicit key indicator
+                    if self.scanner.remains().starts_with("? ") {
+                        self.scanner.next_char();
+                        self.skip_flow_separation()?;
+                    }
+                    if self.is_flow_value_indicator() {
+                        // Empty implicit key
+                        self.push_event(YamlEvent::Scalar(
+                            None,
+                            None,
+                            YamlScalarStyle::Plain,
+                            String::new(),
+                            self.scanner.next_pos,
+                            self.scanner.next_pos,
+                        ));
+                    } else {
+                        self.handle_flow_node(None)?;
+                    }
+                    self.skip_flow_separation()?;
+                    self.pop_state();
+                    self.push_state(YamlState::InFlowMapValue);
+                    // JSON compatible: `:` is allowed to be adjacent to
+                    // the value after the key.
+                    if self.scanner.peek_char() == Some(':') {
+                        self.scanner.next_char();
+                        self.handle_flow_map_value('}')?;
+                    } else {
+                        self.push_empty_flow_scalar();
+                    }
+                    self.pop_state();
+                    self.push_state(YamlState::InFlowMapKey);
+                    if self.expect_flow_entry_end('}')? {
+                        break;
+                    }
+                }
+            }
+        }
+        self.scanner.next_char();
+        self.push_event(YamlEvent::MapEnd(self.scanner.done_pos));
+        self.flow_starts.pop();
+        self.pop_state();
+        Ok(())
     }
 }
 
@@ -275,33 +601,41 @@ mod test {
             vec![
                 YamlEvent::StreamStart,
                 YamlEvent::DocumentStart(false, YamlPosition::new(1, 1)),
-                YamlEvent::MapStart(None, YamlPosition::new(1, 1)),
+                YamlEvent::MapStart(None, None, YamlPosition::new(1, 1)),
                 YamlEvent::Scalar(
                     None,
+                    None,
+                    YamlScalarStyle::Plain,
                     "a".to_string(),
                     YamlPosition::new(1, 1),
                     YamlPosition::new(1, 1)
                 ),
                 YamlEvent::Scalar(
                     None,
+                    None,
+                    YamlScalarStyle::Plain,
                     "1".to_string(),
                     YamlPosition::new(1, 4),
                     YamlPosition::new(1, 4)
                 ),
                 YamlEvent::Scalar(
                     None,
+                    None,
+                    YamlScalarStyle::Plain,
                     "b".to_string(),
                     YamlPosition::new(2, 1),
                     YamlPosition::new(2, 1)
                 ),
                 YamlEvent::Scalar(
                     None,
+                    None,
+                    YamlScalarStyle::Plain,
                     "2".to_string(),
                     YamlPosition::new(2, 4),
                     YamlPosition::new(2, 4)
                 ),
-                YamlEvent::MapEnd(YamlPosition::new(2, 5)),
-                YamlEvent::DocumentEnd(false, YamlPosition::new(2, 5)),
+                YamlEvent::MapEnd(YamlPosition::new(2, 4)),
+                YamlEvent::DocumentEnd(false, YamlPosition::new(2, 4)),
                 YamlEvent::StreamEnd,
             ]
         )
@@ -314,23 +648,353 @@ mod test {
             vec![
                 YamlEvent::StreamStart,
                 YamlEvent::DocumentStart(false, YamlPosition::new(1, 1)),
-                YamlEvent::MapStart(None, YamlPosition::new(1, 1)),
+                YamlEvent::MapStart(None, None, YamlPosition::new(1, 1)),
                 YamlEvent::Scalar(
                     None,
+                    None,
+                    YamlScalarStyle::Plain,
                     "a".to_string(),
                     YamlPosition::new(1, 1),
                     YamlPosition::new(1, 1)
                 ),
                 YamlEvent::Scalar(
                     None,
+                    None,
+                    YamlScalarStyle::Plain,
                     "b".to_string(),
                     YamlPosition::new(2, 3),
                     YamlPosition::new(2, 3)
                 ),
-                YamlEvent::MapEnd(YamlPosition::new(2, 4)),
-                YamlEvent::DocumentEnd(false, YamlPosition::new(2, 4)),
+                YamlEvent::MapEnd(YamlPosition::new(2, 3)),
+                YamlEvent::DocumentEnd(false, YamlPosition::new(2, 3)),
                 YamlEvent::StreamEnd,
             ]
         )
     }
+
+    #[test]
+    fn test_map_of_multiline_plain_scalar() {
+        let events =
+            YamlParser::parse_to_events("a: b\n  c\n\n  d\ne: f\n").unwrap();
+        assert_eq!(
+            events[4],
+            YamlEvent::Scalar(
+                None,
+                None,
+                YamlScalarStyle::Plain,
+                "b c\nd".to_string(),
+                YamlPosition::new(1, 4),
+                YamlPosition::new(4, 3)
+            )
+        );
+        assert_eq!(
+            events[5],
+            YamlEvent::Scalar(
+                None,
+                None,
+                YamlScalarStyle::Plain,
+                "e".to_string(),
+                YamlPosition::new(5, 1),
+                YamlPosition::new(5, 1)
+            )
+        );
+    }
+
+    #[test]
+    fn test_map_multiline_implicit_key() {
+        for (input, pos) in [
+            ("a:\n  b c\n  d: e\n", YamlPosition::new(3, 3)),
+            ("a: b\n c: d\n", YamlPosition::new(2, 2)),
+            ("a: b: c\n", YamlPosition::new(1, 4)),
+        ] {
+            let e = YamlParser::parse_to_events(input).unwrap_err();
+            assert_eq!(e.kind(), ErrorKind::InvalidImplicitKey);
+            assert_eq!(e.start_pos(), pos);
+        }
+    }
+
+    #[test]
+    fn test_map_trailing_scalar() {
+        for (input, pos) in [
+            ("a: 1\nstray scalar\n", YamlPosition::new(2, 1)),
+            ("a:\n  b: 1\nstray\n", YamlPosition::new(3, 1)),
+            ("  a: 1\n  stray\n", YamlPosition::new(2, 3)),
+        ] {
+            let e = YamlParser::parse_to_events(input).unwrap_err();
+            assert_eq!(e.kind(), ErrorKind::TrailingContent, "{input:?}");
+            assert_eq!(e.start_pos(), pos, "{input:?}");
+        }
+        let e =
+            YamlParser::parse_to_events("a:\n  b: 1\n  stray\n").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidImplicitKey);
+    }
+
+    #[test]
+    fn test_map_key_missing_value_space() {
+        for (input, hint) in [
+            ("a: 1\nb:c\n", "\"b: c\""),
+            ("- a: 1\n  mac-addr:00:01\n", "\"mac-addr: 00:01\""),
+            ("b:c\na: 1\n", "\"b: c\""),
+        ] {
+            let e = YamlParser::parse_to_events(input).unwrap_err();
+            assert_eq!(e.kind(), ErrorKind::InvalidImplicitKey);
+            assert!(
+                e.msg().ends_with(&format!(
+                    "missing space after ':'? e.g. {hint}"
+                )),
+                "{e}"
+            );
+        }
+        let e = YamlParser::parse_to_events("a: 1\nb c\n").unwrap_err();
+        assert!(!e.msg().contains("missing space"), "{e}");
+    }
+
+    #[test]
+    fn test_map_empty_nodes() {
+        let events = YamlParser::parse_to_events("a:\n: b\n").unwrap();
+        assert_eq!(
+            events[4],
+            YamlEvent::Scalar(
+                None,
+                None,
+                YamlScalarStyle::Plain,
+                String::new(),
+                YamlPosition::new(1, 3),
+                YamlPosition::new(1, 3)
+            )
+        );
+        assert_eq!(
+            events[5],
+            YamlEvent::Scalar(
+                None,
+                None,
+                YamlScalarStyle::Plain,
+                String::new(),
+                YamlPosition::new(2, 1),
+                YamlPosition::new(2, 1)
+            )
+        );
+
+        let value =
+            crate::to_value("- a:\n  b: 1\n- c:\n- d:\n  - e\n").unwrap();
+        let expected =
+            crate::to_value("[{a: , b: 1}, {c: }, {d: [e]}]").unwrap();
+        assert_eq!(value.canonical_cmp(&expected), std::cmp::Ordering::Equal);
+    }
+
+    #[test]
+    fn test_map_explicit_entry() {
+        let value = crate::to_value(
+            "? a\n? - b\n  - c\n: d: e\n? # comment\n  f\n:\n  g\nh: i\n",
+        )
+        .unwrap();
+        let expected =
+            crate::to_value("{a: , [b, c]: {d: e}, f: g, h: i}").unwrap();
+        assert_eq!(value.canonical_cmp(&expected), std::cmp::Ordering::Equal);
+
+        let e = YamlParser::parse_to_events("?\t- a\n").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::TabIndentation);
+    }
+
+    #[test]
+    fn test_map_api_ignore_key_position() -> Result<(), YamlError> {
+        let mut map = match crate::to_value("a: 1\nb: 2\n")?.data {
+            YamlValueData::Map(map) => *map,
+            _ => panic!("Expecting a map"),
+        };
+
+        assert_eq!(map.get("b").map(|v| v.as_u32()), Some(Ok(2)));
+        assert_eq!(map.insert("a", 3u32).map(|v| v.as_u32()), Some(Ok(1)));
+        assert_eq!(map.len(), 2);
+        // Existing key should keep its position and order
+        assert_eq!(
+            map.keys().next().map(|k| k.start),
+            Some(YamlPosition::new(1, 1))
+        );
+
+        *map.entry("c").or_insert(YamlValue::from(0u8)) =
+            YamlValue::from(vec!["x", "y"]);
+        map.entry("b")
+            .and_modify(|v| *v = YamlValue::from(None::<u8>));
+        assert_eq!(map.remove("a").map(|v| v.as_u32()), Some(Ok(3)));
+
+        assert_eq!(
+            map.iter()
+                .map(|(k, v)| (k.data.clone(), v.data.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (YamlValueData::String("b".into()), YamlValueData::Null),
+                (
+                    YamlValueData::String("c".into()),
+                    YamlValue::from(vec!["x", "y"]).data
+                ),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_map_api_ignore_collection_key_position() -> Result<(), YamlError> {
+        let mut map =
+            match crate::to_value("? [a]\n: 1\n? {b: 'c'}\n: 2\n")?.data {
+                YamlValueData::Map(map) => *map,
+                _ => panic!("Expecting a map"),
+            };
+        let seq_key = YamlValue::from(vec!["a"]);
+        let map_key =
+            YamlValue::from([("b", "c")].into_iter().collect::<YamlValueMap>());
+
+        assert_eq!(map.get(vec!["a"]).map(|v| v.as_u32()), Some(Ok(1)));
+        assert!(map.contains_key(map_key.clone()));
+        assert_eq!(
+            map.insert(seq_key.clone(), 3u32).map(|v| v.as_u32()),
+            Some(Ok(1))
+        );
+        assert_eq!(map.len(), 2);
+        map.entry(map_key.clone())
+            .and_modify(|v| *v = YamlValue::from(4u32));
+        assert_eq!(map.remove(map_key).map(|v| v.as_u32()), Some(Ok(4)));
+        assert_eq!(map.remove(seq_key).map(|v| v.as_u32()), Some(Ok(3)));
+        assert!(map.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_flow_map_json_style() {
+        assert_eq!(
+            YamlParser::parse_to_events("{\"a\":{\"b\":[]},c}").unwrap(),
+            vec![
+                YamlEvent::StreamStart,
+                YamlEvent::DocumentStart(false, YamlPosition::new(1, 1)),
+                YamlEvent::MapStart(None, None, YamlPosition::new(1, 1)),
+                YamlEvent::Scalar(
+                    None,
+                    None,
+                    YamlScalarStyle::DoubleQuoted,
+                    "a".to_string(),
+                    YamlPosition::new(1, 2),
+                    YamlPosition::new(1, 4)
+                ),
+                YamlEvent::MapStart(None, None, YamlPosition::new(1, 6)),
+                YamlEvent::Scalar(
+                    None,
+                    None,
+                    YamlScalarStyle::DoubleQuoted,
+                    "b".to_string(),
+                    YamlPosition::new(1, 7),
+                    YamlPosition::new(1, 9)
+                ),
+                YamlEvent::SequenceStart(None, None, YamlPosition::new(1, 11)),
+                YamlEvent::SequenceEnd(YamlPosition::new(1, 12)),
+                YamlEvent::MapEnd(YamlPosition::new(1, 13)),
+                YamlEvent::Scalar(
+                    None,
+                    None,
+                    YamlScalarStyle::Plain,
+                    "c".to_string(),
+                    YamlPosition::new(1, 15),
+                    YamlPosition::new(1, 15)
+                ),
+                YamlEvent::Scalar(
+                    None,
+                    None,
+                    YamlScalarStyle::Plain,
+                    String::new(),
+                    YamlPosition::new(1, 15),
+                    YamlPosition::new(1, 15)
+                ),
+                YamlEvent::MapEnd(YamlPosition::new(1, 16)),
+                YamlEvent::DocumentEnd(false, YamlPosition::new(1, 16)),
+                YamlEvent::StreamEnd,
+            ]
+        )
+    }
+
+    #[test]
+    fn test_flow_map_unclosed() {
+        let e = YamlParser::parse_to_events("a:\n  {b: [1, 2],\n   c: d\n")
+            .unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::UnfinishedMapIndicator);
+        assert_eq!(e.start_pos(), YamlPosition::new(2, 3));
+        assert_eq!(e.end_pos(), YamlPosition::new(3, 8));
+    }
+
+    #[test]
+    fn test_map_access_in_document_order() -> Result<(), YamlError> {
+        // Keep the visiting order of map entries
+        struct OrderTest(Vec<(String, Vec<u32>)>);
+
+        impl<'de> serde::Deserialize<'de> for OrderTest {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                struct OrderVisitor;
+
+                impl<'de> serde::de::Visitor<'de> for OrderVisitor {
+                    type Value = OrderTest;
+
+                    fn expecting(
+                        &self,
+                        formatter: &mut std::fmt::Formatter,
+                    ) -> std::fmt::Result {
+                        formatter.write_str("map")
+                    }
+
+                    fn visit_map<A>(
+                        self,
+                        mut map: A,
+                    ) -> Result<OrderTest, A::Error>
+                    where
+                        A: MapAccess<'de>,
+                    {
+                        let mut ret = Vec::new();
+                        while let Some(entry) = map.next_entry()? {
+                            ret.push(entry);
+                        }
+                        Ok(OrderTest(ret))
+                    }
+                }
+
+                deserializer.deserialize_map(OrderVisitor)
+            }
+        }
+
+        let OrderTest(entries) =
+            crate::from_str("z: [3, 2, 1]\na: []\nm: [5]\n")?;
+        assert_eq!(
+            entries,
+            vec![
+                ("z".to_string(), vec![3, 2, 1]),
+                ("a".to_string(), vec![]),
+                ("m".to_string(), vec![5]),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_map_order_preserved() -> Result<(), YamlError> {
+        let yaml_str = "z: 1\na: 2\nm: 3\n";
+
+        let value: YamlValue = yaml_str.parse()?;
+        let YamlValueData::Map(map) = &value.data else {
+            panic!("Expecting map, got {value:?}");
+        };
+        assert_eq!(
+            map.keys()
+                .map(|k| k.as_str())
+                .collect::<Result<Vec<&str>, YamlError>>()?,
+            vec!["z", "a", "m"]
+        );
+        assert_eq!(crate::to_string(&value)?, yaml_str);
+
+        let map: IndexMap<String, u32> = crate::from_str(yaml_str)?;
+        assert_eq!(
+            map.keys().map(String::as_str).collect::<Vec<&str>>(),
+            vec!["z", "a", "m"]
+        );
+        assert_eq!(crate::to_string(&map)?, yaml_str);
+        Ok(())
+    }
 }
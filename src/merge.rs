@@ -0,0 +1,156 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{YamlValue, YamlValueData};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct YamlMergeStrategy {
+    /// How to merge two sequences. Default is [YamlMergeSequence::Replace].
+    pub sequence: YamlMergeSequence,
+    /// Whether null value in overriding map removes the same key from the
+    /// base map. When false, null value overrides like other values.
+    /// Default is false.
+    pub null_remove: bool,
+}
+
+/// How to merge two sequences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum YamlMergeSequence {
+    /// Overriding sequence replaces the base sequence.
+    #[default]
+    Replace,
+    /// Items of overriding sequence are appended to the base sequence.
+    Append,
+}
+
+impl YamlValue {
+    /// Merge `other` into self, useful for applying override config file on
+    /// top of base config file:
+    ///     * Maps are merged recursively, key only in `other` is appended.
+    ///     * Sequences are merged according to [YamlMergeStrategy::sequence].
+    ///     * Tagged nodes with the same tag are merged by their content.
+    ///     * Otherwise `other` replaces self.
+    ///
+    /// Position of merged node is kept from self, position of replaced node
+    /// is copied from `other`.
+    pub fn merge(&mut self, other: &YamlValue, strategy: YamlMergeStrategy) {
+        if !merge_data(&mut self.data, &other.data, strategy) {
+            *self = other.clone();
+        }
+    }
+}
+
+// Return false if `other` should replace `base`
+fn merge_data(
+    base: &mut YamlValueData,
+    other: &YamlValueData,
+    strategy: YamlMergeStrategy,
+) -> bool {
+    match (base, other) {
+        (YamlValueData::Map(base_map), YamlValueData::Map(other_map)) => {
+            for (key, value) in other_map.iter() {
+                if strategy.null_remove && value.is_null() {
+                    base_map.remove(key.clone());
+                } else if let Some(base_value) = base_map.get_mut(key.clone()) {
+                    base_value.merge(value, strategy);
+                } else {
+                    base_map.insert(key.clone(), value.clone());
+                }
+            }
+            true
+        }
+        (
+            YamlValueData::Array(base_items),
+            YamlValueData::Array(other_items),
+        ) if strategy.sequence == YamlMergeSequence::Append => {
+            base_items.extend_from_slice(other_items);
+            true
+        }
+        (YamlValueData::Tag(base_tag), YamlValueData::Tag(other_tag))
+            if base_tag.name == other_tag.name =>
+        {
+            if !merge_data(&mut base_tag.data, &other_tag.data, strategy) {
+                base_tag.data = other_tag.data.clone();
+            }
+            true
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::YamlError;
+
+    fn canonical_eq(a: &YamlValue, b: &YamlValue) -> bool {
+        a.canonical_cmp(b) == std::cmp::Ordering::Equal
+    }
+
+    #[test]
+    fn test_merge_map_and_replace_sequence() -> Result<(), YamlError> {
+        let mut base = crate::to_value(
+            "name: eth1\nmtu: 1500\nipv4:\n  dhcp: true\n  address:\n    - \
+             192.0.2.1\n",
+        )?;
+        let other = crate::to_value(
+            "mtu: 9000\nipv4:\n  address:\n    - 192.0.2.2\nstate: up\n",
+        )?;
+        base.merge(&other, YamlMergeStrategy::default());
+
+        let expected = crate::to_value(
+            "name: eth1\nmtu: 9000\nipv4:\n  dhcp: true\n  address:\n    - \
+             192.0.2.2\nstate: up\n",
+        )?;
+        assert!(canonical_eq(&base, &expected));
+        assert_eq!(base.start, expected.start);
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_append_sequence_and_null_remove() -> Result<(), YamlError> {
+        let mut base =
+            crate::to_value("dns:\n  - 192.0.2.1\nmtu: 1500\nstate: up\n")?;
+        let other = crate::to_value("dns:\n  - 192.0.2.2\nmtu: ~\n")?;
+        base.merge(
+            &other,
+            YamlMergeStrategy {
+                sequence: YamlMergeSequence::Append,
+                null_remove: true,
+            },
+        );
+
+        let expected =
+            crate::to_value("dns:\n  - 192.0.2.1\n  - 192.0.2.2\nstate: up\n")?;
+        assert!(canonical_eq(&base, &expected));
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_type_mismatch_replaces() -> Result<(), YamlError> {
+        let mut base = crate::to_value("a:\n  b: 1\n")?;
+        let other = crate::to_value("a: 2\n")?;
+        base.merge(&other, YamlMergeStrategy::default());
+
+        assert!(canonical_eq(&base, &other));
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_collection_key() -> Result<(), YamlError> {
+        let mut base = crate::to_value("? [a]\n: 1\n? {b: c}\n: {d: 2}\n")?;
+        let other = crate::to_value("? {b: c}\n: {e: 3}\n\n? [a]\n: 4\n")?;
+        base.merge(&other, YamlMergeStrategy::default());
+
+        let expected =
+            crate::to_value("? [a]\n: 4\n? {b: c}\n: {d: 2, e: 3}\n")?;
+        assert!(canonical_eq(&base, &expected));
+        assert!(
+            matches!(&base.data, YamlValueData::Map(map) if map.len() == 2)
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,326 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize, de::Visitor};
+
+use crate::{ErrorKind, YamlError, YamlPosition, YamlValue};
+
+pub(crate) const NUMBER_TOKEN: &str = "$rmsd_yaml::number";
+
+/// Type of [YamlNumber].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum YamlNumberKind {
+    /// Integer without sign, e.g. `12`, `0x1f`.
+    Unsigned,
+    /// Integer with sign, e.g. `-12`.
+    Signed,
+    /// Float, e.g. `1.5`, `1e3`, `.inf`.
+    Float,
+}
+
+/// Number scalar in YAML 1.2.2 core schema with its literal text preserved,
+/// hence no precision lost when stored or emitted again.
+/// The serializer of this crate emits the literal text as it is, other
+/// serializers get the text as string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct YamlNumber {
+    text: String,
+    kind: YamlNumberKind,
+}
+
+impl YamlNumber {
+    /// Literal text of the number.
+    pub fn as_str(&self) -> &str {
+        self.text.as_str()
+    }
+
+    pub fn kind(&self) -> YamlNumberKind {
+        self.kind
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.kind != YamlNumberKind::Float
+    }
+
+    pub fn as_u64(&self) -> Result<u64, YamlError> {
+        self.to_value().as_u64()
+    }
+
+    pub fn as_i64(&self) -> Result<i64, YamlError> {
+        self.to_value().as_i64()
+    }
+
+    pub fn as_u128(&self) -> Result<u128, YamlError> {
+        self.to_value().as_u128()
+    }
+
+    pub fn as_i128(&self) -> Result<i128, YamlError> {
+        self.to_value().as_i128()
+    }
+
+    /// Convert to the nearest f64, precision is silently lost when the
+    /// literal cannot be represented by f64 exactly, e.g.
+    /// `0.10000000000000000001` or `9007199254740993`. Use
+    /// [YamlNumber::as_f64_lossless()] to detect it.
+    pub fn as_f64(&self) -> Result<f64, YamlError> {
+        Ok(self.to_f64()?.0)
+    }
+
+    /// Convert to f64, error [ErrorKind::PrecisionLoss] when the literal
+    /// cannot be represented by f64 exactly.
+    pub fn as_f64_lossless(&self) -> Result<f64, YamlError> {
+        let (num, is_lossless) = self.to_f64()?;
+        if is_lossless {
+            Ok(num)
+        } else {
+            Err(YamlError::new(
+                ErrorKind::PrecisionLoss,
+                format!(
+                    "Number {} cannot be represented by f64, nearest is {num}",
+                    self.text
+                ),
+                YamlPosition::EOF,
+                YamlPosition::EOF,
+            ))
+        }
+    }
+
+    fn to_value(&self) -> YamlValue {
+        YamlValue::from(self.text.as_str())
+    }
+
+    // Return the nearest f64 and whether it equals to the literal.
+    fn to_f64(&self) -> Result<(f64, bool), YamlError> {
+        let value = self.to_value();
+        if self.is_integer() {
+            let num = value.as_i128()?;
+            let float = num as f64;
+            Ok((float, float.is_finite() && float as i128 == num))
+        } else {
+            let float = value.as_f64()?;
+            let is_lossless = !float.is_finite()
+                || decimal_digits(&self.text)
+                    == decimal_digits(&format!("{float:e}"));
+            Ok((float, is_lossless))
+        }
+    }
+}
+
+// Sign, significant digits and position of decimal point relative to the
+// first significant digit, e.g. `-012.50e1` is (true, "125", 3).
+fn decimal_digits(s: &str) -> (bool, String, i64) {
+    let is_negative = s.starts_with('-');
+    let s = s.trim_start_matches(['-', '+']);
+    let (mantissa, exponent) = match s.split_once(['e', 'E']) {
+        Some((m, e)) => (m, e.parse::<i64>().unwrap_or_default()),
+        None => (s, 0),
+    };
+    let (int_part, frac_part) =
+        mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = format!("{int_part}{frac_part}");
+    let leading_zeros = digits.chars().take_while(|c| *c == '0').count();
+    let digits = digits.trim_start_matches('0').trim_end_matches('0');
+    if digits.is_empty() {
+        (false, String::new(), 0)
+    } else {
+        (
+            is_negative,
+            digits.to_string(),
+            int_part.len() as i64 + exponent - leading_zeros as i64,
+        )
+    }
+}
+
+impl std::fmt::Display for YamlNumber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+impl FromStr for YamlNumber {
+    type Err = YamlError;
+
+    fn from_str(s: &str) -> Result<Self, YamlError> {
+        Self::try_from(&YamlValue::from(s))
+    }
+}
+
+impl TryFrom<&YamlValue> for YamlNumber {
+    type Error = YamlError;
+
+    fn try_from(value: &YamlValue) -> Result<Self, YamlError> {
+        let text = value.as_str()?;
+        let kind = if value.is_integer() {
+            YamlNumberKind::Unsigned
+        } else if value.is_signed_integer() {
+            YamlNumberKind::Signed
+        } else if value.as_f64().is_ok() {
+            YamlNumberKind::Float
+        } else {
+            return Err(YamlError::new(
+                ErrorKind::InvalidNumber,
+                format!("Expecting a number, but got {text:?}"),
+                value.start,
+                value.end,
+            ));
+        };
+        Ok(Self {
+            text: text.to_string(),
+            kind,
+        })
+    }
+}
+
+macro_rules! impl_from_num {
+    ($($t:ty),+) => {
+        $(
+            impl From<$t> for YamlNumber {
+                fn from(v: $t) -> Self {
+                    Self::from_str(&v.to_string())
+                        .expect("integer should be valid YAML number")
+                }
+            }
+        )+
+    };
+}
+
+impl_from_num!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl From<f64> for YamlNumber {
+    fn from(v: f64) -> Self {
+        let text = if v.is_nan() {
+            ".nan".to_string()
+        } else if v.is_infinite() {
+            if v > 0.0 { ".inf" } else { "-.inf" }.to_string()
+        } else {
+            format!("{v:?}")
+        };
+        Self {
+            text,
+            kind: YamlNumberKind::Float,
+        }
+    }
+}
+
+impl Serialize for YamlNumber {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_newtype_struct(NUMBER_TOKEN, self.text.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for YamlNumber {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(NUMBER_TOKEN, NumberVisitor)
+    }
+}
+
+struct NumberVisitor;
+
+impl<'de> Visitor<'de> for NumberVisitor {
+    type Value = YamlNumber;
+
+    fn expecting(
+        &self,
+        formatter: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        formatter.write_str("number")
+    }
+
+    fn visit_newtype_struct<D>(
+        self,
+        deserializer: D,
+    ) -> Result<YamlNumber, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<YamlNumber, E> {
+        Ok(v.into())
+    }
+
+    fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<YamlNumber, E> {
+        Ok(v.into())
+    }
+
+    fn visit_u128<E: serde::de::Error>(self, v: u128) -> Result<YamlNumber, E> {
+        Ok(v.into())
+    }
+
+    fn visit_i128<E: serde::de::Error>(self, v: i128) -> Result<YamlNumber, E> {
+        Ok(v.into())
+    }
+
+    fn visit_f64<E: serde::de::Error>(self, v: f64) -> Result<YamlNumber, E> {
+        Ok(v.into())
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<YamlNumber, E> {
+        YamlNumber::from_str(v).map_err(E::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct NumberTest {
+        price: YamlNumber,
+        count: YamlNumber,
+        id: YamlNumber,
+    }
+
+    #[test]
+    fn test_number_keep_literal() -> Result<(), YamlError> {
+        let yaml_str = "price: 19.90\ncount: 0x1F\nid: -12345678901234567890\n";
+        let data: NumberTest = crate::from_str(yaml_str)?;
+
+        assert_eq!(data.price.kind(), YamlNumberKind::Float);
+        assert_eq!(data.count.kind(), YamlNumberKind::Unsigned);
+        assert_eq!(data.id.kind(), YamlNumberKind::Signed);
+        assert_eq!(data.count.as_u64()?, 31);
+        assert_eq!(data.id.as_i128()?, -12345678901234567890);
+        assert_eq!(crate::to_string(&data)?, yaml_str);
+        assert!(
+            crate::from_str::<NumberTest>("price: '1'\ncount: 1\nid: 1")
+                .is_err()
+        );
+        assert!(
+            crate::from_str::<NumberTest>("price: a\ncount: 1\nid: 1").is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_number_precision_loss() -> Result<(), YamlError> {
+        for lossless in
+            ["19.90", "0.1", "-1.5e-3", "1e300", ".inf", "0", "-0.0"]
+        {
+            let num = YamlNumber::from_str(lossless)?;
+            assert_eq!(num.as_f64_lossless()?, num.as_f64()?);
+        }
+        for lossy in [
+            "0.10000000000000000001",
+            "9007199254740993",
+            "1.00000000000000001",
+        ] {
+            let num = YamlNumber::from_str(lossy)?;
+            assert_eq!(
+                num.as_f64_lossless().unwrap_err().kind(),
+                ErrorKind::PrecisionLoss
+            );
+            assert_eq!(num.as_f64()?, lossy.parse::<f64>().unwrap());
+        }
+        Ok(())
+    }
+}
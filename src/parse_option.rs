@@ -0,0 +1,284 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::borrow::Cow;
+
+use crate::{
+    ErrorKind, YamlError, YamlParser, YamlPosition, YamlVersion,
+    find_missing_value_space,
+    version::{document_version, normalize_yaml_1_1_line_breaks},
+};
+
+/// Options of parsing YAML document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct YamlParseOption {
+    /// Fail with [crate::ErrorKind::DuplicateAnchor] when an anchor is
+    /// redefined before any alias referring to it, otherwise it is a
+    /// warning returned by [crate::to_value_with_warnings()]. Default is
+    /// false.
+    pub deny_duplicate_anchor: bool,
+    /// Handling of tab(`\t`) in indentation. Default is
+    /// [YamlTabIndent::Deny].
+    pub tab_indent: YamlTabIndent,
+    /// Columns between tab stops used when expanding tab in indentation.
+    /// Default is 8.
+    pub tab_width: usize,
+    /// Accept `key:value` missing space after `:` as mapping entry when the
+    /// line cannot be parsed otherwise and the key only contains ASCII
+    /// alphanumeric, `-` or `_`. Columns after the `:` in the line are
+    /// shifted by one in positions. Note that plain scalar like `http://a`
+    /// in such line is also accepted as mapping entry. Default is false.
+    pub allow_missing_value_space: bool,
+    /// Version of document without `%YAML` directive, the `%YAML 1.1`
+    /// directive switches to [YamlVersion::V1_1] regardless of this option.
+    /// Default is [YamlVersion::V1_2].
+    pub default_version: YamlVersion,
+}
+
+impl Default for YamlParseOption {
+    fn default() -> Self {
+        Self {
+            deny_duplicate_anchor: false,
+            tab_indent: YamlTabIndent::default(),
+            tab_width: 8,
+            allow_missing_value_space: false,
+            default_version: YamlVersion::default(),
+        }
+    }
+}
+
+/// Handling of tab(`\t`) in indentation, which is forbidden by YAML spec but
+/// common in hand-written files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum YamlTabIndent {
+    /// Fail with [crate::ErrorKind::TabIndentation].
+    #[default]
+    Deny,
+    /// Same as [YamlTabIndent::Expand] with a
+    /// [crate::ErrorKind::TabIndentation] warning for each line, returned by
+    /// [crate::to_value_with_warnings()]. Other functions only log the
+    /// warnings with the `logging` feature.
+    Warn,
+    /// Before parsing, replace tabs in the leading white spaces of each line
+    /// with spaces up to the next tab stop of
+    /// [YamlParseOption::tab_width]. Tabs after indentation of block scalar
+    /// are expanded too. Positions are columns of the expanded line.
+    Expand,
+}
+
+// Fix the input as requested by option before parsing.
+pub(crate) fn prepare_input<'a>(
+    input: &'a str,
+    option: &YamlParseOption,
+    warnings: &mut Vec<YamlError>,
+) -> Result<Cow<'a, str>, YamlError> {
+    let mut input = expand_tab_indent(input, option, warnings)?;
+    if document_version(&input, option) == YamlVersion::V1_1
+        && let Cow::Owned(s) = normalize_yaml_1_1_line_breaks(&input)
+    {
+        input = Cow::Owned(s);
+    }
+    if !option.allow_missing_value_space {
+        return Ok(input);
+    }
+    // Each retry inserts a space into a line which no longer matches, hence
+    // the loop ends.
+    while let Err(e) = YamlParser::parse_to_events(&input) {
+        if e.kind() != ErrorKind::InvalidImplicitKey {
+            break;
+        }
+        match insert_value_space(&input, e.end_pos().line) {
+            Some(fixed) => input = Cow::Owned(fixed),
+            None => break,
+        }
+    }
+    Ok(input)
+}
+
+// Insert space after `:` of the last `key:value` line till the error line.
+fn insert_value_space(input: &str, error_line: usize) -> Option<String> {
+    let mut lines: Vec<&str> = input.split('\n').collect();
+    let (index, offset) = lines
+        .iter()
+        .take(error_line)
+        .enumerate()
+        .rev()
+        .find_map(|(i, l)| find_missing_value_space(l).map(|o| (i, o)))?;
+    let fixed = format!(
+        "{}: {}",
+        &lines[index][..offset],
+        &lines[index][offset + 1..]
+    );
+    lines[index] = &fixed;
+    Some(lines.join("\n"))
+}
+
+// Expand tabs in leading white spaces of each line as requested.
+fn expand_tab_indent<'a>(
+    input: &'a str,
+    option: &YamlParseOption,
+    warnings: &mut Vec<YamlError>,
+) -> Result<Cow<'a, str>, YamlError> {
+    if option.tab_indent == YamlTabIndent::Deny {
+        return Ok(Cow::Borrowed(input));
+    }
+    if option.tab_width == 0 {
+        return Err(YamlError::new(
+            ErrorKind::IndentTooSmall,
+            "Tab width should be at least 1".to_string(),
+            YamlPosition::EOF,
+            YamlPosition::EOF,
+        ));
+    }
+    if !input.split('\n').any(has_tab_indent) {
+        return Ok(Cow::Borrowed(input));
+    }
+    let mut ret = String::with_capacity(input.len());
+    for (index, line) in input.split('\n').enumerate() {
+        if index > 0 {
+            ret.push('\n');
+        }
+        if !has_tab_indent(line) {
+            ret.push_str(line);
+            continue;
+        }
+        let content = line.trim_start_matches([' ', '\t']);
+        let indent = &line[..line.len() - content.len()];
+        let mut column = 0;
+        for c in indent.chars() {
+            column = if c == '\t' {
+                (column / option.tab_width + 1) * option.tab_width
+            } else {
+                column + 1
+            };
+        }
+        if option.tab_indent == YamlTabIndent::Warn {
+            let warning = YamlError::new(
+                ErrorKind::TabIndentation,
+                format!(
+                    "Tab(\\t) used as indentation, expanded to {column} spaces"
+                ),
+                YamlPosition::new(index + 1, 1),
+                YamlPosition::new(index + 1, indent.len()),
+            );
+            warn!("{} {}", warning.start_pos(), warning.msg());
+            warnings.push(warning);
+        }
+        ret.extend(std::iter::repeat_n(' ', column));
+        ret.push_str(content);
+    }
+    Ok(Cow::Owned(ret))
+}
+
+fn has_tab_indent(line: &str) -> bool {
+    let content = line.trim_start_matches([' ', '\t']);
+    !content.is_empty() && line[..line.len() - content.len()].contains('\t')
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_tab_indent() -> Result<(), YamlError> {
+        let input = "a:\n\tb: 1\n\tc:\n\t  - d\n \te: |\n\t\tx\n\t\t  y\n";
+        let e = crate::to_value(input).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::TabIndentation);
+
+        for tab_indent in [YamlTabIndent::Warn, YamlTabIndent::Expand] {
+            let option = YamlParseOption {
+                tab_indent,
+                tab_width: 4,
+                ..Default::default()
+            };
+            let value = crate::to_value_with_opt(input, option.clone())?;
+            let expected = crate::to_value(
+                "a:\n    b: 1\n    c:\n      - d\n    e: \"x\\n  y\\n\"\n",
+            )?;
+            assert!(value.canonical_cmp(&expected).is_eq(), "{value}");
+
+            let (_, warnings) =
+                crate::to_value_with_warnings(input, option.clone())?;
+            if tab_indent == YamlTabIndent::Warn {
+                assert_eq!(warnings.len(), 6);
+                assert_eq!(warnings[0].kind(), ErrorKind::TabIndentation);
+                assert_eq!(warnings[0].start_pos(), YamlPosition::new(2, 1));
+                assert_eq!(warnings[3].end_pos(), YamlPosition::new(5, 2));
+            } else {
+                assert!(warnings.is_empty());
+            }
+
+            let e = crate::to_value_with_opt(
+                input,
+                YamlParseOption {
+                    tab_width: 0,
+                    ..option
+                },
+            )
+            .unwrap_err();
+            assert_eq!(e.kind(), ErrorKind::IndentTooSmall);
+        }
+
+        // Tab as separation after content is untouched
+        let option = YamlParseOption {
+            tab_indent: YamlTabIndent::Expand,
+            ..Default::default()
+        };
+        assert_eq!(
+            expand_tab_indent("a:\tb\n\t\n", &option, &mut Vec::new())?,
+            "a:\tb\n\t\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_allow_missing_value_space() -> Result<(), YamlError> {
+        let input = "a: 1\nb:\n  - c:x\n    d: http://e\nf:g\n";
+        let e = crate::to_value(input).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidImplicitKey);
+
+        let option = YamlParseOption {
+            allow_missing_value_space: true,
+            ..Default::default()
+        };
+        let value = crate::to_value_with_opt(input, option.clone())?;
+        let expected =
+            crate::to_value("a: 1\nb:\n  - c: x\n    d: http://e\nf: g\n")?;
+        assert!(value.canonical_cmp(&expected).is_eq(), "{value}");
+
+        // Valid plain scalar is untouched
+        let value = crate::to_value_with_opt("a:1", option.clone())?;
+        assert_eq!(value.as_str()?, "a:1");
+
+        let e = crate::to_value_with_opt("a: 1\nb c\n", option).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::TrailingContent);
+        Ok(())
+    }
+
+    #[test]
+    fn test_prepare_normalized_input() -> Result<(), YamlError> {
+        let option = YamlParseOption {
+            allow_missing_value_space: true,
+            tab_indent: YamlTabIndent::Expand,
+            ..Default::default()
+        };
+        let value = crate::to_value_with_opt(
+            "\u{feff}a:\r\n\tb: 1\r\nc:2\r\n",
+            option,
+        )?;
+        let expected = crate::to_value("a:\n  b: 1\nc: 2\n")?;
+        assert!(value.canonical_cmp(&expected).is_eq(), "{value}");
+
+        // `%YAML 1.1` after byte order mark
+        let value = crate::to_value_with_opt(
+            "\u{feff}%YAML 1.1\r\n---\r\na: yes\r\n",
+            Default::default(),
+        )?;
+        let expected = crate::to_value("a: true\n")?;
+        assert!(value.canonical_cmp(&expected).is_eq(), "{value}");
+        Ok(())
+    }
+}
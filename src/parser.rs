@@ -1,9 +1,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use std::cmp::max;
+use std::{borrow::Cow, cmp::max, collections::VecDeque};
 
 use crate::{
-    ErrorKind, YamlError, YamlEvent, YamlPosition, YamlScanner, YamlState,
+    ErrorKind, YamlDirective, YamlError, YamlEvent, YamlPosition,
+    YamlScalarStyle, YamlScanner, YamlState,
+    scanner::{invalid_comment_error, normalize_input},
 };
 
 #[derive(Debug)]
@@ -11,36 +13,212 @@ pub(crate) struct YamlParser<'a> {
     pub(crate) scanner: YamlScanner<'a>,
     states: Vec<YamlState>,
     events: Vec<YamlEvent>,
+    /// Indentation of block collections we are currently in.
+    block_indents: Vec<usize>,
+    /// Position of `[` or `{` of flow collections we are currently in.
+    pub(crate) flow_starts: Vec<YamlPosition>,
+    /// Anchor waiting for the event of its node.
+    pub(crate) anchor: Option<String>,
+    /// Directives of all documents, in the order of the stream.
+    directives: Vec<YamlDirective>,
+    /// Index of the first directive of current document in `directives`.
+    doc_directives: usize,
+    /// Whether we are inside of a document not ended yet.
+    in_document: bool,
+    /// Position of directive which is not followed by `---` yet.
+    pending_directive: Option<YamlPosition>,
 }
 
 impl<'a> YamlParser<'a> {
+    fn new(scanner: YamlScanner<'a>) -> Self {
+        Self {
+            scanner,
+            states: Vec::new(),
+            events: Vec::new(),
+            block_indents: Vec::new(),
+            flow_starts: Vec::new(),
+            anchor: None,
+            directives: Vec::new(),
+            doc_directives: 0,
+            in_document: false,
+            pending_directive: None,
+        }
+    }
+
     /// Current state
     pub(crate) fn cur_state(&self) -> &YamlState {
         self.states.last().unwrap_or(&YamlState::EndOfFile)
     }
 
-    pub(crate) fn push_event(&mut self, event: YamlEvent) {
-        log::trace!("Got event {:?}", event);
+    pub(crate) fn push_event(&mut self, mut event: YamlEvent) {
+        if self.anchor.is_some()
+            && let Some(anchor) = event.anchor_mut()
+        {
+            *anchor = self.anchor.take();
+        }
+        trace!("Got event {:?}", event);
         self.events.push(event);
     }
 
     pub(crate) fn push_state(&mut self, state: YamlState) {
-        log::trace!("Push state {:?}", state);
+        trace!("Push state {:?}", state);
         self.states.push(state);
     }
 
     pub(crate) fn pop_state(&mut self) {
-        log::trace!("Pop state: {:?}", self.states.pop());
+        let state = self.states.pop();
+        trace!("Pop state: {:?}", state);
+    }
+
+    /// Indentation of the innermost block collection, None if not in any
+    /// block collection.
+    pub(crate) fn parent_block_indent(&self) -> Option<usize> {
+        self.block_indents.last().copied()
+    }
+
+    pub(crate) fn push_block_indent(&mut self, indent_count: usize) {
+        self.block_indents.push(indent_count);
+    }
+
+    pub(crate) fn pop_block_indent(&mut self) {
+        self.block_indents.pop();
+    }
+
+    /// Count of block collections we are currently in.
+    pub(crate) fn block_depth(&self) -> usize {
+        self.block_indents.len()
+    }
+
+    pub(crate) fn event_count(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Insert event before the event of specified index, used when the
+    /// container type is only known after its first node parsed.
+    pub(crate) fn insert_event(&mut self, index: usize, event: YamlEvent) {
+        trace!("Got event {:?} at index {index}", event);
+        self.events.insert(index, event);
+    }
+
+    /// End position of the last event, used as the end of block collection
+    /// and document without end marker, so that trailing empty lines and
+    /// comments are not included.
+    pub(crate) fn last_event_end(&self) -> YamlPosition {
+        self.events
+            .last()
+            .map(|e| e.end_position())
+            .unwrap_or(self.scanner.done_pos)
+    }
+
+    /// Only white spaces or comment are allowed after the node which ends
+    /// by indicator, e.g. quoted scalar or flow collection. Consume till
+    /// line break.
+    pub(crate) fn expect_no_trailing_content(
+        &mut self,
+    ) -> Result<(), YamlError> {
+        let rest = self.scanner.peek_till_linebreak();
+        let trimmed = rest.trim_start_matches([' ', '\t']).to_string();
+        if rest.starts_with('#') {
+            Err(invalid_comment_error(self.scanner.next_pos))
+        } else if trimmed.is_empty() || trimmed.starts_with('#') {
+            self.scanner.advance_till_linebreak();
+            Ok(())
+        } else {
+            self.scanner
+                .advance(rest.chars().count() - trimmed.chars().count());
+            let start_pos = self.scanner.next_pos;
+            self.scanner.advance_till_linebreak();
+            Err(YamlError::new(
+                ErrorKind::TrailingContent,
+                format!(
+                    "Unexpected content after the end of node: {trimmed:?}"
+                ),
+                start_pos,
+                self.scanner.done_pos,
+            ))
+        }
+    }
+
+    /// Block collection cannot start at the same line of mapping key, the
+    /// compact notation is only allowed in block sequence entry.
+    fn expect_not_inline_map_value(
+        &mut self,
+        indent_count: usize,
+        kind: ErrorKind,
+    ) -> Result<(), YamlError> {
+        if self.cur_state().is_block_map_value()
+            && self.scanner.next_pos.column > 1
+        {
+            self.scanner.advance(indent_count);
+            Err(YamlError::new(
+                kind,
+                format!(
+                    "Block collection cannot start at the same line of \
+                     mapping key: {:?}",
+                    self.scanner.peek_till_linebreak()
+                ),
+                self.scanner.next_pos,
+                self.scanner.next_pos,
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Continuation lines of flow node inside block collection should be
+    /// indented more than the block collection. Tab cannot be used as
+    /// indentation.
+    pub(crate) fn expect_flow_line_indent(&self) -> Result<(), YamlError> {
+        let (Some(parent_indent), Some(line)) =
+            (self.parent_block_indent(), self.scanner.peek_line())
+        else {
+            return Ok(());
+        };
+        let indent_count = line.chars().take_while(|c| *c == ' ').count();
+        let content = line.trim_start_matches([' ', '\t']);
+        if indent_count > parent_indent
+            || content.is_empty()
+            || content.starts_with('#')
+        {
+            return Ok(());
+        }
+        let pos = YamlPosition::new(
+            self.scanner.next_pos.line,
+            self.scanner.next_pos.column + indent_count,
+        );
+        Err(if line[indent_count..].starts_with('\t') {
+            YamlError::new(
+                ErrorKind::TabIndentation,
+                format!("Tab(\\t) cannot be used as indentation: {line:?}"),
+                pos,
+                pos,
+            )
+        } else {
+            YamlError::new(
+                ErrorKind::WrongIndentation,
+                format!(
+                    "Flow node should be indented more than its parent block \
+                     collection: {line:?}"
+                ),
+                pos,
+                pos,
+            )
+        })
     }
 
     pub(crate) fn parse_to_events(
         input: &'a str,
     ) -> Result<Vec<YamlEvent>, YamlError> {
-        let mut parser = Self {
-            scanner: YamlScanner::new(input),
-            states: Vec::new(),
-            events: Vec::new(),
-        };
+        Ok(Self::parse_to_events_and_directives(input)?.0)
+    }
+
+    /// Besides events, also return directives which are not included in
+    /// events.
+    pub(crate) fn parse_to_events_and_directives(
+        input: &'a str,
+    ) -> Result<(Vec<YamlEvent>, Vec<YamlDirective>), YamlError> {
+        let input = normalize_input(input)?;
+        let mut parser = YamlParser::new(YamlScanner::new(&input));
         while !parser.scanner.is_empty() {
             let cur_pos = parser.scanner.done_pos;
             parser.handle_stream()?;
@@ -58,72 +236,143 @@ impl<'a> YamlParser<'a> {
             }
         }
         for event in &parser.events {
-            log::trace!("{:?}", event);
+            trace!("{:?}", event);
         }
 
-        Ok(parser.events)
+        Ok((parser.events, parser.directives))
     }
 
     /// Stream started, but not `---` or string other than `b-break` found yet.
     fn handle_stream(&mut self) -> Result<(), YamlError> {
         self.push_event(YamlEvent::StreamStart);
-        log::trace!("handle_stream {:?}", self.scanner.remains());
+        while self.handle_document()? {}
+        Ok(())
+    }
+
+    /// Consume lines till the end of current document. Return false when
+    /// the whole stream is consumed and [YamlEvent::StreamEnd] emitted.
+    pub(crate) fn handle_document(&mut self) -> Result<bool, YamlError> {
+        trace!("handle_document {:?}", self.scanner.remains());
         while let Some(line) = self.scanner.peek_line() {
             let trimmed = line.trim_start_matches(' ');
-            if trimmed.is_empty() {
+            if trimmed.trim_start_matches('\t').is_empty()
+                || trimmed.starts_with('#')
+            {
                 self.scanner.advance_till_linebreak();
-            } else if trimmed == "---" {
-                let indent_count =
-                    line.chars().take_while(|c| *c == ' ').count();
+            } else if is_document_start(trimmed) {
+                if self.in_document {
+                    self.push_event(YamlEvent::DocumentEnd(
+                        false,
+                        self.last_event_end(),
+                    ));
+                    self.in_document = false;
+                    return Ok(true);
+                }
+                let indent_count = line.len() - trimmed.len();
+                self.scanner.advance(indent_count);
                 self.push_event(YamlEvent::DocumentStart(
                     true,
                     self.scanner.next_pos,
                 ));
+                self.in_document = true;
+                self.pending_directive = None;
+                self.scanner.advance(3);
+                let rest = self.scanner.peek_till_linebreak();
+                let rest = rest.trim_start_matches([' ', '\t']);
+                if rest.is_empty() || rest.starts_with('#') {
+                    self.scanner.advance_till_linebreak();
+                    self.handle_node(indent_count, indent_count, None)?;
+                } else {
+                    // Skip the separator after `---`
+                    self.scanner.next_char();
+                    let tag = self.handle_node_properties(None)?;
+                    let rest = self.scanner.peek_till_linebreak();
+                    if rest.is_empty() || rest.starts_with('#') {
+                        self.scanner.advance_till_linebreak();
+                        self.handle_next_line_node(tag)?;
+                    } else if is_block_collection_start(rest) {
+                        return Err(YamlError::new(
+                            ErrorKind::TrailingContent,
+                            format!(
+                                "Block collection cannot start at the same \
+                                 line of document start marker: {line:?}"
+                            ),
+                            self.scanner.next_pos,
+                            self.scanner.next_pos,
+                        ));
+                    } else {
+                        self.handle_node(0, 0, tag)?;
+                    }
+                }
+            } else if line.starts_with('%') {
+                if self.in_document {
+                    return Err(YamlError::new(
+                        ErrorKind::MissingDocumentEnd,
+                        format!(
+                            "Directive should be preceded by document end \
+                             marker `...`: {line:?}"
+                        ),
+                        self.scanner.next_pos,
+                        self.scanner.next_pos,
+                    ));
+                }
+                if self.pending_directive.is_none() {
+                    self.doc_directives = self.directives.len();
+                }
+                let directive =
+                    YamlDirective::parse(line, self.scanner.next_pos);
+                self.validate_directive(&directive)?;
+                // TODO: Apply `%TAG` directives to tags
+                self.pending_directive = Some(self.scanner.next_pos);
+                self.directives.push(directive);
                 self.scanner.advance_till_linebreak();
-                self.handle_node(indent_count, indent_count, None)?;
-            } else if let Some(offset) = line.find("--- ") {
-                self.push_event(YamlEvent::DocumentStart(
-                    true,
-                    self.scanner.next_pos,
-                ));
-                self.scanner.advance_offset(offset + 4);
-                self.handle_node(0, 0, None)?;
-            } else if trimmed == "..." {
-                self.push_event(YamlEvent::DocumentEnd(
-                    true,
-                    self.scanner.next_pos,
+            } else if let Some(pos) = self.pending_directive {
+                return Err(missing_document_start_error(pos));
+            } else if is_document_end(trimmed) {
+                self.scanner.advance(line.len() - trimmed.len());
+                let is_document_ended = self.in_document;
+                if self.in_document {
+                    self.push_event(YamlEvent::DocumentEnd(
+                        true,
+                        self.scanner.next_pos,
+                    ));
+                    self.in_document = false;
+                }
+                self.scanner.advance(3);
+                self.expect_no_trailing_content()?;
+                if is_document_ended {
+                    return Ok(true);
+                }
+            } else if self.in_document {
+                let pos = self.scanner.next_pos;
+                return Err(YamlError::new(
+                    ErrorKind::TrailingContent,
+                    format!("Unexpected content after document root: {line:?}"),
+                    pos,
+                    pos,
                 ));
-                self.scanner.advance_till_linebreak_or_space();
             } else {
                 self.push_event(YamlEvent::DocumentStart(
                     false,
                     self.scanner.next_pos,
                 ));
+                self.in_document = true;
                 self.handle_node(0, 0, None)?;
             }
         }
 
-        if !self
-            .events
-            .iter()
-            .any(|e| matches!(e, YamlEvent::DocumentStart(_, _)))
-        {
-            // Empty content
-            self.push_event(YamlEvent::DocumentStart(false, YamlPosition::EOF));
+        if let Some(pos) = self.pending_directive {
+            return Err(missing_document_start_error(pos));
         }
-        // No explicit document end `...`
-        if !self
-            .events
-            .iter()
-            .any(|e| matches!(e, YamlEvent::DocumentEnd(_, _)))
-        {
+        if self.in_document {
             self.push_event(YamlEvent::DocumentEnd(
                 false,
-                self.scanner.done_pos,
+                self.last_event_end(),
             ));
         }
+        self.in_document = false;
         self.push_event(YamlEvent::StreamEnd);
-        Ok(())
+        Ok(false)
     }
 
     /// Handle a container or scalar
@@ -133,7 +382,7 @@ impl<'a> YamlParser<'a> {
         rest_indent_count: usize,
         tag: Option<String>,
     ) -> Result<(), YamlError> {
-        log::trace!(
+        trace!(
             "handle_node {} {} {:?}, {:?}",
             first_indent_count,
             rest_indent_count,
@@ -144,7 +393,8 @@ impl<'a> YamlParser<'a> {
         while let Some(line) = self.scanner.peek_line() {
             let trimmed = line.trim_start_matches(' ');
             let indent_count = line.chars().take_while(|c| *c == ' ').count();
-            if (trimmed.is_empty() && indent_count <= first_indent_count)
+            if (trimmed.trim_start_matches('\t').is_empty()
+                && indent_count <= first_indent_count)
                 || trimmed.starts_with("# ")
             {
                 self.scanner.advance_till_linebreak();
@@ -175,44 +425,104 @@ impl<'a> YamlParser<'a> {
 
             let trimmed = line.trim_start_matches(' ');
 
-            if trimmed.starts_with("- ") || trimmed == "-" {
+            // YAML 1.2.2 SPEC, 6.1. Indentation Spaces:
+            //      To maintain portability, tab characters must not be used in
+            //      indentation. ... tab characters may be used for separation
+            //      between the indentation and the content.
+            if trimmed.starts_with('\t') {
+                let content = trimmed.trim_start_matches([' ', '\t']);
+                if is_block_collection_start(content) {
+                    self.scanner.advance(indent_count);
+                    return Err(YamlError::new(
+                        ErrorKind::TabIndentation,
+                        format!(
+                            "Tab(\\t) cannot be used as indentation of block \
+                             collection: {line:?}"
+                        ),
+                        self.scanner.next_pos,
+                        self.scanner.next_pos,
+                    ));
+                }
+                self.scanner
+                    .advance(line.chars().count() - content.chars().count());
+                return self.handle_node(0, rest_indent_count, tag);
+            }
+
+            if is_block_seq_indicator(trimmed) {
+                self.expect_not_inline_map_value(
+                    indent_count,
+                    ErrorKind::InvalidSequnceStartIndicator,
+                )?;
                 let expected_indent_count =
                     rest_indent_count + indent_count - first_indent_count;
-                self.handle_block_seq(expected_indent_count, tag)?;
-            } else if trimmed.starts_with('\'') || trimmed.starts_with('"') {
+                self.handle_block_seq(
+                    indent_count,
+                    expected_indent_count,
+                    tag,
+                )?;
+            } else if (trimmed.starts_with('\'') || trimmed.starts_with('"'))
+                && !is_quoted_implicit_key(trimmed)
+            {
                 // Flow style does not care indentation
                 self.handle_scalar(0, 0, tag)?;
-            } else if trimmed.contains(": ") {
+                self.expect_no_trailing_content()?;
+            } else if trimmed.starts_with("[") {
+                self.handle_flow_seq(tag)?;
+                self.expect_no_trailing_content()?;
+            } else if trimmed.starts_with("{") {
+                self.handle_flow_map(tag)?;
+                self.expect_no_trailing_content()?;
+            } else if (find_value_indicator(trimmed).is_some()
+                && is_implicit_key_content(trimmed))
+                || is_explicit_key_indicator(trimmed)
+            {
+                self.expect_not_inline_map_value(
+                    indent_count,
+                    ErrorKind::InvalidImplicitKey,
+                )?;
                 // Guess out the indent
-
                 self.handle_block_map(
                     max(first_indent_count, indent_count),
                     max(rest_indent_count, indent_count),
                     tag,
                 )?;
-            } else if trimmed.ends_with(":") {
+            } else if ends_with_value_indicator(trimmed)
+                && is_implicit_key_content(trimmed)
+            {
+                self.expect_not_inline_map_value(
+                    indent_count,
+                    ErrorKind::InvalidImplicitKey,
+                )?;
                 self.handle_block_map(
                     first_indent_count,
                     rest_indent_count,
                     tag,
                 )?;
-            } else if trimmed.starts_with("[") {
-                self.handle_flow_seq(tag)?;
-            } else if trimmed.starts_with("{") {
-                self.handle_flow_map(tag)?;
-            } else if trimmed.starts_with("!") {
+            } else if trimmed.starts_with(['!', '&']) {
                 self.scanner.advance(indent_count);
-                // Tag decorating its container
-                let tag = self.handle_tag();
-                self.handle_node(first_indent_count, rest_indent_count, tag)?;
-            } else if line.trim_start_matches(' ').starts_with('\t') {
-                return Err(YamlError::new(
-                    ErrorKind::InvalidStartOfToken,
-                    "Tab(\\t) cannot be used as start of any YAML node"
-                        .to_string(),
-                    self.scanner.next_pos,
-                    self.scanner.next_pos,
-                ));
+                let tag = self.handle_node_properties(tag)?;
+                let rest = self.scanner.peek_till_linebreak();
+                if rest.is_empty() || rest.starts_with('#') {
+                    // Properties decorating the node in next line
+                    self.scanner.advance_till_linebreak();
+                    self.handle_next_line_node(tag)?;
+                } else if is_block_collection_start(rest) {
+                    return Err(YamlError::new(
+                        ErrorKind::InvalidNodeProperty,
+                        format!(
+                            "Properties of block collection should be placed \
+                             in previous line: {line:?}"
+                        ),
+                        self.scanner.next_pos,
+                        self.scanner.next_pos,
+                    ));
+                } else {
+                    self.handle_node(0, rest_indent_count, tag)?;
+                }
+            } else if trimmed.starts_with('*') {
+                self.scanner.advance(indent_count);
+                self.handle_alias(tag)?;
+                self.expect_no_trailing_content()?;
             } else {
                 self.handle_scalar(first_indent_count, rest_indent_count, tag)?;
             }
@@ -221,12 +531,454 @@ impl<'a> YamlParser<'a> {
     }
 }
 
+impl<'a> YamlParser<'a> {
+    // YAML 1.2.2 SPEC, 6.8.1. “YAML” Directives:
+    //      It is an error to specify more than one “YAML” directive for the
+    //      same document, even if both occurrences give the same version
+    //      number.
+    // Directive of unknown name or later minor version is ignored with a
+    // warning.
+    fn validate_directive(
+        &self,
+        directive: &YamlDirective,
+    ) -> Result<(), YamlError> {
+        match directive.name.as_str() {
+            "YAML" => {
+                if let Some(pre) = self.directives[self.doc_directives..]
+                    .iter()
+                    .find(|d| d.name == "YAML")
+                {
+                    return Err(YamlError::new(
+                        ErrorKind::InvalidDirective,
+                        format!(
+                            "Duplicate %YAML directive for the same document, \
+                             previous one at line {} column {}",
+                            pre.position.line, pre.position.column
+                        ),
+                        directive.position,
+                        directive.position,
+                    ));
+                }
+                let version = match directive.parameters.as_slice() {
+                    [version] => {
+                        version.split_once('.').and_then(|(major, minor)| {
+                            Some((
+                                major.parse::<u32>().ok()?,
+                                minor.parse::<u32>().ok()?,
+                            ))
+                        })
+                    }
+                    _ => None,
+                };
+                match version {
+                    Some((1, minor)) => {
+                        if minor > 2 {
+                            warn!(
+                                "Parsing %YAML 1.{minor} document as YAML 1.2"
+                            );
+                        }
+                    }
+                    _ => {
+                        return Err(YamlError::new(
+                            ErrorKind::InvalidDirective,
+                            format!(
+                                "Expecting %YAML directive with version 1.x, \
+                                 but got {:?}",
+                                directive.parameters.join(" ")
+                            ),
+                            directive.position,
+                            directive.position,
+                        ));
+                    }
+                }
+            }
+            "TAG" => (),
+            name => {
+                warn!("Ignoring unknown directive %{name}");
+            }
+        }
+        Ok(())
+    }
+
+    /// Consume the tag and anchor properties in any order and the white
+    /// spaces after them. Return the tag.
+    pub(crate) fn handle_node_properties(
+        &mut self,
+        mut tag: Option<String>,
+    ) -> Result<Option<String>, YamlError> {
+        loop {
+            match self.scanner.peek_char() {
+                Some('!') => {
+                    let start_pos = self.scanner.next_pos;
+                    let new_tag = self.handle_tag();
+                    if tag.is_some() && new_tag.is_some() {
+                        return Err(YamlError::new(
+                            ErrorKind::InvalidNodeProperty,
+                            "Node can only have one tag".to_string(),
+                            start_pos,
+                            self.scanner.done_pos,
+                        ));
+                    }
+                    tag = tag.or(new_tag);
+                }
+                Some('&') => self.handle_anchor()?,
+                _ => break,
+            }
+            while let Some(' ' | '\t') = self.scanner.peek_char() {
+                self.scanner.next_char();
+            }
+        }
+        Ok(tag)
+    }
+
+    /// Handle the node starts from next line. Empty node if next line is not
+    /// indented more than the parent block collection, except the
+    /// zero-indented block sequence as value of block map.
+    pub(crate) fn handle_next_line_node(
+        &mut self,
+        tag: Option<String>,
+    ) -> Result<(), YamlError> {
+        if let Some(line) = self.scanner.peek_content_line()
+            && !is_document_marker(line)
+        {
+            let indent = line.chars().take_while(|c| *c == ' ').count();
+            let is_node = match self.parent_block_indent() {
+                Some(parent_indent) => {
+                    indent > parent_indent
+                        || (indent == parent_indent
+                            && (self.cur_state().is_block_map_value()
+                                || self
+                                    .cur_state()
+                                    .is_block_map_explicit_entry())
+                            && is_block_seq_indicator(&line[indent..]))
+                }
+                None => true,
+            };
+            if is_node {
+                return self.handle_node(indent, indent, tag);
+            }
+        }
+        self.push_event(YamlEvent::Scalar(
+            None,
+            tag,
+            YamlScalarStyle::Plain,
+            String::new(),
+            self.scanner.done_pos,
+            self.scanner.done_pos,
+        ));
+        Ok(())
+    }
+}
+
+fn missing_document_start_error(pos: YamlPosition) -> YamlError {
+    YamlError::new(
+        ErrorKind::MissingDocumentStart,
+        "Directives should be followed by document start marker `---`"
+            .to_string(),
+        pos,
+        pos,
+    )
+}
+
+// Document markers `---` and `...` should be at the beginning of line and
+// followed by white space or line break.
+fn starts_with_marker(line: &str, marker: &str) -> bool {
+    line.strip_prefix(marker)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '\t']))
+}
+
+pub(crate) fn is_document_start(line: &str) -> bool {
+    starts_with_marker(line, "---")
+}
+
+pub(crate) fn is_document_end(line: &str) -> bool {
+    starts_with_marker(line, "...")
+}
+
+pub(crate) fn is_document_marker(line: &str) -> bool {
+    is_document_start(line) || is_document_end(line)
+}
+
+// Offset of the mapping value indicator `:` which is followed by white space.
+// Searching single character is accelerated by memchr of std.
+pub(crate) fn find_value_indicator(line: &str) -> Option<usize> {
+    let bytes = line.as_bytes();
+    let skip = leading_properties_len(line);
+    line[skip..]
+        .match_indices(':')
+        .map(|(i, _)| i + skip)
+        .find(|i| matches!(bytes.get(i + 1), Some(b' ' | b'\t')))
+}
+
+// Offset of `:` in `key:value` which is likely a mapping entry missing the
+// space after `:`. The key could follow indentation or `- ` indicators and
+// should only contain ASCII alphanumeric, `-` or `_`.
+pub(crate) fn find_missing_value_space(line: &str) -> Option<usize> {
+    let mut rest = line.trim_start_matches(' ');
+    while let Some(entry) = rest.strip_prefix("- ") {
+        rest = entry.trim_start_matches(' ');
+    }
+    let key_len = rest
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_'))?;
+    let offset = line.len() - rest.len() + key_len;
+    let value = rest[key_len..].strip_prefix(':')?;
+    (key_len > 0 && value.starts_with(|c: char| !c.is_whitespace() && c != ':'))
+        .then_some(offset)
+}
+
+// Whether the line ends with mapping value indicator, e.g. `a:`.
+pub(crate) fn ends_with_value_indicator(line: &str) -> bool {
+    line[leading_properties_len(line)..]
+        .trim_end_matches([' ', '\t'])
+        .ends_with(':')
+}
+
+// Length of leading node properties or alias and white spaces after them.
+// Their names might contain `:`, e.g. `&a: b` is anchor `a:` on scalar `b`.
+fn leading_properties_len(line: &str) -> usize {
+    let mut offset = 0;
+    while line[offset..].starts_with(['&', '*', '!']) {
+        offset += line[offset..]
+            .find([' ', '\t'])
+            .unwrap_or(line.len() - offset);
+        offset += line[offset..].len()
+            - line[offset..].trim_start_matches([' ', '\t']).len();
+    }
+    offset
+}
+
+// Offsets of `#` which could start comment, i.e. at line start or
+// preceded by white space. The one inside of quoted scalar should be
+// excluded by caller, e.g. [crate::YamlLexer].
+pub(crate) fn comment_indicators(line: &str) -> impl Iterator<Item = usize> {
+    let bytes = line.as_bytes();
+    line.match_indices('#')
+        .map(|(i, _)| i)
+        .filter(|i| *i == 0 || matches!(bytes[i - 1], b' ' | b'\t'))
+}
+
+// Offset of the white space before `#` starting comment.
+pub(crate) fn find_comment_start(line: &str) -> Option<usize> {
+    comment_indicators(line).find(|i| *i > 0).map(|i| i - 1)
+}
+
+// Sequence entry indicator `-` should be followed by white space or line
+// break.
+pub(crate) fn is_block_seq_indicator(line: &str) -> bool {
+    line == "-" || line.starts_with("- ") || line.starts_with("-\t")
+}
+
+// Mapping key indicator `?` of explicit entry.
+pub(crate) fn is_explicit_key_indicator(line: &str) -> bool {
+    line == "?" || line.starts_with("? ") || line.starts_with("?\t")
+}
+
+// Mapping value indicator `:` of explicit entry.
+pub(crate) fn is_map_value_indicator(line: &str) -> bool {
+    line == ":" || line.starts_with(": ") || line.starts_with(":\t")
+}
+
+// Whether the line is the start of block sequence or block map.
+fn is_block_collection_start(line: &str) -> bool {
+    if is_block_seq_indicator(line) || is_explicit_key_indicator(line) {
+        true
+    } else if line.starts_with(['\'', '"']) {
+        is_quoted_implicit_key(line)
+    } else if line.starts_with(['[', '{', '#']) {
+        false
+    } else {
+        find_value_indicator(line).is_some() || ends_with_value_indicator(line)
+    }
+}
+
+// Whether the content after node properties could be implicit key, the `:`
+// inside quoted scalar or flow collection is not mapping value indicator,
+// e.g. `&a {"b": c}`.
+fn is_implicit_key_content(line: &str) -> bool {
+    let rest = &line[leading_properties_len(line)..];
+    if rest.starts_with(['\'', '"']) {
+        is_quoted_implicit_key(rest)
+    } else {
+        !rest.starts_with(['[', '{'])
+    }
+}
+
+// Whether the line starts with a single line quoted scalar followed by the
+// mapping value indicator, e.g. `"a": b`.
+fn is_quoted_implicit_key(line: &str) -> bool {
+    let mut chars = line.char_indices();
+    let Some((_, quote)) = chars.next() else {
+        return false;
+    };
+    let mut escaped = false;
+    while let Some((offset, c)) = chars.next() {
+        if quote == '"' && c == '\\' {
+            escaped = !escaped;
+        } else if c == quote && !escaped {
+            // Two single quotes are escaped single quote
+            if quote == '\'' && line[offset + 1..].starts_with('\'') {
+                chars.next();
+                continue;
+            }
+            let rest = line[offset + 1..].trim_start_matches([' ', '\t']);
+            return rest == ":" || find_value_indicator(rest) == Some(0);
+        } else {
+            escaped = false;
+        }
+    }
+    false
+}
+
+/// Pull parser of YAML stream at the granularity of document: the first
+/// [Iterator::next()] of each document parses the whole document and
+/// buffers its events. Hence stopping early, e.g. after the first key, still
+/// parses the whole current document, but later documents are not parsed.
+/// The whole input is checked for non-printable characters by
+/// [YamlDocumentEventParser::new()]. Iteration stops after the first error.
+#[derive(Debug)]
+pub struct YamlDocumentEventParser<'a> {
+    input: Cow<'a, str>,
+    // Byte offset of input not parsed yet
+    offset: usize,
+    next_pos: YamlPosition,
+    done_pos: YamlPosition,
+    pending_directive: Option<YamlPosition>,
+    events: VecDeque<YamlEvent>,
+    is_started: bool,
+    is_finished: bool,
+}
+
+impl<'a> YamlDocumentEventParser<'a> {
+    pub fn new(input: &'a str) -> Result<Self, YamlError> {
+        let input = normalize_input(input)?;
+        let scanner = YamlScanner::new(&input);
+        let (next_pos, done_pos) = (scanner.next_pos, scanner.done_pos);
+        Ok(Self {
+            is_finished: input.is_empty(),
+            input,
+            offset: 0,
+            next_pos,
+            done_pos,
+            pending_directive: None,
+            events: VecDeque::new(),
+            is_started: false,
+        })
+    }
+
+    fn parse_next_document(&mut self) -> Result<(), YamlError> {
+        let remains = &self.input[self.offset..];
+        let mut parser = YamlParser::new(YamlScanner::resume(
+            remains,
+            self.next_pos,
+            self.done_pos,
+        ));
+        parser.pending_directive = self.pending_directive;
+        if !self.is_started {
+            self.is_started = true;
+            parser.push_event(YamlEvent::StreamStart);
+        }
+        self.is_finished = !parser.handle_document()?;
+        self.offset += remains.len() - parser.scanner.remains().len();
+        self.next_pos = parser.scanner.next_pos;
+        self.done_pos = parser.scanner.done_pos;
+        self.pending_directive = parser.pending_directive;
+        self.events.extend(parser.events);
+        Ok(())
+    }
+}
+
+impl Iterator for YamlDocumentEventParser<'_> {
+    type Item = Result<YamlEvent, YamlError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.events.is_empty() && !self.is_finished {
+            if let Err(e) = self.parse_next_document() {
+                self.is_finished = true;
+                return Some(Err(e));
+            }
+        }
+        self.events.pop_front().map(Ok)
+    }
+}
+
+/// Parsing events in the notation of yaml-test-suite, e.g. `+MAP`, `=VAL :a`
+/// and `-MAP`, useful for debugging how input is interpreted. Use the
+/// `logging` feature for trace log of every parsing step.
+pub fn parse_trace(input: &str) -> Result<Vec<String>, YamlError> {
+    Ok(YamlParser::parse_to_events(input)?
+        .iter()
+        .map(ToString::to_string)
+        .collect())
+}
+
 #[cfg(test)]
 mod test {
     use pretty_assertions::assert_eq;
 
     use super::*;
 
+    #[test]
+    fn test_parse_trace() -> Result<(), YamlError> {
+        assert_eq!(
+            parse_trace("a: [b]\n")?,
+            vec![
+                "+STR", "+DOC", "+MAP", "=VAL :a", "+SEQ", "=VAL :b", "-SEQ",
+                "-MAP", "-DOC", "-STR"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_indicators() {
+        assert_eq!(find_value_indicator("a:b: c"), Some(3));
+        assert_eq!(find_value_indicator("你:\t好"), Some(3));
+        assert_eq!(find_value_indicator("a:b:"), None);
+        assert_eq!(find_comment_start("a#b #c"), Some(3));
+        assert_eq!(find_comment_start("#a\t#b"), Some(2));
+        assert_eq!(find_comment_start("好#"), None);
+        assert_eq!(
+            comment_indicators("#a#b 好\t#c").collect::<Vec<_>>(),
+            [0, 9]
+        );
+    }
+
+    #[test]
+    fn test_event_parser() -> Result<(), YamlError> {
+        for input in [
+            "",
+            "\n",
+            "a: [b]\n",
+            "%YAML 1.2\r\n---\r\na\r\n...\r\n--- b\n---\n- c\n",
+            "a\n...\n...\n# d\n",
+        ] {
+            assert_eq!(
+                YamlDocumentEventParser::new(input)?
+                    .collect::<Result<Vec<_>, _>>()?,
+                YamlParser::parse_to_events(input)?,
+                "{input:?}"
+            );
+        }
+
+        let mut parser = YamlDocumentEventParser::new("a: b\n---\n[c\n")?;
+        assert_eq!(parser.next(), Some(Ok(YamlEvent::StreamStart)));
+        let events: Vec<String> = parser
+            .by_ref()
+            .take(6)
+            .map(|e| e.map(|e| e.to_string()))
+            .collect::<Result<_, _>>()?;
+        assert_eq!(
+            events,
+            ["+DOC", "+MAP", "=VAL :a", "=VAL :b", "-MAP", "-DOC"]
+        );
+        assert_eq!(
+            parser.next().map(|e| e.map_err(|e| e.kind())),
+            Some(Err(ErrorKind::UnfinishedSequenceIndicator))
+        );
+        assert_eq!(parser.next(), None);
+        Ok(())
+    }
+
     #[test]
     fn test_document_explcitly_start() {
         assert_eq!(
@@ -240,6 +992,63 @@ mod test {
         )
     }
 
+    #[test]
+    fn test_document_start_with_content() {
+        for (input, expected) in [
+            ("--- value\n", "=VAL :value"),
+            ("--- |\n  a\n", "=VAL |a\\n"),
+            ("--- !t value\n", "=VAL t :value"),
+            ("--- !t [a]\n", "+SEQ t|=VAL :a|-SEQ"),
+            ("--- {a: b}\n", "+MAP|=VAL :a|=VAL :b|-MAP"),
+            ("--- a\n  b\n", "=VAL :a b"),
+            ("---word\n", "=VAL :---word"),
+        ] {
+            let events: Vec<String> = YamlParser::parse_to_events(input)
+                .unwrap()
+                .iter()
+                .map(|e| e.to_string())
+                .collect();
+            let content = &events[2..events.len() - 2];
+            assert_eq!(content.join("|"), expected, "{input:?}");
+        }
+        for input in ["--- a: b\n", "--- - a\n"] {
+            let e = YamlParser::parse_to_events(input).unwrap_err();
+            assert_eq!(e.kind(), ErrorKind::TrailingContent);
+        }
+    }
+
+    #[test]
+    fn test_trailing_comments_and_empty_lines() {
+        let events = YamlParser::parse_to_events(
+            "a # c\n  \n\n--- \"b\"  # d\n# e\n   ",
+        )
+        .unwrap();
+        assert_eq!(
+            events
+                .iter()
+                .map(|e| (e.to_string(), e.end_position()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("+STR".to_string(), YamlPosition::EOF),
+                ("+DOC".to_string(), YamlPosition::new(1, 1)),
+                ("=VAL :a".to_string(), YamlPosition::new(1, 1)),
+                ("-DOC".to_string(), YamlPosition::new(1, 1)),
+                ("+DOC ---".to_string(), YamlPosition::new(4, 3)),
+                ("=VAL \"b".to_string(), YamlPosition::new(4, 7)),
+                ("-DOC".to_string(), YamlPosition::new(4, 7)),
+                ("-STR".to_string(), YamlPosition::EOF),
+            ]
+        );
+
+        let events =
+            YamlParser::parse_to_events("- a\n# b\n\n- c # d\n\n# e\n")
+                .unwrap();
+        assert_eq!(events[5], YamlEvent::SequenceEnd(YamlPosition::new(4, 3)));
+        let events =
+            YamlParser::parse_to_events("a: b\n  # c\n\nd: e\n#f\n").unwrap();
+        assert_eq!(events[7], YamlEvent::MapEnd(YamlPosition::new(4, 4)));
+    }
+
     #[test]
     fn test_document_explcitly_start_and_end() {
         assert_eq!(
@@ -249,6 +1058,8 @@ mod test {
                 YamlEvent::DocumentStart(true, YamlPosition::new(3, 1)),
                 YamlEvent::Scalar(
                     None,
+                    None,
+                    YamlScalarStyle::Plain,
                     "a".to_string(),
                     YamlPosition::new(4, 1),
                     YamlPosition::new(4, 1)
@@ -272,4 +1083,235 @@ mod test {
             ]
         )
     }
+
+    #[test]
+    fn test_tab_as_separation() {
+        let events =
+            YamlParser::parse_to_events("- \t-1\n-\t[a,\tb]\n").unwrap();
+        assert_eq!(
+            events[3],
+            YamlEvent::Scalar(
+                None,
+                None,
+                YamlScalarStyle::Plain,
+                "-1".to_string(),
+                YamlPosition::new(1, 4),
+                YamlPosition::new(1, 5)
+            )
+        );
+        assert_eq!(
+            events[4],
+            YamlEvent::SequenceStart(None, None, YamlPosition::new(2, 3))
+        );
+    }
+
+    #[test]
+    fn test_tab_as_indentation() {
+        for input in ["a:\n\tb: 1\n", "- \t- a\n", "a:\n  b: 1\n  \tc: 2\n"] {
+            let e = YamlParser::parse_to_events(input).unwrap_err();
+            assert_eq!(e.kind(), ErrorKind::TabIndentation);
+        }
+        let e = YamlParser::parse_to_events("a: [b,\n\tc]\n").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::TabIndentation);
+        assert_eq!(e.start_pos(), YamlPosition::new(2, 1));
+    }
+
+    #[test]
+    fn test_invalid_comment_placement() {
+        for (input, pos) in [
+            ("a: \"b\"# c\n", YamlPosition::new(1, 7)),
+            ("[a, b]#c\n", YamlPosition::new(1, 7)),
+            ("[a,#b\n]\n", YamlPosition::new(1, 4)),
+            ("a: |#b\n  c\n", YamlPosition::new(1, 5)),
+        ] {
+            let e = YamlParser::parse_to_events(input).unwrap_err();
+            assert_eq!(e.kind(), ErrorKind::InvalidCommentPlacement);
+            assert_eq!(e.start_pos(), pos, "{input:?}");
+        }
+        for input in ["a: b#c\n", "a: \"b\" # c\n", "[a, #b\n]\n", "a: |\t#b\n"]
+        {
+            YamlParser::parse_to_events(input).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_line_break_normalization() {
+        let input = "a: |\n  b\n  c\nd:\n  - e\n    f\n  - \"g\n\n    h\"\n";
+        let expected = YamlParser::parse_to_events(input).unwrap();
+        for line_break in ["\r\n", "\r"] {
+            let events =
+                YamlParser::parse_to_events(&input.replace('\n', line_break))
+                    .unwrap();
+            assert_eq!(events, expected);
+        }
+        let mixed =
+            "a: |\r\n  b\r  c\nd:\r  - e\r\n    f\n  - \"g\r\r\n    h\"\r\n";
+        assert_eq!(YamlParser::parse_to_events(mixed).unwrap(), expected);
+        assert_eq!(
+            expected[4],
+            YamlEvent::Scalar(
+                None,
+                None,
+                YamlScalarStyle::Literal,
+                "b\nc\n".to_string(),
+                YamlPosition::new(2, 3),
+                YamlPosition::new(3, 4)
+            )
+        );
+    }
+
+    #[test]
+    fn test_byte_order_mark() {
+        assert_eq!(
+            YamlParser::parse_to_events("\u{FEFF}a: b\n").unwrap(),
+            YamlParser::parse_to_events("a: b\n").unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_unicode_line_separators_are_content() {
+        let events = YamlParser::parse_to_events(
+            "- a\u{2028}b\u{2029}c\n- \"d\u{85}e\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            events[3],
+            YamlEvent::Scalar(
+                None,
+                None,
+                YamlScalarStyle::Plain,
+                "a\u{2028}b\u{2029}c".to_string(),
+                YamlPosition::new(1, 3),
+                YamlPosition::new(1, 7)
+            )
+        );
+        assert_eq!(
+            events[4],
+            YamlEvent::Scalar(
+                None,
+                None,
+                YamlScalarStyle::DoubleQuoted,
+                "d\u{85}e".to_string(),
+                YamlPosition::new(2, 3),
+                YamlPosition::new(2, 7)
+            )
+        );
+    }
+
+    #[test]
+    fn test_invalid_character() {
+        let e =
+            YamlParser::parse_to_events("a: b\r\nc: \"d\u{7}\"\n").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidCharacter);
+        assert_eq!(e.start_pos(), YamlPosition::new(2, 6));
+        let e = YamlParser::parse_to_events("\u{FEFF}a\u{0}").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidCharacter);
+        assert_eq!(e.start_pos(), YamlPosition::new(1, 2));
+    }
+
+    #[test]
+    fn test_document_end_marker() {
+        assert_eq!(
+            YamlParser::parse_to_events("a\n... # footer\nb\n").unwrap(),
+            vec![
+                YamlEvent::StreamStart,
+                YamlEvent::DocumentStart(false, YamlPosition::new(1, 1)),
+                YamlEvent::Scalar(
+                    None,
+                    None,
+                    YamlScalarStyle::Plain,
+                    "a".to_string(),
+                    YamlPosition::new(1, 1),
+                    YamlPosition::new(1, 1)
+                ),
+                YamlEvent::DocumentEnd(true, YamlPosition::new(2, 1)),
+                YamlEvent::DocumentStart(false, YamlPosition::new(3, 1)),
+                YamlEvent::Scalar(
+                    None,
+                    None,
+                    YamlScalarStyle::Plain,
+                    "b".to_string(),
+                    YamlPosition::new(3, 1),
+                    YamlPosition::new(3, 1)
+                ),
+                YamlEvent::DocumentEnd(false, YamlPosition::new(3, 1)),
+                YamlEvent::StreamEnd,
+            ]
+        );
+        assert_eq!(
+            YamlParser::parse_to_events("# comment\n...\n").unwrap(),
+            vec![YamlEvent::StreamStart, YamlEvent::StreamEnd]
+        );
+    }
+
+    #[test]
+    fn test_document_end_marker_errors() {
+        let e = YamlParser::parse_to_events("a\n... b\n").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::TrailingContent);
+        assert_eq!(e.start_pos(), YamlPosition::new(2, 5));
+
+        let e = YamlParser::parse_to_events("\"a\"\n%YAML 1.2\n---\nb\n")
+            .unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::MissingDocumentEnd);
+        assert_eq!(e.start_pos(), YamlPosition::new(2, 1));
+
+        let e = YamlParser::parse_to_events("%YAML 1.2\n...\n").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::MissingDocumentStart);
+        assert_eq!(e.start_pos(), YamlPosition::new(1, 1));
+    }
+
+    #[test]
+    fn test_properties_before_flow_node_with_colon() -> Result<(), YamlError> {
+        assert_eq!(
+            parse_trace("&x {\"a\":1, \"b\": 2}\n")?,
+            vec![
+                "+STR", "+DOC", "+MAP &x", "=VAL \"a", "=VAL :1", "=VAL \"b",
+                "=VAL :2", "-MAP", "-DOC", "-STR"
+            ]
+        );
+        assert_eq!(
+            parse_trace("- !t [\"a\":1]\n")?,
+            vec![
+                "+STR", "+DOC", "+SEQ", "+SEQ t", "+MAP", "=VAL \"a",
+                "=VAL :1", "-MAP", "-SEQ", "-SEQ", "-DOC", "-STR"
+            ]
+        );
+        assert_eq!(
+            parse_trace("&x \"a: b\"\n")?,
+            vec!["+STR", "+DOC", "=VAL &x \"a: b", "-DOC", "-STR"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_yaml_directive() -> Result<(), YamlError> {
+        for (input, pos) in [
+            ("%YAML 1.2\n%YAML 1.2\n---\n", YamlPosition::new(2, 1)),
+            (
+                "%YAML 1.2\n---\na\n...\n%TAG ! !a\n%YAML 1.1\n%YAML \
+                 1.2\n---\n",
+                YamlPosition::new(7, 1),
+            ),
+            ("%YAML 1.2 foo\n---\n", YamlPosition::new(1, 1)),
+            ("%YAML 2.0\n---\n", YamlPosition::new(1, 1)),
+            ("%YAML\n---\n", YamlPosition::new(1, 1)),
+        ] {
+            let e = YamlParser::parse_to_events(input).unwrap_err();
+            assert_eq!(e.kind(), ErrorKind::InvalidDirective, "{input:?}");
+            assert_eq!(e.start_pos(), pos, "{input:?}");
+        }
+
+        // Later minor version and unknown directive are ignored
+        let (_, directives) = YamlParser::parse_to_events_and_directives(
+            "%YAML 1.3 # comment\n%FOO bar\n---\na\n...\n%YAML 1.2\n---\nb\n",
+        )?;
+        assert_eq!(
+            directives
+                .iter()
+                .map(|d| d.name.as_str())
+                .collect::<Vec<_>>(),
+            ["YAML", "FOO", "YAML"]
+        );
+        Ok(())
+    }
 }
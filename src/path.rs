@@ -0,0 +1,102 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{YamlError, YamlValue, YamlValueData};
+
+impl YamlValue {
+    /// Node at the key path, which is map keys and sequence indexes joined
+    /// by `.`, e.g. `interfaces.0.name`. The document root is empty string.
+    /// Map key containing `.` is matched as a whole.
+    pub fn get_path(&self, path: &str) -> Option<&YamlValue> {
+        if path.is_empty() {
+            return Some(self);
+        }
+        let data = if let YamlValueData::Tag(tag) = &self.data {
+            &tag.data
+        } else {
+            &self.data
+        };
+        match data {
+            YamlValueData::Array(items) => {
+                let (index, rest) = path.split_once('.').unwrap_or((path, ""));
+                items.get(index.parse::<usize>().ok()?)?.get_path(rest)
+            }
+            YamlValueData::Map(map) => map.iter().find_map(|(key, value)| {
+                match path.strip_prefix(key.key_path_name().as_str())? {
+                    "" => Some(value),
+                    rest => value.get_path(rest.strip_prefix('.')?),
+                }
+            }),
+            _ => None,
+        }
+    }
+
+    /// String at the key path, None if not found or null. Number and bool
+    /// are provided as their text.
+    pub fn get_str(&self, path: &str) -> Result<Option<&str>, YamlError> {
+        self.get_path_as(path, YamlValue::as_str)
+    }
+
+    /// Unsigned integer at the key path, None if not found or null.
+    pub fn get_u64(&self, path: &str) -> Result<Option<u64>, YamlError> {
+        self.get_path_as(path, YamlValue::as_u64)
+    }
+
+    /// Bool at the key path, None if not found or null.
+    pub fn get_bool(&self, path: &str) -> Result<Option<bool>, YamlError> {
+        self.get_path_as(path, YamlValue::as_bool)
+    }
+
+    fn get_path_as<'a, T>(
+        &'a self,
+        path: &str,
+        convert: impl FnOnce(&'a YamlValue) -> Result<T, YamlError>,
+    ) -> Result<Option<T>, YamlError> {
+        match self.get_path(path) {
+            Some(value) if !value.is_null() => convert(value)
+                .map(Some)
+                .map_err(|e| e.or_key_path(|| path.to_string())),
+            _ => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{ErrorKind, YamlPosition};
+
+    #[test]
+    fn test_typed_getters() -> Result<(), YamlError> {
+        let value = YamlValue::from_str(
+            "interfaces:\n  - name: eth0\n    mtu: !!int 1500\n    enabled: \
+             true\n    ipv4:\n  - name: eth1\n    mtu: big\nexample.org: \
+             {port: 80}\n",
+        )?;
+        assert_eq!(value.get_str("interfaces.0.name")?, Some("eth0"));
+        assert_eq!(value.get_u64("interfaces.0.mtu")?, Some(1500));
+        assert_eq!(value.get_bool("interfaces.0.enabled")?, Some(true));
+        assert_eq!(value.get_bool("interfaces.1.enabled")?, None);
+        assert_eq!(value.get_str("interfaces.0.ipv4")?, None);
+        assert_eq!(value.get_str("interfaces.2.name")?, None);
+        assert_eq!(value.get_str("interfaces.name")?, None);
+        assert_eq!(value.get_u64("example.org.port")?, Some(80));
+        assert_eq!(
+            value.get_path("").map(|v| v.start),
+            Some(YamlPosition::new(1, 1))
+        );
+
+        let e = value.get_u64("interfaces.1.mtu").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidNumber);
+        assert_eq!(e.key_path(), Some("interfaces.1.mtu"));
+        assert_eq!(e.start_pos(), YamlPosition::new(7, 10));
+
+        let e = value.get_str("interfaces").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::UnexpectedYamlNodeType);
+        assert_eq!(e.key_path(), Some("interfaces"));
+        Ok(())
+    }
+}
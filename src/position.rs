@@ -2,6 +2,8 @@
 
 use std::str::FromStr;
 
+use serde::{Serialize, Serializer, ser::SerializeStruct};
+
 use crate::{ErrorKind, YamlError};
 
 /// Position of character
@@ -10,7 +12,7 @@ use crate::{ErrorKind, YamlError};
 /// null of this line.
 /// Default to first character of first line: line 1 column 1.
 /// The line 0 and column 0 means End of file [YamlPosition::EOF]
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
 pub struct YamlPosition {
     /// Line number, start from 1.
     pub line: usize,
@@ -37,6 +39,19 @@ impl std::fmt::Display for YamlPosition {
     }
 }
 
+/// Serialized as map of `line` and `column`.
+impl Serialize for YamlPosition {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("YamlPosition", 2)?;
+        state.serialize_field("line", &self.line)?;
+        state.serialize_field("column", &self.column)?;
+        state.end()
+    }
+}
+
 impl TryFrom<&str> for YamlPosition {
     type Error = YamlError;
 
@@ -0,0 +1,345 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::{
+    Deserialize, Serialize,
+    de::{DeserializeOwned, MapAccess, Visitor},
+};
+
+use crate::{
+    YamlError, YamlPosition, YamlScalarStyle, YamlValue, YamlValueData,
+    YamlValueMap, find_comment_start,
+};
+
+pub(crate) const RAW_VALUE_TOKEN: &str = "$rmsd_yaml::raw_value";
+
+const TEXT_KEY: &str = "text";
+const START_LINE_KEY: &str = "start_line";
+const START_COLUMN_KEY: &str = "start_column";
+const END_LINE_KEY: &str = "end_line";
+const END_COLUMN_KEY: &str = "end_column";
+
+/// Source text of a node captured during deserialization, could be stored
+/// and parsed later, e.g. section unknown to current schema.
+/// Node properties (tag and anchor) are not included. The first line of
+/// block collection is indented as other lines. Trailing empty lines are
+/// only included for block scalar with keep chomping indicator `+`.
+/// Only supported by the deserializer of this crate.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct YamlRawValue {
+    text: String,
+    start: YamlPosition,
+    end: YamlPosition,
+}
+
+impl YamlRawValue {
+    /// Captured source text.
+    pub fn text(&self) -> &str {
+        self.text.as_str()
+    }
+
+    /// Position of the node in source.
+    pub fn start(&self) -> YamlPosition {
+        self.start
+    }
+
+    /// End position of the node in source.
+    pub fn end(&self) -> YamlPosition {
+        self.end
+    }
+
+    /// Deserialize the captured text, position in error is relative to the
+    /// captured text.
+    pub fn parse<T>(&self) -> Result<T, YamlError>
+    where
+        T: DeserializeOwned,
+    {
+        crate::from_str(&self.text)
+    }
+}
+
+impl std::fmt::Display for YamlRawValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+// Emitted as parsed node, the source format is not preserved.
+impl Serialize for YamlRawValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.text
+            .parse::<YamlValue>()
+            .map_err(serde::ser::Error::custom)?
+            .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for YamlRawValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer
+            .deserialize_newtype_struct(RAW_VALUE_TOKEN, RawValueVisitor)
+    }
+}
+
+struct RawValueVisitor;
+
+impl<'de> Visitor<'de> for RawValueVisitor {
+    type Value = YamlRawValue;
+
+    fn expecting(
+        &self,
+        formatter: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        formatter.write_str("YAML node from rmsd_yaml deserializer")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<YamlRawValue, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut ret = YamlRawValue::default();
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                TEXT_KEY => ret.text = map.next_value()?,
+                START_LINE_KEY => ret.start.line = map.next_value()?,
+                START_COLUMN_KEY => ret.start.column = map.next_value()?,
+                END_LINE_KEY => ret.end.line = map.next_value()?,
+                END_COLUMN_KEY => ret.end.column = map.next_value()?,
+                _ => {
+                    return Err(serde::de::Error::unknown_field(
+                        &key,
+                        &[
+                            TEXT_KEY,
+                            START_LINE_KEY,
+                            START_COLUMN_KEY,
+                            END_LINE_KEY,
+                            END_COLUMN_KEY,
+                        ],
+                    ));
+                }
+            }
+        }
+        Ok(ret)
+    }
+}
+
+/// The map consumed by [RawValueVisitor] holding source text and position
+/// of specified node.
+pub(crate) fn raw_value_map(source: &str, value: &YamlValue) -> YamlValueMap {
+    let mut map = YamlValueMap::new();
+    map.insert(TEXT_KEY, capture_text(source, value));
+    map.insert(START_LINE_KEY, value.start.line.to_string());
+    map.insert(START_COLUMN_KEY, value.start.column.to_string());
+    map.insert(END_LINE_KEY, value.end.line.to_string());
+    map.insert(END_COLUMN_KEY, value.end.column.to_string());
+    map
+}
+
+fn capture_text(source: &str, value: &YamlValue) -> String {
+    let (Some(mut start), Some(end)) = (
+        char_offset(source, value.start),
+        char_offset(source, value.end),
+    ) else {
+        return String::new();
+    };
+    // The end position is inclusive
+    let mut end = source[end..]
+        .chars()
+        .next()
+        .map(|c| end + c.len_utf8())
+        .unwrap_or(source.len());
+    let mut indent = 0;
+    let mut keep_trailing_lines = false;
+    match &value.data {
+        YamlValueData::String(_)
+            if matches!(
+                value.style,
+                YamlScalarStyle::Literal | YamlScalarStyle::Folded
+            ) =>
+        {
+            // Position of block scalar is its content, include the header.
+            if let Some(offset) = block_scalar_header_offset(&source[..start]) {
+                start = offset;
+            }
+            // Trailing empty lines are content of keep chomping
+            if is_keep_chomping(&source[start..]) {
+                keep_trailing_lines = true;
+                end += source[end..]
+                    .split_inclusive('\n')
+                    .take_while(|l| l.trim_start_matches([' ', '\t']) == "\n")
+                    .map(|l| l.len())
+                    .sum::<usize>();
+            }
+        }
+        YamlValueData::Array(_) | YamlValueData::Map(_)
+            if !source[start..].starts_with(['[', '{']) =>
+        {
+            indent = value.start.column.saturating_sub(1);
+        }
+        _ => (),
+    }
+    if start >= end {
+        return String::new();
+    }
+    if keep_trailing_lines {
+        return source[start..end].to_string();
+    }
+    format!(
+        "{}{}",
+        " ".repeat(indent),
+        source[start..end].trim_end_matches([' ', '\t', '\n'])
+    )
+}
+
+// Byte offset of the character at specified position.
+fn char_offset(source: &str, pos: YamlPosition) -> Option<usize> {
+    if pos == YamlPosition::EOF {
+        return None;
+    }
+    let line_start = if pos.line <= 1 {
+        0
+    } else {
+        source.match_indices('\n').nth(pos.line - 2)?.0 + 1
+    };
+    Some(
+        source[line_start..]
+            .char_indices()
+            .nth(pos.column.saturating_sub(1))
+            .map(|(i, _)| line_start + i)
+            .unwrap_or(source.len()),
+    )
+}
+
+// The header is the last `|` or `>` before the content and empty lines.
+fn block_scalar_header_offset(before: &str) -> Option<usize> {
+    let before = before.trim_end_matches([' ', '\t', '\n']);
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line = &before[line_start..];
+    let line = find_comment_start(line).map(|i| &line[..i]).unwrap_or(line);
+    line.rfind(['|', '>']).map(|i| line_start + i)
+}
+
+// Whether the block scalar header has `+` chomping indicator.
+fn is_keep_chomping(header: &str) -> bool {
+    header
+        .chars()
+        .skip(1)
+        .take_while(|c| c.is_ascii_digit() || matches!(c, '+' | '-'))
+        .any(|c| c == '+')
+}
+
+#[cfg(test)]
+mod test {
+    use serde::{Deserialize, Serialize};
+
+    use super::YamlRawValue;
+    use crate::{YamlError, YamlPosition};
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct Plugin {
+        name: String,
+        config: YamlRawValue,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct PluginConfig {
+        port: u16,
+        hosts: Vec<String>,
+    }
+
+    #[test]
+    fn test_raw_value_block_map() -> Result<(), YamlError> {
+        let plugins: Vec<Plugin> = crate::from_str(
+            r#"- name: a
+  config:
+    port: 80  # http
+    hosts:
+      - "a.example.org"
+- name: b
+  config: {port: 443, hosts: [b.example.org]}
+- name: c
+  config: |
+    port: 8080
+    hosts: []
+- config:
+    - port: 1
+      hosts: []
+  name: d
+"#,
+        )?;
+
+        assert_eq!(
+            plugins[0].config.text(),
+            "    port: 80  # http\n    hosts:\n      - \"a.example.org\""
+        );
+        assert_eq!(plugins[0].config.start().line, 3);
+        assert_eq!(
+            plugins[0].config.parse::<PluginConfig>()?,
+            PluginConfig {
+                port: 80,
+                hosts: vec!["a.example.org".into()],
+            }
+        );
+        assert_eq!(
+            plugins[1].config.text(),
+            "{port: 443, hosts: [b.example.org]}"
+        );
+        assert_eq!(plugins[1].config.start(), YamlPosition::new(7, 11));
+        assert_eq!(
+            plugins[1].config.parse::<PluginConfig>()?.hosts,
+            vec!["b.example.org".to_string()]
+        );
+        assert_eq!(
+            plugins[2].config.text(),
+            "|\n    port: 8080\n    hosts: []"
+        );
+        assert_eq!(
+            plugins[2].config.parse::<String>()?,
+            "port: 8080\nhosts: []\n"
+        );
+        assert_eq!(
+            plugins[3].config.parse::<Vec<PluginConfig>>()?,
+            vec![PluginConfig {
+                port: 1,
+                hosts: Vec::new(),
+            }]
+        );
+
+        assert_eq!(
+            crate::to_string(&plugins[1])?,
+            "name: b\nconfig:\n  port: 443\n  hosts:\n    - b.example.org\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_raw_value_keep_chomping() -> Result<(), YamlError> {
+        #[derive(Debug, Deserialize)]
+        struct Raw {
+            cfg: YamlRawValue,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct Parsed {
+            cfg: String,
+        }
+
+        for yaml_str in [
+            "cfg: |+\n  a\n\n\nafter: 3\n",
+            "cfg: >+2\n  a\n  b\n\n",
+            "cfg: |-\n  a\n\nafter: 3\n",
+        ] {
+            let raw: Raw = crate::from_str(yaml_str)?;
+            let parsed: Parsed = crate::from_str(yaml_str)?;
+            assert_eq!(raw.cfg.parse::<String>()?, parsed.cfg, "{yaml_str:?}");
+        }
+        let raw: Raw = crate::from_str("cfg: |+\n  a\n\n\nafter: 3\n")?;
+        assert_eq!(raw.cfg.text(), "|+\n  a\n\n\n");
+        Ok(())
+    }
+}
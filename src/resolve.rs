@@ -0,0 +1,189 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::borrow::Cow;
+
+use crate::{
+    YamlNumber, YamlNumberKind, YamlScalarStyle, YamlValue,
+    version::resolve_yaml_1_1_scalar,
+};
+
+/// Schema deciding the type of untagged plain scalar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum YamlScalarSchema {
+    /// YAML 1.2 failsafe schema: every scalar is string.
+    Failsafe,
+    /// YAML 1.2 JSON schema: only `null`, `true`, `false` and JSON numbers
+    /// are resolved, other scalars are string.
+    Json,
+    /// YAML 1.2 core schema used by the deserializer.
+    #[default]
+    Core,
+    /// YAML 1.1 types used by the deserializer for `%YAML 1.1` document,
+    /// see [crate::YamlVersion::V1_1].
+    V1_1,
+}
+
+/// Type of scalar resolved by [resolve_scalar].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum YamlResolvedScalar {
+    Null,
+    Bool(bool),
+    /// Integer in its YAML 1.2 form, e.g. `0755` of YAML 1.1 is `0o755`.
+    Int(YamlNumber),
+    Float(YamlNumber),
+    Str,
+}
+
+/// Resolve the type of untagged scalar `text` like the deserializer does.
+/// Quoted and block scalars are always string.
+pub fn resolve_scalar(
+    text: &str,
+    style: YamlScalarStyle,
+    schema: YamlScalarSchema,
+) -> YamlResolvedScalar {
+    if style != YamlScalarStyle::Plain {
+        return YamlResolvedScalar::Str;
+    }
+    let text = match schema {
+        YamlScalarSchema::Failsafe => return YamlResolvedScalar::Str,
+        YamlScalarSchema::Json => return resolve_json_scalar(text),
+        YamlScalarSchema::Core => Cow::Borrowed(text),
+        YamlScalarSchema::V1_1 => resolve_yaml_1_1_scalar(text)
+            .map(Cow::Owned)
+            .unwrap_or(Cow::Borrowed(text)),
+    };
+    let value = YamlValue::from(text.as_ref());
+    if value.is_null() {
+        YamlResolvedScalar::Null
+    } else if let Ok(v) = value.as_bool() {
+        YamlResolvedScalar::Bool(v)
+    } else if let Ok(num) = YamlNumber::try_from(&value) {
+        if num.kind() == YamlNumberKind::Float {
+            YamlResolvedScalar::Float(num)
+        } else {
+            YamlResolvedScalar::Int(num)
+        }
+    } else {
+        YamlResolvedScalar::Str
+    }
+}
+
+// YAML 1.2.2 SPEC, 10.2.2. Tag Resolution:
+//      null: null
+//      bool: true|false
+//      int: -?(0|[1-9][0-9]*)
+//      float: -?(0|[1-9][0-9]*)(\.[0-9]*)?([eE][-+]?[0-9]+)?
+// The spec treats other scalars as error, string is used instead.
+fn resolve_json_scalar(text: &str) -> YamlResolvedScalar {
+    match text {
+        "null" => return YamlResolvedScalar::Null,
+        "true" => return YamlResolvedScalar::Bool(true),
+        "false" => return YamlResolvedScalar::Bool(false),
+        _ => (),
+    }
+    let rest = text.strip_prefix('-').unwrap_or(text);
+    let int_len = rest.len()
+        - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let (int, mut rest) = rest.split_at(int_len);
+    if int.is_empty() || (int.len() > 1 && int.starts_with('0')) {
+        return YamlResolvedScalar::Str;
+    }
+    let mut is_float = false;
+    if let Some(fraction) = rest.strip_prefix('.') {
+        is_float = true;
+        rest = fraction.trim_start_matches(|c: char| c.is_ascii_digit());
+    }
+    if let Some(exponent) = rest.strip_prefix(['e', 'E']) {
+        let exponent = exponent.strip_prefix(['-', '+']).unwrap_or(exponent);
+        if exponent.is_empty() || !exponent.chars().all(|c| c.is_ascii_digit())
+        {
+            return YamlResolvedScalar::Str;
+        }
+        is_float = true;
+        rest = "";
+    }
+    if !rest.is_empty() {
+        return YamlResolvedScalar::Str;
+    }
+    // Every JSON number is valid YAML 1.2 core schema number
+    match YamlNumber::try_from(&YamlValue::from(text)) {
+        Ok(num) if is_float => YamlResolvedScalar::Float(num),
+        Ok(num) => YamlResolvedScalar::Int(num),
+        Err(_) => YamlResolvedScalar::Str,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::YamlError;
+
+    fn resolve(text: &str, schema: YamlScalarSchema) -> YamlResolvedScalar {
+        resolve_scalar(text, YamlScalarStyle::Plain, schema)
+    }
+
+    #[test]
+    fn test_resolve_scalar() -> Result<(), YamlError> {
+        use YamlResolvedScalar::*;
+        use YamlScalarSchema::*;
+
+        assert_eq!(resolve("~", Core), Null);
+        assert_eq!(resolve("", Core), Null);
+        assert_eq!(resolve("True", Core), Bool(true));
+        assert_eq!(resolve("yes", Core), Str);
+        assert_eq!(resolve("0x1f", Core), Int(YamlNumber::from_str("0x1f")?));
+        assert_eq!(resolve("-12", Core), Int(YamlNumber::from_str("-12")?));
+        assert_eq!(resolve(".inf", Core), Float(YamlNumber::from_str(".inf")?));
+        assert_eq!(resolve("1e3", Core), Float(YamlNumber::from_str("1e3")?));
+        assert_eq!(resolve("0755", Core), Int(YamlNumber::from_str("0755")?));
+        assert_eq!(
+            resolve_scalar("true", YamlScalarStyle::SingleQuoted, Core),
+            Str
+        );
+
+        assert_eq!(resolve("Off", V1_1), Bool(false));
+        assert_eq!(resolve("0755", V1_1), Int(YamlNumber::from_str("0o755")?));
+        assert_eq!(resolve("null", V1_1), Null);
+
+        assert_eq!(resolve("null", Failsafe), Str);
+        assert_eq!(resolve("1", Failsafe), Str);
+
+        assert_eq!(resolve("null", Json), Null);
+        assert_eq!(resolve("~", Json), Str);
+        assert_eq!(resolve("True", Json), Str);
+        assert_eq!(resolve("-0", Json), Int(YamlNumber::from_str("-0")?));
+        assert_eq!(resolve("012", Json), Str);
+        assert_eq!(resolve("0x1f", Json), Str);
+        assert_eq!(resolve("1.", Json), Float(YamlNumber::from_str("1.")?));
+        assert_eq!(
+            resolve("-1.5E+3", Json),
+            Float(YamlNumber::from_str("-1.5E+3")?)
+        );
+        assert_eq!(resolve(".5", Json), Str);
+        assert_eq!(resolve("1e", Json), Str);
+        assert_eq!(resolve(".inf", Json), Str);
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_scalar_matches_deserializer() -> Result<(), YamlError> {
+        for text in ["~", "false", "12", "-0x1f", "1.5", ".NaN", "abc", "1_0"] {
+            let value: serde_yaml::Value = crate::from_str(text)?;
+            let expected = match resolve(text, YamlScalarSchema::Core) {
+                YamlResolvedScalar::Null => value.is_null(),
+                YamlResolvedScalar::Bool(_) => value.is_bool(),
+                YamlResolvedScalar::Int(_) => value.is_i64() || value.is_u64(),
+                YamlResolvedScalar::Float(_) => value.is_f64(),
+                YamlResolvedScalar::Str => value.is_string(),
+            };
+            assert!(expected, "{text}: {value:?}");
+        }
+        Ok(())
+    }
+}
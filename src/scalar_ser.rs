@@ -1,20 +1,437 @@
 // SPDX-License-Identifier: Apache-2.0
 
+use std::cmp::max;
+
+use crate::{
+    YamlBlockChomping, YamlQuoteStyle, YamlScalarStyle, YamlSerializeOption,
+    YamlValue,
+};
+
 /// Prefer unquoted string and use double quoted string if any of below:
-///     * Line is longer than `max_width`
+///     * Line is longer than `max_width` and cannot be folded
 ///     * Has non-printable character
 ///     * Has NS_ESC_XXX characters
+/// Single line string which cannot be plain scalar or `must_quote` is quoted
+/// as `quote` desired.
+/// Multi-line string is emitted as literal block scalar when possible, or
+/// folded block scalar when any line is longer than `max_width`.
+/// The `column` is the width of text before the scalar in the same line,
+/// the `indent_count` is the indentation of continuation lines.
 pub(crate) fn to_scalar_string(
+    column: usize,
     indent_count: usize,
     input: &str,
-    max_width: usize,
+    must_quote: bool,
+    quote: YamlQuoteStyle,
+    option: &YamlSerializeOption,
 ) -> String {
-    // TODO: Escape non-printable character
-    // TODO: Escape NS_ESC_XXX characters
-    // TODO: Break long line
-    if indent_count + input.chars().count() < max_width {
+    if quote == YamlQuoteStyle::Always {
+        to_double_quoted_string(column, indent_count, input, option)
+    } else if input.contains('\n') {
+        if input
+            .split('\n')
+            .any(|l| exceeds_width(option, indent_count + l.chars().count()))
+            && let Some(block) =
+                to_folded_block_string(indent_count, input, false, option)
+        {
+            block
+        } else if let Some(block) =
+            to_literal_block_string(indent_count, input, option)
+        {
+            block
+        } else {
+            to_double_quoted_string(column, indent_count, input, option)
+        }
+    } else if input.chars().any(need_escape)
+        || ((must_quote || need_quote(input))
+            && (quote == YamlQuoteStyle::Double
+                || (quote == YamlQuoteStyle::Plain && input.contains('\''))))
+    {
+        to_double_quoted_string(column, indent_count, input, option)
+    } else if must_quote || need_quote(input) {
+        to_single_quoted_string(column, indent_count, input, option)
+    } else if !exceeds_width(option, column + input.chars().count()) {
         input.to_string()
+    } else if let Some(block) =
+        to_folded_block_string(indent_count, input, false, option)
+    {
+        block
     } else {
-        format!("\"{input}\"")
+        to_double_quoted_string(column, indent_count, input, option)
     }
 }
+
+/// Emit string in desired style, fallback to [to_scalar_string] if specified
+/// string cannot be represented by that style.
+pub(crate) fn to_styled_scalar_string(
+    column: usize,
+    indent_count: usize,
+    input: &str,
+    style: YamlScalarStyle,
+    must_quote: bool,
+    quote: YamlQuoteStyle,
+    option: &YamlSerializeOption,
+) -> String {
+    let ret = match style {
+        YamlScalarStyle::Plain => None,
+        YamlScalarStyle::SingleQuoted => (!input.contains('\n')
+            && !input.chars().any(need_escape))
+        .then(|| to_single_quoted_string(column, indent_count, input, option)),
+        YamlScalarStyle::DoubleQuoted => {
+            Some(to_double_quoted_string(column, indent_count, input, option))
+        }
+        YamlScalarStyle::Literal => {
+            to_literal_block_string(indent_count, input, option)
+        }
+        YamlScalarStyle::Folded => {
+            to_folded_block_string(indent_count, input, true, option)
+        }
+    };
+    ret.unwrap_or_else(|| {
+        to_scalar_string(column, indent_count, input, must_quote, quote, option)
+    })
+}
+
+/// Whether single line string cannot be plain scalar due to indicators,
+/// leading or trailing white spaces. Empty string is kept as empty plain
+/// scalar.
+fn need_quote(input: &str) -> bool {
+    let mut chars = input.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    let second = chars.next();
+    input.starts_with([' ', '\t'])
+        || input.ends_with([' ', '\t', ':'])
+        || input.contains(": ")
+        || input.contains(" #")
+        || input.contains(":\t")
+        || input.contains("\t#")
+        || matches!(
+            first,
+            ',' | '['
+                | ']'
+                | '{'
+                | '}'
+                | '#'
+                | '&'
+                | '*'
+                | '!'
+                | '|'
+                | '>'
+                | '\''
+                | '"'
+                | '%'
+                | '@'
+                | '`'
+        )
+        || (matches!(first, '-' | '?' | ':')
+            && second.is_none_or(|c| matches!(c, ' ' | '\t')))
+        || ((input.starts_with("---") || input.starts_with("..."))
+            && (input.len() == 3 || input[3..].starts_with([' ', '\t'])))
+}
+
+/// Whether plain scalar of the string is resolved as null, bool or number
+/// by YAML 1.2.2 core schema.
+pub(crate) fn is_plain_non_string(input: &str) -> bool {
+    let value = YamlValue::from(input);
+    value.is_null()
+        || value.is_bool()
+        || value.is_integer()
+        || value.is_signed_integer()
+        || value.as_f64().is_ok()
+}
+
+fn exceeds_width(option: &YamlSerializeOption, width: usize) -> bool {
+    option.max_width != 0 && width > option.max_width
+}
+
+/// Generate block scalar header with indentation indicator and chomping
+/// indicator. Return None if specified string cannot be represented by block
+/// scalar with desired chomping indicator.
+fn block_scalar_header(
+    style_indicator: char,
+    input: &str,
+    option: &YamlSerializeOption,
+) -> Option<String> {
+    if input
+        .chars()
+        .any(|c| c != '\n' && c != '\t' && need_escape(c))
+        || input.chars().all(|c| c == '\n')
+    {
+        return None;
+    }
+
+    let trailing_line_break_count =
+        input.chars().rev().take_while(|c| *c == '\n').count();
+
+    let chomping_indicator =
+        match (option.block_scalar_chomping, trailing_line_break_count) {
+            (YamlBlockChomping::Auto | YamlBlockChomping::Strip, 0) => "-",
+            (YamlBlockChomping::Auto | YamlBlockChomping::Clip, 1) => "",
+            (YamlBlockChomping::Auto | YamlBlockChomping::Keep, 2..)
+            | (YamlBlockChomping::Keep, 1) => "+",
+            _ => return None,
+        };
+
+    // When first non-empty line starts with space, the auto-detection of
+    // indentation will fail, hence indentation indicator is required.
+    let need_indent_indicator = option.block_scalar_indent_indicator
+        || input
+            .split('\n')
+            .find(|l| !l.is_empty())
+            .map(|l| l.starts_with(' '))
+            .unwrap_or_default();
+
+    // The indentation indicator is relative to parent node which is always
+    // indented by `indent_count` less than block scalar content.
+    let indent_indicator = if need_indent_indicator {
+        if (1..=9).contains(&option.indent_count) {
+            option.indent_count.to_string()
+        } else {
+            return None;
+        }
+    } else {
+        String::new()
+    };
+
+    Some(format!(
+        "{style_indicator}{indent_indicator}{chomping_indicator}\n"
+    ))
+}
+
+/// YAML 1.2.2 SPEC, 8.1.2. Literal Style
+/// Return None if specified string cannot be represented by literal block
+/// scalar with desired chomping indicator.
+fn to_literal_block_string(
+    indent_count: usize,
+    input: &str,
+    option: &YamlSerializeOption,
+) -> Option<String> {
+    let mut ret = block_scalar_header('|', input, option)?;
+    let content = input.strip_suffix('\n').unwrap_or(input);
+    push_block_lines(&mut ret, indent_count, content.split('\n'), option);
+    Some(ret)
+}
+
+/// YAML 1.2.2 SPEC, 8.1.3. Folded Style
+/// Return None if specified string cannot be represented by folded block
+/// scalar with desired chomping indicator or no line can be wrapped while
+/// `always` is false.
+fn to_folded_block_string(
+    indent_count: usize,
+    input: &str,
+    always: bool,
+    option: &YamlSerializeOption,
+) -> Option<String> {
+    let mut ret = block_scalar_header('>', input, option)?;
+    let content = input.trim_end_matches('\n');
+    let trailing_line_break_count = input.len() - content.len();
+    let width = option
+        .max_width
+        .saturating_sub(max(indent_count, option.indent_count));
+
+    let mut lines: Vec<&str> = Vec::new();
+    // Whether previous non-empty line is more-indented.
+    let mut pre_more_indented: Option<bool> = None;
+    // Line breaks since previous non-empty line.
+    let mut line_break_count = 0usize;
+    let mut wrapped = false;
+    for line in content.split('\n') {
+        if line.is_empty() {
+            if pre_more_indented.is_none() {
+                lines.push(line);
+            } else {
+                line_break_count += 1;
+            }
+            continue;
+        }
+        let more_indented = line.starts_with([' ', '\t']);
+        if let Some(pre_more_indented) = pre_more_indented {
+            // Line break between lines is folded into space unless
+            // surrounding line is more-indented, so an empty line is needed
+            // to represent each of them.
+            let empty_line_count = if pre_more_indented || more_indented {
+                line_break_count - 1
+            } else {
+                line_break_count
+            };
+            lines.extend(std::iter::repeat_n("", empty_line_count));
+        }
+        if more_indented || width == 0 {
+            lines.push(line);
+        } else {
+            let pieces = wrap_line(line, width, width);
+            wrapped |= pieces.len() > 1;
+            lines.extend(pieces);
+        }
+        pre_more_indented = Some(more_indented);
+        line_break_count = 1;
+    }
+    if !wrapped && !always {
+        return None;
+    }
+    // The final line break is generated by the line break of the last line.
+    lines.extend(std::iter::repeat_n(
+        "",
+        trailing_line_break_count.saturating_sub(1),
+    ));
+
+    push_block_lines(&mut ret, indent_count, lines.into_iter(), option);
+    Some(ret)
+}
+
+fn push_block_lines<'a>(
+    output: &mut String,
+    indent_count: usize,
+    lines: impl Iterator<Item = &'a str>,
+    option: &YamlSerializeOption,
+) {
+    // Document root is treated as indentation 0, so we still need to
+    // indent the content.
+    let indent = " ".repeat(max(indent_count, option.indent_count));
+    for line in lines {
+        if !line.is_empty() {
+            output.push_str(&indent);
+            output.push_str(line);
+        }
+        output.push('\n');
+    }
+}
+
+/// Split line at spaces so each piece is no longer than `width` if possible,
+/// the first piece is limited by `first_width` instead.
+/// Only the space surrounded by non-space characters is used for splitting,
+/// so that line folding can restore it.
+fn wrap_line(line: &str, first_width: usize, width: usize) -> Vec<&str> {
+    let chars: Vec<(usize, char)> = line.char_indices().collect();
+    let break_indexes = (1..chars.len().saturating_sub(1)).filter(|i| {
+        chars[*i].1 == ' '
+            && !matches!(chars[i - 1].1, ' ' | '\t')
+            && !matches!(chars[i + 1].1, ' ' | '\t')
+    });
+
+    let mut ret = Vec::new();
+    // Char index of current piece start
+    let mut start = 0usize;
+    let mut last_break: Option<usize> = None;
+    for i in break_indexes.chain(std::iter::once(chars.len())) {
+        let max_width = if ret.is_empty() { first_width } else { width };
+        if i - start > max_width
+            && let Some(last_break) = last_break
+        {
+            ret.push(&line[chars[start].0..chars[last_break].0]);
+            start = last_break + 1;
+        }
+        last_break = Some(i);
+    }
+    ret.push(&line[chars[start].0..]);
+    ret
+}
+
+/// YAML 1.2.2 SPEC, 7.3.2. Single-Quoted Style
+/// The input should not contain line break or character requiring escape.
+fn to_single_quoted_string(
+    column: usize,
+    indent_count: usize,
+    input: &str,
+    option: &YamlSerializeOption,
+) -> String {
+    let mut ret = format!("'{}'", input.replace('\'', "''"));
+    if exceeds_width(option, column + ret.chars().count()) {
+        let indent = " ".repeat(indent_count);
+        ret = wrap_line(
+            &ret,
+            option.max_width.saturating_sub(column),
+            option.max_width.saturating_sub(indent_count),
+        )
+        .join(&format!("\n{indent}"));
+    }
+    ret
+}
+
+/// YAML 1.2.2 SPEC, 7.3.1. Double-Quoted Style
+/// Line longer than `max_width` is wrapped at spaces, flow folding will
+/// convert the line break back to space.
+fn to_double_quoted_string(
+    column: usize,
+    indent_count: usize,
+    input: &str,
+    option: &YamlSerializeOption,
+) -> String {
+    let mut ret = String::from('"');
+    for c in input.chars() {
+        match c {
+            '"' => ret.push_str("\\\""),
+            '\\' => ret.push_str("\\\\"),
+            '\0' => ret.push_str("\\0"),
+            '\u{07}' => ret.push_str("\\a"),
+            '\u{08}' => ret.push_str("\\b"),
+            '\t' => ret.push_str("\\t"),
+            '\n' => ret.push_str("\\n"),
+            '\u{0b}' => ret.push_str("\\v"),
+            '\u{0c}' => ret.push_str("\\f"),
+            '\r' => ret.push_str("\\r"),
+            '\u{1b}' => ret.push_str("\\e"),
+            '\u{85}' => ret.push_str("\\N"),
+            '\u{a0}' => ret.push_str("\\_"),
+            '\u{2028}' => ret.push_str("\\L"),
+            '\u{2029}' => ret.push_str("\\P"),
+            c if need_escape(c) => {
+                if (c as u32) <= 0xff {
+                    ret.push_str(&format!("\\x{:02x}", c as u32));
+                } else if (c as u32) <= 0xffff {
+                    ret.push_str(&format!("\\u{:04x}", c as u32));
+                } else {
+                    ret.push_str(&format!("\\U{:08x}", c as u32));
+                }
+            }
+            c => ret.push(c),
+        }
+    }
+    ret.push('"');
+    if exceeds_width(option, column + ret.chars().count()) {
+        let indent = " ".repeat(indent_count);
+        ret = wrap_line(
+            &ret,
+            option.max_width.saturating_sub(column),
+            option.max_width.saturating_sub(indent_count),
+        )
+        .join(&format!("\n{indent}"));
+    }
+    ret
+}
+
+/// JSON string, which is also valid YAML double quoted scalar.
+pub(crate) fn to_json_string(input: &str) -> String {
+    let mut ret = String::from('"');
+    for c in input.chars() {
+        match c {
+            '"' => ret.push_str("\\\""),
+            '\\' => ret.push_str("\\\\"),
+            '\u{08}' => ret.push_str("\\b"),
+            '\t' => ret.push_str("\\t"),
+            '\n' => ret.push_str("\\n"),
+            '\u{0c}' => ret.push_str("\\f"),
+            '\r' => ret.push_str("\\r"),
+            c if (c as u32) < 0x10000 && need_escape(c) => {
+                ret.push_str(&format!("\\u{:04x}", c as u32));
+            }
+            c => ret.push(c),
+        }
+    }
+    ret.push('"');
+    ret
+}
+
+// YAML 1.2.2 SPEC, 5.1. Character Set
+//      To ensure readability, YAML streams use only the printable subset of
+//      the Unicode character set.
+fn need_escape(c: char) -> bool {
+    !matches!(c,
+        '\u{20}'..='\u{7e}'
+        | '\u{a0}'..='\u{d7ff}'
+        | '\u{e000}'..='\u{fffd}'
+        | '\u{10000}'..='\u{10ffff}')
+        || matches!(c, '\u{a0}' | '\u{2028}' | '\u{2029}' | '\u{feff}')
+}
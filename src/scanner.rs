@@ -1,9 +1,71 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use std::str::CharIndices;
+use std::{borrow::Cow, cmp::max, str::CharIndices};
 
 use crate::{ErrorKind, YamlError, YamlPosition};
 
+const BYTE_ORDER_MARK: char = '\u{FEFF}';
+
+/// Prepare the input for [YamlScanner]:
+///  * Remove the byte order mark at the start of stream.
+///  * Normalize line breaks.
+///  * Reject non-printable characters.
+pub(crate) fn normalize_input(input: &str) -> Result<Cow<'_, str>, YamlError> {
+    let input = normalize_line_breaks(
+        input.strip_prefix(BYTE_ORDER_MARK).unwrap_or(input),
+    );
+    validate_characters(&input)?;
+    Ok(input)
+}
+
+/// YAML 1.2.2 SPEC, 5.4. Line Break Characters:
+///     Line breaks inside scalar content must be normalized by the YAML
+///     processor. Each such line break must be parsed into a single line feed
+///     character.
+/// Convert `\r\n` and standalone `\r` into `\n`, so that scalar content and
+/// positions are identical regardless of line break style.
+/// The U+2028, U+2029 and NEL(U+0085) are not line breaks in YAML 1.2, they
+/// are kept as normal content characters.
+fn normalize_line_breaks(input: &str) -> Cow<'_, str> {
+    if input.contains('\r') {
+        Cow::Owned(input.replace("\r\n", "\n").replace('\r', "\n"))
+    } else {
+        Cow::Borrowed(input)
+    }
+}
+
+/// YAML 1.2.2 SPEC, 5.1. Character Set:
+///     [1] c-printable ::=
+///           x09 | x0A | x0D | [x20-x7E]
+///         | x85 | [xA0-xD7FF] | [xE000-xFFFD]
+///         | [x10000-x10FFFF]
+fn is_printable(c: char) -> bool {
+    matches!(c,
+        '\t' | '\n' | '\r' | '\x20'..='\x7E' | '\u{85}'
+        | '\u{A0}'..='\u{D7FF}' | '\u{E000}'..='\u{FFFD}'
+        | '\u{10000}'..='\u{10FFFF}')
+}
+
+fn validate_characters(input: &str) -> Result<(), YamlError> {
+    let mut pos = YamlPosition::new(1, 1);
+    for c in input.chars() {
+        if !is_printable(c) {
+            return Err(YamlError::new(
+                ErrorKind::InvalidCharacter,
+                format!("Non-printable character {c:?} is not allowed"),
+                pos,
+                pos,
+            ));
+        }
+        if c == '\n' {
+            pos.next_line();
+        } else {
+            pos.next_column();
+        }
+    }
+    Ok(())
+}
+
 #[derive(Debug)]
 pub(crate) struct YamlScanner<'a> {
     // We are Peekable does not have `as_str()`, so we use  CharIndices
@@ -29,6 +91,19 @@ impl<'a> YamlScanner<'a> {
         }
     }
 
+    /// Scanner of the remaining input with positions of previous scanner.
+    pub(crate) fn resume(
+        remains: &'a str,
+        next_pos: YamlPosition,
+        done_pos: YamlPosition,
+    ) -> Self {
+        Self {
+            iter: remains.char_indices(),
+            next_pos,
+            done_pos,
+        }
+    }
+
     pub(crate) fn is_empty(&self) -> bool {
         self.iter.as_str().is_empty()
     }
@@ -48,11 +123,17 @@ impl<'a> YamlScanner<'a> {
             .unwrap_or_default()
     }
 
-    pub(crate) fn peek_till_linebreak(&self) -> &str {
-        self.remains()
-            .split(['\r', '\n'])
-            .next()
-            .unwrap_or_default()
+    /// Next line which is not empty or comment only line.
+    pub(crate) fn peek_content_line(&self) -> Option<&'a str> {
+        self.remains().split('\n').find(|line| {
+            let trimmed = line.trim_start_matches([' ', '\t']);
+            !trimmed.is_empty() && !trimmed.starts_with('#')
+        })
+    }
+
+    pub(crate) fn peek_till_linebreak(&self) -> &'a str {
+        let remains = self.remains();
+        &remains[..find_line_break(remains).unwrap_or(remains.len())]
     }
 
     /// Count leading spaces of the first non-empty line
@@ -64,37 +145,39 @@ impl<'a> YamlScanner<'a> {
     ///     the longest line.
     pub(crate) fn count_block_identation(&self) -> usize {
         let mut max_indent = 0usize;
-        // The rust str::lines() will not take standalone `\r` as new line, but
-        // YAML spec says `\r` is new line. So we use split here.
-        for line in self.remains().split(['\n', '\r']) {
-            if line.chars().all(|c| c == ' ') {
-                let cur_indent = line.chars().count();
-                if max_indent < cur_indent {
-                    max_indent = cur_indent;
-                }
-            } else {
-                return line.chars().take_while(|c| c == &' ').count();
+        let mut remains = self.remains();
+        loop {
+            let line_end = find_line_break(remains).unwrap_or(remains.len());
+            // Space is single byte in UTF-8, hence byte count is char count.
+            let indent = remains.bytes().take_while(|b| *b == b' ').count();
+            if indent < line_end {
+                return indent;
             }
+            max_indent = max(max_indent, indent);
+            if line_end == remains.len() {
+                return max_indent;
+            }
+            remains = &remains[line_end + 1..];
         }
-        max_indent
+    }
+
+    /// Whether the next character is the first of line, document markers
+    /// are only valid there.
+    pub(crate) fn at_line_start(&self) -> bool {
+        self.next_pos.column == 1
     }
 
     pub(crate) fn peek_line(&self) -> Option<&'a str> {
         if self.remains().is_empty() {
             None
         } else {
-            Some(
-                self.remains()
-                    .split_once(['\n', '\r'])
-                    .map(|(s, _)| s)
-                    .unwrap_or(self.remains()),
-            )
+            Some(self.peek_till_linebreak())
         }
     }
 
     pub(crate) fn next_line(&mut self) -> Option<&'a str> {
         let ret = self.peek_line();
-        log::trace!("next line {:?}", ret);
+        trace!("next line {:?}", ret);
         self.advance_till_linebreak();
         ret
     }
@@ -117,12 +200,18 @@ impl<'a> YamlScanner<'a> {
     }
 
     pub(crate) fn advance_till_linebreak(&mut self) {
-        self.advance(self.peek_till_linebreak().chars().count());
-        self.next_char();
-    }
-
-    pub(crate) fn advance_till_linebreak_or_space(&mut self) {
-        self.advance(self.peek_till_linebreak_or_space().chars().count());
+        let count = self.peek_till_linebreak().chars().count();
+        if count > 0 {
+            // Skip characters in the same line without tracing each of them
+            self.iter.nth(count - 1);
+            self.done_pos = self.next_pos;
+            self.done_pos.column += count - 1;
+            if self.remains().is_empty() {
+                self.next_pos = self.done_pos;
+            } else {
+                self.next_pos.column += count;
+            }
+        }
         self.next_char();
     }
 
@@ -147,10 +236,9 @@ impl<'a> YamlScanner<'a> {
 
     pub(crate) fn next_char(&mut self) -> Option<char> {
         let c = self.iter.next()?.1;
-        log::trace!("next char {:?}", c);
-        // Windows use `\r\n` for single line break, so we should not increase
-        // line number if found `\r` and next one is `\n`.
-        if c == '\n' || (c == '\r' && self.peek_char() != Some('\n')) {
+        trace!("next char {:?}", c);
+        // Line breaks are normalized to `\n` by `normalize_line_breaks()`.
+        if c == '\n' {
             self.done_pos = self.next_pos;
             self.next_pos.next_line();
         } else if !self.remains().is_empty() {
@@ -164,20 +252,25 @@ impl<'a> YamlScanner<'a> {
     }
 
     /// Consume comment or line break or both.
-    /// Raise Error if not followed by comment or line break.
+    /// Raise Error if not followed by comment or line break, or the comment
+    /// is not separated by white space.
     pub(crate) fn expect_comment_or_line_break(
         &mut self,
     ) -> Result<(), YamlError> {
+        let mut separated = false;
         while let Some(c) = self.next_char() {
             match c {
                 '\r' | '\n' => {
                     break;
                 }
+                '#' if !separated => {
+                    return Err(invalid_comment_error(self.done_pos));
+                }
                 '#' => {
                     self.advance_till_linebreak();
                 }
-                ' ' => {
-                    continue;
+                ' ' | '\t' => {
+                    separated = true;
                 }
                 c => {
                     return Err(YamlError::new(
@@ -192,3 +285,23 @@ impl<'a> YamlScanner<'a> {
         Ok(())
     }
 }
+
+// Byte offset of the first line break. The `\r` is also included as YAML
+// spec treats it as line break. Byte search is safe for UTF-8 as ASCII bytes
+// never appear inside multi-byte characters.
+fn find_line_break(input: &str) -> Option<usize> {
+    input.bytes().position(|b| matches!(b, b'\n' | b'\r'))
+}
+
+/// YAML 1.2.2 SPEC, 6.6. Comments:
+///     Comments must be separated from other tokens by white space
+///     characters.
+pub(crate) fn invalid_comment_error(pos: YamlPosition) -> YamlError {
+    YamlError::new(
+        ErrorKind::InvalidCommentPlacement,
+        "Comment should be separated from other tokens by white space"
+            .to_string(),
+        pos,
+        pos,
+    )
+}
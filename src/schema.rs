@@ -0,0 +1,365 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use regex_lite::Regex;
+
+use crate::{
+    ErrorKind, YamlError, YamlScalarStyle, YamlValue, YamlValueData,
+    sub_key_path,
+};
+
+/// Declarative schema of YAML node, checked by [YamlSchema::validate()].
+#[derive(Debug, Clone, PartialEq, Default)]
+#[non_exhaustive]
+pub enum YamlSchema {
+    /// Any node.
+    #[default]
+    Any,
+    /// Null scalar, e.g. empty, `~` or `null`.
+    Null,
+    /// Bool scalar, `true` or `false`.
+    Bool,
+    /// Integer within optional inclusive range.
+    Integer {
+        min: Option<i128>,
+        max: Option<i128>,
+    },
+    /// Float or integer within optional inclusive range.
+    Float { min: Option<f64>, max: Option<f64> },
+    /// Any non-null scalar, with optional regular expression which the whole
+    /// string should match.
+    String { pattern: Option<String> },
+    /// Scalar equal to one of the strings.
+    Enum(Vec<String>),
+    /// Sequence with all items matching the schema.
+    Sequence(Box<YamlSchema>),
+    /// Mapping with string keys.
+    Map(YamlMapSchema),
+    /// Node matching any of the schemas.
+    OneOf(Vec<YamlSchema>),
+}
+
+/// Schema of mapping node.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[non_exhaustive]
+pub struct YamlMapSchema {
+    /// Schema of known keys.
+    pub properties: Vec<(String, YamlSchema)>,
+    /// Keys should exist.
+    pub required: Vec<String>,
+    /// Schema of keys not found in `properties`. Unknown keys are not
+    /// allowed when set to None.
+    pub additional: Option<Box<YamlSchema>>,
+}
+
+impl YamlMapSchema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add known key with its schema.
+    pub fn property(
+        mut self,
+        key: impl Into<String>,
+        schema: YamlSchema,
+    ) -> Self {
+        self.properties.push((key.into(), schema));
+        self
+    }
+
+    /// Add known key which should exist.
+    pub fn required_property(
+        mut self,
+        key: impl Into<String>,
+        schema: YamlSchema,
+    ) -> Self {
+        let key = key.into();
+        self.required.push(key.clone());
+        self.properties.push((key, schema));
+        self
+    }
+
+    /// Allow unknown keys matching the schema.
+    pub fn additional(mut self, schema: YamlSchema) -> Self {
+        self.additional = Some(Box::new(schema));
+        self
+    }
+}
+
+impl YamlSchema {
+    /// Validate the whole tree, return all violations in the order of the
+    /// document. The message of violation contains the key path which is map
+    /// keys and sequence indexes joined by `.`.
+    pub fn validate(&self, value: &YamlValue) -> Result<(), Vec<YamlError>> {
+        let mut errors = Vec::new();
+        self.validate_node(value, "", &mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn validate_node(
+        &self,
+        value: &YamlValue,
+        path: &str,
+        errors: &mut Vec<YamlError>,
+    ) {
+        let data = match &value.data {
+            YamlValueData::Tag(tag) => &tag.data,
+            data => data,
+        };
+        let is_plain = value.style == YamlScalarStyle::Plain;
+        match (self, data) {
+            (Self::Any, _) => (),
+            (Self::Null, YamlValueData::Null) => (),
+            (Self::Null, YamlValueData::String(_))
+                if is_plain && value.is_null() => {}
+            (Self::Bool, YamlValueData::String(_))
+                if is_plain && value.is_bool() => {}
+            (Self::Integer { min, max }, YamlValueData::String(_))
+                if is_plain && value.as_i128().is_ok() =>
+            {
+                if let Ok(v) = value.as_i128() {
+                    check_range(v, *min, *max, value, path, errors);
+                }
+            }
+            (Self::Float { min, max }, YamlValueData::String(_))
+                if is_plain && value.as_f64().is_ok() =>
+            {
+                if let Ok(v) = value.as_f64() {
+                    check_range(v, *min, *max, value, path, errors);
+                }
+            }
+            (Self::String { pattern }, YamlValueData::String(s)) => {
+                if let Some(pattern) = pattern {
+                    match Regex::new(&format!("^(?:{pattern})$")) {
+                        Ok(regex) => {
+                            if !regex.is_match(s) {
+                                errors.push(violation(
+                                    format!(
+                                        "{s:?} does not match pattern \
+                                         {pattern:?}"
+                                    ),
+                                    value,
+                                    path,
+                                ));
+                            }
+                        }
+                        Err(e) => errors.push(YamlError::new(
+                            ErrorKind::InvalidSchema,
+                            format!("Invalid pattern {pattern:?}: {e}"),
+                            value.start,
+                            value.end,
+                        )),
+                    }
+                }
+            }
+            (Self::Enum(items), YamlValueData::String(s)) => {
+                if !items.contains(s) {
+                    errors.push(violation(
+                        format!("Expecting one of {items:?}, but got {s:?}"),
+                        value,
+                        path,
+                    ));
+                }
+            }
+            (Self::Sequence(schema), YamlValueData::Array(items)) => {
+                for (i, item) in items.iter().enumerate() {
+                    schema.validate_node(
+                        item,
+                        &sub_key_path(path, &i.to_string()),
+                        errors,
+                    );
+                }
+            }
+            (Self::Map(schema), YamlValueData::Map(map)) => {
+                for key in &schema.required {
+                    if !map.contains_key(key.as_str()) {
+                        errors.push(violation(
+                            format!("Missing required key {key:?}"),
+                            value,
+                            path,
+                        ));
+                    }
+                }
+                for (key, item) in map.iter() {
+                    let key_name = key.key_path_name();
+                    let sub_path = sub_key_path(path, &key_name);
+                    if let Some((_, item_schema)) =
+                        schema.properties.iter().find(|(k, _)| k == &key_name)
+                    {
+                        item_schema.validate_node(item, &sub_path, errors);
+                    } else if let Some(item_schema) = &schema.additional {
+                        item_schema.validate_node(item, &sub_path, errors);
+                    } else {
+                        errors.push(violation(
+                            format!("Unknown key {key_name:?}"),
+                            key,
+                            path,
+                        ));
+                    }
+                }
+            }
+            (Self::OneOf(schemas), _) => {
+                if !schemas.iter().any(|schema| {
+                    let mut sub_errors = Vec::new();
+                    schema.validate_node(value, path, &mut sub_errors);
+                    sub_errors.is_empty()
+                }) {
+                    errors.push(violation(
+                        format!("Expecting any of {schemas:?}, but got {data}"),
+                        value,
+                        path,
+                    ));
+                }
+            }
+            (schema, data) => {
+                errors.push(violation(
+                    format!("Expecting {}, but got {data}", schema.name()),
+                    value,
+                    path,
+                ));
+            }
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Any => "any",
+            Self::Null => "null",
+            Self::Bool => "bool",
+            Self::Integer { .. } => "integer",
+            Self::Float { .. } => "float",
+            Self::String { .. } => "string",
+            Self::Enum(_) => "enum",
+            Self::Sequence(_) => "sequence",
+            Self::Map(_) => "map",
+            Self::OneOf(_) => "one of schemas",
+        }
+    }
+}
+
+fn check_range<T>(
+    v: T,
+    min: Option<T>,
+    max: Option<T>,
+    value: &YamlValue,
+    path: &str,
+    errors: &mut Vec<YamlError>,
+) where
+    T: PartialOrd + std::fmt::Display + Copy,
+{
+    if let Some(min) = min
+        && v < min
+    {
+        errors.push(violation(
+            format!("Expecting value >= {min}, but got {v}"),
+            value,
+            path,
+        ));
+    }
+    if let Some(max) = max
+        && v > max
+    {
+        errors.push(violation(
+            format!("Expecting value <= {max}, but got {v}"),
+            value,
+            path,
+        ));
+    }
+}
+
+fn violation(msg: String, value: &YamlValue, path: &str) -> YamlError {
+    YamlError::new(
+        ErrorKind::SchemaViolation,
+        format!("{path:?}: {msg}"),
+        value.start,
+        value.end,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::YamlPosition;
+
+    fn iface_schema() -> YamlSchema {
+        YamlSchema::Sequence(Box::new(YamlSchema::Map(
+            YamlMapSchema::new()
+                .required_property(
+                    "name",
+                    YamlSchema::String {
+                        pattern: Some("[a-z]+[0-9]*".into()),
+                    },
+                )
+                .required_property(
+                    "state",
+                    YamlSchema::Enum(vec!["up".into(), "down".into()]),
+                )
+                .property(
+                    "mtu",
+                    YamlSchema::Integer {
+                        min: Some(68),
+                        max: Some(65535),
+                    },
+                )
+                .property(
+                    "dhcp",
+                    YamlSchema::OneOf(vec![YamlSchema::Bool, YamlSchema::Null]),
+                ),
+        )))
+    }
+
+    #[test]
+    fn test_schema_valid() -> Result<(), YamlError> {
+        let value: YamlValue = r#"
+            - name: eth0
+              state: up
+              mtu: 1500
+              dhcp: true
+            - name: lo
+              state: down
+              dhcp:
+            "#
+        .parse()?;
+        assert_eq!(iface_schema().validate(&value), Ok(()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_schema_collect_all_violations() -> Result<(), YamlError> {
+        let value: YamlValue = r#"- name: eth0
+  state: unknown
+  mtu: 9
+- name: "Eth1"
+  mtu: '1500'
+  dhcp: yes
+  foo: 1
+"#
+        .parse()?;
+        let errors = iface_schema().validate(&value).unwrap_err();
+        let msgs: Vec<&str> = errors.iter().map(|e| e.msg()).collect();
+        assert_eq!(
+            msgs,
+            vec![
+                "\"0.state\": Expecting one of [\"up\", \"down\"], but got \
+                 \"unknown\"",
+                "\"0.mtu\": Expecting value >= 68, but got 9",
+                "\"1\": Missing required key \"state\"",
+                "\"1.name\": \"Eth1\" does not match pattern \"[a-z]+[0-9]*\"",
+                "\"1.mtu\": Expecting integer, but got String(\"1500\")",
+                "\"1.dhcp\": Expecting any of [Bool, Null], but got \
+                 String(\"yes\")",
+                "\"1\": Unknown key \"foo\"",
+            ]
+        );
+        assert!(
+            errors
+                .iter()
+                .all(|e| e.kind() == ErrorKind::SchemaViolation)
+        );
+        assert_eq!(errors[1].start_pos(), YamlPosition::new(3, 8));
+        Ok(())
+    }
+}
@@ -1,24 +1,30 @@
 // SPDX-License-Identifier: Apache-2.0
 
+use std::sync::Arc;
+
 use serde::de::{DeserializeSeed, SeqAccess};
 
 use crate::{
-    ErrorKind, YamlDeserializer, YamlError, YamlEvent, YamlParser, YamlState,
-    YamlValue,
+    ErrorKind, YamlDeContext, YamlDeserializer, YamlError, YamlEvent,
+    YamlParser, YamlScalarStyle, YamlState, YamlValue, is_block_seq_indicator,
+    is_document_marker,
 };
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 pub(crate) struct YamlValueSeqAccess {
-    data: Vec<YamlValue>,
+    items: std::iter::Enumerate<std::vec::IntoIter<YamlValue>>,
+    ctx: Arc<YamlDeContext>,
+    // Items are entries of `!!omap` or `!!pairs`
+    pub(crate) pairs: bool,
 }
 
 impl YamlValueSeqAccess {
-    pub(crate) fn new(data: Vec<YamlValue>) -> Self {
-        // The Vec::pop() is much quicker than Vec::remove(0), so we
-        // reverse it.
-        let mut data = data;
-        data.reverse();
-        Self { data }
+    pub(crate) fn new(data: Vec<YamlValue>, ctx: Arc<YamlDeContext>) -> Self {
+        Self {
+            items: data.into_iter().enumerate(),
+            ctx,
+            pairs: false,
+        }
     }
 }
 
@@ -32,8 +38,16 @@ impl<'de> SeqAccess<'de> for YamlValueSeqAccess {
     where
         K: DeserializeSeed<'de>,
     {
-        if let Some(value) = self.data.pop() {
-            seed.deserialize(&mut YamlDeserializer { parsed: value })
+        if let Some((index, value)) = self.items.next() {
+            let (start, end) = (value.start, value.end);
+            self.ctx
+                .in_child(index.to_string(), || {
+                    let mut deserializer =
+                        YamlDeserializer::child(value, self.ctx.clone());
+                    deserializer.pair_entry = self.pairs;
+                    seed.deserialize(&mut deserializer)
+                        .map_err(|e| e.or_position(start, end))
+                })
                 .map(Some)
         } else {
             Ok(None)
@@ -41,58 +55,107 @@ impl<'de> SeqAccess<'de> for YamlValueSeqAccess {
     }
 
     fn size_hint(&self) -> Option<usize> {
-        Some(self.data.len())
+        Some(self.items.len())
     }
 }
 
 impl<'a> YamlParser<'a> {
-    /// Invoked when there is `: ` in line or ends with `:`.
-    /// Advance till map finished.
+    /// Invoked when line starts with `- ` or `-\n`.
+    /// Advance till sequence finished.
+    /// The `first_indent_count` is the indentation of first line which might
+    /// follow other indicator in compact notation, e.g. `- - a`.
     pub(crate) fn handle_block_seq(
         &mut self,
+        first_indent_count: usize,
         indent_count: usize,
         tag: Option<String>,
     ) -> Result<(), YamlError> {
-        log::trace!(
-            "handle_block_seq {} {:?}",
-            indent_count,
+        trace!(
+            "handle_block_seq {first_indent_count} {indent_count} {:?}",
             self.scanner.remains()
         );
-        self.push_event(YamlEvent::SequenceStart(tag, self.scanner.next_pos));
+        // YAML 1.2.2 SPEC, 8.2.1. Block Sequences:
+        //      ... block sequence may be used as the value of a block mapping
+        //      entry without indenting it more than the mapping key.
+        // The zero-indented sequence ends at the next mapping key.
+        let is_zero_indented = (self.cur_state().is_block_map_value()
+            || self.cur_state().is_block_map_explicit_entry())
+            && self.parent_block_indent() == Some(indent_count);
+        self.push_event(YamlEvent::SequenceStart(
+            None,
+            tag,
+            self.scanner.next_pos,
+        ));
         self.push_state(YamlState::InBlockSequnce);
+        self.push_block_indent(indent_count);
+        let mut is_first_line = true;
         while let Some(line) = self.scanner.peek_line() {
-            if line.is_empty() {
+            // Empty line or comment line
+            let content = line.trim_start_matches([' ', '\t']);
+            if content.is_empty() || content.starts_with('#') {
+                self.scanner.next_line();
                 continue;
             }
             let cur_indent = line.chars().take_while(|c| *c == ' ').count();
-            if cur_indent < indent_count {
+            // Column of the `-` indicator in the line
+            let indicator_indent = if is_first_line {
+                is_first_line = false;
+                if cur_indent < first_indent_count {
+                    break;
+                }
+                indent_count + cur_indent - first_indent_count
+            } else {
+                cur_indent
+            };
+            if indicator_indent < indent_count
+                || (self.scanner.at_line_start() && is_document_marker(line))
+            {
                 break;
             }
             let trimmed = line.trim_start_matches(' ');
+            if is_zero_indented && !is_block_seq_indicator(trimmed) {
+                break;
+            }
 
-            if trimmed == "-" {
+            if trimmed.trim_end_matches([' ', '\t']) == "-" {
+                self.scanner.advance(cur_indent + 1);
+                let indicator_pos = self.scanner.done_pos;
                 self.scanner.next_line();
-                if let Some(next_line) = self.scanner.peek_line() {
-                    let next_indent =
-                        next_line.chars().take_while(|c| *c == ' ').count();
-                    self.handle_node(next_indent, next_indent, None)?;
-                } else {
-                    if self.scanner.remains().is_empty() {
-                        // Empty array
+                let next_indent = self
+                    .scanner
+                    .peek_content_line()
+                    .map(|l| (l.chars().take_while(|c| *c == ' ').count(), l));
+                match next_indent {
+                    // Entry content should be more indented than `-`
+                    Some((next_indent, next_line))
+                        if next_indent > indicator_indent
+                            && !is_document_marker(next_line) =>
+                    {
+                        self.handle_node(next_indent, next_indent, None)?;
+                    }
+                    _ => {
+                        // Empty entry
                         self.push_event(YamlEvent::Scalar(
                             None,
+                            None,
+                            YamlScalarStyle::Plain,
                             String::new(),
-                            self.scanner.done_pos,
-                            self.scanner.done_pos,
+                            indicator_pos,
+                            indicator_pos,
                         ));
                     }
                 }
-            } else if trimmed.starts_with("- ") {
-                self.scanner.advance(cur_indent + 2);
-                self.handle_node(0, cur_indent + 2, None)?;
-            } else if trimmed.is_empty() {
-                self.scanner.next_line();
-                continue;
+            } else if let Some(rest) = trimmed.strip_prefix("- ") {
+                // Compact entry content might be padded by more spaces, e.g.
+                // `-   a: 1`, its indentation is the column of the content.
+                let pad = rest.chars().take_while(|c| *c == ' ').count();
+                self.scanner.advance(cur_indent + 2 + pad);
+                self.handle_node(0, indicator_indent + 2 + pad, None)?;
+            } else if trimmed.starts_with("-\t") {
+                // Leave the tab to `handle_node()` as it cannot be used as
+                // indentation of block collection.
+                self.scanner.advance(cur_indent + 1);
+                self.handle_node(0, indicator_indent + 2, None)?;
             } else {
                 return Err(YamlError::new(
                     ErrorKind::InvalidSequnceStartIndicator,
@@ -106,16 +169,99 @@ impl<'a> YamlParser<'a> {
             }
         }
 
-        self.push_event(YamlEvent::SequenceEnd(self.scanner.done_pos));
+        self.push_event(YamlEvent::SequenceEnd(self.last_event_end()));
         self.pop_state();
+        self.pop_block_indent();
         Ok(())
     }
 
+    /// Consume the scanner till a flow sequence is finished. Single pair
+    /// mapping like `[a: b]` is also supported as sequence entry.
     pub(crate) fn handle_flow_seq(
         &mut self,
-        _tag: Option<String>,
+        tag: Option<String>,
     ) -> Result<(), YamlError> {
-        todo!()
+        trace!("handle_flow_seq {:?}", self.scanner.remains());
+        self.scanner.advance_till_non_space();
+        self.scanner.next_char();
+        self.push_event(YamlEvent::SequenceStart(
+            None,
+            tag,
+            self.scanner.done_pos,
+        ));
+        self.flow_starts.push(self.scanner.done_pos);
+        self.push_state(YamlState::InFlowSequnce);
+        loop {
+            self.skip_flow_separation()?;
+            match self.scanner.peek_char() {
+                Some(']') => break,
+                Some(',') => {
+                    return Err(YamlError::new(
+                        ErrorKind::InvalidFlowEntry,
+                        "Got ',' without flow sequence entry before it"
+                            .to_string(),
+                        self.scanner.next_pos,
+                        self.scanner.next_pos,
+                    ));
+                }
+                None => return Err(self.unfinished_flow_error()),
+                Some(_) => {
+                    let entry_index = self.event_count();
+                    let entry_pos = self.scanner.next_pos;
+                    if self.is_flow_value_indicator() {
+                        // Empty implicit key
+                        self.push_event(YamlEvent::Scalar(
+                            None,
+                            None,
+                            YamlScalarStyle::Plain,
+                            String::new(),
+                            entry_pos,
+                            entry_pos,
+                        ));
+                    } else {
+                        self.handle_flow_node(None)?;
+                    }
+                    let key_end_line = self.scanner.done_pos.line;
+                    self.skip_flow_separation()?;
+                    if self.scanner.peek_char() == Some(':') {
+                        // YAML 1.2.2 SPEC, 7.4.1. Flow Sequences:
+                        //      ... such a key is subject to the same
+                        //      restrictions as an implicit block mapping key.
+                        //      In particular, the key is restricted to a
+                        //      single line ...
+                        if entry_pos.line != self.scanner.next_pos.line
+                            || key_end_line != entry_pos.line
+                        {
+                            return Err(YamlError::new(
+                                ErrorKind::InvalidImplicitKey,
+                                "Implicit key of single pair mapping in flow \
+                                 sequence should be in single line with ':'"
+                                    .to_string(),
+                                entry_pos,
+                                self.scanner.next_pos,
+                            ));
+                        }
+                        self.scanner.next_char();
+                        self.insert_event(
+                            entry_index,
+                            YamlEvent::MapStart(None, None, entry_pos),
+                        );
+                        self.handle_flow_map_value(']')?;
+                        self.push_event(YamlEvent::MapEnd(
+                            self.scanner.done_pos,
+                        ));
+                    }
+                    if self.expect_flow_entry_end(']')? {
+                        break;
+                    }
+                }
+            }
+        }
+        self.scanner.next_char();
+        self.push_event(YamlEvent::SequenceEnd(self.scanner.done_pos));
+        self.flow_starts.pop();
+        self.pop_state();
+        Ok(())
     }
 }
 
@@ -133,23 +279,151 @@ mod test {
             vec![
                 YamlEvent::StreamStart,
                 YamlEvent::DocumentStart(false, YamlPosition::new(1, 1)),
-                YamlEvent::SequenceStart(None, YamlPosition::new(1, 1)),
+                YamlEvent::SequenceStart(None, None, YamlPosition::new(1, 1)),
                 YamlEvent::Scalar(
                     None,
+                    None,
+                    YamlScalarStyle::Plain,
                     "abc".to_string(),
                     YamlPosition::new(1, 5),
                     YamlPosition::new(1, 7)
                 ),
                 YamlEvent::Scalar(
                     None,
+                    None,
+                    YamlScalarStyle::Plain,
                     "def".to_string(),
                     YamlPosition::new(2, 5),
                     YamlPosition::new(2, 7)
                 ),
-                YamlEvent::SequenceEnd(YamlPosition::new(2, 8)),
-                YamlEvent::DocumentEnd(false, YamlPosition::new(2, 8)),
+                YamlEvent::SequenceEnd(YamlPosition::new(2, 7)),
+                YamlEvent::DocumentEnd(false, YamlPosition::new(2, 7)),
+                YamlEvent::StreamEnd,
+            ]
+        )
+    }
+
+    #[test]
+    fn test_zero_indented_sequence() {
+        let events =
+            YamlParser::parse_to_events("a:\n- b\n- c\nd:\n  e:\n  - f\n")
+                .unwrap();
+        assert_eq!(
+            events[4..9],
+            [
+                YamlEvent::SequenceStart(None, None, YamlPosition::new(2, 1)),
+                YamlEvent::Scalar(
+                    None,
+                    None,
+                    YamlScalarStyle::Plain,
+                    "b".to_string(),
+                    YamlPosition::new(2, 3),
+                    YamlPosition::new(2, 3)
+                ),
+                YamlEvent::Scalar(
+                    None,
+                    None,
+                    YamlScalarStyle::Plain,
+                    "c".to_string(),
+                    YamlPosition::new(3, 3),
+                    YamlPosition::new(3, 3)
+                ),
+                YamlEvent::SequenceEnd(YamlPosition::new(3, 3)),
+                YamlEvent::Scalar(
+                    None,
+                    None,
+                    YamlScalarStyle::Plain,
+                    "d".to_string(),
+                    YamlPosition::new(4, 1),
+                    YamlPosition::new(4, 1)
+                ),
+            ]
+        );
+        let value = crate::to_value("a:\n- b\n- c\nd:\n  e:\n  - f\n").unwrap();
+        let expected = crate::to_value("a: [b, c]\nd: {e: [f]}").unwrap();
+        assert_eq!(value.canonical_cmp(&expected), std::cmp::Ordering::Equal);
+    }
+
+    #[test]
+    fn test_compact_nested_collections() {
+        let value = crate::to_value(
+            "- name  : eth1\n  mtu: 1500\n- - a\n  - - b\n    - c\n",
+        )
+        .unwrap();
+        let expected =
+            crate::to_value("[{name: eth1, mtu: 1500}, [a, [b, c]]]").unwrap();
+        assert_eq!(value.canonical_cmp(&expected), std::cmp::Ordering::Equal);
+
+        // Document marker is only valid at the beginning of line
+        let value = crate::to_value("- - ...\n  - ---\n- a\n").unwrap();
+        let expected = crate::to_value("[['...', '---'], a]").unwrap();
+        assert_eq!(value.canonical_cmp(&expected), std::cmp::Ordering::Equal);
+
+        // Compact entry padded by more spaces
+        let value =
+            crate::to_value("-   a: 1\n    b: 2\n-   -   c\n    -   d\n")
+                .unwrap();
+        let expected = crate::to_value("[{a: 1, b: 2}, [c, d]]").unwrap();
+        assert_eq!(value.canonical_cmp(&expected), std::cmp::Ordering::Equal);
+
+        let e = YamlParser::parse_to_events("a: - b\n   - c\n").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidSequnceStartIndicator);
+        assert_eq!(e.start_pos(), YamlPosition::new(1, 4));
+    }
+
+    #[test]
+    fn test_flow_sequence_with_single_pair_map() {
+        assert_eq!(
+            YamlParser::parse_to_events("[a, \"b\":c]").unwrap(),
+            vec![
+                YamlEvent::StreamStart,
+                YamlEvent::DocumentStart(false, YamlPosition::new(1, 1)),
+                YamlEvent::SequenceStart(None, None, YamlPosition::new(1, 1)),
+                YamlEvent::Scalar(
+                    None,
+                    None,
+                    YamlScalarStyle::Plain,
+                    "a".to_string(),
+                    YamlPosition::new(1, 2),
+                    YamlPosition::new(1, 2)
+                ),
+                YamlEvent::MapStart(None, None, YamlPosition::new(1, 5)),
+                YamlEvent::Scalar(
+                    None,
+                    None,
+                    YamlScalarStyle::DoubleQuoted,
+                    "b".to_string(),
+                    YamlPosition::new(1, 5),
+                    YamlPosition::new(1, 7)
+                ),
+                YamlEvent::Scalar(
+                    None,
+                    None,
+                    YamlScalarStyle::Plain,
+                    "c".to_string(),
+                    YamlPosition::new(1, 9),
+                    YamlPosition::new(1, 9)
+                ),
+                YamlEvent::MapEnd(YamlPosition::new(1, 9)),
+                YamlEvent::SequenceEnd(YamlPosition::new(1, 10)),
+                YamlEvent::DocumentEnd(false, YamlPosition::new(1, 10)),
                 YamlEvent::StreamEnd,
             ]
         )
     }
+
+    #[test]
+    fn test_flow_sequence_invalid_entry() {
+        let e = YamlParser::parse_to_events("[a,,b]").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidFlowEntry);
+        assert_eq!(e.start_pos(), YamlPosition::new(1, 4));
+
+        let e = YamlParser::parse_to_events("[a, b\n").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::UnfinishedSequenceIndicator);
+        assert_eq!(e.start_pos(), YamlPosition::new(1, 1));
+
+        let e = YamlParser::parse_to_events("- [a, {b: c}, [d]\n").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::UnfinishedSequenceIndicator);
+        assert_eq!(e.start_pos(), YamlPosition::new(1, 3));
+    }
 }
@@ -5,38 +5,310 @@
 //      (https://github.com/serde-rs/serde-rs.github.io)
 // which is licensed under CC-BY-SA-4.0 license
 
-use std::fmt::Write;
+use std::{collections::HashMap, fmt::Write};
 
 use serde::{Serialize, ser};
 
-use crate::{ErrorKind, YamlError, YamlPosition, to_scalar_string};
+use crate::{
+    ErrorKind, YamlError, YamlEvent, YamlParser, YamlPosition,
+    YamlResolvedScalar, YamlScalarSchema, YamlScalarStyle, YamlValue,
+    YamlValueData, is_plain_non_string, json_ser::YamlJsonSerializer,
+    number::NUMBER_TOKEN, resolve_scalar, to_styled_scalar_string,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[non_exhaustive]
 pub struct YamlSerializeOption {
     /// Whether include `---\n` at the beginning. Default is false.
     pub leading_start_indicator: bool,
+    /// Whether include `...\n` at the end. Default is false.
+    pub trailing_end_indicator: bool,
+    /// Whether end the output with line break, which is expected by POSIX
+    /// tools and linters. Kept when output ends with block scalar as the
+    /// line break is part of its content. Default is true.
+    pub trailing_newline: bool,
     /// How many space should be used for each indent level. Default is 2.
     pub indent_count: usize,
     /// The max width of each line. 0 means no limit. Default is 80.
+    /// Longer string is wrapped at spaces as folded block scalar or
+    /// multi-line double quoted string. Sequences and maps are emitted in
+    /// block style(only empty ones as `[]` or `{}`), hence there is no flow
+    /// collection to wrap. Ignored when
+    /// [YamlSerializeOption::json_compatible] is enabled, which emits the
+    /// whole document as single flow line.
     pub max_width: usize,
+    /// Chomping indicator of literal block scalar used for multi-line
+    /// string. Default is [YamlBlockChomping::Auto].
+    pub block_scalar_chomping: YamlBlockChomping,
+    /// Whether always include indentation indicator(e.g. `|2`) in literal
+    /// block scalar. When false, the indentation indicator is only included
+    /// when first line of content starts with space. Default is false.
+    pub block_scalar_indent_indicator: bool,
+    /// Whether to fail on NaN and infinite float instead of emitting them as
+    /// `.nan`, `.inf` and `-.inf`, useful when output should be compatible
+    /// with JSON. Default is false.
+    pub reject_non_finite_float: bool,
+    /// Whether to emit JSON, which is also valid YAML in flow style. All
+    /// strings are double quoted, no tag is emitted and map keys must be
+    /// string. Other options except [YamlSerializeOption::trailing_newline]
+    /// are ignored when enabled. Default is false.
+    pub json_compatible: bool,
+    /// How enum variant with data is represented. Default is
+    /// [YamlVariantStyle::Tag].
+    pub variant_style: YamlVariantStyle,
+    /// Indentation of block sequence which is value of block mapping.
+    /// Default is [YamlSequenceIndent::Indented].
+    pub sequence_indent: YamlSequenceIndent,
+    /// Representation of null for map value, sequence entry and document
+    /// root. Default is [YamlNullStyle::Null].
+    pub null_style: YamlNullStyle,
+    /// Quoting of string value, map key is only quoted when required.
+    /// Ignored for string with original style, e.g. [YamlValue] parsed from
+    /// quoted scalar. Default is [YamlQuoteStyle::Plain].
+    pub prefer_quote: YamlQuoteStyle,
+    /// Letter case of bool. Default is [YamlBoolStyle::Lowercase].
+    pub bool_style: YamlBoolStyle,
+    /// Local tag emitted for struct and newtype struct indexed by the Rust
+    /// type name, e.g. `Secret` to `secret` for `!secret value`. Newtype
+    /// struct not listed is serialized as its inner value. Use
+    /// [crate::YamlDeserializer::resolve_tag()] for deserializing.
+    /// Default is empty.
+    pub type_tags: HashMap<String, String>,
+    /// Whether to parse the output of [to_string_with_opt()] back and fail
+    /// with [ErrorKind::RoundtripMismatch] when the structure, tags, types
+    /// or content of scalars differ from the serialized data. Ignored by
+    /// [YamlSerializer] and [YamlSerializeOption::json_compatible].
+    /// Default is false.
+    pub verify_roundtrip: bool,
 }
 
 impl Default for YamlSerializeOption {
     fn default() -> Self {
         Self {
             leading_start_indicator: false,
+            trailing_end_indicator: false,
+            trailing_newline: true,
             indent_count: 2,
             max_width: 80,
+            block_scalar_chomping: YamlBlockChomping::default(),
+            block_scalar_indent_indicator: false,
+            reject_non_finite_float: false,
+            json_compatible: false,
+            variant_style: YamlVariantStyle::default(),
+            sequence_indent: YamlSequenceIndent::default(),
+            null_style: YamlNullStyle::default(),
+            prefer_quote: YamlQuoteStyle::default(),
+            bool_style: YamlBoolStyle::default(),
+            type_tags: HashMap::new(),
+            verify_roundtrip: false,
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
-pub struct YamlSerializer {
+/// Representation of enum variant with data, unit variant is always emitted
+/// as plain string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum YamlVariantStyle {
+    /// Use variant name as tag, e.g. `!Variant value`.
+    #[default]
+    Tag,
+    /// Use single entry map, e.g. `Variant: value`, which is the style used
+    /// by serde_yaml.
+    Map,
+}
+
+/// Indentation of block sequence which is value of block mapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum YamlSequenceIndent {
+    /// Indent `- ` by `indent_count` from the key, e.g. `key:\n  - a`.
+    #[default]
+    Indented,
+    /// Place `- ` at the column of the key, e.g. `key:\n- a`.
+    Aligned,
+}
+
+/// Representation of null.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum YamlNullStyle {
+    /// `null`
+    #[default]
+    Null,
+    /// `~`
+    Tilde,
+    /// Nothing, e.g. `key:` and `-`.
+    Empty,
+}
+
+/// Quoting of string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum YamlQuoteStyle {
+    /// Plain scalar when possible, otherwise single quoted, or double quoted
+    /// when escape is required or string contains `'`.
+    #[default]
+    Plain,
+    /// Plain scalar when possible, otherwise single quoted unless escape is
+    /// required.
+    Single,
+    /// Plain scalar when possible, otherwise double quoted.
+    Double,
+    /// Every string is double quoted, including multi-line string.
+    Always,
+}
+
+/// Letter case of bool, all of them are bool in YAML 1.2 core schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum YamlBoolStyle {
+    /// `true` and `false`
+    #[default]
+    Lowercase,
+    /// `True` and `False`
+    Capitalized,
+    /// `TRUE` and `FALSE`
+    Uppercase,
+}
+
+/// Chomping indicator of literal block scalar.
+/// Multi-line string cannot be represented by desired chomping indicator will
+/// be emitted as double quoted string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum YamlBlockChomping {
+    /// Use `|-` for string without trailing line break, `|` for string with
+    /// single trailing line break, `|+` for string with multiple trailing
+    /// line breaks.
+    #[default]
+    Auto,
+    /// Always use `|-`.
+    Strip,
+    /// Always use `|`.
+    Clip,
+    /// Always use `|+`.
+    Keep,
+}
+
+/// Serializer emitting single YAML document, use [to_string_with_opt()]
+/// unless the output should be streamed into writer.
+#[derive(Debug, Default)]
+pub struct YamlSerializer<'a> {
     option: YamlSerializeOption,
+    // Pending output, completed lines except the last two are moved to
+    // writer if any.
     output: String,
+    writer: Option<YamlWriter<'a>>,
+    // Length of output already moved to writer.
+    written: usize,
     current_indent_level: usize,
+    // Output length after last block scalar emitted, used to prevent
+    // trailing empty lines of kept block scalar being removed.
+    block_scalar_end: usize,
+    // Style of next string, set by newtype struct of YamlScalarStyle.
+    // None for Rust string which should not be resolved as other type.
+    scalar_style: Option<YamlScalarStyle>,
+    // Block collections being emitted, innermost last.
+    containers: Vec<YamlContainer>,
+    // Whether the innermost collection has no entry emitted yet.
+    collection_pending: bool,
+    // What is emitted before the next node in current line.
+    line_state: YamlLineState,
+    // Whether any block sequence or mapping started, used to find out map
+    // key requiring `?` indicator.
+    has_collection: bool,
+    // Whether serializing map key, which is only quoted when required.
+    is_key: bool,
+    // Nodes expected to be parsed back from output, only recorded for
+    // YamlSerializeOption::verify_roundtrip.
+    recorded: Option<Vec<YamlEvent>>,
+    // Tag emitted for the next recorded node.
+    pending_tag: Option<String>,
+}
+
+// Block collection being emitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum YamlContainer {
+    // Whether entries increased the indent level, false for sequence
+    // aligned with its parent map key.
+    Seq(bool),
+    Map,
+}
+
+// Tracked explicitly instead of checking the tail of output, which could be
+// the content of scalar, e.g. an empty key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum YamlLineState {
+    // Nothing emitted in current line, the next node should be indented.
+    #[default]
+    LineStart,
+    // After `- ` of block sequence entry.
+    SeqEntry,
+    // After `: ` of block mapping entry.
+    MapValue,
+    // After node property, `? ` indicator or scalar.
+    Inline,
+}
+
+enum YamlWriter<'a> {
+    Fmt(Box<dyn std::fmt::Write + 'a>),
+    Io(Box<dyn std::io::Write + 'a>),
+}
+
+impl std::fmt::Debug for YamlWriter<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Fmt(_) => write!(f, "YamlWriter::Fmt"),
+            Self::Io(_) => write!(f, "YamlWriter::Io"),
+        }
+    }
+}
+
+impl YamlWriter<'_> {
+    fn write_str(&mut self, s: &str) -> Result<(), YamlError> {
+        match self {
+            Self::Fmt(writer) => writer.write_str(s)?,
+            Self::Io(writer) => writer.write_all(s.as_bytes())?,
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), YamlError> {
+        if let Self::Io(writer) = self {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+}
+
+fn validate_option(option: &YamlSerializeOption) -> Result<(), YamlError> {
+    if option.indent_count < 2 {
+        return Err(YamlError::new(
+            ErrorKind::IndentTooSmall,
+            "Minimum supported indent count is 2".to_string(),
+            YamlPosition::EOF,
+            YamlPosition::EOF,
+        ));
+    }
+    if let Some(tag) = option.type_tags.values().find(|tag| {
+        tag.is_empty()
+            || tag.starts_with('!')
+            || tag.contains(|c: char| {
+                c.is_whitespace() || crate::flow::FLOW_INDICATORS.contains(&c)
+            })
+    }) {
+        return Err(YamlError::new(
+            ErrorKind::InvalidNodeProperty,
+            format!(
+                "Invalid tag {tag:?}, expecting non-empty tag without leading \
+                 `!`, white space or flow indicator"
+            ),
+            YamlPosition::EOF,
+            YamlPosition::EOF,
+        ));
+    }
+    Ok(())
 }
 
 pub fn to_string_with_opt<T>(
@@ -46,31 +318,175 @@ pub fn to_string_with_opt<T>(
 where
     T: Serialize,
 {
-    if option.indent_count < 2 {
-        return Err(YamlError::new(
-            ErrorKind::IndentTooSmall,
-            "Minimum supported indent count is 2".to_string(),
+    validate_option(&option)?;
+    if option.json_compatible {
+        let mut serializer = YamlJsonSerializer::default();
+        value.serialize(&mut serializer)?;
+        if option.trailing_newline {
+            serializer.output.push('\n');
+        }
+        return Ok(serializer.output);
+    }
+    let mut output = String::new();
+    let verify_roundtrip = option.verify_roundtrip;
+    let mut serializer = YamlSerializer::new(&mut output, option)?;
+    if verify_roundtrip {
+        serializer.recorded = Some(Vec::new());
+    }
+    value.serialize(&mut serializer)?;
+    let recorded = serializer.recorded.take();
+    serializer.finish()?;
+    if let Some(recorded) = recorded {
+        verify_roundtrip_output(&output, recorded)?;
+    }
+    Ok(output)
+}
+
+// Compare the nodes parsed from output with the recorded ones.
+fn verify_roundtrip_output(
+    output: &str,
+    recorded: Vec<YamlEvent>,
+) -> Result<(), YamlError> {
+    let events = YamlParser::parse_to_events(output).map_err(|e| {
+        YamlError::new(
+            ErrorKind::RoundtripMismatch,
+            format!("Serialized output cannot be parsed back: {e}"),
+            e.start_pos(),
+            e.end_pos(),
+        )
+    })?;
+    let mut parsed: Vec<YamlEvent> = events
+        .into_iter()
+        .filter(|event| {
+            !matches!(
+                event,
+                YamlEvent::StreamStart
+                    | YamlEvent::StreamEnd
+                    | YamlEvent::DocumentStart(..)
+                    | YamlEvent::DocumentEnd(..)
+            )
+        })
+        .collect();
+    // Document of empty null has no node
+    if parsed.is_empty() {
+        parsed.push(YamlEvent::Scalar(
+            None,
+            None,
+            YamlScalarStyle::Plain,
+            String::new(),
             YamlPosition::EOF,
             YamlPosition::EOF,
         ));
     }
-    let mut serializer = YamlSerializer {
-        output: if option.leading_start_indicator {
-            "---\n".to_string()
-        } else {
-            String::new()
-        },
-        option,
-        ..Default::default()
-    };
-    value.serialize(&mut serializer)?;
-    if serializer.output.ends_with("\n\n") {
-        serializer.output.pop();
+    let mut parsed = parsed.into_iter();
+    for expected in recorded {
+        match parsed.next() {
+            Some(event) if is_same_node(&expected, &event) => (),
+            Some(event) => {
+                return Err(YamlError::new(
+                    ErrorKind::RoundtripMismatch,
+                    format!("Serialized {expected} is parsed back as {event}"),
+                    event.position(),
+                    event.end_position(),
+                ));
+            }
+            None => {
+                return Err(YamlError::new(
+                    ErrorKind::RoundtripMismatch,
+                    format!(
+                        "Serialized {expected} is missing when parsed back"
+                    ),
+                    YamlPosition::EOF,
+                    YamlPosition::EOF,
+                ));
+            }
+        }
+    }
+    if let Some(event) = parsed.next() {
+        return Err(YamlError::new(
+            ErrorKind::RoundtripMismatch,
+            format!("Unexpected {event} when parsing back serialized output"),
+            event.position(),
+            event.end_position(),
+        ));
+    }
+    Ok(())
+}
+
+// Scalars are compared by the type resolved from their style, and the
+// content when being string.
+fn is_same_node(expected: &YamlEvent, parsed: &YamlEvent) -> bool {
+    match (expected, parsed) {
+        (
+            YamlEvent::SequenceStart(_, tag, _),
+            YamlEvent::SequenceStart(_, parsed_tag, _),
+        )
+        | (
+            YamlEvent::MapStart(_, tag, _),
+            YamlEvent::MapStart(_, parsed_tag, _),
+        ) => tag == parsed_tag,
+        (YamlEvent::SequenceEnd(_), YamlEvent::SequenceEnd(_))
+        | (YamlEvent::MapEnd(_), YamlEvent::MapEnd(_)) => true,
+        (
+            YamlEvent::Scalar(_, tag, style, value, ..),
+            YamlEvent::Scalar(_, parsed_tag, parsed_style, parsed_value, ..),
+        ) => {
+            tag == parsed_tag
+                && match (
+                    resolve_event_scalar(tag.as_deref(), *style, value),
+                    resolve_event_scalar(
+                        parsed_tag.as_deref(),
+                        *parsed_style,
+                        parsed_value,
+                    ),
+                ) {
+                    (YamlResolvedScalar::Str, YamlResolvedScalar::Str) => {
+                        value == parsed_value
+                    }
+                    (resolved, parsed_resolved) => resolved == parsed_resolved,
+                }
+        }
+        _ => false,
     }
-    if !serializer.output.ends_with("\n") {
-        serializer.output.push('\n');
+}
+
+// Type of scalar event, the `!!str` tag forces string regardless of style.
+fn resolve_event_scalar(
+    tag: Option<&str>,
+    style: YamlScalarStyle,
+    value: &str,
+) -> YamlResolvedScalar {
+    if tag == Some("<tag:yaml.org,2002:str>") {
+        YamlResolvedScalar::Str
+    } else {
+        resolve_scalar(value, style, YamlScalarSchema::Core)
     }
-    Ok(serializer.output)
+}
+
+/// Emit each item as a separate document. Documents after the first one
+/// always start with `---`, the `...` is only emitted after the last
+/// document when [YamlSerializeOption::trailing_end_indicator] is set.
+pub fn to_string_multi<T>(
+    docs: &[T],
+    option: YamlSerializeOption,
+) -> Result<String, YamlError>
+where
+    T: Serialize,
+{
+    let mut ret = String::new();
+    for (i, doc) in docs.iter().enumerate() {
+        let mut doc_option = option.clone();
+        if i > 0 {
+            ret += "---\n";
+            doc_option.leading_start_indicator = false;
+        }
+        if i + 1 < docs.len() {
+            doc_option.trailing_end_indicator = false;
+            doc_option.trailing_newline = true;
+        }
+        ret += &to_string_with_opt(doc, doc_option)?;
+    }
+    Ok(ret)
 }
 
 pub fn to_string<T>(value: &T) -> Result<String, YamlError>
@@ -80,10 +496,147 @@ where
     to_string_with_opt(value, YamlSerializeOption::default())
 }
 
-impl YamlSerializer {
+/// Scalar is emitted in its original style when possible, tag is not
+/// emitted. Plain scalar of `!!str` looks like null, bool or number is
+/// quoted to stay string.
+impl Serialize for YamlValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        let data = match &self.data {
+            YamlValueData::Tag(tag) => &tag.data,
+            data => data,
+        };
+        match data {
+            YamlValueData::Null => serializer.serialize_unit(),
+            YamlValueData::String(s) => {
+                if self.style == YamlScalarStyle::Plain
+                    && self.core_tag_type() == Some("str")
+                    && is_plain_non_string(s)
+                {
+                    serializer.serialize_str(s)
+                } else if let Some(name) = self.style.newtype_name() {
+                    serializer.serialize_newtype_struct(name, s)
+                } else {
+                    serializer.serialize_str(s)
+                }
+            }
+            YamlValueData::Array(v) => serializer.collect_seq(v),
+            YamlValueData::Map(v) => serializer.collect_map(v.iter()),
+            YamlValueData::Tag(tag) => YamlValue {
+                data: tag.data.clone(),
+                start: self.start,
+                end: self.end,
+                style: self.style,
+            }
+            .serialize(serializer),
+        }
+    }
+}
+
+impl<'a> YamlSerializer<'a> {
+    /// Serializer writing YAML document into [std::fmt::Write], the output
+    /// is only complete after [YamlSerializer::finish()].
+    /// The [YamlSerializeOption::json_compatible] is not supported.
+    pub fn new<W>(
+        writer: W,
+        option: YamlSerializeOption,
+    ) -> Result<Self, YamlError>
+    where
+        W: std::fmt::Write + 'a,
+    {
+        Self::with_writer(YamlWriter::Fmt(Box::new(writer)), option)
+    }
+
+    /// Serializer writing YAML document into [std::io::Write], the output
+    /// is only complete after [YamlSerializer::finish()].
+    /// The [YamlSerializeOption::json_compatible] is not supported.
+    pub fn new_io<W>(
+        writer: W,
+        option: YamlSerializeOption,
+    ) -> Result<Self, YamlError>
+    where
+        W: std::io::Write + 'a,
+    {
+        Self::with_writer(YamlWriter::Io(Box::new(writer)), option)
+    }
+
+    fn with_writer(
+        writer: YamlWriter<'a>,
+        option: YamlSerializeOption,
+    ) -> Result<Self, YamlError> {
+        validate_option(&option)?;
+        if option.json_compatible {
+            return Err(YamlError::new(
+                ErrorKind::NotSupported,
+                "JSON compatible output is not supported by YamlSerializer, \
+                 please use to_string_with_opt()"
+                    .to_string(),
+                YamlPosition::EOF,
+                YamlPosition::EOF,
+            ));
+        }
+        Ok(Self {
+            output: if option.leading_start_indicator {
+                "---\n".to_string()
+            } else {
+                String::new()
+            },
+            writer: Some(writer),
+            option,
+            ..Default::default()
+        })
+    }
+
+    /// Finish the document and write all pending output to writer.
+    pub fn finish(mut self) -> Result<(), YamlError> {
+        if self.output.ends_with("\n\n")
+            && self.block_scalar_end != self.output_len()
+        {
+            self.output.pop();
+        }
+        if !self.output.ends_with("\n") {
+            self.output.push('\n');
+        }
+        // The line break of block scalar at the end is part of its content
+        let ends_with_block_scalar = self.block_scalar_end == self.output_len();
+        if self.option.trailing_end_indicator {
+            self.output += "...\n";
+        }
+        if !self.option.trailing_newline
+            && (self.option.trailing_end_indicator || !ends_with_block_scalar)
+        {
+            self.output.pop();
+        }
+        if let Some(writer) = self.writer.as_mut() {
+            writer.write_str(&self.output)?;
+            writer.flush()?;
+        }
+        Ok(())
+    }
+
+    // Move completed lines to writer, the last two lines are kept for
+    // checking the tail of output.
+    fn flush_lines(&mut self) -> Result<(), YamlError> {
+        if let Some(writer) = self.writer.as_mut()
+            && let Some(last) = self.output.rfind('\n')
+            && let Some(end) = self.output[..last].rfind('\n')
+            && end > 0
+        {
+            writer.write_str(&self.output[..end])?;
+            self.output.drain(..end);
+            self.written += end;
+        }
+        Ok(())
+    }
+
+    fn output_len(&self) -> usize {
+        self.written + self.output.len()
+    }
+
     fn get_indent_count(&self) -> usize {
-        if !self.output.ends_with("\n")
-            || self.output.ends_with("- ")
+        if self.line_state != YamlLineState::LineStart
             || self.current_indent_level == 0
         {
             0
@@ -95,9 +648,176 @@ impl YamlSerializer {
     pub(crate) fn get_indent(&self) -> String {
         " ".repeat(self.get_indent_count())
     }
+
+    // Emit indent if at line start and the scalar or node property, the
+    // property should be followed by space.
+    fn write_inline(&mut self, s: &str) {
+        write!(self.output, "{}{s}", self.get_indent()).ok();
+        self.line_state = YamlLineState::Inline;
+    }
+
+    // Emit empty plain scalar, e.g. `key:` and `-`.
+    fn write_empty(&mut self) {
+        // Remove the spaces after `key:`, `-` or tag
+        if self.line_state != YamlLineState::LineStart {
+            let len = self.output.trim_end_matches(' ').len();
+            self.output.truncate(len);
+        }
+        self.line_state = YamlLineState::Inline;
+    }
+
+    // Emit scalar not being string, e.g. bool, number and null.
+    fn write_plain(&mut self, s: &str) {
+        self.write_inline(s);
+        self.record_scalar(YamlScalarStyle::Plain, s);
+    }
+
+    // Emit node property of tag, the tag should not have leading `!`.
+    fn write_tag(&mut self, tag: &str) {
+        self.write_inline(&format!("!{tag} "));
+        if self.recorded.is_some() {
+            self.pending_tag = Some(tag.to_string());
+        }
+    }
+
+    // Record node for verifying round trip.
+    fn record(&mut self, event: YamlEvent) {
+        if let Some(recorded) = self.recorded.as_mut() {
+            recorded.push(event);
+        }
+    }
+
+    // The type of recorded scalar is resolved from its style.
+    fn record_scalar(&mut self, style: YamlScalarStyle, value: &str) {
+        if self.recorded.is_some() {
+            let tag = self.pending_tag.take();
+            self.record(YamlEvent::Scalar(
+                None,
+                tag,
+                style,
+                value.to_string(),
+                YamlPosition::EOF,
+                YamlPosition::EOF,
+            ));
+        }
+    }
+
+    // Finish current line if not yet.
+    fn end_line(&mut self) {
+        if !self.output.ends_with('\n') {
+            self.output.push('\n');
+        }
+        self.line_state = YamlLineState::LineStart;
+    }
+
+    // Nothing is emitted until the first entry, because empty collection
+    // is emitted as `[]` or `{}` in current line.
+    fn start_collection(&mut self, container: YamlContainer) {
+        if self.recorded.is_some() {
+            let tag = self.pending_tag.take();
+            self.record(if container == YamlContainer::Map {
+                YamlEvent::MapStart(None, tag, YamlPosition::EOF)
+            } else {
+                YamlEvent::SequenceStart(None, tag, YamlPosition::EOF)
+            });
+        }
+        self.containers.push(container);
+        self.collection_pending = true;
+        self.has_collection = true;
+    }
+
+    // Invoked before each entry. Block collection should start at new line
+    // after `key: ` or node property, the trailing space is removed.
+    fn start_entry(&mut self) {
+        if !self.collection_pending {
+            return;
+        }
+        self.collection_pending = false;
+        if matches!(
+            self.line_state,
+            YamlLineState::MapValue | YamlLineState::Inline
+        ) {
+            if self.output.ends_with(' ') {
+                self.output.pop();
+            }
+            self.end_line();
+        } else if self.line_state == YamlLineState::SeqEntry {
+            // Pad the `- ` indicator of compact nested collection to indent
+            // count, so that its entries are aligned with the first one.
+            self.output += &" ".repeat(self.option.indent_count - 2);
+        }
+        if self.containers.last() != Some(&YamlContainer::Seq(false)) {
+            self.current_indent_level += 1;
+        }
+    }
+
+    fn end_collection(&mut self) {
+        let container = self.containers.pop();
+        self.record(if container == Some(YamlContainer::Map) {
+            YamlEvent::MapEnd(YamlPosition::EOF)
+        } else {
+            YamlEvent::SequenceEnd(YamlPosition::EOF)
+        });
+        if self.collection_pending {
+            self.collection_pending = false;
+            self.write_inline(if container == Some(YamlContainer::Map) {
+                "{}"
+            } else {
+                "[]"
+            });
+        } else if container != Some(YamlContainer::Seq(false)) {
+            self.current_indent_level =
+                self.current_indent_level.saturating_sub(1);
+        }
+    }
+
+    // Emit the tag or the key of single entry map for variant with data,
+    // the data should be emitted as sequence or map afterwards.
+    fn serialize_type_tag(&mut self, name: &str) {
+        if let Some(tag) = self.option.type_tags.get(name).cloned() {
+            self.write_tag(&tag);
+        }
+    }
+
+    fn serialize_variant_name(
+        &mut self,
+        variant: &'static str,
+    ) -> Result<(), YamlError> {
+        match self.option.variant_style {
+            YamlVariantStyle::Tag => {
+                self.write_tag(variant);
+            }
+            YamlVariantStyle::Map => {
+                ser::Serializer::serialize_map(&mut *self, Some(1))?;
+                self.start_entry();
+                ser::Serializer::serialize_str(&mut *self, variant)?;
+                self.output += ": ";
+                self.line_state = YamlLineState::MapValue;
+            }
+        }
+        Ok(())
+    }
+
+    // Close the data of variant and the single entry map if any.
+    fn end_variant(&mut self) -> Result<(), YamlError> {
+        self.end_collection();
+        if self.option.variant_style == YamlVariantStyle::Map {
+            self.end_collection();
+        }
+        Ok(())
+    }
+
+    // Width of current line after indent emitted.
+    fn get_column(&self) -> usize {
+        let line = match self.output.rfind('\n') {
+            Some(i) => &self.output[i + 1..],
+            None => self.output.as_str(),
+        };
+        line.chars().count() + self.get_indent_count()
+    }
 }
 
-impl ser::Serializer for &mut YamlSerializer {
+impl ser::Serializer for &mut YamlSerializer<'_> {
     type Ok = ();
 
     type Error = YamlError;
@@ -118,13 +838,14 @@ impl ser::Serializer for &mut YamlSerializer {
     // of the primitive types of the data model and map it to JSON by appending
     // into the output string.
     fn serialize_bool(self, v: bool) -> Result<(), YamlError> {
-        write!(
-            self.output,
-            "{}{}",
-            self.get_indent(),
-            if v { "true" } else { "false" }
-        )
-        .ok();
+        self.write_plain(match (self.option.bool_style, v) {
+            (YamlBoolStyle::Capitalized, true) => "True",
+            (YamlBoolStyle::Capitalized, false) => "False",
+            (YamlBoolStyle::Uppercase, true) => "TRUE",
+            (YamlBoolStyle::Uppercase, false) => "FALSE",
+            (_, true) => "true",
+            (_, false) => "false",
+        });
         Ok(())
     }
 
@@ -141,7 +862,12 @@ impl ser::Serializer for &mut YamlSerializer {
     }
 
     fn serialize_i64(self, v: i64) -> Result<(), YamlError> {
-        write!(self.output, "{}{v}", self.get_indent()).ok();
+        self.write_plain(&v.to_string());
+        Ok(())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), YamlError> {
+        self.write_plain(&v.to_string());
         Ok(())
     }
 
@@ -158,17 +884,50 @@ impl ser::Serializer for &mut YamlSerializer {
     }
 
     fn serialize_u64(self, v: u64) -> Result<(), YamlError> {
-        write!(self.output, "{}{v}", self.get_indent()).ok();
+        self.write_plain(&v.to_string());
 
         Ok(())
     }
 
+    fn serialize_u128(self, v: u128) -> Result<(), YamlError> {
+        self.write_plain(&v.to_string());
+        Ok(())
+    }
+
     fn serialize_f32(self, v: f32) -> Result<(), YamlError> {
-        self.serialize_f64(f64::from(v))
+        if v.is_finite() {
+            // Shortest form of f32, e.g. `0.1` instead of
+            // `0.10000000149011612` of f64
+            self.write_plain(&format!("{v:?}"));
+            Ok(())
+        } else {
+            self.serialize_f64(f64::from(v))
+        }
     }
 
     fn serialize_f64(self, v: f64) -> Result<(), YamlError> {
-        write!(self.output, "{}{v}", self.get_indent()).ok();
+        if !v.is_finite() && self.option.reject_non_finite_float {
+            return Err(YamlError::new(
+                ErrorKind::InvalidNumber,
+                format!("Non-finite float {v} is not allowed"),
+                YamlPosition::EOF,
+                YamlPosition::EOF,
+            ));
+        }
+        // YAML 1.2.2 SPEC, 10.2.1.4. Floating Point
+        let v = if v.is_nan() {
+            ".nan".to_string()
+        } else if v == f64::INFINITY {
+            ".inf".to_string()
+        } else if v == f64::NEG_INFINITY {
+            "-.inf".to_string()
+        } else {
+            // Debug format is the shortest form parsed back to the same
+            // value and always resolved as float, e.g. `1.0`, `-0.0` and
+            // `1e300`.
+            format!("{v:?}")
+        };
+        self.write_plain(&v.to_string());
         Ok(())
     }
 
@@ -178,17 +937,39 @@ impl ser::Serializer for &mut YamlSerializer {
     }
 
     fn serialize_str(self, v: &str) -> Result<(), YamlError> {
-        write!(
-            self.output,
-            "{}{}",
-            self.get_indent(),
-            to_scalar_string(
-                self.current_indent_level * self.option.indent_count,
-                v,
-                self.option.max_width
-            )
-        )
-        .ok();
+        let style = self.scalar_style.take();
+        let scalar = to_styled_scalar_string(
+            self.get_column(),
+            self.current_indent_level * self.option.indent_count,
+            v,
+            style.unwrap_or_default(),
+            // YAML 1.2.2 SPEC, 7.3.3. Plain Style:
+            //      when used as implicit keys, plain scalars must not
+            //      contain the “[”, “]”, “{”, “}” and “,” characters.
+            // Rust string looks like null, bool or number is quoted to
+            // stay string. Empty map key or document is null.
+            (self.is_key && v.contains(['[', ']', '{', '}', ',']))
+                || (style.is_none() && is_plain_non_string(v))
+                || (v.is_empty()
+                    && style.is_none()
+                    && (self.is_key || !self.has_collection)),
+            if self.is_key {
+                YamlQuoteStyle::Plain
+            } else {
+                self.option.prefer_quote
+            },
+            &self.option,
+        );
+        if scalar.is_empty() {
+            self.write_empty();
+        } else {
+            self.write_inline(&scalar);
+        }
+        if self.output.ends_with('\n') {
+            self.block_scalar_end = self.output_len();
+        }
+        // Rust string is expected to stay string
+        self.record_scalar(style.unwrap_or(YamlScalarStyle::DoubleQuoted), v);
         Ok(())
     }
 
@@ -203,7 +984,15 @@ impl ser::Serializer for &mut YamlSerializer {
     }
 
     fn serialize_none(self) -> Result<(), YamlError> {
-        write!(self.output, "{}null", self.get_indent()).ok();
+        match (self.option.null_style, self.is_key) {
+            (YamlNullStyle::Tilde, _) => self.write_plain("~"),
+            (YamlNullStyle::Empty, false) => {
+                self.write_empty();
+                self.record_scalar(YamlScalarStyle::Plain, "");
+            }
+            // Empty implicit key would be lost without indentation
+            _ => self.write_plain("null"),
+        }
         Ok(())
     }
 
@@ -222,8 +1011,8 @@ impl ser::Serializer for &mut YamlSerializer {
         self,
         name: &'static str,
     ) -> Result<(), YamlError> {
-        write!(self.output, "{}!{name} null", self.get_indent()).ok();
-        Ok(())
+        self.write_tag(name);
+        self.serialize_none()
     }
 
     fn serialize_unit_variant(
@@ -243,39 +1032,50 @@ impl ser::Serializer for &mut YamlSerializer {
     where
         T: ?Sized + Serialize,
     {
-        writeln!(self.output, "{}!{name}", self.get_indent()).ok();
+        if let Some(style) = YamlScalarStyle::from_newtype_name(name) {
+            self.scalar_style = Some(style);
+            return value.serialize(self);
+        }
+        if name == NUMBER_TOKEN {
+            // Literal text of YamlNumber
+            self.scalar_style = Some(YamlScalarStyle::Plain);
+            return value.serialize(self);
+        }
+        self.serialize_type_tag(name);
         value.serialize(self)
     }
 
     fn serialize_newtype_variant<T>(
         self,
-        name: &'static str,
+        _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
         value: &T,
     ) -> Result<(), YamlError>
     where
         T: ?Sized + Serialize,
     {
-        writeln!(self.output, "{}!{name}", self.get_indent()).ok();
-        value.serialize(self)
+        match self.option.variant_style {
+            YamlVariantStyle::Tag => {
+                self.write_tag(variant);
+                value.serialize(self)
+            }
+            YamlVariantStyle::Map => {
+                let mut map = self.serialize_map(Some(1))?;
+                ser::SerializeMap::serialize_entry(&mut map, variant, value)?;
+                ser::SerializeMap::end(map)
+            }
+        }
     }
 
     fn serialize_seq(
         self,
         _len: Option<usize>,
     ) -> Result<Self::SerializeSeq, YamlError> {
-        if self.output.ends_with(": ") {
-            self.output.pop();
-        }
-
-        if !self.output.ends_with("\n")
-            && !self.output.is_empty()
-            && !self.output.ends_with("- ")
-        {
-            self.output.push('\n');
-        }
-        self.current_indent_level += 1;
+        let is_aligned = self.option.sequence_indent
+            == YamlSequenceIndent::Aligned
+            && self.line_state == YamlLineState::MapValue;
+        self.start_collection(YamlContainer::Seq(!is_aligned));
         Ok(self)
     }
 
@@ -300,10 +1100,11 @@ impl ser::Serializer for &mut YamlSerializer {
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
-        _len: usize,
+        variant: &'static str,
+        len: usize,
     ) -> Result<Self::SerializeTupleVariant, YamlError> {
-        todo!()
+        self.serialize_variant_name(variant)?;
+        self.serialize_seq(Some(len))
     }
 
     // Maps are represented in JSON as `{ K: V, K: V, ... }`.
@@ -311,11 +1112,7 @@ impl ser::Serializer for &mut YamlSerializer {
         self,
         _len: Option<usize>,
     ) -> Result<Self::SerializeMap, YamlError> {
-        if self.output.ends_with(": ") {
-            self.output.pop();
-            self.output += "\n";
-        }
-        self.current_indent_level += 1;
+        self.start_collection(YamlContainer::Map);
         Ok(self)
     }
 
@@ -326,9 +1123,10 @@ impl ser::Serializer for &mut YamlSerializer {
     // looking at the serialized data.
     fn serialize_struct(
         self,
-        _name: &'static str,
+        name: &'static str,
         len: usize,
     ) -> Result<Self::SerializeStruct, YamlError> {
+        self.serialize_type_tag(name);
         self.serialize_map(Some(len))
     }
 
@@ -336,14 +1134,13 @@ impl ser::Serializer for &mut YamlSerializer {
     // This is the externally tagged representation.
     fn serialize_struct_variant(
         self,
-        name: &'static str,
+        _name: &'static str,
         _variant_index: u32,
         variant: &'static str,
-        _len: usize,
+        len: usize,
     ) -> Result<Self::SerializeStructVariant, YamlError> {
-        write!(self.output, "{}!{}", self.get_indent(), name).ok();
-        variant.serialize(&mut *self)?;
-        Ok(self)
+        self.serialize_variant_name(variant)?;
+        self.serialize_map(Some(len))
     }
 }
 
@@ -354,7 +1151,7 @@ impl ser::Serializer for &mut YamlSerializer {
 //
 // This impl is SerializeSeq so these methods are called after `serialize_seq`
 // is called on the Serializer.
-impl ser::SerializeSeq for &mut YamlSerializer {
+impl ser::SerializeSeq for &mut YamlSerializer<'_> {
     type Ok = ();
     type Error = YamlError;
 
@@ -363,24 +1160,22 @@ impl ser::SerializeSeq for &mut YamlSerializer {
     where
         T: ?Sized + Serialize,
     {
+        self.start_entry();
         write!(self.output, "{}- ", self.get_indent()).ok();
+        self.line_state = YamlLineState::SeqEntry;
         value.serialize(&mut **self)?;
-        if !self.output.ends_with("\n") {
-            self.output.push('\n');
-        }
-        Ok(())
+        self.end_line();
+        self.flush_lines()
     }
 
     // Close the sequence.
     fn end(self) -> Result<(), YamlError> {
-        if self.current_indent_level > 0 {
-            self.current_indent_level -= 1;
-        }
+        self.end_collection();
         Ok(())
     }
 }
 
-impl ser::SerializeTuple for &mut YamlSerializer {
+impl ser::SerializeTuple for &mut YamlSerializer<'_> {
     type Ok = ();
     type Error = YamlError;
 
@@ -388,19 +1183,16 @@ impl ser::SerializeTuple for &mut YamlSerializer {
     where
         T: ?Sized + Serialize,
     {
-        write!(self.output, "{}- ", self.get_indent()).ok();
-        value.serialize(&mut **self)
+        ser::SerializeSeq::serialize_element(self, value)
     }
 
     fn end(self) -> Result<(), YamlError> {
-        if self.current_indent_level > 0 {
-            self.current_indent_level -= 1;
-        }
+        self.end_collection();
         Ok(())
     }
 }
 
-impl ser::SerializeTupleStruct for &mut YamlSerializer {
+impl ser::SerializeTupleStruct for &mut YamlSerializer<'_> {
     type Ok = ();
     type Error = YamlError;
 
@@ -408,44 +1200,97 @@ impl ser::SerializeTupleStruct for &mut YamlSerializer {
     where
         T: ?Sized + Serialize,
     {
-        write!(self.output, "{}- ", self.get_indent()).ok();
-        value.serialize(&mut **self)
+        ser::SerializeSeq::serialize_element(self, value)
     }
 
     fn end(self) -> Result<(), YamlError> {
-        if self.current_indent_level > 0 {
-            self.current_indent_level -= 1;
-        }
+        self.end_collection();
         Ok(())
     }
 }
 
-impl ser::SerializeTupleVariant for &mut YamlSerializer {
+impl ser::SerializeTupleVariant for &mut YamlSerializer<'_> {
     type Ok = ();
     type Error = YamlError;
 
-    fn serialize_field<T>(&mut self, _value: &T) -> Result<(), YamlError>
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), YamlError>
     where
         T: ?Sized + Serialize,
     {
-        todo!()
+        ser::SerializeSeq::serialize_element(self, value)
     }
 
     fn end(self) -> Result<(), YamlError> {
-        todo!()
+        self.end_variant()
     }
 }
 
-impl ser::SerializeMap for &mut YamlSerializer {
+impl ser::SerializeMap for &mut YamlSerializer<'_> {
     type Ok = ();
     type Error = YamlError;
 
+    // Key of collection or multiple lines is emitted as complex key:
+    //      ? - 1
+    //        - 2
+    //      : value
     fn serialize_key<T>(&mut self, key: &T) -> Result<(), YamlError>
     where
         T: ?Sized + Serialize,
     {
-        key.serialize(&mut **self)?;
-        self.output += ": ";
+        self.start_entry();
+        let mut key_serializer = YamlSerializer {
+            option: self.option.clone(),
+            is_key: true,
+            recorded: self.recorded.as_ref().map(|_| Vec::new()),
+            pending_tag: self.pending_tag.clone(),
+            ..Default::default()
+        };
+        key.serialize(&mut key_serializer)?;
+        let key_str = key_serializer
+            .output
+            .strip_suffix('\n')
+            .unwrap_or(key_serializer.output.as_str());
+        // Empty plain key(null) is only valid as explicit key
+        if !key_serializer.has_collection
+            && !key_str.is_empty()
+            && !key_str.contains('\n')
+        {
+            self.is_key = true;
+            let result = key.serialize(&mut **self);
+            self.is_key = false;
+            result?;
+            self.output += ": ";
+            self.line_state = YamlLineState::MapValue;
+            return Ok(());
+        }
+        let column = self.get_column();
+        let content_indent = " ".repeat(column + self.option.indent_count);
+        self.write_inline(&format!(
+            "?{}",
+            " ".repeat(self.option.indent_count - 1)
+        ));
+        if !key_serializer.has_collection {
+            // Multi-line scalar is indented as map value, so that the
+            // indentation indicator of block scalar is still valid.
+            key.serialize(&mut **self)?;
+            self.end_line();
+            write!(self.output, "{}: ", " ".repeat(column)).ok();
+            self.line_state = YamlLineState::MapValue;
+            return Ok(());
+        }
+        if let Some(recorded) = self.recorded.as_mut() {
+            recorded.append(&mut key_serializer.recorded.unwrap_or_default());
+            self.pending_tag = None;
+        }
+        for (index, line) in key_str.split('\n').enumerate() {
+            if index != 0 && !line.is_empty() {
+                self.output += &content_indent;
+            }
+            self.output += line;
+            self.output.push('\n');
+        }
+        write!(self.output, "{}: ", " ".repeat(column)).ok();
+        self.line_state = YamlLineState::MapValue;
         Ok(())
     }
 
@@ -454,23 +1299,19 @@ impl ser::SerializeMap for &mut YamlSerializer {
         T: ?Sized + Serialize,
     {
         value.serialize(&mut **self)?;
-        if !self.output.ends_with("\n") {
-            self.output.push('\n');
-        }
-        Ok(())
+        self.end_line();
+        self.flush_lines()
     }
 
     fn end(self) -> Result<(), YamlError> {
-        if self.current_indent_level > 0 {
-            self.current_indent_level -= 1;
-        }
+        self.end_collection();
         Ok(())
     }
 }
 
 // Structs are like maps in which the keys are constrained to be compile-time
 // constant strings.
-impl ser::SerializeStruct for &mut YamlSerializer {
+impl ser::SerializeStruct for &mut YamlSerializer<'_> {
     type Ok = ();
     type Error = YamlError;
 
@@ -482,47 +1323,118 @@ impl ser::SerializeStruct for &mut YamlSerializer {
     where
         T: ?Sized + Serialize,
     {
-        key.serialize(&mut **self)?;
+        self.start_entry();
+        self.is_key = true;
+        let result = key.serialize(&mut **self);
+        self.is_key = false;
+        result?;
         self.output += ": ";
+        self.line_state = YamlLineState::MapValue;
         value.serialize(&mut **self)?;
-        if !self.output.ends_with("\n") {
-            self.output += "\n";
-        }
-        Ok(())
+        self.end_line();
+        self.flush_lines()
     }
 
     fn end(self) -> Result<(), YamlError> {
-        if self.current_indent_level > 0 {
-            self.current_indent_level -= 1;
-        }
+        self.end_collection();
         Ok(())
     }
 }
 
-impl ser::SerializeStructVariant for &mut YamlSerializer {
+impl ser::SerializeStructVariant for &mut YamlSerializer<'_> {
     type Ok = ();
     type Error = YamlError;
 
     fn serialize_field<T>(
         &mut self,
-        _key: &'static str,
-        _value: &T,
+        key: &'static str,
+        value: &T,
     ) -> Result<(), YamlError>
     where
         T: ?Sized + Serialize,
     {
-        todo!()
+        ser::SerializeStruct::serialize_field(self, key, value)
     }
 
     fn end(self) -> Result<(), YamlError> {
-        todo!()
+        self.end_variant()
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::Deserialize;
+
     use super::*;
-    use crate::ErrorKind;
+    use crate::{ErrorKind, YamlDeserializer};
+
+    #[test]
+    fn test_type_tags() -> Result<(), YamlError> {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Secret(String);
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Meters(u32);
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Address {
+            ip: String,
+        }
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Config {
+            password: Secret,
+            mtu: Meters,
+            address: Address,
+        }
+
+        let config = Config {
+            password: Secret("abc".into()),
+            mtu: Meters(1500),
+            address: Address {
+                ip: "192.0.2.1".into(),
+            },
+        };
+        let opt = YamlSerializeOption {
+            type_tags: HashMap::from([
+                ("Secret".to_string(), "secret".to_string()),
+                ("Address".to_string(), "addr".to_string()),
+            ]),
+            ..Default::default()
+        };
+        let yaml = to_string_with_opt(&config, opt)?;
+        assert_eq!(
+            yaml,
+            "password: !secret abc\nmtu: 1500\naddress: !addr\n  ip: \
+             192.0.2.1\n"
+        );
+
+        let mut deserializer: YamlDeserializer = yaml.parse()?;
+        deserializer
+            .resolve_tag("secret", |v| Ok(v.as_str()?.to_uppercase().into()))?;
+        deserializer.resolve_tag("addr", Ok)?;
+        assert_eq!(
+            Config::deserialize(&mut deserializer)?,
+            Config {
+                password: Secret("ABC".into()),
+                ..config
+            }
+        );
+
+        let opt = YamlSerializeOption {
+            type_tags: HashMap::from([("Secret".into(), "a b".into())]),
+            ..Default::default()
+        };
+        assert_eq!(
+            to_string_with_opt(&Secret("abc".into()), opt)
+                .unwrap_err()
+                .kind(),
+            ErrorKind::InvalidNodeProperty
+        );
+        Ok(())
+    }
 
     #[test]
     fn test_indent_too_small() {
@@ -537,4 +1449,1119 @@ mod tests {
             assert_eq!(e.kind(), ErrorKind::IndentTooSmall);
         }
     }
+
+    #[test]
+    fn test_128_bit_integer() -> Result<(), YamlError> {
+        #[derive(
+            Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize,
+        )]
+        struct CounterTest {
+            rx_bytes: u128,
+            delta: i128,
+        }
+
+        let data = CounterTest {
+            rx_bytes: u128::MAX,
+            delta: i128::MIN + 1,
+        };
+        let yaml_str = to_string(&data)?;
+        assert_eq!(
+            yaml_str,
+            format!("rx_bytes: {}\ndelta: {}\n", u128::MAX, i128::MIN + 1)
+        );
+        assert_eq!(crate::from_str::<CounterTest>(&yaml_str)?, data);
+        Ok(())
+    }
+
+    #[test]
+    fn test_complex_map_key() -> Result<(), YamlError> {
+        #[derive(
+            Debug,
+            PartialEq,
+            Eq,
+            PartialOrd,
+            Ord,
+            serde::Serialize,
+            serde::Deserialize,
+        )]
+        struct KeyTest {
+            uint_a: u32,
+            str_b: String,
+        }
+
+        let data: BTreeMap<(u32, u32), String> =
+            [((1, 2), "a".to_string()), ((3, 4), "b".to_string())].into();
+        let yaml_str = to_string(&data)?;
+        assert_eq!(yaml_str, "? - 1\n  - 2\n: a\n? - 3\n  - 4\n: b\n");
+        assert_eq!(
+            crate::from_str::<BTreeMap<(u32, u32), String>>(&yaml_str)?,
+            data
+        );
+
+        let data: Vec<BTreeMap<KeyTest, u32>> = vec![
+            [(
+                KeyTest {
+                    uint_a: 1,
+                    str_b: "x".into(),
+                },
+                2,
+            )]
+            .into(),
+        ];
+        let yaml_str = to_string(&data)?;
+        assert_eq!(yaml_str, "- ? uint_a: 1\n    str_b: x\n  : 2\n");
+        assert_eq!(
+            crate::from_str::<Vec<BTreeMap<KeyTest, u32>>>(&yaml_str)?,
+            data
+        );
+
+        let data: BTreeMap<u32, bool> = [(1, true), (2, false)].into();
+        let yaml_str = to_string(&data)?;
+        assert_eq!(yaml_str, "1: true\n2: false\n");
+        assert_eq!(crate::from_str::<BTreeMap<u32, bool>>(&yaml_str)?, data);
+        Ok(())
+    }
+
+    #[test]
+    fn test_value_to_string_pretty() -> Result<(), YamlError> {
+        #[derive(
+            Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize,
+        )]
+        struct PrettyTest {
+            name: String,
+            ports: Vec<u16>,
+            desc: String,
+        }
+
+        let yaml_str =
+            "name: eth1\nports: [1, 2]\ndesc: |\n  first line\n  second line\n";
+        let value: crate::YamlValue = yaml_str.parse()?;
+        let data: PrettyTest = crate::from_str(yaml_str)?;
+        let opt = YamlSerializeOption {
+            indent_count: 4,
+            leading_start_indicator: true,
+            ..Default::default()
+        };
+
+        let output = value.to_string_pretty(opt.clone())?;
+        assert_eq!(output, to_string_with_opt(&data, opt)?);
+        assert_eq!(
+            output,
+            "---\nname: eth1\nports:\n    - 1\n    - 2\ndesc: |\n    first \
+             line\n    second line\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_null_style() -> Result<(), YamlError> {
+        #[derive(
+            Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize,
+        )]
+        struct NullTest {
+            opt_a: Option<u32>,
+            list_b: Vec<Option<u32>>,
+        }
+
+        let data = NullTest {
+            opt_a: None,
+            list_b: vec![None, Some(1)],
+        };
+        for (null_style, expected) in [
+            (
+                YamlNullStyle::Null,
+                "opt_a: null\nlist_b:\n  - null\n  - 1\n",
+            ),
+            (YamlNullStyle::Tilde, "opt_a: ~\nlist_b:\n  - ~\n  - 1\n"),
+            (YamlNullStyle::Empty, "opt_a:\nlist_b:\n  -\n  - 1\n"),
+        ] {
+            let opt = YamlSerializeOption {
+                null_style,
+                ..Default::default()
+            };
+            let yaml_str = to_string_with_opt(&data, opt.clone())?;
+            assert_eq!(yaml_str, expected);
+            assert_eq!(crate::from_str::<NullTest>(&yaml_str)?, data);
+
+            let yaml_str = to_string_with_opt(&Option::<u32>::None, opt)?;
+            assert_eq!(crate::from_str::<Option<u32>>(&yaml_str)?, None);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_collections() -> Result<(), YamlError> {
+        #[derive(Debug, PartialEq, Default, Serialize, Deserialize)]
+        struct Empty {}
+
+        #[derive(Debug, PartialEq, Default, Serialize, Deserialize)]
+        struct EmptyTest {
+            list: Vec<u8>,
+            map: BTreeMap<String, u8>,
+            nested: Vec<Vec<u8>>,
+            empty: Empty,
+            empties: Vec<Empty>,
+        }
+
+        assert_eq!(to_string(&Vec::<u8>::new())?, "[]\n");
+        assert_eq!(to_string(&BTreeMap::<u8, u8>::new())?, "{}\n");
+        assert_eq!(to_string(&Empty {})?, "{}\n");
+        assert_eq!(crate::from_str::<Vec<u8>>("[]\n")?, Vec::<u8>::new());
+        assert_eq!(crate::from_str::<Empty>("{}\n")?, Empty {});
+
+        let data = EmptyTest {
+            nested: vec![vec![], vec![1]],
+            empties: vec![Empty {}],
+            ..Default::default()
+        };
+        let yaml_str = to_string(&data)?;
+        assert_eq!(
+            yaml_str,
+            "list: []\nmap: {}\nnested:\n  - []\n  - - 1\nempty: \
+             {}\nempties:\n  - {}\n"
+        );
+        assert_eq!(crate::from_str::<EmptyTest>(&yaml_str)?, data);
+
+        let opt = YamlSerializeOption {
+            variant_style: YamlVariantStyle::Map,
+            ..Default::default()
+        };
+        #[derive(Serialize)]
+        enum EmptyVariant {
+            Tuple(Vec<u8>),
+        }
+        assert_eq!(
+            to_string_with_opt(&EmptyVariant::Tuple(Vec::new()), opt)?,
+            "Tuple: []\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_nested_sequence_indent() -> Result<(), YamlError> {
+        type Nested = BTreeMap<String, Vec<Vec<BTreeMap<String, Vec<u8>>>>>;
+
+        let data: Nested = [(
+            "k".to_string(),
+            vec![
+                vec![
+                    [("a".to_string(), vec![1, 2]), ("b".to_string(), vec![])]
+                        .into(),
+                ],
+                vec![],
+            ],
+        )]
+        .into();
+        let deep = vec![vec![vec![vec![1u8], vec![]], vec![vec![2, 3]]]];
+
+        for (indent_count, sequence_indent, expected, deep_expected) in [
+            (
+                2,
+                YamlSequenceIndent::Indented,
+                "k:\n  - - a:\n        - 1\n        - 2\n      b: []\n  - []\n",
+                "- - - - 1\n    - []\n  - - - 2\n      - 3\n",
+            ),
+            (
+                4,
+                YamlSequenceIndent::Indented,
+                "k:\n    -   -   a:\n                - 1\n                - \
+                 2\n            b: []\n    - []\n",
+                "-   -   -   - 1\n        - []\n    -   -   - 2\n            \
+                 - 3\n",
+            ),
+            (
+                4,
+                YamlSequenceIndent::Aligned,
+                "k:\n-   -   a:\n        - 1\n        - 2\n        b: []\n- \
+                 []\n",
+                "-   -   -   - 1\n        - []\n    -   -   - 2\n            \
+                 - 3\n",
+            ),
+        ] {
+            let opt = YamlSerializeOption {
+                indent_count,
+                sequence_indent,
+                ..Default::default()
+            };
+            let yaml_str = to_string_with_opt(&data, opt.clone())?;
+            assert_eq!(yaml_str, expected);
+            assert_eq!(crate::from_str::<Nested>(&yaml_str)?, data);
+
+            let yaml_str = to_string_with_opt(&deep, opt)?;
+            assert_eq!(yaml_str, deep_expected);
+            assert_eq!(
+                crate::from_str::<Vec<Vec<Vec<Vec<u8>>>>>(&yaml_str)?,
+                deep
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_scalar_keys() -> Result<(), YamlError> {
+        type Data = BTreeMap<String, BTreeMap<Option<bool>, Vec<u8>>>;
+        let inner: BTreeMap<Option<bool>, Vec<u8>> =
+            [(None, vec![1]), (Some(true), vec![2, 3])].into();
+        let data: Data =
+            [(String::new(), inner.clone()), ("a- ".to_string(), inner)].into();
+        let opt = YamlSerializeOption {
+            null_style: YamlNullStyle::Empty,
+            ..Default::default()
+        };
+        let yaml_str = to_string_with_opt(&data, opt)?;
+        assert_eq!(
+            yaml_str,
+            "'':\n  null:\n    - 1\n  true:\n    - 2\n    - 3\n'a- ':\n  \
+             null:\n    - 1\n  true:\n    - 2\n    - 3\n"
+        );
+        assert_eq!(crate::from_str::<Data>(&yaml_str)?, data);
+        Ok(())
+    }
+
+    #[test]
+    fn test_adversarial_string_roundtrip() -> Result<(), YamlError> {
+        let strings = [
+            "",
+            " ",
+            "a: b",
+            "a:",
+            ": a",
+            "- a",
+            "-",
+            "? a",
+            "a #b",
+            "#a",
+            "---",
+            "...",
+            "--- a",
+            "... a",
+            "[a]",
+            "{a: b}",
+            "a,b",
+            "!a",
+            "&a",
+            "*a",
+            "|",
+            ">",
+            "'a",
+            "\"a",
+            "%a",
+            "@a",
+            "`a",
+            " a",
+            "a ",
+            "a\n",
+            "\n",
+            "a\nb",
+            "a: b\nc: d",
+            "\t",
+            "null",
+            "Null",
+            "NULL",
+            "~",
+            "true",
+            "True",
+            "FALSE",
+            "0",
+            "-1",
+            "+1",
+            "0x10",
+            "0o7",
+            "1_000",
+            "1.5",
+            "1e3",
+            "-.inf",
+            ".NaN",
+        ];
+        for s in strings {
+            let yaml_str = to_string(&s)?;
+            assert_eq!(crate::from_str::<String>(&yaml_str)?, s, "{yaml_str}");
+            let value: YamlValue = yaml_str.parse()?;
+            assert_eq!(value.data, YamlValueData::String(s.to_string()));
+            assert!(
+                value.style != YamlScalarStyle::Plain
+                    || !is_plain_non_string(s),
+                "{s:?} is emitted as {yaml_str:?}"
+            );
+
+            let data: BTreeMap<String, Vec<Option<String>>> =
+                [(s.to_string(), vec![Some(s.to_string()), None])].into();
+            let yaml_str = to_string(&data)?;
+            assert_eq!(crate::from_str::<BTreeMap<_, _>>(&yaml_str)?, data);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_prefer_quote() -> Result<(), YamlError> {
+        let data: BTreeMap<&str, Vec<&str>> = BTreeMap::from([
+            ("a key", vec!["abc", "a: b", "- c", "it's", "x\ny"]),
+            ("b", vec!["#d", "e #f", " g", "tab\t", "'h"]),
+        ]);
+        for (prefer_quote, expected) in
+            [
+                (
+                    YamlQuoteStyle::Plain,
+                    "a key:\n  - abc\n  - 'a: b'\n  - '- c'\n  - it's\n  - \
+                     |-\n    x\n    y\nb:\n  - '#d'\n  - 'e #f'\n  - ' g'\n  \
+                     - \"tab\\t\"\n  - \"'h\"\n",
+                ),
+                (
+                    YamlQuoteStyle::Single,
+                    "a key:\n  - abc\n  - 'a: b'\n  - '- c'\n  - it's\n  - \
+                     |-\n    x\n    y\nb:\n  - '#d'\n  - 'e #f'\n  - ' g'\n  \
+                     - \"tab\\t\"\n  - '''h'\n",
+                ),
+                (
+                    YamlQuoteStyle::Double,
+                    "a key:\n  - abc\n  - \"a: b\"\n  - \"- c\"\n  - it's\n  \
+                     - |-\n    x\n    y\nb:\n  - \"#d\"\n  - \"e #f\"\n  - \" \
+                     g\"\n  - \"tab\\t\"\n  - \"'h\"\n",
+                ),
+                (
+                    YamlQuoteStyle::Always,
+                    "a key:\n  - \"abc\"\n  - \"a: b\"\n  - \"- c\"\n  - \
+                     \"it's\"\n  - \"x\\ny\"\nb:\n  - \"#d\"\n  - \"e #f\"\n  \
+                     - \" g\"\n  - \"tab\\t\"\n  - \"'h\"\n",
+                ),
+            ]
+        {
+            let yaml_str = to_string_with_opt(
+                &data,
+                YamlSerializeOption {
+                    prefer_quote,
+                    ..Default::default()
+                },
+            )?;
+            assert_eq!(yaml_str, expected);
+            assert_eq!(
+                crate::from_str::<BTreeMap<String, Vec<String>>>(&yaml_str)?,
+                data.iter()
+                    .map(|(k, v)| (
+                        k.to_string(),
+                        v.iter().map(|s| s.to_string()).collect()
+                    ))
+                    .collect::<BTreeMap<String, Vec<String>>>()
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_bool_style() -> Result<(), YamlError> {
+        let data = vec![true, false];
+        for (bool_style, expected) in [
+            (YamlBoolStyle::Lowercase, "- true\n- false\n"),
+            (YamlBoolStyle::Capitalized, "- True\n- False\n"),
+            (YamlBoolStyle::Uppercase, "- TRUE\n- FALSE\n"),
+        ] {
+            let yaml_str = to_string_with_opt(
+                &data,
+                YamlSerializeOption {
+                    bool_style,
+                    ..Default::default()
+                },
+            )?;
+            assert_eq!(yaml_str, expected);
+            assert_eq!(crate::from_str::<Vec<bool>>(&yaml_str)?, data);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_non_finite_float() -> Result<(), YamlError> {
+        let data = vec![f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 1.5];
+        let yaml_str = to_string(&data)?;
+        assert_eq!(yaml_str, "- .nan\n- .inf\n- -.inf\n- 1.5\n");
+
+        let parsed: Vec<f64> = crate::from_str(&yaml_str)?;
+        assert!(parsed[0].is_nan());
+        assert_eq!(parsed[1..], data[1..]);
+
+        let opt = YamlSerializeOption {
+            reject_non_finite_float: true,
+            ..Default::default()
+        };
+        let e = to_string_with_opt(&data, opt.clone()).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidNumber);
+        assert_eq!(to_string_with_opt(&vec![1.5f32], opt)?, "- 1.5\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_float_round_trip() -> Result<(), YamlError> {
+        let data = vec![
+            1.0,
+            -0.0,
+            0.1,
+            1e300,
+            -1e-300,
+            f64::MAX,
+            f64::MIN_POSITIVE,
+            5e-324,
+            123456789.123,
+        ];
+        let yaml_str = to_string(&data)?;
+        assert!(yaml_str.starts_with("- 1.0\n- -0.0\n- 0.1\n- 1e300\n"));
+
+        let parsed: Vec<f64> = crate::from_str(&yaml_str)?;
+        assert_eq!(
+            parsed.iter().map(|v| v.to_bits()).collect::<Vec<u64>>(),
+            data.iter().map(|v| v.to_bits()).collect::<Vec<u64>>()
+        );
+
+        // Not resolved as integer
+        let value: crate::YamlValue = yaml_str.parse()?;
+        if let crate::YamlValueData::Array(items) = value.data {
+            assert!(items.iter().all(|v| !v.is_integer()));
+        }
+
+        assert_eq!(to_string(&vec![0.1f32, 3f32])?, "- 0.1\n- 3.0\n");
+        Ok(())
+    }
+
+    #[derive(Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+    struct BlockScalarTest {
+        strip: String,
+        clip: String,
+        keep: String,
+        leading_space: Vec<String>,
+    }
+
+    fn gen_block_scalar_test() -> BlockScalarTest {
+        BlockScalarTest {
+            strip: "abc\ndef".into(),
+            clip: "abc\ndef\n".into(),
+            keep: "abc\n\n".into(),
+            leading_space: vec![" abc\ndef".into()],
+        }
+    }
+
+    #[test]
+    fn test_literal_block_scalar_auto_chomping() -> Result<(), YamlError> {
+        let data = gen_block_scalar_test();
+        let yaml_str = to_string(&data)?;
+
+        assert_eq!(
+            yaml_str,
+            r#"strip: |-
+  abc
+  def
+clip: |
+  abc
+  def
+keep: |+
+  abc
+
+leading_space:
+  - |2-
+     abc
+    def
+"#
+        );
+        assert_eq!(crate::from_str::<BlockScalarTest>(&yaml_str)?, data);
+        Ok(())
+    }
+
+    #[test]
+    fn test_literal_block_scalar_fixed_chomping() -> Result<(), YamlError> {
+        let data = gen_block_scalar_test();
+        let yaml_str = to_string_with_opt(
+            &data,
+            YamlSerializeOption {
+                block_scalar_chomping: YamlBlockChomping::Strip,
+                block_scalar_indent_indicator: true,
+                ..Default::default()
+            },
+        )?;
+
+        assert_eq!(
+            yaml_str,
+            r#"strip: |2-
+  abc
+  def
+clip: "abc\ndef\n"
+keep: "abc\n\n"
+leading_space:
+  - |2-
+     abc
+    def
+"#
+        );
+        assert_eq!(crate::from_str::<BlockScalarTest>(&yaml_str)?, data);
+        Ok(())
+    }
+
+    #[test]
+    fn test_literal_block_scalar_keep_at_end() -> Result<(), YamlError> {
+        let yaml_str = to_string_with_opt(
+            &"abc\n\n",
+            YamlSerializeOption {
+                block_scalar_chomping: YamlBlockChomping::Keep,
+                indent_count: 4,
+                ..Default::default()
+            },
+        )?;
+
+        assert_eq!(yaml_str, "|+\n    abc\n\n");
+        assert_eq!(crate::from_str::<String>(&yaml_str)?, "abc\n\n");
+        Ok(())
+    }
+
+    #[derive(Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+    struct LongLineTest {
+        plain: String,
+        multi_line: Vec<String>,
+        escaped: String,
+        unbreakable: String,
+    }
+
+    #[test]
+    fn test_long_line_wrapping() -> Result<(), YamlError> {
+        let text = "The quick brown fox jumps over the lazy dog";
+        let data = LongLineTest {
+            plain: text.into(),
+            multi_line: vec![format!("{text}\n\n  indented\n{text}\n")],
+            escaped: format!("{text}\u{7}"),
+            unbreakable: "x".repeat(30),
+        };
+        let yaml_str = to_string_with_opt(
+            &data,
+            YamlSerializeOption {
+                max_width: 24,
+                ..Default::default()
+            },
+        )?;
+
+        assert_eq!(
+            yaml_str,
+            r#"plain: >-
+  The quick brown fox
+  jumps over the lazy
+  dog
+multi_line:
+  - >
+    The quick brown fox
+    jumps over the lazy
+    dog
+
+      indented
+    The quick brown fox
+    jumps over the lazy
+    dog
+escaped: "The quick
+  brown fox jumps over
+  the lazy dog\a"
+unbreakable: "xxxxxxxxxxxxxxxxxxxxxxxxxxxxxx"
+"#
+        );
+        for line in yaml_str.lines() {
+            if !line.contains("xxx") {
+                assert!(line.len() <= 24);
+            }
+        }
+        assert_eq!(crate::from_str::<LongLineTest>(&yaml_str)?, data);
+        Ok(())
+    }
+
+    #[test]
+    fn test_long_collection_in_block_style() -> Result<(), YamlError> {
+        let data: HashMap<String, Vec<String>> = HashMap::from([(
+            "a".to_string(),
+            (0..20).map(|i| format!("item{i}")).collect(),
+        )]);
+        let yaml_str = to_string_with_opt(
+            &data,
+            YamlSerializeOption {
+                max_width: 24,
+                ..Default::default()
+            },
+        )?;
+
+        assert!(yaml_str.starts_with("a:\n  - item0\n  - item1\n"));
+        assert!(yaml_str.lines().all(|l| l.len() <= 24));
+        assert_eq!(
+            crate::from_str::<HashMap<String, Vec<String>>>(&yaml_str)?,
+            data
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_wrapping_when_max_width_is_zero() -> Result<(), YamlError> {
+        let text = "The quick brown fox jumps over the lazy dog";
+        let yaml_str = to_string_with_opt(
+            &text,
+            YamlSerializeOption {
+                max_width: 0,
+                ..Default::default()
+            },
+        )?;
+
+        assert_eq!(yaml_str, format!("{text}\n"));
+        Ok(())
+    }
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    enum JsonTestEnum {
+        Unit,
+        Newtype(u32),
+        Struct { a: bool },
+    }
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct JsonTest {
+        text: String,
+        num: Option<f64>,
+        list: Vec<JsonTestEnum>,
+    }
+
+    #[test]
+    fn test_json_compatible() -> Result<(), YamlError> {
+        let opt = YamlSerializeOption {
+            json_compatible: true,
+            ..Default::default()
+        };
+        let data = JsonTest {
+            text: "a: \"b\"\n\u{7}".into(),
+            num: None,
+            list: vec![
+                JsonTestEnum::Unit,
+                JsonTestEnum::Newtype(1),
+                JsonTestEnum::Struct { a: true },
+            ],
+        };
+        let yaml_str = to_string_with_opt(&data, opt.clone())?;
+        assert_eq!(
+            yaml_str,
+            "{\"text\": \"a: \\\"b\\\"\\n\\u0007\", \"num\": null, \"list\": \
+             [\"Unit\", {\"Newtype\": 1}, {\"Struct\": {\"a\": true}}]}\n"
+        );
+        assert_eq!(crate::from_str::<JsonTest>(&yaml_str)?, data);
+
+        // max_width is ignored
+        let narrow = YamlSerializeOption {
+            max_width: 10,
+            ..opt.clone()
+        };
+        assert_eq!(to_string_with_opt(&data, narrow)?, yaml_str);
+
+        let mut map = BTreeMap::new();
+        map.insert(1u8, "a");
+        let e = to_string_with_opt(&map, opt.clone()).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::NonStringMapKey);
+        let e = to_string_with_opt(&f64::NAN, opt).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidNumber);
+        Ok(())
+    }
+
+    #[test]
+    fn test_yaml_value_keep_empty_plain_scalar() -> Result<(), YamlError> {
+        for yaml_str in ["a:\nb: ~\n", "-\n- x\n"] {
+            let value: YamlValue = yaml_str.parse()?;
+            assert_eq!(to_string(&value)?, yaml_str);
+        }
+        for (yaml_str, expected) in
+            [("a: \n", "a:\n"), ("- \n- x\n", "-\n- x\n")]
+        {
+            let value: YamlValue = yaml_str.parse()?;
+            for prefer_quote in [
+                YamlQuoteStyle::Plain,
+                YamlQuoteStyle::Single,
+                YamlQuoteStyle::Double,
+            ] {
+                let output = to_string_with_opt(
+                    &value,
+                    YamlSerializeOption {
+                        prefer_quote,
+                        verify_roundtrip: true,
+                        ..Default::default()
+                    },
+                )?;
+                assert_eq!(output, expected);
+                let parsed: YamlValue = output.parse()?;
+                assert!(parsed.canonical_cmp(&value).is_eq());
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_yaml_value_str_tag_stay_string() -> Result<(), YamlError> {
+        for (yaml_str, expected) in [
+            ("a: !!str 5\n", "a: '5'\n"),
+            ("a: !!str true\n", "a: 'true'\n"),
+            ("a: !!str ~\n", "a: '~'\n"),
+            ("a: !!str\n", "a: ''\n"),
+            ("a: !!str b\n", "a: b\n"),
+        ] {
+            let value: YamlValue = yaml_str.parse()?;
+            let output = to_string_with_opt(
+                &value,
+                YamlSerializeOption {
+                    verify_roundtrip: true,
+                    ..Default::default()
+                },
+            )?;
+            assert_eq!(output, expected);
+            let parsed: YamlValue = output.parse()?;
+            let YamlValueData::Map(map) = &parsed.data else {
+                panic!("Expecting map, got {parsed:?}");
+            };
+            let value = map.get("a").unwrap();
+            assert_eq!(
+                resolve_scalar(
+                    value.as_str()?,
+                    value.style,
+                    YamlScalarSchema::Core
+                ),
+                YamlResolvedScalar::Str
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_roundtrip_str_tag() {
+        let recorded = vec![YamlEvent::Scalar(
+            None,
+            Some("<tag:yaml.org,2002:str>".into()),
+            YamlScalarStyle::Plain,
+            "5".into(),
+            YamlPosition::EOF,
+            YamlPosition::EOF,
+        )];
+        assert_eq!(
+            verify_roundtrip_output("5\n", recorded).unwrap_err().kind(),
+            ErrorKind::RoundtripMismatch
+        );
+    }
+
+    #[test]
+    fn test_yaml_value_keep_scalar_style() -> Result<(), YamlError> {
+        let yaml_str = r#"a: plain
+b: 'single ''quoted'''
+c: "double\tquoted"
+d: |
+  literal
+  block
+e: >-
+  folded
+f:
+  - 1
+  - null
+"#;
+        let value: YamlValue = yaml_str.parse()?;
+        assert_eq!(to_string(&value)?, yaml_str);
+        Ok(())
+    }
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    enum VariantTestEnum {
+        Unit,
+        Newtype(u32),
+        Tuple(u32, String),
+        Struct { a: u32, b: Vec<u32> },
+    }
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct VariantTest {
+        one: VariantTestEnum,
+        list: Vec<VariantTestEnum>,
+    }
+
+    fn variant_test_data() -> VariantTest {
+        VariantTest {
+            one: VariantTestEnum::Newtype(1),
+            list: vec![
+                VariantTestEnum::Unit,
+                VariantTestEnum::Newtype(2),
+                VariantTestEnum::Tuple(3, "c".into()),
+                VariantTestEnum::Struct { a: 4, b: vec![5] },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_variant_style_tag() -> Result<(), YamlError> {
+        let data = variant_test_data();
+        let yaml_str = to_string(&data)?;
+        assert_eq!(
+            yaml_str,
+            r#"one: !Newtype 1
+list:
+  - Unit
+  - !Newtype 2
+  - !Tuple
+    - 3
+    - c
+  - !Struct
+    a: 4
+    b:
+      - 5
+"#
+        );
+        assert_eq!(crate::from_str::<VariantTest>(&yaml_str)?, data);
+        Ok(())
+    }
+
+    #[test]
+    fn test_variant_style_map() -> Result<(), YamlError> {
+        let data = variant_test_data();
+        let yaml_str = to_string_with_opt(
+            &data,
+            YamlSerializeOption {
+                variant_style: YamlVariantStyle::Map,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(
+            yaml_str,
+            r#"one:
+  Newtype: 1
+list:
+  - Unit
+  - Newtype: 2
+  - Tuple:
+      - 3
+      - c
+  - Struct:
+      a: 4
+      b:
+        - 5
+"#
+        );
+        assert_eq!(crate::from_str::<VariantTest>(&yaml_str)?, data);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sequence_indent_aligned() -> Result<(), YamlError> {
+        let data = variant_test_data();
+        let yaml_str = to_string_with_opt(
+            &data,
+            YamlSerializeOption {
+                variant_style: YamlVariantStyle::Map,
+                sequence_indent: YamlSequenceIndent::Aligned,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(
+            yaml_str,
+            r#"one:
+  Newtype: 1
+list:
+- Unit
+- Newtype: 2
+- Tuple:
+  - 3
+  - c
+- Struct:
+    a: 4
+    b:
+    - 5
+"#
+        );
+        assert_eq!(crate::from_str::<VariantTest>(&yaml_str)?, data);
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_string_multi() -> Result<(), YamlError> {
+        let docs = vec![
+            VariantTestEnum::Newtype(1),
+            VariantTestEnum::Struct { a: 2, b: vec![3] },
+            VariantTestEnum::Unit,
+        ];
+        assert_eq!(
+            crate::to_string_multi(
+                &docs,
+                YamlSerializeOption {
+                    trailing_end_indicator: true,
+                    ..Default::default()
+                }
+            )?,
+            "!Newtype 1\n---\n!Struct\na: 2\nb:\n  - 3\n---\nUnit\n...\n"
+        );
+        assert_eq!(
+            crate::to_string_multi(
+                &docs[..1],
+                YamlSerializeOption {
+                    leading_start_indicator: true,
+                    json_compatible: true,
+                    ..Default::default()
+                }
+            )?,
+            "{\"Newtype\": 1}\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_trailing_newline() -> Result<(), YamlError> {
+        let opt = YamlSerializeOption {
+            trailing_newline: false,
+            ..Default::default()
+        };
+        let data: BTreeMap<&str, &str> = [("a", "b")].into();
+        assert_eq!(to_string_with_opt(&data, opt.clone())?, "a: b");
+        assert_eq!(
+            to_string_with_opt(
+                &data,
+                YamlSerializeOption {
+                    trailing_end_indicator: true,
+                    ..opt.clone()
+                }
+            )?,
+            "a: b\n..."
+        );
+        assert_eq!(
+            to_string_with_opt(
+                &data,
+                YamlSerializeOption {
+                    json_compatible: true,
+                    ..opt.clone()
+                }
+            )?,
+            "{\"a\": \"b\"}"
+        );
+        assert_eq!(crate::to_string_multi(&[1, 2], opt.clone())?, "1\n---\n2");
+
+        // Line break of block scalar is kept as it is part of the content
+        let data: BTreeMap<&str, &str> = [("a", "b\nc\n")].into();
+        let yaml_str = to_string_with_opt(&data, opt)?;
+        assert_eq!(yaml_str, "a: |\n  b\n  c\n");
+        assert_eq!(
+            crate::from_str::<BTreeMap<String, String>>(&yaml_str)?["a"],
+            "b\nc\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_serialize_to_writer() -> Result<(), YamlError> {
+        let data: YamlValue = concat!(
+            "a:\n  - |+\n    b\n\n  - c: \"d\"\n    e:\n",
+            "    - |\n      f\n      g\n  -\nh: |+\n  i\n\n\n"
+        )
+        .parse()?;
+        let opt = YamlSerializeOption {
+            leading_start_indicator: true,
+            trailing_end_indicator: true,
+            ..Default::default()
+        };
+        let expected = to_string_with_opt(&data, opt.clone())?;
+
+        let mut output = String::new();
+        let mut serializer = YamlSerializer::new(&mut output, opt.clone())?;
+        data.serialize(&mut serializer)?;
+        serializer.finish()?;
+        assert_eq!(output, expected);
+
+        let mut output: Vec<u8> = Vec::new();
+        let mut serializer = YamlSerializer::new_io(&mut output, opt)?;
+        data.serialize(&mut serializer)?;
+        serializer.finish()?;
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_serialize_to_broken_writer() -> Result<(), YamlError> {
+        struct BrokenWriter;
+
+        impl std::io::Write for BrokenWriter {
+            fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("broken"))
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut serializer =
+            YamlSerializer::new_io(BrokenWriter, Default::default())?;
+        let result = vec!["a"; 10].serialize(&mut serializer);
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::Io);
+
+        let opt = YamlSerializeOption {
+            json_compatible: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            YamlSerializer::new(String::new(), opt).unwrap_err().kind(),
+            ErrorKind::NotSupported
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_map_key_quoting() -> Result<(), YamlError> {
+        let data: BTreeMap<String, u32> =
+            [("->".into(), 1), ("a[".into(), 2), (" \n".into(), 3)].into();
+        let yaml_str = to_string(&data)?;
+        assert_eq!(yaml_str, "? |2\n   \n: 3\n->: 1\n'a[': 2\n");
+        assert_eq!(crate::from_str::<BTreeMap<String, u32>>(&yaml_str)?, data);
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_roundtrip() -> Result<(), YamlError> {
+        #[derive(Debug, Serialize)]
+        enum Action {
+            Up(u32),
+            Move { to: String },
+            Down,
+        }
+
+        #[derive(Debug, Serialize)]
+        struct Unit;
+
+        let mut keys: BTreeMap<Vec<u32>, &str> = BTreeMap::new();
+        keys.insert(vec![1, 2], "true");
+        let data = (
+            vec![
+                Action::Up(1),
+                Action::Move {
+                    to: "a\nb\n\n".into(),
+                },
+            ],
+            Action::Down,
+            Unit,
+            keys,
+            ["null", "", " x", "1e3", "a: b"],
+            (Some(1.5f64), None::<u8>, f64::NAN, -7i64, u128::MAX),
+        );
+        for variant_style in [YamlVariantStyle::Tag, YamlVariantStyle::Map] {
+            let opt = YamlSerializeOption {
+                variant_style,
+                null_style: YamlNullStyle::Empty,
+                verify_roundtrip: true,
+                ..Default::default()
+            };
+            assert_eq!(
+                to_string_with_opt(&data, opt.clone())?,
+                to_string_with_opt(
+                    &data,
+                    YamlSerializeOption {
+                        verify_roundtrip: false,
+                        ..opt
+                    }
+                )?
+            );
+        }
+
+        let opt = YamlSerializeOption {
+            verify_roundtrip: true,
+            ..Default::default()
+        };
+        assert_eq!(to_string_with_opt(&(), opt.clone())?, "null\n");
+        // Plain empty map key is null, emitted as explicit key
+        let value: YamlValue = "? \n: a\n".parse()?;
+        let output = to_string_with_opt(&value, opt)?;
+        assert_eq!(output, "?\n: a\n");
+        assert!(output.parse::<YamlValue>()?.canonical_cmp(&value).is_eq());
+        Ok(())
+    }
 }
@@ -0,0 +1,1249 @@
+// SPDX-License-Identifier: Apache-2.0
+
+// Serializer and deserializer wrappers for
+// [crate::with::singleton_map_recursive]. They work with any serde format:
+// enum variant with data is rewritten into single entry map
+// `{variant: data}` on serializing, and single entry map or string is
+// accepted as enum on deserializing. Everything else is passed to the
+// wrapped serializer or deserializer with nested values wrapped again.
+
+use std::{fmt, marker::PhantomData};
+
+use serde::{
+    Deserializer, Serialize, Serializer,
+    de::{
+        self, DeserializeSeed, EnumAccess, IgnoredAny, IntoDeserializer,
+        MapAccess, SeqAccess, VariantAccess, Visitor,
+    },
+    ser::{
+        self, SerializeMap, SerializeSeq, SerializeStruct,
+        SerializeStructVariant, SerializeTuple, SerializeTupleStruct,
+        SerializeTupleVariant,
+    },
+};
+
+pub(crate) struct SingletonMap<T>(pub(crate) T);
+
+impl<T> Serialize for SingletonMap<&T>
+where
+    T: ?Sized + Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize(SingletonMap(serializer))
+    }
+}
+
+macro_rules! forward_serialize {
+    ($($method:ident($ty:ty)),* $(,)?) => {
+        $(
+            fn $method(self, v: $ty) -> Result<S::Ok, S::Error> {
+                self.0.$method(v)
+            }
+        )*
+    };
+}
+
+impl<S> Serializer for SingletonMap<S>
+where
+    S: Serializer,
+{
+    type Ok = S::Ok;
+    type Error = S::Error;
+    type SerializeSeq = SingletonMap<S::SerializeSeq>;
+    type SerializeTuple = SingletonMap<S::SerializeTuple>;
+    type SerializeTupleStruct = SingletonMap<S::SerializeTupleStruct>;
+    type SerializeTupleVariant = VariantAsMap<S::SerializeMap>;
+    type SerializeMap = SingletonMap<S::SerializeMap>;
+    type SerializeStruct = SingletonMap<S::SerializeStruct>;
+    type SerializeStructVariant = VariantAsMap<S::SerializeMap>;
+
+    forward_serialize!(
+        serialize_bool(bool),
+        serialize_i8(i8),
+        serialize_i16(i16),
+        serialize_i32(i32),
+        serialize_i64(i64),
+        serialize_i128(i128),
+        serialize_u8(u8),
+        serialize_u16(u16),
+        serialize_u32(u32),
+        serialize_u64(u64),
+        serialize_u128(u128),
+        serialize_f32(f32),
+        serialize_f64(f64),
+        serialize_char(char),
+        serialize_str(&str),
+        serialize_bytes(&[u8]),
+        serialize_unit_struct(&'static str),
+    );
+
+    fn serialize_unit(self) -> Result<S::Ok, S::Error> {
+        self.0.serialize_unit()
+    }
+
+    fn serialize_none(self) -> Result<S::Ok, S::Error> {
+        self.0.serialize_none()
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<S::Ok, S::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.0.serialize_some(&SingletonMap(value))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<S::Ok, S::Error> {
+        self.0.serialize_unit_variant(name, variant_index, variant)
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.0.serialize_newtype_struct(name, &SingletonMap(value))
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let mut map = self.0.serialize_map(Some(1))?;
+        map.serialize_entry(variant, &SingletonMap(value))?;
+        map.end()
+    }
+
+    fn serialize_seq(
+        self,
+        len: Option<usize>,
+    ) -> Result<Self::SerializeSeq, S::Error> {
+        Ok(SingletonMap(self.0.serialize_seq(len)?))
+    }
+
+    fn serialize_tuple(
+        self,
+        len: usize,
+    ) -> Result<Self::SerializeTuple, S::Error> {
+        Ok(SingletonMap(self.0.serialize_tuple(len)?))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, S::Error> {
+        Ok(SingletonMap(self.0.serialize_tuple_struct(name, len)?))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, S::Error> {
+        VariantAsMap::new(self.0, variant, len)
+    }
+
+    fn serialize_map(
+        self,
+        len: Option<usize>,
+    ) -> Result<Self::SerializeMap, S::Error> {
+        Ok(SingletonMap(self.0.serialize_map(len)?))
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, S::Error> {
+        Ok(SingletonMap(self.0.serialize_struct(name, len)?))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, S::Error> {
+        VariantAsMap::new(self.0, variant, len)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.0.is_human_readable()
+    }
+}
+
+impl<S> SerializeSeq for SingletonMap<S>
+where
+    S: SerializeSeq,
+{
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), S::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.0.serialize_element(&SingletonMap(value))
+    }
+
+    fn end(self) -> Result<S::Ok, S::Error> {
+        self.0.end()
+    }
+}
+
+impl<S> SerializeTuple for SingletonMap<S>
+where
+    S: SerializeTuple,
+{
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), S::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.0.serialize_element(&SingletonMap(value))
+    }
+
+    fn end(self) -> Result<S::Ok, S::Error> {
+        self.0.end()
+    }
+}
+
+impl<S> SerializeTupleStruct for SingletonMap<S>
+where
+    S: SerializeTupleStruct,
+{
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), S::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.0.serialize_field(&SingletonMap(value))
+    }
+
+    fn end(self) -> Result<S::Ok, S::Error> {
+        self.0.end()
+    }
+}
+
+impl<S> SerializeMap for SingletonMap<S>
+where
+    S: SerializeMap,
+{
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), S::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.0.serialize_key(&SingletonMap(key))
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), S::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.0.serialize_value(&SingletonMap(value))
+    }
+
+    fn serialize_entry<K, V>(
+        &mut self,
+        key: &K,
+        value: &V,
+    ) -> Result<(), S::Error>
+    where
+        K: ?Sized + Serialize,
+        V: ?Sized + Serialize,
+    {
+        self.0
+            .serialize_entry(&SingletonMap(key), &SingletonMap(value))
+    }
+
+    fn end(self) -> Result<S::Ok, S::Error> {
+        self.0.end()
+    }
+}
+
+impl<S> SerializeStruct for SingletonMap<S>
+where
+    S: SerializeStruct,
+{
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_field<T>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), S::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.0.serialize_field(key, &SingletonMap(value))
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), S::Error> {
+        self.0.skip_field(key)
+    }
+
+    fn end(self) -> Result<S::Ok, S::Error> {
+        self.0.end()
+    }
+}
+
+// Tuple or struct variant as `{variant: [fields]}` or
+// `{variant: {name: field}}`. The value of map entry has to be serialized
+// at once, hence fields are buffered.
+pub(crate) struct VariantAsMap<M> {
+    map: M,
+    fields: Vec<(Option<&'static str>, Content)>,
+}
+
+impl<M> VariantAsMap<M>
+where
+    M: SerializeMap,
+{
+    fn new<S>(
+        serializer: S,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self, M::Error>
+    where
+        S: Serializer<SerializeMap = M, Error = M::Error>,
+    {
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_key(variant)?;
+        Ok(Self {
+            map,
+            fields: Vec::with_capacity(len),
+        })
+    }
+
+    fn push<T>(
+        &mut self,
+        name: Option<&'static str>,
+        value: &T,
+    ) -> Result<(), M::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.fields.push((name, to_content(&SingletonMap(value))?));
+        Ok(())
+    }
+}
+
+impl<M> SerializeTupleVariant for VariantAsMap<M>
+where
+    M: SerializeMap,
+{
+    type Ok = M::Ok;
+    type Error = M::Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), M::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(None, value)
+    }
+
+    fn end(mut self) -> Result<M::Ok, M::Error> {
+        let items = self.fields.into_iter().map(|(_, v)| v).collect();
+        self.map.serialize_value(&Content::Seq(items))?;
+        self.map.end()
+    }
+}
+
+impl<M> SerializeStructVariant for VariantAsMap<M>
+where
+    M: SerializeMap,
+{
+    type Ok = M::Ok;
+    type Error = M::Error;
+
+    fn serialize_field<T>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), M::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(Some(key), value)
+    }
+
+    fn end(mut self) -> Result<M::Ok, M::Error> {
+        let entries = self
+            .fields
+            .into_iter()
+            .map(|(k, v)| (Content::Str(k.unwrap_or_default()), v))
+            .collect();
+        self.map.serialize_value(&Content::Map(entries))?;
+        self.map.end()
+    }
+}
+
+// Buffered value keeping the data model of serde, so the output of
+// replaying it is identical to serializing the original value. Enum
+// variants never reach here as they are rewritten by `SingletonMap`.
+enum Content {
+    Bool(bool),
+    I64(i64),
+    I128(i128),
+    U64(u64),
+    U128(u128),
+    F32(f32),
+    F64(f64),
+    Char(char),
+    Str(&'static str),
+    String(String),
+    Bytes(Vec<u8>),
+    None,
+    Some(Box<Content>),
+    Unit,
+    UnitStruct(&'static str),
+    UnitVariant(&'static str, u32, &'static str),
+    NewtypeStruct(&'static str, Box<Content>),
+    Seq(Vec<Content>),
+    Tuple(Vec<Content>),
+    TupleStruct(&'static str, Vec<Content>),
+    Map(Vec<(Content, Content)>),
+    Struct(&'static str, Vec<(&'static str, Content)>),
+}
+
+impl Serialize for Content {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Self::Bool(v) => serializer.serialize_bool(*v),
+            Self::I64(v) => serializer.serialize_i64(*v),
+            Self::I128(v) => serializer.serialize_i128(*v),
+            Self::U64(v) => serializer.serialize_u64(*v),
+            Self::U128(v) => serializer.serialize_u128(*v),
+            Self::F32(v) => serializer.serialize_f32(*v),
+            Self::F64(v) => serializer.serialize_f64(*v),
+            Self::Char(v) => serializer.serialize_char(*v),
+            Self::Str(v) => serializer.serialize_str(v),
+            Self::String(v) => serializer.serialize_str(v),
+            Self::Bytes(v) => serializer.serialize_bytes(v),
+            Self::None => serializer.serialize_none(),
+            Self::Some(v) => serializer.serialize_some(v),
+            Self::Unit => serializer.serialize_unit(),
+            Self::UnitStruct(name) => serializer.serialize_unit_struct(name),
+            Self::UnitVariant(name, index, variant) => {
+                serializer.serialize_unit_variant(name, *index, variant)
+            }
+            Self::NewtypeStruct(name, v) => {
+                serializer.serialize_newtype_struct(name, v)
+            }
+            Self::Seq(items) => serializer.collect_seq(items),
+            Self::Tuple(items) => {
+                let mut tuple = serializer.serialize_tuple(items.len())?;
+                for item in items {
+                    tuple.serialize_element(item)?;
+                }
+                tuple.end()
+            }
+            Self::TupleStruct(name, items) => {
+                let mut tuple =
+                    serializer.serialize_tuple_struct(name, items.len())?;
+                for item in items {
+                    tuple.serialize_field(item)?;
+                }
+                tuple.end()
+            }
+            Self::Map(entries) => {
+                serializer.collect_map(entries.iter().map(|(k, v)| (k, v)))
+            }
+            Self::Struct(name, fields) => {
+                let mut st = serializer.serialize_struct(name, fields.len())?;
+                for (key, value) in fields {
+                    st.serialize_field(key, value)?;
+                }
+                st.end()
+            }
+        }
+    }
+}
+
+struct ContentSerializer<E>(PhantomData<E>);
+
+macro_rules! content_serialize {
+    ($($method:ident($ty:ty) => $variant:ident),* $(,)?) => {
+        $(
+            fn $method(self, v: $ty) -> Result<Content, E> {
+                Ok(Content::$variant(v.into()))
+            }
+        )*
+    };
+}
+
+impl<E> Serializer for ContentSerializer<E>
+where
+    E: ser::Error,
+{
+    type Ok = Content;
+    type Error = E;
+    type SerializeSeq = ContentSeq<E>;
+    type SerializeTuple = ContentSeq<E>;
+    type SerializeTupleStruct = ContentSeq<E>;
+    type SerializeTupleVariant = ser::Impossible<Content, E>;
+    type SerializeMap = ContentMap<E>;
+    type SerializeStruct = ContentMap<E>;
+    type SerializeStructVariant = ser::Impossible<Content, E>;
+
+    content_serialize!(
+        serialize_bool(bool) => Bool,
+        serialize_i8(i8) => I64,
+        serialize_i16(i16) => I64,
+        serialize_i32(i32) => I64,
+        serialize_i64(i64) => I64,
+        serialize_i128(i128) => I128,
+        serialize_u8(u8) => U64,
+        serialize_u16(u16) => U64,
+        serialize_u32(u32) => U64,
+        serialize_u64(u64) => U64,
+        serialize_u128(u128) => U128,
+        serialize_f32(f32) => F32,
+        serialize_f64(f64) => F64,
+        serialize_char(char) => Char,
+        serialize_str(&str) => String,
+        serialize_bytes(&[u8]) => Bytes,
+    );
+
+    fn serialize_none(self) -> Result<Content, E> {
+        Ok(Content::None)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Content, E>
+    where
+        T: ?Sized + Serialize,
+    {
+        Ok(Content::Some(Box::new(value.serialize(self)?)))
+    }
+
+    fn serialize_unit(self) -> Result<Content, E> {
+        Ok(Content::Unit)
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Content, E> {
+        Ok(Content::UnitStruct(name))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Content, E> {
+        Ok(Content::UnitVariant(name, variant_index, variant))
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Content, E>
+    where
+        T: ?Sized + Serialize,
+    {
+        Ok(Content::NewtypeStruct(
+            name,
+            Box::new(value.serialize(self)?),
+        ))
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _value: &T,
+    ) -> Result<Content, E>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(unexpected_variant(variant))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<ContentSeq<E>, E> {
+        Ok(ContentSeq::new(
+            ContentSeqKind::Seq,
+            len.unwrap_or_default(),
+        ))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<ContentSeq<E>, E> {
+        Ok(ContentSeq::new(ContentSeqKind::Tuple, len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<ContentSeq<E>, E> {
+        Ok(ContentSeq::new(ContentSeqKind::TupleStruct(name), len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, E> {
+        Err(unexpected_variant(variant))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<ContentMap<E>, E> {
+        Ok(ContentMap::new(None, len.unwrap_or_default()))
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<ContentMap<E>, E> {
+        Ok(ContentMap::new(Some(name), len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, E> {
+        Err(unexpected_variant(variant))
+    }
+}
+
+fn unexpected_variant<E: ser::Error>(variant: &str) -> E {
+    E::custom(format!(
+        "Enum variant {variant} should be serialized as single entry map"
+    ))
+}
+
+enum ContentSeqKind {
+    Seq,
+    Tuple,
+    TupleStruct(&'static str),
+}
+
+struct ContentSeq<E> {
+    kind: ContentSeqKind,
+    items: Vec<Content>,
+    error: PhantomData<E>,
+}
+
+impl<E> ContentSeq<E>
+where
+    E: ser::Error,
+{
+    fn new(kind: ContentSeqKind, len: usize) -> Self {
+        Self {
+            kind,
+            items: Vec::with_capacity(len),
+            error: PhantomData,
+        }
+    }
+
+    fn push<T>(&mut self, value: &T) -> Result<(), E>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.items.push(to_content(value)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Content {
+        match self.kind {
+            ContentSeqKind::Seq => Content::Seq(self.items),
+            ContentSeqKind::Tuple => Content::Tuple(self.items),
+            ContentSeqKind::TupleStruct(name) => {
+                Content::TupleStruct(name, self.items)
+            }
+        }
+    }
+}
+
+impl<E> SerializeSeq for ContentSeq<E>
+where
+    E: ser::Error,
+{
+    type Ok = Content;
+    type Error = E;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), E>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Content, E> {
+        Ok(self.finish())
+    }
+}
+
+impl<E> SerializeTuple for ContentSeq<E>
+where
+    E: ser::Error,
+{
+    type Ok = Content;
+    type Error = E;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), E>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Content, E> {
+        Ok(self.finish())
+    }
+}
+
+impl<E> SerializeTupleStruct for ContentSeq<E>
+where
+    E: ser::Error,
+{
+    type Ok = Content;
+    type Error = E;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), E>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Content, E> {
+        Ok(self.finish())
+    }
+}
+
+// Map, or struct when name is set.
+struct ContentMap<E> {
+    name: Option<&'static str>,
+    entries: Vec<(Content, Content)>,
+    fields: Vec<(&'static str, Content)>,
+    key: Option<Content>,
+    error: PhantomData<E>,
+}
+
+impl<E> ContentMap<E>
+where
+    E: ser::Error,
+{
+    fn new(name: Option<&'static str>, len: usize) -> Self {
+        Self {
+            name,
+            entries: Vec::with_capacity(len),
+            fields: Vec::new(),
+            key: None,
+            error: PhantomData,
+        }
+    }
+}
+
+// Nested values are already wrapped by `SingletonMap` when reaching the
+// collectors of `ContentSerializer`.
+fn to_content<T, E>(value: &T) -> Result<Content, E>
+where
+    T: ?Sized + Serialize,
+    E: ser::Error,
+{
+    value.serialize(ContentSerializer(PhantomData))
+}
+
+impl<E> SerializeMap for ContentMap<E>
+where
+    E: ser::Error,
+{
+    type Ok = Content;
+    type Error = E;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), E>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.key = Some(to_content(key)?);
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), E>
+    where
+        T: ?Sized + Serialize,
+    {
+        let key = self.key.take().ok_or_else(|| {
+            E::custom("serialize_value() called before serialize_key()")
+        })?;
+        self.entries.push((key, to_content(value)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Content, E> {
+        Ok(Content::Map(self.entries))
+    }
+}
+
+impl<E> SerializeStruct for ContentMap<E>
+where
+    E: ser::Error,
+{
+    type Ok = Content;
+    type Error = E;
+
+    fn serialize_field<T>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), E>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.fields.push((key, to_content(value)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Content, E> {
+        Ok(Content::Struct(self.name.unwrap_or_default(), self.fields))
+    }
+}
+
+macro_rules! forward_deserialize {
+    ($($method:ident),* $(,)?) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, D::Error>
+            where
+                V: Visitor<'de>,
+            {
+                self.0.$method(SingletonMap(visitor))
+            }
+        )*
+    };
+}
+
+impl<'de, D> Deserializer<'de> for SingletonMap<D>
+where
+    D: Deserializer<'de>,
+{
+    type Error = D::Error;
+
+    forward_deserialize!(
+        deserialize_any,
+        deserialize_bool,
+        deserialize_i8,
+        deserialize_i16,
+        deserialize_i32,
+        deserialize_i64,
+        deserialize_i128,
+        deserialize_u8,
+        deserialize_u16,
+        deserialize_u32,
+        deserialize_u64,
+        deserialize_u128,
+        deserialize_f32,
+        deserialize_f64,
+        deserialize_char,
+        deserialize_str,
+        deserialize_string,
+        deserialize_bytes,
+        deserialize_byte_buf,
+        deserialize_option,
+        deserialize_unit,
+        deserialize_seq,
+        deserialize_map,
+        deserialize_identifier,
+        deserialize_ignored_any,
+    );
+
+    fn deserialize_unit_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.0.deserialize_unit_struct(name, SingletonMap(visitor))
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.0
+            .deserialize_newtype_struct(name, SingletonMap(visitor))
+    }
+
+    fn deserialize_tuple<V>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.0.deserialize_tuple(len, SingletonMap(visitor))
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.0
+            .deserialize_tuple_struct(name, len, SingletonMap(visitor))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.0
+            .deserialize_struct(name, fields, SingletonMap(visitor))
+    }
+
+    // The enum is either variant name or single entry map.
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.0.deserialize_any(EnumVisitor(visitor))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.0.is_human_readable()
+    }
+}
+
+macro_rules! forward_visit {
+    ($($method:ident($ty:ty)),* $(,)?) => {
+        $(
+            fn $method<E>(self, v: $ty) -> Result<V::Value, E>
+            where
+                E: de::Error,
+            {
+                self.0.$method(v)
+            }
+        )*
+    };
+}
+
+impl<'de, V> Visitor<'de> for SingletonMap<V>
+where
+    V: Visitor<'de>,
+{
+    type Value = V::Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.0.expecting(formatter)
+    }
+
+    forward_visit!(
+        visit_bool(bool),
+        visit_i8(i8),
+        visit_i16(i16),
+        visit_i32(i32),
+        visit_i64(i64),
+        visit_i128(i128),
+        visit_u8(u8),
+        visit_u16(u16),
+        visit_u32(u32),
+        visit_u64(u64),
+        visit_u128(u128),
+        visit_f32(f32),
+        visit_f64(f64),
+        visit_char(char),
+        visit_str(&str),
+        visit_borrowed_str(&'de str),
+        visit_string(String),
+        visit_bytes(&[u8]),
+        visit_borrowed_bytes(&'de [u8]),
+        visit_byte_buf(Vec<u8>),
+    );
+
+    fn visit_none<E>(self) -> Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.0.visit_none()
+    }
+
+    fn visit_unit<E>(self) -> Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.0.visit_unit()
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<V::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.0.visit_some(SingletonMap(deserializer))
+    }
+
+    fn visit_newtype_struct<D>(
+        self,
+        deserializer: D,
+    ) -> Result<V::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.0.visit_newtype_struct(SingletonMap(deserializer))
+    }
+
+    fn visit_seq<A>(self, seq: A) -> Result<V::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        self.0.visit_seq(SingletonMap(seq))
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<V::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        self.0.visit_map(SingletonMap(map))
+    }
+}
+
+impl<'de, T> DeserializeSeed<'de> for SingletonMap<T>
+where
+    T: DeserializeSeed<'de>,
+{
+    type Value = T::Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<T::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.0.deserialize(SingletonMap(deserializer))
+    }
+}
+
+impl<'de, A> SeqAccess<'de> for SingletonMap<A>
+where
+    A: SeqAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn next_element_seed<T>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, A::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.0.next_element_seed(SingletonMap(seed))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.0.size_hint()
+    }
+}
+
+impl<'de, A> MapAccess<'de> for SingletonMap<A>
+where
+    A: MapAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn next_key_seed<K>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, A::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        self.0.next_key_seed(SingletonMap(seed))
+    }
+
+    fn next_value_seed<T>(&mut self, seed: T) -> Result<T::Value, A::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.0.next_value_seed(SingletonMap(seed))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.0.size_hint()
+    }
+}
+
+struct EnumVisitor<V>(V);
+
+impl<'de, V> Visitor<'de> for EnumVisitor<V>
+where
+    V: Visitor<'de>,
+{
+    type Value = V::Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("variant name or single entry map")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.0.visit_enum(v.into_deserializer())
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<V::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        self.0.visit_enum(MapAsEnum(map))
+    }
+}
+
+struct MapAsEnum<A>(A);
+
+impl<'de, A> EnumAccess<'de> for MapAsEnum<A>
+where
+    A: MapAccess<'de>,
+{
+    type Error = A::Error;
+    type Variant = Self;
+
+    fn variant_seed<T>(mut self, seed: T) -> Result<(T::Value, Self), A::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.0.next_key_seed(seed)? {
+            Some(variant) => Ok((variant, self)),
+            None => Err(de::Error::invalid_length(0, &"single entry map")),
+        }
+    }
+}
+
+impl<'de, A> MapAsEnum<A>
+where
+    A: MapAccess<'de>,
+{
+    // Read the variant data and make sure it is the only entry.
+    fn value<T>(mut self, seed: T) -> Result<T::Value, A::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let value = self.0.next_value_seed(seed)?;
+        if self.0.next_key::<IgnoredAny>()?.is_some() {
+            Err(de::Error::invalid_length(2, &"single entry map"))
+        } else {
+            Ok(value)
+        }
+    }
+}
+
+impl<'de, A> VariantAccess<'de> for MapAsEnum<A>
+where
+    A: MapAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn unit_variant(self) -> Result<(), A::Error> {
+        self.value(PhantomData::<()>)
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, A::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.value(SingletonMap(seed))
+    }
+
+    fn tuple_variant<V>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, A::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.value(VariantSeed::Tuple(len, visitor))
+    }
+
+    fn struct_variant<V>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, A::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.value(VariantSeed::Struct(fields, visitor))
+    }
+}
+
+enum VariantSeed<V> {
+    Tuple(usize, V),
+    Struct(&'static [&'static str], V),
+}
+
+impl<'de, V> DeserializeSeed<'de> for VariantSeed<V>
+where
+    V: Visitor<'de>,
+{
+    type Value = V::Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<V::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match self {
+            Self::Tuple(len, visitor) => {
+                SingletonMap(deserializer).deserialize_tuple(len, visitor)
+            }
+            Self::Struct(fields, visitor) => SingletonMap(deserializer)
+                .deserialize_struct("", fields, visitor),
+        }
+    }
+}
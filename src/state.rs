@@ -4,6 +4,8 @@
 pub(crate) enum YamlState {
     InBlockMapKey,
     InBlockMapValue,
+    /// Key or value of explicit entry started by `?` indicator
+    InBlockMapExplicitEntry,
     InBlockSequnce,
     InFlowMapKey,
     InFlowMapValue,
@@ -20,6 +22,10 @@ impl YamlState {
         )
     }
 
+    pub(crate) fn is_flow_seq(&self) -> bool {
+        self == &Self::InFlowSequnce
+    }
+
     pub(crate) fn is_block_map_key(&self) -> bool {
         self == &Self::InBlockMapKey
     }
@@ -28,6 +34,10 @@ impl YamlState {
         self == &Self::InBlockMapValue
     }
 
+    pub(crate) fn is_block_map_explicit_entry(&self) -> bool {
+        self == &Self::InBlockMapExplicitEntry
+    }
+
     pub(crate) fn is_block_seq(&self) -> bool {
         self == &Self::InBlockSequnce
     }
@@ -38,6 +48,7 @@ impl YamlState {
             self,
             &Self::InBlockMapKey
                 | &Self::InBlockMapValue
+                | &Self::InBlockMapExplicitEntry
                 | &Self::InBlockSequnce
                 | &Self::InFlowMapKey
                 | &Self::InFlowMapValue
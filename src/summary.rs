@@ -0,0 +1,135 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fmt::Write;
+
+use crate::{YamlPosition, YamlValue, YamlValueData};
+
+// Longer scalars are truncated with `...` appended.
+const MAX_SCALAR_CHARS: usize = 40;
+
+impl YamlValue {
+    /// Indented outline of the value with positions for logs and error
+    /// contexts, one node per line, e.g. `name: "eth0" 2:11-2:14`.
+    /// Collections show their size and only children within `depth` levels
+    /// are included, `0` for root node only. At most `max_items` children
+    /// of each collection are included followed by `... N more`. Long
+    /// scalars are truncated.
+    pub fn summary(&self, depth: usize, max_items: usize) -> String {
+        let mut ret = String::new();
+        summary_node(&mut ret, self, "", 0, depth, max_items);
+        ret.pop();
+        ret
+    }
+}
+
+fn summary_node(
+    out: &mut String,
+    value: &YamlValue,
+    label: &str,
+    level: usize,
+    depth: usize,
+    max_items: usize,
+) {
+    let indent = "  ".repeat(level);
+    out.push_str(&indent);
+    out.push_str(label);
+    let data = match &value.data {
+        YamlValueData::Tag(tag) => {
+            let _ = write!(out, "{} ", tag.name);
+            &tag.data
+        }
+        data => data,
+    };
+    let children: Vec<(String, &YamlValue)> = match data {
+        YamlValueData::Null => {
+            out.push_str("null");
+            Vec::new()
+        }
+        YamlValueData::String(s) => {
+            if s.chars().count() > MAX_SCALAR_CHARS {
+                let s: String = s.chars().take(MAX_SCALAR_CHARS).collect();
+                let _ = write!(out, "{s:?}...");
+            } else {
+                let _ = write!(out, "{s:?}");
+            }
+            Vec::new()
+        }
+        YamlValueData::Array(items) => {
+            let _ = write!(out, "sequence({})", items.len());
+            items.iter().map(|item| ("- ".to_string(), item)).collect()
+        }
+        YamlValueData::Map(map) => {
+            let _ = write!(out, "map({})", map.len());
+            map.iter()
+                .map(|(k, v)| (format!("{}: ", k.key_path_name()), v))
+                .collect()
+        }
+        YamlValueData::Tag(_) => Vec::new(),
+    };
+    let _ =
+        writeln!(out, " {}-{}", short_pos(value.start), short_pos(value.end));
+    if level >= depth {
+        return;
+    }
+    for (label, child) in children.iter().take(max_items) {
+        summary_node(out, child, label, level + 1, depth, max_items);
+    }
+    if children.len() > max_items {
+        let _ =
+            writeln!(out, "{indent}  ... {} more", children.len() - max_items);
+    }
+}
+
+fn short_pos(pos: YamlPosition) -> String {
+    if pos == YamlPosition::EOF {
+        "EOF".to_string()
+    } else {
+        format!("{}:{}", pos.line, pos.column)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::YamlError;
+
+    #[test]
+    fn test_summary() -> Result<(), YamlError> {
+        let value = YamlValue::from_str(
+            "interfaces:\n  - name: eth0\n    mtu: !!int 1500\n    addresses: \
+             [192.0.2.1, 192.0.2.2]\n  - name: eth1\ndescription: >\n  This \
+             is a very long description which is folded\n",
+        )?;
+        assert_eq!(
+            value.summary(2, 2),
+            [
+                "map(2) 1:1-7:50",
+                "  interfaces: sequence(2) 2:1-5:14",
+                "    - map(3) 2:5-4:37",
+                "    - map(1) 5:5-5:14",
+                "  description: \"This is a very long description which \
+                 is\"... 7:3-7:50",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            value.summary(3, 1),
+            [
+                "map(2) 1:1-7:50",
+                "  interfaces: sequence(2) 2:1-5:14",
+                "    - map(3) 2:5-4:37",
+                "      name: \"eth0\" 2:11-2:14",
+                "      ... 2 more",
+                "    ... 1 more",
+                "  ... 1 more",
+            ]
+            .join("\n")
+        );
+        assert_eq!(value.summary(0, 10), "map(2) 1:1-7:50");
+        Ok(())
+    }
+}
@@ -2,12 +2,22 @@
 
 use crate::{YamlParser, YamlValueData};
 
+// The `!!str` is stored as `<tag:yaml.org,2002:str>`.
+const CORE_TAG_PREFIX: &str = "<tag:yaml.org,2002:";
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct YamlTag {
     pub name: String,
     pub data: YamlValueData,
 }
 
+impl YamlTag {
+    /// Type name of `!!` tag, e.g. `str` for `!!str`, None for local tag.
+    pub(crate) fn core_type(&self) -> Option<&str> {
+        self.name.strip_prefix(CORE_TAG_PREFIX)?.strip_suffix('>')
+    }
+}
+
 impl<'a> YamlParser<'a> {
     // TODO:
     //   * It is possible to override this default behavior by providing an
@@ -18,14 +28,14 @@ impl<'a> YamlParser<'a> {
 
         if let Some(tag) = tag_name.strip_prefix("!!") {
             let ret = format!("<tag:yaml.org,2002:{tag}>");
-            self.scanner.advance_till_linebreak_or_space();
+            self.scanner.advance(tag_name.chars().count());
             return Some(ret);
         } else if let Some(tag) = tag_name.strip_prefix("!") {
             let ret = tag.to_string();
-            self.scanner.advance_till_linebreak_or_space();
+            self.scanner.advance(tag_name.chars().count());
             return Some(ret);
         } else if !tag_name.is_empty() {
-            log::trace!("Unknown tag {tag_name}");
+            trace!("Unknown tag {tag_name}");
         }
         None
     }
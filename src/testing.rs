@@ -0,0 +1,135 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Property-based testing helpers on top of [proptest], useful for checking
+//! custom types survive serializing to YAML and deserializing back.
+
+use std::fmt::Debug;
+
+use proptest::{prelude::*, test_runner::TestCaseError};
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{YamlValue, YamlValueData, YamlValueMap};
+
+/// Strategy generating strings mixed with YAML indicators, white spaces and
+/// line breaks.
+pub fn yaml_string() -> impl Strategy<Value = String> {
+    "[a-z0-9 :#?!&*'\"{}\\[\\],|>%@`.\\-\\t\\n]{0,12}"
+}
+
+/// Strategy generating [YamlValue] trees of scalars, sequences and maps
+/// with string keys. Like the parsed value, null is a scalar instead of
+/// [YamlValueData::Null]. Root is never empty string, because empty
+/// document is parsed as [YamlValueData::Null].
+pub fn yaml_value() -> impl Strategy<Value = YamlValue> {
+    let leaf = prop_oneof![Just("null".to_string()), yaml_string(),]
+        .prop_map(|s| YamlValue::from(YamlValueData::String(s)));
+    leaf.prop_recursive(4, 32, 4, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..4)
+                .prop_map(|items| YamlValue::from(YamlValueData::Array(items))),
+            prop::collection::vec((yaml_string(), inner), 0..4).prop_map(
+                |entries| {
+                    let mut map = YamlValueMap::new();
+                    for (key, value) in entries {
+                        map.insert(
+                            YamlValue::from(YamlValueData::String(key)),
+                            value,
+                        );
+                    }
+                    YamlValue::from(YamlValueData::Map(Box::new(map)))
+                }
+            ),
+        ]
+    })
+    .prop_filter("Empty document is null", |value| {
+        value.data != YamlValueData::String(String::new())
+    })
+}
+
+/// Serialize the value by [crate::to_string()], deserialize it back by
+/// [crate::from_str()] and fail if it is changed. The YAML text is included
+/// in the failure message, hence the shrunk failure reported by proptest
+/// holds the minimal YAML reproducing the issue.
+pub fn check_roundtrip<T>(value: &T) -> Result<(), TestCaseError>
+where
+    T: Serialize + DeserializeOwned + PartialEq + Debug,
+{
+    let yaml = crate::to_string(value).map_err(|e| {
+        TestCaseError::fail(format!("Failed to serialize {value:?}: {e}"))
+    })?;
+    let parsed: T = crate::from_str(&yaml).map_err(|e| {
+        TestCaseError::fail(format!("Failed to deserialize {yaml:?}: {e}"))
+    })?;
+    prop_assert_eq!(&parsed, value, "YAML: {:?}", yaml);
+    Ok(())
+}
+
+/// Same as [check_roundtrip()] for [YamlValue] which is compared by
+/// [YamlValue::canonical_cmp()], hence positions and scalar styles are
+/// ignored.
+pub fn check_value_roundtrip(value: &YamlValue) -> Result<(), TestCaseError> {
+    let yaml = crate::to_string(value).map_err(|e| {
+        TestCaseError::fail(format!("Failed to serialize {value:?}: {e}"))
+    })?;
+    let parsed: YamlValue = yaml.parse().map_err(|e| {
+        TestCaseError::fail(format!("Failed to parse {yaml:?}: {e}"))
+    })?;
+    prop_assert!(
+        parsed.canonical_cmp(value).is_eq(),
+        "YAML {:?} parsed as {:?}",
+        yaml,
+        parsed
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Interface {
+        name: String,
+        mtu: Option<u32>,
+        enabled: bool,
+        addresses: Vec<String>,
+        options: BTreeMap<String, i64>,
+    }
+
+    fn interface() -> impl Strategy<Value = Interface> {
+        (
+            yaml_string(),
+            any::<Option<u32>>(),
+            any::<bool>(),
+            prop::collection::vec(yaml_string(), 0..4),
+            prop::collection::btree_map(yaml_string(), any::<i64>(), 0..4),
+        )
+            .prop_map(|(name, mtu, enabled, addresses, options)| {
+                Interface {
+                    name,
+                    mtu,
+                    enabled,
+                    addresses,
+                    options,
+                }
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn test_roundtrip_value(value in yaml_value()) {
+            check_value_roundtrip(&value)?;
+        }
+
+        #[test]
+        fn test_roundtrip_struct(
+            value in prop::collection::vec(interface(), 0..4)
+        ) {
+            check_roundtrip(&value)?;
+        }
+    }
+}
@@ -9,3 +9,45 @@ pub(crate) fn init_logger() {
             .init()
     });
 }
+
+// Allocations are counted per thread, hence tests running in parallel do
+// not affect each other.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOC_COUNT: std::cell::Cell<usize> =
+        const { std::cell::Cell::new(0) };
+}
+
+unsafe impl std::alloc::GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: std::alloc::Layout) -> *mut u8 {
+        ALLOC_COUNT.try_with(|c| c.set(c.get() + 1)).ok();
+        unsafe { std::alloc::System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+        unsafe { std::alloc::System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(
+        &self,
+        ptr: *mut u8,
+        layout: std::alloc::Layout,
+        new_size: usize,
+    ) -> *mut u8 {
+        ALLOC_COUNT.try_with(|c| c.set(c.get() + 1)).ok();
+        unsafe { std::alloc::System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+// Number of allocations and reallocations done by the function. Trace log
+// allocates when `logging` feature is enabled, hence not counted.
+#[cfg(not(feature = "logging"))]
+pub(crate) fn count_allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOC_COUNT.with(|c| c.get());
+    let ret = f();
+    (ret, ALLOC_COUNT.with(|c| c.get()) - before)
+}
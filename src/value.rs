@@ -3,15 +3,28 @@
 use std::str::FromStr;
 
 use crate::{
-    ErrorKind, YamlError, YamlParser, YamlPosition, YamlTag,
-    YamlValueMap,
+    ErrorKind, YamlError, YamlParseOption, YamlPosition, YamlScalarStyle,
+    YamlSerializeOption, YamlTag, YamlValueMap,
 };
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct YamlValue {
     pub data: YamlValueData,
     pub start: YamlPosition,
     pub end: YamlPosition,
+    /// Style of scalar in YAML document, always
+    /// [YamlScalarStyle::Plain] for non-scalar.
+    pub style: YamlScalarStyle,
+}
+
+// Position is not included, so map key can be found by data only.
+impl std::hash::Hash for YamlValue {
+    fn hash<H>(&self, state: &mut H)
+    where
+        H: std::hash::Hasher,
+    {
+        self.data.hash(state);
+    }
 }
 
 impl std::fmt::Display for YamlValue {
@@ -25,23 +38,84 @@ impl FromStr for YamlValue {
     type Err = YamlError;
 
     fn from_str(input: &str) -> Result<Self, YamlError> {
-        let events = YamlParser::parse_to_events(input)?;
-        Self::compose(events)
+        crate::to_value_with_opt(input, YamlParseOption::default())
     }
 }
 
 impl YamlValue {
+    /// Float in YAML 1.2.2 core schema, including `.inf`, `-.inf` and
+    /// `.nan`.
+    pub fn as_f64(&self) -> Result<f64, YamlError> {
+        if let YamlValueData::String(s) = self.content() {
+            match s.as_str() {
+                ".nan" | ".NaN" | ".NAN" => Ok(f64::NAN),
+                ".inf" | ".Inf" | ".INF" | "+.inf" | "+.Inf" | "+.INF" => {
+                    Ok(f64::INFINITY)
+                }
+                "-.inf" | "-.Inf" | "-.INF" => Ok(f64::NEG_INFINITY),
+                _ => {
+                    if str_is_float(s)
+                        && let Ok(v) = f64::from_str(s)
+                    {
+                        Ok(v)
+                    } else {
+                        Err(YamlError::new(
+                            ErrorKind::InvalidNumber,
+                            format!(
+                                "Expecting float like 1.5, .inf or .nan, but \
+                                 got {s}"
+                            ),
+                            self.start,
+                            self.end,
+                        ))
+                    }
+                }
+            }
+        } else {
+            Err(YamlError::new(
+                ErrorKind::UnexpectedYamlNodeType,
+                format!("Expecting a float, but got {}", &self.data),
+                self.start,
+                self.end,
+            ))
+        }
+    }
+
+    pub fn as_f32(&self) -> Result<f32, YamlError> {
+        let num = self.as_f64()?;
+        if num.is_finite() && (num as f32).is_infinite() {
+            Err(YamlError::new(
+                ErrorKind::NumberOverflow,
+                format!(
+                    "Specified number {} overflow f32 range [{}, {}]",
+                    num,
+                    f32::MIN,
+                    f32::MAX
+                ),
+                self.start,
+                self.end,
+            ))
+        } else {
+            Ok(num as f32)
+        }
+    }
+
+    /// Single character of unescaped scalar in any style, e.g. `q`, `'q'`
+    /// and `"\u2665"`.
     pub fn as_char(&self) -> Result<char, YamlError> {
-        if let YamlValueData::String(v) = &self.data {
-            if v.len() == 1 {
-                Ok(v.chars().next().unwrap())
-            } else {
-                Err(YamlError::new(
+        if let YamlValueData::String(v) = self.content() {
+            let mut chars = v.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Ok(c),
+                _ => Err(YamlError::new(
                     ErrorKind::UnexpectedYamlNodeType,
-                    format!("Expecting a char, but got multi-char string {v}"),
+                    format!(
+                        "Expecting a char, but got string of {} chars {v:?}",
+                        v.chars().count()
+                    ),
                     self.start,
                     self.end,
-                ))
+                )),
             }
         } else {
             Err(YamlError::new(
@@ -54,7 +128,7 @@ impl YamlValue {
     }
 
     pub fn as_str(&self) -> Result<&str, YamlError> {
-        if let YamlValueData::String(v) = &self.data {
+        if let YamlValueData::String(v) = self.content() {
             Ok(v.as_str())
         } else if let YamlValueData::Tag(tag) = &self.data {
             // The `as_str()` is called to get tag name of enum instead of
@@ -73,10 +147,10 @@ impl YamlValue {
     }
 
     pub fn as_bool(&self) -> Result<bool, YamlError> {
-        if let YamlValueData::String(s) = &self.data {
+        if let YamlValueData::String(s) = self.content() {
             match s.as_str() {
-                "true" => Ok(true),
-                "false" => Ok(false),
+                "true" | "True" | "TRUE" => Ok(true),
+                "false" | "False" | "FALSE" => Ok(false),
                 _ => Err(YamlError::new(
                     ErrorKind::InvalidBool,
                     format!("Expecting bool (true or false), but got {s}"),
@@ -94,79 +168,101 @@ impl YamlValue {
         }
     }
 
+    /// Tag of this node. The `!!` tags are in the form of
+    /// `<tag:yaml.org,2002:str>`, the local tags like `!foo` are stored
+    /// without leading `!`.
+    pub fn tag(&self) -> Option<&str> {
+        if let YamlValueData::Tag(tag) = &self.data {
+            Some(tag.name.as_str())
+        } else {
+            None
+        }
+    }
+
+    /// Type name of `!!` tag, e.g. `str` for `!!str 5`.
+    pub(crate) fn core_tag_type(&self) -> Option<&str> {
+        if let YamlValueData::Tag(tag) = &self.data {
+            tag.core_type()
+        } else {
+            None
+        }
+    }
+
+    /// Content of `!!` tagged node is resolved as untagged one, node with
+    /// local tag is kept as it is.
+    pub(crate) fn content(&self) -> &YamlValueData {
+        match &self.data {
+            YamlValueData::Tag(tag) if tag.core_type().is_some() => &tag.data,
+            data => data,
+        }
+    }
+
+    // Name of map key used in key path.
+    pub(crate) fn key_path_name(&self) -> String {
+        match &self.data {
+            YamlValueData::String(s) => s.to_string(),
+            YamlValueData::Null => String::new(),
+            data => data.to_string(),
+        }
+    }
+
+    /// Whether this value is null, including the plain scalars treated as
+    /// null by YAML 1.2.2 core schema: empty, `~`, `null`, `Null` and `NULL`.
+    pub fn is_null(&self) -> bool {
+        match self.content() {
+            YamlValueData::Null => true,
+            YamlValueData::String(s)
+                if self.style == YamlScalarStyle::Plain
+                    && self.core_tag_type() != Some("str") =>
+            {
+                matches!(s.as_str(), "" | "~" | "null" | "Null" | "NULL")
+            }
+            _ => false,
+        }
+    }
+
     pub fn is_bool(&self) -> bool {
         self.as_bool().is_ok()
     }
 
+    /// Integer without sign, e.g. `12`, `0x1f` or `1_000`.
     pub fn is_integer(&self) -> bool {
-        if let YamlValueData::String(s) = &self.data {
-            str_is_integer(s)
+        if let YamlValueData::String(s) = self.content() {
+            !s.starts_with(['-', '+']) && parse_integer(s).is_some()
         } else {
             false
         }
     }
 
+    /// Integer with leading `-` or `+`, e.g. `-12` or `+0x1f`.
     pub fn is_signed_integer(&self) -> bool {
-        if let YamlValueData::String(s) = &self.data {
-            if s.starts_with("-") || s.starts_with("+") {
-                str_is_integer(&s[1..])
-            } else {
-                false
-            }
+        if let YamlValueData::String(s) = self.content() {
+            s.starts_with(['-', '+']) && parse_integer(s).is_some()
         } else {
             false
         }
     }
 
-    pub fn as_u64(&self) -> Result<u64, YamlError> {
-        if let YamlValueData::String(s) = &self.data {
-            if s.starts_with("0x") | s.starts_with("0X") {
-                u64::from_str_radix(&s[2..], 16).map_err(|_| {
-                    YamlError::new(
-                        ErrorKind::InvalidNumber,
-                        format!(
-                            "Expecting unsigned hexadecimal integer like \
-                             0xfa, but got {s}"
-                        ),
-                        self.start,
-                        self.end,
-                    )
-                })
-            } else if s.starts_with("0o") | s.starts_with("0O") {
-                u64::from_str_radix(&s[2..], 8).map_err(|_| {
-                    YamlError::new(
-                        ErrorKind::InvalidNumber,
-                        format!(
-                            "Expecting unsigned octal integer like 0o20, but \
-                             got {s}"
-                        ),
-                        self.start,
-                        self.end,
-                    )
-                })
-            } else if s.starts_with("0b") | s.starts_with("0B") {
-                u64::from_str_radix(&s[2..], 2).map_err(|_| {
-                    YamlError::new(
-                        ErrorKind::InvalidNumber,
-                        format!(
-                            "Expecting unsigned binary integer like 0b10, but \
-                             got {s}"
-                        ),
-                        self.start,
-                        self.end,
-                    )
-                })
-            } else {
-                u64::from_str(s.as_str()).map_err(|_| {
-                    YamlError::new(
-                        ErrorKind::InvalidNumber,
-                        format!(
-                            "Expecting unsigned integer like 87, but got {s}"
-                        ),
-                        self.start,
-                        self.end,
-                    )
-                })
+    pub fn as_u128(&self) -> Result<u128, YamlError> {
+        if let YamlValueData::String(s) = self.content() {
+            match parse_integer(s) {
+                Some((false, Some(num))) => Ok(num),
+                Some((true, Some(0))) => Ok(0),
+                Some((false, None)) => Err(YamlError::new(
+                    ErrorKind::NumberOverflow,
+                    format!("Specified number {s} overflow u128::MAX"),
+                    self.start,
+                    self.end,
+                )),
+                _ => Err(YamlError::new(
+                    ErrorKind::InvalidNumber,
+                    format!(
+                        "Expecting unsigned integer like 87, 0xfa, 0o20 or \
+                         0b10, but got {s}"
+                    ),
+                    self.start,
+                    self.end,
+                )),
             }
         } else {
             Err(YamlError::new(
@@ -178,6 +274,24 @@ impl YamlValue {
         }
     }
 
+    pub fn as_u64(&self) -> Result<u64, YamlError> {
+        let num = self.as_u128()?;
+        if num > u64::MAX as u128 {
+            Err(YamlError::new(
+                ErrorKind::NumberOverflow,
+                format!(
+                    "Specified number {} overflow u64::MAX {}",
+                    num,
+                    u64::MAX
+                ),
+                self.start,
+                self.end,
+            ))
+        } else {
+            Ok(num as u64)
+        }
+    }
+
     pub fn as_u32(&self) -> Result<u32, YamlError> {
         let num = self.as_u64()?;
         if num > u32::MAX as u64 {
@@ -232,65 +346,36 @@ impl YamlValue {
         }
     }
 
-    pub fn as_i64(&self) -> Result<i64, YamlError> {
-        if let YamlValueData::String(s) = &self.data {
-            let original = s;
-            let positive: bool = !s.starts_with("-");
-
-            let s = s.as_str().strip_prefix("-").unwrap_or(s.as_str());
-
-            let s = s.strip_prefix("+").unwrap_or(s);
-
-            let number = if s.starts_with("0x") | s.starts_with("0X") {
-                i64::from_str_radix(&s[2..], 16).map_err(|_| {
-                    YamlError::new(
-                        ErrorKind::InvalidNumber,
-                        format!(
-                            "Expecting signed hexadecimal integer like -0xfa, \
-                             but got {original}"
-                        ),
-                        self.start,
-                        self.end,
-                    )
-                })?
-            } else if s.starts_with("0o") | s.starts_with("0O") {
-                i64::from_str_radix(&s[2..], 8).map_err(|_| {
-                    YamlError::new(
-                        ErrorKind::InvalidNumber,
-                        format!(
-                            "Expecting signed octal integer like -0o20, but \
-                             got {original}"
-                        ),
-                        self.start,
-                        self.end,
-                    )
-                })?
-            } else if s.starts_with("0b") | s.starts_with("0B") {
-                i64::from_str_radix(&s[2..], 2).map_err(|_| {
-                    YamlError::new(
-                        ErrorKind::InvalidNumber,
-                        format!(
-                            "Expecting signed binary integer like -0b10, but \
-                             got {original}"
-                        ),
-                        self.start,
-                        self.end,
-                    )
-                })?
-            } else {
-                i64::from_str(s).map_err(|_| {
-                    YamlError::new(
+    pub fn as_i128(&self) -> Result<i128, YamlError> {
+        if let YamlValueData::String(s) = self.content() {
+            let number = match parse_integer(s) {
+                Some((false, Some(num))) => i128::try_from(num).ok(),
+                Some((true, Some(num))) => 0i128.checked_sub_unsigned(num),
+                Some((_, None)) => None,
+                None => {
+                    return Err(YamlError::new(
                         ErrorKind::InvalidNumber,
                         format!(
-                            "Expecting signed integer like -1298, but got \
-                             {original}"
+                            "Expecting signed integer like -1298, -0xfa, \
+                             -0o20 or -0b10, but got {s}"
                         ),
                         self.start,
                         self.end,
-                    )
-                })?
+                    ));
+                }
             };
-            if positive { Ok(number) } else { Ok(0 - number) }
+            number.ok_or_else(|| {
+                YamlError::new(
+                    ErrorKind::NumberOverflow,
+                    format!(
+                        "Specified number {s} overflow i128 range [{}, {}]",
+                        i128::MIN,
+                        i128::MAX
+                    ),
+                    self.start,
+                    self.end,
+                )
+            })
         } else {
             Err(YamlError::new(
                 ErrorKind::UnexpectedYamlNodeType,
@@ -301,6 +386,25 @@ impl YamlValue {
         }
     }
 
+    pub fn as_i64(&self) -> Result<i64, YamlError> {
+        let num = self.as_i128()?;
+        if num > i64::MAX as i128 || num < i64::MIN as i128 {
+            Err(YamlError::new(
+                ErrorKind::NumberOverflow,
+                format!(
+                    "Specified number {} overflow i64 range [{}, {}]",
+                    num,
+                    i64::MIN,
+                    i64::MAX
+                ),
+                self.start,
+                self.end,
+            ))
+        } else {
+            Ok(num as i64)
+        }
+    }
+
     pub fn as_i32(&self) -> Result<i32, YamlError> {
         let num = self.as_i64()?;
         if num > i32::MAX as i64 || num < i32::MIN as i64 {
@@ -357,6 +461,55 @@ impl YamlValue {
             Ok(num as i8)
         }
     }
+
+    /// Emit YAML string using the same emitter and option of
+    /// [crate::to_string_with_opt], scalars are emitted in their original
+    /// style when possible.
+    pub fn to_string_pretty(
+        &self,
+        option: YamlSerializeOption,
+    ) -> Result<String, YamlError> {
+        crate::to_string_with_opt(self, option)
+    }
+
+    /// Deepest node whose span covers the position and its key path. Map
+    /// keys are included and share the key path of their values. The key
+    /// path is map keys and sequence indexes joined by `.`, the document
+    /// root is empty string.
+    pub fn node_at(&self, pos: YamlPosition) -> Option<(&YamlValue, String)> {
+        self.node_at_path(pos, String::new())
+    }
+
+    fn node_at_path(
+        &self,
+        pos: YamlPosition,
+        path: String,
+    ) -> Option<(&YamlValue, String)> {
+        let data = if let YamlValueData::Tag(tag) = &self.data {
+            &tag.data
+        } else {
+            &self.data
+        };
+        let child = match data {
+            YamlValueData::Array(items) => {
+                items.iter().enumerate().find_map(|(index, item)| {
+                    item.node_at_path(
+                        pos,
+                        sub_key_path(&path, &index.to_string()),
+                    )
+                })
+            }
+            YamlValueData::Map(map) => map.iter().find_map(|(key, value)| {
+                let sub_path = sub_key_path(&path, &key.key_path_name());
+                key.node_at_path(pos, sub_path.clone())
+                    .or_else(|| value.node_at_path(pos, sub_path))
+            }),
+            _ => None,
+        };
+        child.or_else(|| {
+            (self.start <= pos && pos <= self.end).then_some((self, path))
+        })
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
@@ -376,14 +529,178 @@ impl std::fmt::Display for YamlValueData {
     }
 }
 
-fn str_is_integer(s: &str) -> bool {
-    if s.starts_with("0x") | s.starts_with("0X") {
-        s[2..].chars().all(|c| c.is_ascii_hexdigit())
-    } else if s.starts_with("0o") | s.starts_with("0O") {
-        s[2..].chars().all(|c| c.is_digit(8))
-    } else if s.starts_with("0b") | s.starts_with("0B") {
-        s[2..].chars().all(|c| c.is_digit(2))
+impl From<YamlValueData> for YamlValue {
+    fn from(data: YamlValueData) -> Self {
+        Self {
+            data,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&str> for YamlValue {
+    fn from(v: &str) -> Self {
+        YamlValueData::String(v.to_string()).into()
+    }
+}
+
+impl From<String> for YamlValue {
+    fn from(v: String) -> Self {
+        YamlValueData::String(v).into()
+    }
+}
+
+impl From<char> for YamlValue {
+    fn from(v: char) -> Self {
+        YamlValueData::String(v.to_string()).into()
+    }
+}
+
+macro_rules! impl_from_scalar {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for YamlValue {
+                fn from(v: $t) -> Self {
+                    YamlValueData::String(v.to_string()).into()
+                }
+            }
+        )*
+    };
+}
+
+impl_from_scalar!(
+    bool, u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, f32, f64
+);
+
+impl<T> From<Option<T>> for YamlValue
+where
+    T: Into<YamlValue>,
+{
+    fn from(v: Option<T>) -> Self {
+        match v {
+            Some(v) => v.into(),
+            None => YamlValueData::Null.into(),
+        }
+    }
+}
+
+impl<T> From<Vec<T>> for YamlValue
+where
+    T: Into<YamlValue>,
+{
+    fn from(v: Vec<T>) -> Self {
+        YamlValueData::Array(v.into_iter().map(Into::into).collect()).into()
+    }
+}
+
+impl From<YamlValueMap> for YamlValue {
+    fn from(v: YamlValueMap) -> Self {
+        YamlValueData::Map(Box::new(v)).into()
+    }
+}
+
+// Append map key or sequence index to the key path, joined by `.`.
+pub(crate) fn sub_key_path(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
     } else {
-        s.chars().all(|c| c.is_ascii_digit())
+        format!("{path}.{name}")
+    }
+}
+
+/// Parse integer in decimal, hexadecimal(`0x`), octal(`0o`) or binary(`0b`)
+/// form with optional leading `-` or `+`. The `_` is allowed between digits
+/// as digit separator, e.g. `0xFF_FF`.
+/// Return None if not integer, otherwise whether it is negative and its
+/// absolute value which is None on overflow of u128.
+fn parse_integer(s: &str) -> Option<(bool, Option<u128>)> {
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(s) => (true, s),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let (radix, digits) = match s.get(..2) {
+        Some("0x" | "0X") => (16, &s[2..]),
+        Some("0o" | "0O") => (8, &s[2..]),
+        Some("0b" | "0B") => (2, &s[2..]),
+        _ => (10, s),
+    };
+    if digits.starts_with('_')
+        || digits.ends_with('_')
+        || !digits.chars().all(|c| c == '_' || c.is_digit(radix))
+        || !digits.chars().any(|c| c.is_digit(radix))
+    {
+        return None;
+    }
+    let mut num: Option<u128> = Some(0);
+    for c in digits.chars().filter(|c| *c != '_') {
+        num = num
+            .and_then(|n| n.checked_mul(radix.into()))
+            .and_then(|n| n.checked_add(c.to_digit(radix)?.into()));
+    }
+    Some((negative, num))
+}
+
+// YAML 1.2.2 SPEC, 10.3.2. Tag Resolution of core schema:
+//      [-+]? ( \. [0-9]+ | [0-9]+ ( \. [0-9]* )? ) ( [eE] [-+]? [0-9]+ )?
+fn str_is_float(s: &str) -> bool {
+    let s = s.strip_prefix(['-', '+']).unwrap_or(s);
+    let (mantissa, exponent) = match s.split_once(['e', 'E']) {
+        Some((m, e)) => (m, Some(e.strip_prefix(['-', '+']).unwrap_or(e))),
+        None => (s, None),
+    };
+    let (int_part, frac_part) = match mantissa.split_once('.') {
+        Some((i, f)) => (i, Some(f)),
+        None => (mantissa, None),
+    };
+    let is_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    let is_mantissa = match frac_part {
+        Some(f) => {
+            is_digits(int_part)
+                && is_digits(f)
+                && !(int_part.is_empty() && f.is_empty())
+        }
+        None => !int_part.is_empty() && is_digits(int_part),
+    };
+    is_mantissa && exponent.is_none_or(|e| !e.is_empty() && is_digits(e))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_node_at() -> Result<(), YamlError> {
+        let value = YamlValue::from_str(
+            "interfaces:\n  - name: eth0\n    mtu: !!int 1500\n    addresses: \
+             [192.0.2.1, 192.0.2.2]\n",
+        )?;
+        let node_at = |line, column| {
+            value
+                .node_at(YamlPosition::new(line, column))
+                .map(|(node, path)| (node.data.to_string(), path))
+        };
+        assert_eq!(
+            node_at(2, 13),
+            Some((r#"String("eth0")"#.to_string(), "interfaces.0.name".into()))
+        );
+        assert_eq!(
+            node_at(2, 6),
+            Some((r#"String("name")"#.to_string(), "interfaces.0.name".into()))
+        );
+        let (mtu, path) = value.node_at(YamlPosition::new(3, 19)).unwrap();
+        assert_eq!((mtu.as_u64()?, path.as_str()), (1500, "interfaces.0.mtu"));
+        assert_eq!(
+            node_at(4, 31),
+            Some((
+                r#"String("192.0.2.2")"#.to_string(),
+                "interfaces.0.addresses.1".into()
+            ))
+        );
+        assert_eq!(
+            node_at(1, 1).map(|(_, path)| path),
+            Some("interfaces".into())
+        );
+        assert_eq!(node_at(9, 1), None);
+        Ok(())
     }
 }
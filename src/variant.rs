@@ -1,23 +1,26 @@
 // SPDX-License-Identifier: Apache-2.0
 
+use std::sync::Arc;
+
 use serde::de::{
     DeserializeSeed, Deserializer, EnumAccess, VariantAccess, Visitor,
     value::StrDeserializer,
 };
 
 use crate::{
-    ErrorKind, YamlDeserializer, YamlError, YamlValue,
+    ErrorKind, YamlDeContext, YamlDeserializer, YamlError, YamlValue,
     YamlValueData,
 };
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 pub(crate) struct YamlValueEnumAccess {
     value: YamlValue,
+    ctx: Arc<YamlDeContext>,
 }
 
 impl YamlValueEnumAccess {
-    pub(crate) fn new(value: YamlValue) -> Self {
-        Self { value }
+    pub(crate) fn new(value: YamlValue, ctx: Arc<YamlDeContext>) -> Self {
+        Self { value, ctx }
     }
 }
 
@@ -25,7 +28,11 @@ impl<'de> VariantAccess<'de> for YamlValueEnumAccess {
     type Error = YamlError;
 
     fn unit_variant(self) -> Result<(), Self::Error> {
-        if matches!(self.value.data, YamlValueData::String(_)) {
+        // The `Variant:` of single entry map has empty value.
+        if matches!(
+            self.value.data,
+            YamlValueData::String(_) | YamlValueData::Null
+        ) {
             Ok(())
         } else {
             Err(YamlError::new(
@@ -49,10 +56,11 @@ impl<'de> VariantAccess<'de> for YamlValueEnumAccess {
                 start: self.value.start,
                 end: self.value.end,
                 data: tag.data,
+                style: self.value.style,
             };
-            seed.deserialize(&mut YamlDeserializer { parsed: value })
+            seed.deserialize(&mut YamlDeserializer::child(value, self.ctx))
         } else {
-            seed.deserialize(&mut YamlDeserializer { parsed: self.value })
+            seed.deserialize(&mut YamlDeserializer::child(self.value, self.ctx))
         }
     }
 
@@ -64,9 +72,7 @@ impl<'de> VariantAccess<'de> for YamlValueEnumAccess {
     where
         V: Visitor<'de>,
     {
-        YamlDeserializer {
-            parsed: self.value.clone(),
-        }
+        YamlDeserializer::child(self.value, self.ctx)
         .deserialize_seq(visitor)
     }
 
@@ -78,9 +84,7 @@ impl<'de> VariantAccess<'de> for YamlValueEnumAccess {
     where
         V: Visitor<'de>,
     {
-        YamlDeserializer {
-            parsed: self.value.clone(),
-        }
+        YamlDeserializer::child(self.value, self.ctx)
         .deserialize_map(visitor)
     }
 }
@@ -103,17 +107,35 @@ impl<'de> EnumAccess<'de> for YamlValueEnumAccess {
                 seed.deserialize(tag_name)?,
                 Self {
                     value: YamlValue {
-                        data: tag.data.clone(),
+                        data: tag.data,
                         start: self.value.start,
                         end: self.value.end,
+                        style: self.value.style,
                     },
+                    ctx: self.ctx,
                 },
             ))
+        } else if let YamlValueData::Map(mut map) = self.value.data {
+            // Externally tagged enum in the form of `{variant: value}`
+            match (map.pop(), map.is_empty()) {
+                (Some((key, value)), true) => Ok((
+                    seed.deserialize(&mut YamlDeserializer::child(key, self.ctx.clone()))?,
+                    Self {
+                        value,
+                        ctx: self.ctx,
+                    },
+                )),
+                _ => Err(YamlError::new(
+                    ErrorKind::UnexpectedYamlNodeType,
+                    "Expecting map with single entry for enum variant"
+                        .to_string(),
+                    self.value.start,
+                    self.value.end,
+                )),
+            }
         } else {
             Ok((
-                seed.deserialize(&mut YamlDeserializer {
-                    parsed: self.value.clone(),
-                })?,
+                seed.deserialize(&mut YamlDeserializer::child(self.value.clone(), self.ctx.clone()))?,
                 self,
             ))
         }
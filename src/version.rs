@@ -0,0 +1,164 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use std::borrow::Cow;
+
+use crate::{
+    YamlParseOption, YamlScalarStyle, YamlValue, YamlValueData,
+    find_comment_start,
+};
+
+/// YAML specification version deciding how plain scalars are resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum YamlVersion {
+    /// YAML 1.1: `yes`, `no`, `on`, `off`, `y` and `n` in any case form are
+    /// bool, integer with leading `0` is octal, e.g. `0755`, and `\u{85}`,
+    /// `\u{2028}` and `\u{2029}` are line breaks.
+    V1_1,
+    /// YAML 1.2 core schema.
+    #[default]
+    V1_2,
+}
+
+// Version of the first document of the stream by its `%YAML` directive.
+pub(crate) fn document_version(
+    input: &str,
+    option: &YamlParseOption,
+) -> YamlVersion {
+    for line in input.lines() {
+        let line = &line[..find_comment_start(line).unwrap_or(line.len())];
+        let trimmed = line.trim_start_matches([' ', '\t']);
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        } else if let Some(directive) = line.strip_prefix('%') {
+            let mut words =
+                directive.split([' ', '\t']).filter(|w| !w.is_empty());
+            if words.next() == Some("YAML") {
+                return if words.next() == Some("1.1") {
+                    YamlVersion::V1_1
+                } else {
+                    YamlVersion::V1_2
+                };
+            }
+        } else {
+            break;
+        }
+    }
+    option.default_version
+}
+
+// YAML 1.1 SPEC, 5.4. Line Break Characters:
+//      This specification defines the following line break characters:
+//      ... b-next-line ::= #x85  b-line-separator ::= #x2028
+//      b-paragraph-separator ::= #x2029
+// Folded into line feed, as YAML 1.2 treats them as content.
+pub(crate) fn normalize_yaml_1_1_line_breaks(input: &str) -> Cow<'_, str> {
+    if input.contains(['\u{85}', '\u{2028}', '\u{2029}']) {
+        Cow::Owned(input.replace(['\u{85}', '\u{2028}', '\u{2029}'], "\n"))
+    } else {
+        Cow::Borrowed(input)
+    }
+}
+
+impl YamlValue {
+    // Rewrite plain scalars resolved differently by YAML 1.1 into their
+    // YAML 1.2 form, e.g. `yes` to `true` and `0755` to `0o755`. Map keys
+    // are untouched as they usually name struct fields, e.g. `on`.
+    pub(crate) fn resolve_yaml_1_1(&mut self) {
+        self.walk(|_, value| {
+            if value.style != YamlScalarStyle::Plain {
+                return;
+            }
+            if let YamlValueData::String(s) = &mut value.data
+                && let Some(resolved) = resolve_yaml_1_1_scalar(s)
+            {
+                *s = resolved;
+            }
+        })
+    }
+}
+
+pub(crate) fn resolve_yaml_1_1_scalar(s: &str) -> Option<String> {
+    match s {
+        "y" | "Y" | "yes" | "Yes" | "YES" | "on" | "On" | "ON" => {
+            return Some("true".to_string());
+        }
+        "n" | "N" | "no" | "No" | "NO" | "off" | "Off" | "OFF" => {
+            return Some("false".to_string());
+        }
+        _ => (),
+    }
+    // YAML 1.1 int: [-+]?0[0-7_]+
+    let (sign, digits) = match s.strip_prefix(['-', '+']) {
+        Some(rest) => (&s[..1], rest),
+        None => ("", s),
+    };
+    let octal = digits.strip_prefix('0')?;
+    (!octal.is_empty() && octal.chars().all(|c| c == '_' || c.is_digit(8)))
+        .then(|| format!("{sign}0o{}", octal.trim_start_matches('_')))
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::YamlError;
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct LegacyTest {
+        on: bool,
+        mode: u32,
+        flags: Vec<bool>,
+        name: String,
+        quoted: String,
+        zero: u32,
+    }
+
+    #[test]
+    fn test_yaml_1_1() -> Result<(), YamlError> {
+        let input = "on: Yes\nmode: 0755\nflags: [y, N, off, true]\nname: \
+                     no\nquoted: 'yes'\nzero: 0\n";
+        let expected = LegacyTest {
+            on: true,
+            mode: 0o755,
+            flags: vec![true, false, false, true],
+            name: "false".to_string(),
+            quoted: "yes".to_string(),
+            zero: 0,
+        };
+        assert!(crate::from_str::<LegacyTest>(input).is_err());
+        assert_eq!(
+            crate::from_str::<LegacyTest>(&format!(
+                "# legacy\n%YAML 1.1\n---\n{input}"
+            ))?,
+            expected
+        );
+        let option = YamlParseOption {
+            default_version: YamlVersion::V1_1,
+            ..Default::default()
+        };
+        assert_eq!(
+            crate::from_str_with_opt::<LegacyTest>(input, option.clone())?,
+            expected
+        );
+        // The directive takes precedence over the option
+        assert!(
+            crate::from_str_with_opt::<LegacyTest>(
+                &format!("%YAML 1.2\n---\n{input}"),
+                option.clone()
+            )
+            .is_err()
+        );
+
+        let value =
+            crate::to_value("%YAML 1.1\n---\n[-0_17, +017, 08, !!str on]")?;
+        let expected = crate::to_value("[-0o17, +0o17, 08, !!str on]")?;
+        assert!(value.canonical_cmp(&expected).is_eq(), "{value}");
+
+        let value = crate::to_value_with_opt("a:\u{2028}  b", option)?;
+        let expected = crate::to_value("a:\n  b")?;
+        assert!(value.canonical_cmp(&expected).is_eq(), "{value}");
+        Ok(())
+    }
+}
@@ -0,0 +1,155 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{YamlValue, YamlValueData, value::sub_key_path};
+
+impl YamlValue {
+    /// Invoke `f` on this value and all its descendants except map keys in
+    /// depth-first order with the key path of each node. The key path is
+    /// map keys and sequence indexes joined by `.`, the document root is
+    /// empty string. Node is visited before its children, hence children
+    /// changed by `f` are walked after the change.
+    pub fn walk<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&str, &mut YamlValue),
+    {
+        walk_value(self, "", &mut f)
+    }
+
+    /// Remove map entries whose key path `f` returns false for, entries
+    /// removed are not checked further.
+    pub fn retain_keys<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&str) -> bool,
+    {
+        self.walk(|path, value| {
+            if let YamlValueData::Map(map) = data_mut(value) {
+                map.retain(|key, _| {
+                    f(&sub_key_path(path, &key.key_path_name()))
+                });
+            }
+        })
+    }
+
+    /// Remove map entries holding null value, see [YamlValue::is_null()].
+    /// Sequence items are kept to preserve the indexes.
+    pub fn strip_nulls(&mut self) {
+        self.walk(|_, value| {
+            if let YamlValueData::Map(map) = data_mut(value) {
+                map.retain(|_, v| !v.is_null());
+            }
+        })
+    }
+
+    /// Convert string map keys to lowercase. On conflict, the value of
+    /// latter key is stored at the position of former key.
+    pub fn lowercase_keys(&mut self) {
+        self.walk(|_, value| {
+            if let YamlValueData::Map(map) = data_mut(value)
+                && map.keys().any(|k| has_uppercase(&k.data))
+            {
+                **map = std::mem::take(&mut **map)
+                    .into_iter()
+                    .map(|(mut k, v)| {
+                        if let YamlValueData::String(s) = data_mut(&mut k) {
+                            *s = s.to_lowercase();
+                        }
+                        (k, v)
+                    })
+                    .collect();
+            }
+        })
+    }
+}
+
+fn walk_value<F>(value: &mut YamlValue, path: &str, f: &mut F)
+where
+    F: FnMut(&str, &mut YamlValue),
+{
+    f(path, value);
+    match data_mut(value) {
+        YamlValueData::Array(items) => {
+            for (index, item) in items.iter_mut().enumerate() {
+                walk_value(item, &sub_key_path(path, &index.to_string()), f);
+            }
+        }
+        YamlValueData::Map(map) => {
+            for (key, v) in map.iter_mut() {
+                walk_value(v, &sub_key_path(path, &key.key_path_name()), f);
+            }
+        }
+        _ => (),
+    }
+}
+
+fn data_mut(value: &mut YamlValue) -> &mut YamlValueData {
+    match &mut value.data {
+        YamlValueData::Tag(tag) => &mut tag.data,
+        data => data,
+    }
+}
+
+fn has_uppercase(data: &YamlValueData) -> bool {
+    match data {
+        YamlValueData::String(s) => s.chars().any(char::is_uppercase),
+        YamlValueData::Tag(tag) => has_uppercase(&tag.data),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::YamlError;
+
+    fn assert_value(value: &YamlValue, expected: &str) {
+        let expected = YamlValue::from_str(expected).unwrap();
+        assert!(value.canonical_cmp(&expected).is_eq(), "{value}");
+    }
+
+    #[test]
+    fn test_walk() -> Result<(), YamlError> {
+        let mut value = YamlValue::from_str(
+            "Name: eth0\nMTU: ~\nWiFi:\n  PSK: secret\n  SSID: \
+             home\nAddresses:\n  - ip: 192.0.2.1\n    Prefix: null\n",
+        )?;
+        let mut paths = Vec::new();
+        value.walk(|path, v| {
+            paths.push(path.to_string());
+            if path.ends_with(".PSK") {
+                *v = YamlValue::from("<redacted>");
+            }
+        });
+        assert_eq!(
+            paths,
+            [
+                "",
+                "Name",
+                "MTU",
+                "WiFi",
+                "WiFi.PSK",
+                "WiFi.SSID",
+                "Addresses",
+                "Addresses.0",
+                "Addresses.0.ip",
+                "Addresses.0.Prefix"
+            ]
+        );
+
+        value.strip_nulls();
+        value.lowercase_keys();
+        assert_value(
+            &value,
+            "name: eth0\nwifi:\n  psk: <redacted>\n  ssid: home\n\
+             addresses:\n  - ip: 192.0.2.1\n",
+        );
+
+        value.retain_keys(|path| path != "wifi.ssid" && path != "name");
+        assert_value(
+            &value,
+            "wifi:\n  psk: <redacted>\naddresses:\n  - ip: 192.0.2.1\n",
+        );
+        Ok(())
+    }
+}
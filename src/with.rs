@@ -0,0 +1,484 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Helpers for `#[serde(with = "...")]` on common YAML idioms.
+
+use std::{fmt::Display, marker::PhantomData, str::FromStr};
+
+use serde::de::{Error, Visitor};
+
+// Parse string using `FromStr` and convert the error to deserializer error.
+fn parse_str<T, E>(v: &str) -> Result<T, E>
+where
+    T: FromStr,
+    <T as FromStr>::Err: Display,
+    E: Error,
+{
+    v.parse::<T>()
+        .map_err(|e| E::custom(format!("Invalid value {v:?}: {e}")))
+}
+
+/// Accept both number and string holding number, e.g. `8080` and `"8080"`.
+/// Serialized as number.
+pub mod string_or_number {
+    use super::*;
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: serde::Serialize,
+        S: serde::Serializer,
+    {
+        value.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: FromStr,
+        <T as FromStr>::Err: Display,
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(StringOrNumberVisitor(PhantomData))
+    }
+
+    struct StringOrNumberVisitor<T>(PhantomData<T>);
+
+    impl<T> Visitor<'_> for StringOrNumberVisitor<T>
+    where
+        T: FromStr,
+        <T as FromStr>::Err: Display,
+    {
+        type Value = T;
+
+        fn expecting(
+            &self,
+            formatter: &mut std::fmt::Formatter,
+        ) -> std::fmt::Result {
+            formatter.write_str("number or string holding number")
+        }
+
+        fn visit_u64<E: Error>(self, v: u64) -> Result<T, E> {
+            parse_str(&v.to_string())
+        }
+
+        fn visit_i64<E: Error>(self, v: i64) -> Result<T, E> {
+            parse_str(&v.to_string())
+        }
+
+        fn visit_f64<E: Error>(self, v: f64) -> Result<T, E> {
+            parse_str(&v.to_string())
+        }
+
+        fn visit_str<E: Error>(self, v: &str) -> Result<T, E> {
+            parse_str(v.trim())
+        }
+    }
+}
+
+/// List stored as comma separated string, e.g. `eth0,eth1`. White spaces
+/// around each item are ignored, empty string is empty list.
+pub mod comma_separated {
+    use super::*;
+
+    pub fn serialize<T, S>(
+        value: &[T],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        T: Display,
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(
+            &value
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<String>>()
+                .join(","),
+        )
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Vec<T>, D::Error>
+    where
+        T: FromStr,
+        <T as FromStr>::Err: Display,
+        D: serde::Deserializer<'de>,
+    {
+        let s: String = serde::Deserialize::deserialize(deserializer)?;
+        if s.trim().is_empty() {
+            Ok(Vec::new())
+        } else {
+            s.split(',').map(|item| parse_str(item.trim())).collect()
+        }
+    }
+}
+
+/// Bytes stored as base64 string with padding defined in RFC 4648.
+/// White spaces are ignored on deserialization, hence folded multi-line
+/// string is supported.
+pub mod base64 {
+    use super::*;
+
+    const ALPHABET: &[u8; 64] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: AsRef<[u8]> + ?Sized,
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&encode(value.as_ref()))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s: String = serde::Deserialize::deserialize(deserializer)?;
+        decode(&s).map_err(D::Error::custom)
+    }
+
+    fn encode(input: &[u8]) -> String {
+        let mut ret = String::with_capacity(input.len().div_ceil(3) * 4);
+        for chunk in input.chunks(3) {
+            let bits = chunk
+                .iter()
+                .enumerate()
+                .fold(0u32, |bits, (i, b)| bits | (*b as u32) << (16 - i * 8));
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    ret.push(
+                        ALPHABET[(bits >> (18 - i * 6)) as usize & 0x3f]
+                            as char,
+                    );
+                } else {
+                    ret.push('=');
+                }
+            }
+        }
+        ret
+    }
+
+    fn decode(input: &str) -> Result<Vec<u8>, String> {
+        let chars: Vec<u8> =
+            input.bytes().filter(|c| !c.is_ascii_whitespace()).collect();
+        if !chars.len().is_multiple_of(4) {
+            return Err(format!(
+                "Invalid base64 string {input:?}: length is not multiple of 4"
+            ));
+        }
+        let mut ret = Vec::with_capacity(chars.len() / 4 * 3);
+        for (index, chunk) in chars.chunks(4).enumerate() {
+            let is_last = index == chars.len() / 4 - 1;
+            let pad_count =
+                chunk.iter().rev().take_while(|c| **c == b'=').count();
+            if pad_count > 2 || (pad_count > 0 && !is_last) {
+                return Err(format!(
+                    "Invalid base64 string {input:?}: unexpected padding"
+                ));
+            }
+            let mut bits = 0u32;
+            for (i, c) in chunk[..4 - pad_count].iter().enumerate() {
+                let Some(v) = ALPHABET.iter().position(|a| a == c) else {
+                    return Err(format!(
+                        "Invalid base64 string {input:?}: invalid character \
+                         {:?}",
+                        *c as char
+                    ));
+                };
+                bits |= (v as u32) << (18 - i * 6);
+            }
+            for i in 0..3 - pad_count {
+                ret.push((bits >> (16 - i * 8)) as u8);
+            }
+        }
+        Ok(ret)
+    }
+}
+
+/// Unsigned integer stored as hex string with `0x` prefix, e.g. `0x1f`.
+/// The prefix is optional on deserialization.
+pub mod hex {
+    use super::*;
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Copy + Into<u64>,
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&format!("{:#x}", (*value).into()))
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: TryFrom<u64>,
+        D: serde::Deserializer<'de>,
+    {
+        let v = deserializer.deserialize_str(HexVisitor)?;
+        T::try_from(v).map_err(|_| {
+            D::Error::custom(format!("Hex value {v:#x} is out of range"))
+        })
+    }
+
+    struct HexVisitor;
+
+    impl Visitor<'_> for HexVisitor {
+        type Value = u64;
+
+        fn expecting(
+            &self,
+            formatter: &mut std::fmt::Formatter,
+        ) -> std::fmt::Result {
+            formatter.write_str("hex string")
+        }
+
+        // Integer from self-describing format other than YAML
+        fn visit_u64<E: Error>(self, v: u64) -> Result<u64, E> {
+            Ok(v)
+        }
+
+        fn visit_str<E: Error>(self, v: &str) -> Result<u64, E> {
+            let digits = v
+                .trim()
+                .strip_prefix("0x")
+                .or_else(|| v.trim().strip_prefix("0X"))
+                .unwrap_or(v.trim());
+            u64::from_str_radix(digits, 16)
+                .map_err(|e| E::custom(format!("Invalid hex {v:?}: {e}")))
+        }
+    }
+}
+
+/// Optional value where empty string means `None`, `None` is serialized as
+/// empty string.
+pub mod empty_string_as_none {
+    use super::*;
+
+    pub fn serialize<T, S>(
+        value: &Option<T>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        T: Display,
+        S: serde::Serializer,
+    {
+        match value {
+            Some(v) => serializer.serialize_str(&v.to_string()),
+            None => serializer.serialize_str(""),
+        }
+    }
+
+    pub fn deserialize<'de, T, D>(
+        deserializer: D,
+    ) -> Result<Option<T>, D::Error>
+    where
+        T: FromStr,
+        <T as FromStr>::Err: Display,
+        D: serde::Deserializer<'de>,
+    {
+        let s: String = serde::Deserialize::deserialize(deserializer)?;
+        if s.is_empty() {
+            Ok(None)
+        } else {
+            parse_str(&s).map(Some)
+        }
+    }
+}
+
+/// Represent all nested enum variants with data as single entry map, e.g.
+/// `Variant: value`, compatible with the data produced by
+/// `serde_yaml::with::singleton_map_recursive`. Works with any serde
+/// format, e.g. `{"Variant": 1}` in JSON. Unit variant is its name, enum
+/// is deserialized from the variant name or a single entry map.
+pub mod singleton_map_recursive {
+    use serde::{Deserialize, Serialize};
+
+    use crate::singleton_map::SingletonMap;
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: serde::Serializer,
+    {
+        value.serialize(SingletonMap(serializer))
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: serde::Deserializer<'de>,
+    {
+        T::deserialize(SingletonMap(deserializer))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde::{Deserialize, Serialize};
+
+    use crate::YamlError;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct WithTest {
+        #[serde(with = "super::string_or_number")]
+        port: u16,
+        #[serde(with = "super::comma_separated")]
+        ifaces: Vec<String>,
+        #[serde(with = "super::base64")]
+        data: Vec<u8>,
+        #[serde(with = "super::hex")]
+        mask: u32,
+        #[serde(with = "super::empty_string_as_none")]
+        mtu: Option<u32>,
+    }
+
+    #[test]
+    fn test_with_helpers() -> Result<(), YamlError> {
+        let data: WithTest = crate::from_str(
+            r#"
+            port: "8080"
+            ifaces: eth0, eth1
+            data: eWFt
+              bA==
+            mask: 1000
+            mtu: ''
+            "#,
+        )?;
+        assert_eq!(
+            data,
+            WithTest {
+                port: 8080,
+                ifaces: vec!["eth0".into(), "eth1".into()],
+                data: b"yaml".to_vec(),
+                mask: 0x1000,
+                mtu: None,
+            }
+        );
+        assert_eq!(
+            crate::from_str::<WithTest>(&crate::to_string(&data)?)?,
+            data
+        );
+
+        for bytes in [&b""[..], b"a", b"ab", b"abc", b"\xff\x00\xfe\x01"] {
+            let yaml_str = crate::to_string(&WithTest {
+                data: bytes.to_vec(),
+                ..data.clone()
+            })?;
+            assert_eq!(crate::from_str::<WithTest>(&yaml_str)?.data, bytes);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_with_helpers_invalid() {
+        for yaml_str in [
+            "port: abc\nifaces: ''\ndata: ''\nmask: 0\nmtu: ''",
+            "port: 1\nifaces: ''\ndata: 'a==='\nmask: 0\nmtu: ''",
+            "port: 1\nifaces: ''\ndata: ''\nmask: 0xg\nmtu: ''",
+            "port: 1\nifaces: ''\ndata: ''\nmask: 0x1ffffffff\nmtu: ''",
+            "port: 1\nifaces: ''\ndata: ''\nmask: 0\nmtu: abc",
+        ] {
+            assert!(crate::from_str::<WithTest>(yaml_str).is_err());
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    enum InnerEnum {
+        Unit,
+        Newtype(u32),
+        Struct { a: String },
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    enum OuterEnum {
+        Newtype(InnerEnum),
+        Tuple(InnerEnum, InnerEnum),
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct SingletonMapTest {
+        #[serde(with = "super::singleton_map_recursive")]
+        list: Vec<OuterEnum>,
+        tagged: InnerEnum,
+    }
+
+    #[test]
+    fn test_singleton_map_recursive() -> Result<(), YamlError> {
+        let data = SingletonMapTest {
+            list: vec![
+                OuterEnum::Newtype(InnerEnum::Newtype(1)),
+                OuterEnum::Tuple(
+                    InnerEnum::Unit,
+                    InnerEnum::Struct { a: "1".into() },
+                ),
+            ],
+            tagged: InnerEnum::Newtype(2),
+        };
+        let yaml_str = crate::to_string(&data)?;
+        assert_eq!(
+            yaml_str,
+            r#"list:
+  - Newtype:
+      Newtype: 1
+  - Tuple:
+      - Unit
+      - Struct:
+          a: '1'
+tagged: !Newtype 2
+"#
+        );
+        assert_eq!(crate::from_str::<SingletonMapTest>(&yaml_str)?, data);
+        Ok(())
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct SingletonMapAnyFormat {
+        #[serde(with = "super::singleton_map_recursive")]
+        value: Option<(OuterEnum, u32, Option<InnerEnum>)>,
+    }
+
+    #[test]
+    fn test_singleton_map_recursive_other_format() {
+        let data = SingletonMapAnyFormat {
+            value: Some((
+                OuterEnum::Tuple(
+                    InnerEnum::Newtype(1),
+                    InnerEnum::Struct { a: "b".into() },
+                ),
+                2,
+                None,
+            )),
+        };
+        let yaml_str = serde_yaml::to_string(&data).unwrap();
+        assert_eq!(
+            yaml_str,
+            "value:\n- Tuple:\n  - Newtype: 1\n  - Struct:\n      a: b\n- \
+             2\n- null\n"
+        );
+        assert_eq!(
+            serde_yaml::from_str::<SingletonMapAnyFormat>(&yaml_str).unwrap(),
+            data
+        );
+
+        let unit = SingletonMapAnyFormat {
+            value: Some((OuterEnum::Newtype(InnerEnum::Unit), 0, None)),
+        };
+        let yaml_str = serde_yaml::to_string(&unit).unwrap();
+        assert_eq!(yaml_str, "value:\n- Newtype: Unit\n- 0\n- null\n");
+        assert_eq!(
+            serde_yaml::from_str::<SingletonMapAnyFormat>(&yaml_str).unwrap(),
+            unit
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_singleton_map_recursive_json() {
+        let data = SingletonMapAnyFormat {
+            value: Some((OuterEnum::Newtype(InnerEnum::Newtype(1)), 2, None)),
+        };
+        let json_str = serde_json::to_string(&data).unwrap();
+        assert_eq!(json_str, r#"{"value":[{"Newtype":{"Newtype":1}},2,null]}"#);
+        assert_eq!(
+            serde_json::from_str::<SingletonMapAnyFormat>(&json_str).unwrap(),
+            data
+        );
+    }
+}